@@ -1,4 +1,4 @@
-use std::io::Result;
+use std::{env, io::Result, path::PathBuf};
 
 fn main() -> Result<()> {
     let protoc_bin = protoc_bin_vendored::protoc_bin_path().unwrap();
@@ -6,14 +6,22 @@ fn main() -> Result<()> {
     let mut prost_config = prost_build::Config::new();
     prost_config.protoc_executable(protoc_bin);
 
-    tonic_build::configure().compile_protos_with_config(
-        prost_config,
-        &[
-            "proto/zeloscloud/trace/publish.proto",
-            "proto/zeloscloud/trace/subscribe.proto",
-            "proto/zeloscloud/trace/trace.proto",
-        ],
-        &["proto/"],
-    )?;
+    // Descriptors for gRPC server reflection
+    let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
+
+    tonic_build::configure()
+        .file_descriptor_set_path(out_dir.join("zeloscloud_trace_descriptor.bin"))
+        .compile_protos_with_config(
+            prost_config,
+            &[
+                "proto/zeloscloud/trace/action.proto",
+                "proto/zeloscloud/trace/catalog.proto",
+                "proto/zeloscloud/trace/control.proto",
+                "proto/zeloscloud/trace/publish.proto",
+                "proto/zeloscloud/trace/subscribe.proto",
+                "proto/zeloscloud/trace/trace.proto",
+            ],
+            &["proto/"],
+        )?;
     Ok(())
 }
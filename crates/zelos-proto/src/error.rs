@@ -6,12 +6,40 @@ pub enum Error {
     #[error("Missing the value field")]
     MissingValue,
 
+    #[error("Missing the log level field")]
+    MissingLogLevel,
+
     #[error("Missing a message value")]
     MissingMessage,
 
     #[error("Missing a oneof value")]
     MissingOneOf,
 
+    #[error("Value table key is not a number")]
+    NonNumericValueTableKey,
+
+    #[error("Event batches can only be converted with their event schema")]
+    EventBatchWithoutSchema,
+
+    #[error("No event schema for event batch {0}")]
+    MissingEventSchema(String),
+
+    #[error("Column index {0} is not in the event schema")]
+    UnknownFieldIndex(u32),
+
+    #[error("Column for field index {0} does not match the number of samples")]
+    ColumnLengthMismatch(u32),
+
+    #[error(
+        "Incompatible protocol versions: we support {local_min} to {local}, the peer supports {peer_min} to {peer}"
+    )]
+    IncompatibleProtocol {
+        local_min: u32,
+        local: u32,
+        peer_min: u32,
+        peer: u32,
+    },
+
     #[error("Invalid UUID")]
     InvalidUuid(#[from] uuid::Error),
 
@@ -3,11 +3,39 @@
 //! not give us options to ensure that messages exist (all are options). Hopefully this also gives us flexibility with
 //! our in-memory data structures going forward.
 
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
+
 use uuid::Uuid;
 use zelos_trace_types::ipc;
 
 use crate::error::Error;
 
+/// Most names [`intern`] keeps per thread, it starts over once it is full
+const MAX_INTERNED_NAMES: usize = 4096;
+
+thread_local! {
+    static INTERNED_NAMES: RefCell<HashSet<Arc<str>>> = RefCell::new(HashSet::new());
+}
+
+/// Source and event names repeat on every message, so the messages decoded on a thread share one allocation per name
+fn intern(name: String) -> Arc<str> {
+    INTERNED_NAMES.with_borrow_mut(|names| {
+        if let Some(interned) = names.get(name.as_str()) {
+            return interned.clone();
+        }
+        if names.len() >= MAX_INTERNED_NAMES {
+            names.clear();
+        }
+        let name: Arc<str> = name.into();
+        names.insert(name.clone());
+        name
+    })
+}
+
 // ===== DataType =====
 impl From<zelos_trace_types::DataType> for super::DataType {
     fn from(value: zelos_trace_types::DataType) -> Self {
@@ -107,6 +135,7 @@ impl From<ipc::TraceSegmentStart> for super::TraceSegmentStart {
         Self {
             time_ns: value.time_ns,
             source_name: value.source_name,
+            attributes: value.attributes,
         }
     }
 }
@@ -120,14 +149,84 @@ impl From<ipc::TraceSegmentEnd> for super::TraceSegmentEnd {
     }
 }
 
+// ===== FieldKind =====
+impl From<ipc::FieldKind> for super::FieldKind {
+    fn from(value: ipc::FieldKind) -> Self {
+        match value {
+            ipc::FieldKind::Gauge => Self::Gauge,
+            ipc::FieldKind::Counter => Self::Counter,
+            ipc::FieldKind::Delta => Self::Delta,
+            ipc::FieldKind::State => Self::State,
+        }
+    }
+}
+impl From<super::FieldKind> for ipc::FieldKind {
+    fn from(value: super::FieldKind) -> Self {
+        match value {
+            super::FieldKind::Unspecified | super::FieldKind::Gauge => Self::Gauge,
+            super::FieldKind::Counter => Self::Counter,
+            super::FieldKind::Delta => Self::Delta,
+            super::FieldKind::State => Self::State,
+        }
+    }
+}
+
+// ===== FieldDisplay =====
+impl From<ipc::FieldDisplay> for super::FieldDisplay {
+    fn from(value: ipc::FieldDisplay) -> Self {
+        let hint = match value.hint {
+            None => super::DisplayHint::Unspecified,
+            Some(ipc::DisplayHint::Line) => super::DisplayHint::Line,
+            Some(ipc::DisplayHint::Step) => super::DisplayHint::Step,
+            Some(ipc::DisplayHint::Scatter) => super::DisplayHint::Scatter,
+            Some(ipc::DisplayHint::Bar) => super::DisplayHint::Bar,
+            Some(ipc::DisplayHint::Text) => super::DisplayHint::Text,
+        };
+        Self {
+            description: value.description,
+            min: value.min,
+            max: value.max,
+            precision: value.precision,
+            hint: hint.into(),
+        }
+    }
+}
+impl From<super::FieldDisplay> for ipc::FieldDisplay {
+    fn from(value: super::FieldDisplay) -> Self {
+        let hint = match value.hint() {
+            super::DisplayHint::Unspecified => None,
+            super::DisplayHint::Line => Some(ipc::DisplayHint::Line),
+            super::DisplayHint::Step => Some(ipc::DisplayHint::Step),
+            super::DisplayHint::Scatter => Some(ipc::DisplayHint::Scatter),
+            super::DisplayHint::Bar => Some(ipc::DisplayHint::Bar),
+            super::DisplayHint::Text => Some(ipc::DisplayHint::Text),
+        };
+        Self {
+            description: value.description,
+            min: value.min,
+            max: value.max,
+            precision: value.precision,
+            hint,
+        }
+    }
+}
+
+/// Displays are left out of messages when empty
+fn display_into_proto(display: ipc::FieldDisplay) -> Option<super::FieldDisplay> {
+    (!display.is_empty()).then(|| display.into())
+}
+
 // ===== TraceEventFieldMetadata =====
 impl From<ipc::TraceEventFieldMetadata> for super::TraceEventFieldMetadata {
     fn from(value: ipc::TraceEventFieldMetadata) -> Self {
         let data_type: super::DataType = value.data_type.into();
+        let kind: super::FieldKind = value.kind.into();
         Self {
             name: value.name,
             data_type: data_type.into(),
             unit: value.unit,
+            kind: kind.into(),
+            display: display_into_proto(value.display),
         }
     }
 }
@@ -137,9 +236,11 @@ impl TryInto<ipc::TraceEventFieldMetadata> for super::TraceEventFieldMetadata {
     fn try_into(self) -> Result<ipc::TraceEventFieldMetadata, Self::Error> {
         let data_type = self.data_type().try_into()?;
         Ok(ipc::TraceEventFieldMetadata {
+            kind: self.kind().into(),
             name: self.name,
             data_type,
             unit: self.unit,
+            display: self.display.map(Into::into).unwrap_or_default(),
         })
     }
 }
@@ -150,6 +251,7 @@ impl From<ipc::TraceEventSchema> for super::TraceEventSchema {
         Self {
             name: value.name,
             fields: value.fields.into_iter().map(|field| field.into()).collect(),
+            version: value.version,
         }
     }
 }
@@ -164,6 +266,7 @@ impl TryInto<ipc::TraceEventSchema> for super::TraceEventSchema {
             .collect::<Result<Vec<_>, _>>()?;
         Ok(ipc::TraceEventSchema {
             name: self.name,
+            version: self.version,
             fields,
         })
     }
@@ -189,12 +292,24 @@ impl TryInto<(String, zelos_trace_types::Value)> for super::TraceEventFieldEntry
     }
 }
 
+fn values_from_entries(
+    entries: Vec<super::TraceEventFieldEntry>,
+) -> Result<HashMap<String, zelos_trace_types::Value>, Error> {
+    entries.into_iter().map(|entry| entry.try_into()).collect()
+}
+
+fn values_into_entries(
+    values: HashMap<String, zelos_trace_types::Value>,
+) -> Vec<super::TraceEventFieldEntry> {
+    values.into_iter().map(|entry| entry.into()).collect()
+}
+
 // ===== TraceEvent =====
 impl From<ipc::TraceEvent> for super::TraceEvent {
     fn from(value: ipc::TraceEvent) -> Self {
         Self {
             time_ns: value.time_ns,
-            name: value.name,
+            name: value.name.to_string(),
             fields: value.fields.into_iter().map(|field| field.into()).collect(),
         }
     }
@@ -212,7 +327,7 @@ impl TryInto<ipc::TraceEvent> for super::TraceEvent {
 
         Ok(ipc::TraceEvent {
             time_ns: self.time_ns,
-            name: self.name,
+            name: intern(self.name),
             fields: fields.into_iter().collect(),
         })
     }
@@ -267,12 +382,665 @@ impl TryInto<ipc::TraceEventFieldNamedValues> for super::TraceEventFieldNamedVal
     }
 }
 
+// ===== Signal =====
+impl From<zelos_trace_types::Signal> for super::Signal {
+    fn from(value: zelos_trace_types::Signal) -> Self {
+        let data_type = value.data_type;
+        let value_table = value
+            .value_table
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(number, name)| {
+                let value = zelos_trace_types::Value::from_number_as_type(&number, &data_type)?;
+                Some((value, name).into())
+            })
+            .collect();
+        let data_type: super::DataType = data_type.into();
+        Self {
+            data_segment_id: value.data_segment_id.into_bytes().to_vec(),
+            source: value.source,
+            message: value.message,
+            signal: value.signal,
+            data_type: data_type.into(),
+            unit: value.unit,
+            value_table,
+            display: value.display.and_then(display_into_proto),
+        }
+    }
+}
+impl TryInto<zelos_trace_types::Signal> for super::Signal {
+    type Error = Error;
+
+    fn try_into(self) -> Result<zelos_trace_types::Signal, Self::Error> {
+        let data_type = self.data_type().try_into()?;
+        let value_table = self
+            .value_table
+            .into_iter()
+            .map(|entry| {
+                let (value, name): (zelos_trace_types::Value, String) = entry.try_into()?;
+                let number = value.as_number().ok_or(Error::NonNumericValueTableKey)?;
+                Ok((number, name))
+            })
+            .collect::<Result<std::collections::HashMap<_, _>, Error>>()?;
+
+        Ok(zelos_trace_types::Signal {
+            data_segment_id: Uuid::from_slice(&self.data_segment_id)?,
+            source: self.source,
+            message: self.message,
+            signal: self.signal,
+            data_type,
+            unit: self.unit,
+            display: self
+                .display
+                .map(ipc::FieldDisplay::from)
+                .filter(|display| !display.is_empty()),
+            value_table: if value_table.is_empty() {
+                None
+            } else {
+                Some(value_table)
+            },
+        })
+    }
+}
+
+// ===== TraceEventBatch =====
+impl super::TraceEventColumn {
+    fn push(&mut self, value: zelos_trace_types::Value) {
+        match value {
+            zelos_trace_types::Value::Int8(v) => self.ints.push(v.into()),
+            zelos_trace_types::Value::Int16(v) => self.ints.push(v.into()),
+            zelos_trace_types::Value::Int32(v) => self.ints.push(v.into()),
+            zelos_trace_types::Value::Int64(v) => self.ints.push(v),
+            zelos_trace_types::Value::UInt8(v) => self.uints.push(v.into()),
+            zelos_trace_types::Value::UInt16(v) => self.uints.push(v.into()),
+            zelos_trace_types::Value::UInt32(v) => self.uints.push(v.into()),
+            zelos_trace_types::Value::UInt64(v) => self.uints.push(v),
+            zelos_trace_types::Value::Float32(v) => self.float32s.push(v),
+            zelos_trace_types::Value::Float64(v) => self.float64s.push(v),
+            zelos_trace_types::Value::TimestampNs(v) => self.timestamps_ns.push(v),
+            zelos_trace_types::Value::Binary(v) => self.binaries.push(v),
+            zelos_trace_types::Value::String(v) => self.strings.push(v),
+            zelos_trace_types::Value::Boolean(v) => self.bools.push(v),
+        }
+    }
+
+    /// Take the values from the list matching `data_type`
+    fn into_values(
+        self,
+        data_type: &zelos_trace_types::DataType,
+    ) -> Result<Vec<zelos_trace_types::Value>, Error> {
+        fn convert<T, V: TryInto<T, Error = std::num::TryFromIntError>>(
+            values: Vec<V>,
+            f: fn(T) -> zelos_trace_types::Value,
+        ) -> Result<Vec<zelos_trace_types::Value>, Error> {
+            values.into_iter().map(|v| Ok(f(v.try_into()?))).collect()
+        }
+
+        Ok(match data_type {
+            zelos_trace_types::DataType::Int8 => {
+                convert(self.ints, zelos_trace_types::Value::Int8)?
+            }
+            zelos_trace_types::DataType::Int16 => {
+                convert(self.ints, zelos_trace_types::Value::Int16)?
+            }
+            zelos_trace_types::DataType::Int32 => {
+                convert(self.ints, zelos_trace_types::Value::Int32)?
+            }
+            zelos_trace_types::DataType::Int64 => self
+                .ints
+                .into_iter()
+                .map(zelos_trace_types::Value::Int64)
+                .collect(),
+            zelos_trace_types::DataType::UInt8 => {
+                convert(self.uints, zelos_trace_types::Value::UInt8)?
+            }
+            zelos_trace_types::DataType::UInt16 => {
+                convert(self.uints, zelos_trace_types::Value::UInt16)?
+            }
+            zelos_trace_types::DataType::UInt32 => {
+                convert(self.uints, zelos_trace_types::Value::UInt32)?
+            }
+            zelos_trace_types::DataType::UInt64 => self
+                .uints
+                .into_iter()
+                .map(zelos_trace_types::Value::UInt64)
+                .collect(),
+            zelos_trace_types::DataType::Float32 => self
+                .float32s
+                .into_iter()
+                .map(zelos_trace_types::Value::Float32)
+                .collect(),
+            zelos_trace_types::DataType::Float64 => self
+                .float64s
+                .into_iter()
+                .map(zelos_trace_types::Value::Float64)
+                .collect(),
+            zelos_trace_types::DataType::TimestampNs => self
+                .timestamps_ns
+                .into_iter()
+                .map(zelos_trace_types::Value::TimestampNs)
+                .collect(),
+            zelos_trace_types::DataType::Binary => self
+                .binaries
+                .into_iter()
+                .map(zelos_trace_types::Value::Binary)
+                .collect(),
+            zelos_trace_types::DataType::String => self
+                .strings
+                .into_iter()
+                .map(zelos_trace_types::Value::String)
+                .collect(),
+            zelos_trace_types::DataType::Boolean => self
+                .bools
+                .into_iter()
+                .map(zelos_trace_types::Value::Boolean)
+                .collect(),
+        })
+    }
+}
+impl super::TraceEventBatch {
+    /// Pack samples of the event `name` into a column per field of its schema `fields`. If any value is not in the
+    /// schema or does not have its field's data type, the events are handed back unchanged.
+    pub fn pack(
+        name: String,
+        fields: &[ipc::TraceEventFieldMetadata],
+        events: Vec<ipc::TraceEvent>,
+    ) -> Result<Self, Vec<ipc::TraceEvent>> {
+        let index: HashMap<&str, usize> = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| (field.name.as_str(), i))
+            .collect();
+        let fits = events.iter().all(|event| {
+            event.fields.iter().all(|(name, value)| {
+                index
+                    .get(name)
+                    .is_some_and(|&i| fields[i].data_type == value.data_type())
+            })
+        });
+        if !fits || u32::try_from(events.len().max(fields.len())).is_err() {
+            return Err(events);
+        }
+
+        let mut columns: Vec<super::TraceEventColumn> = (0..fields.len() as u32)
+            .map(|field_index| super::TraceEventColumn {
+                field_index,
+                ..Default::default()
+            })
+            .collect();
+        let mut time_ns = Vec::with_capacity(events.len());
+        for (sample, event) in events.into_iter().enumerate() {
+            time_ns.push(event.time_ns);
+            let mut push = |name: &str, value| {
+                let column = &mut columns[index[name]];
+                column.present.push(sample as u32);
+                column.push(value);
+            };
+            match event.fields {
+                ipc::TraceEventFields::Named(values) => {
+                    for (name, value) in values {
+                        push(&name, value);
+                    }
+                }
+                ipc::TraceEventFields::Indexed { schema, values } => {
+                    for (field, value) in schema.iter().zip(values) {
+                        if let Some(value) = value {
+                            push(&field.name, value);
+                        }
+                    }
+                }
+            }
+        }
+
+        // Drop empty columns, and the presence list of columns with a value in every sample
+        columns.retain(|column| !column.present.is_empty());
+        for column in &mut columns {
+            if column.present.len() == time_ns.len() {
+                column.present.clear();
+            }
+        }
+
+        Ok(Self {
+            name,
+            time_ns,
+            columns,
+        })
+    }
+
+    /// Unpack into one event per sample, using the schema `fields` the batch was packed with. The events share the
+    /// schema and carry their values by field index.
+    pub fn unpack(
+        self,
+        fields: &Arc<[ipc::TraceEventFieldMetadata]>,
+    ) -> Result<Vec<ipc::TraceEvent>, Error> {
+        let samples = self.time_ns.len();
+        let mut rows: Vec<Vec<Option<zelos_trace_types::Value>>> =
+            vec![vec![None; fields.len()]; samples];
+
+        for mut column in self.columns {
+            let field_index = column.field_index;
+            let field = fields
+                .get(field_index as usize)
+                .ok_or(Error::UnknownFieldIndex(field_index))?;
+            let present = std::mem::take(&mut column.present);
+            let values = column.into_values(&field.data_type)?;
+
+            let expected = if present.is_empty() {
+                samples
+            } else {
+                present.len()
+            };
+            if values.len() != expected {
+                return Err(Error::ColumnLengthMismatch(field_index));
+            }
+            if present.is_empty() {
+                for (row, value) in rows.iter_mut().zip(values) {
+                    row[field_index as usize] = Some(value);
+                }
+            } else {
+                for (sample, value) in present.into_iter().zip(values) {
+                    let row = rows
+                        .get_mut(sample as usize)
+                        .ok_or(Error::ColumnLengthMismatch(field_index))?;
+                    row[field_index as usize] = Some(value);
+                }
+            }
+        }
+
+        let name = intern(self.name);
+        Ok(self
+            .time_ns
+            .into_iter()
+            .zip(rows)
+            .map(|(time_ns, values)| ipc::TraceEvent {
+                time_ns,
+                name: name.clone(),
+                fields: ipc::TraceEventFields::Indexed {
+                    schema: fields.clone(),
+                    values,
+                },
+            })
+            .collect())
+    }
+}
+
+// ===== TraceActionSchema =====
+impl From<ipc::TraceActionSchema> for super::TraceActionSchema {
+    fn from(value: ipc::TraceActionSchema) -> Self {
+        Self {
+            name: value.name,
+            parameters: value
+                .parameters
+                .into_iter()
+                .map(|field| field.into())
+                .collect(),
+            returns: value
+                .returns
+                .into_iter()
+                .map(|field| field.into())
+                .collect(),
+        }
+    }
+}
+impl TryInto<ipc::TraceActionSchema> for super::TraceActionSchema {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceActionSchema, Self::Error> {
+        let fields = |fields: Vec<super::TraceEventFieldMetadata>| {
+            fields
+                .into_iter()
+                .map(|field| field.try_into())
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(ipc::TraceActionSchema {
+            name: self.name,
+            parameters: fields(self.parameters)?,
+            returns: fields(self.returns)?,
+        })
+    }
+}
+
+// ===== TraceActionResult =====
+impl From<ipc::TraceActionResult> for super::TraceActionResult {
+    fn from(value: ipc::TraceActionResult) -> Self {
+        let (values, error) = match value.result {
+            Ok(values) => (values_into_entries(values), None),
+            Err(error) => (Vec::new(), Some(error)),
+        };
+        Self {
+            call_id: value.call_id.into_bytes().to_vec(),
+            action_name: value.action_name,
+            values,
+            error,
+        }
+    }
+}
+impl TryInto<ipc::TraceActionResult> for super::TraceActionResult {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceActionResult, Self::Error> {
+        let result = match self.error {
+            Some(error) => Err(error),
+            None => Ok(values_from_entries(self.values)?),
+        };
+        Ok(ipc::TraceActionResult {
+            call_id: Uuid::from_slice(&self.call_id)?,
+            action_name: self.action_name,
+            result,
+        })
+    }
+}
+
+// ===== TraceSessionStart =====
+impl From<ipc::TraceSessionStart> for super::TraceSessionStart {
+    fn from(value: ipc::TraceSessionStart) -> Self {
+        Self {
+            session_id: value.session_id.into_bytes().to_vec(),
+            time_ns: value.time_ns,
+            name: value.name,
+            attributes: value.attributes,
+            join_attributes: value.join_attributes,
+        }
+    }
+}
+impl TryInto<ipc::TraceSessionStart> for super::TraceSessionStart {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceSessionStart, Self::Error> {
+        Ok(ipc::TraceSessionStart {
+            session_id: Uuid::from_slice(&self.session_id)?,
+            time_ns: self.time_ns,
+            name: self.name,
+            attributes: self.attributes,
+            join_attributes: self.join_attributes,
+        })
+    }
+}
+
+// ===== TraceSessionEnd =====
+impl From<ipc::TraceSessionEnd> for super::TraceSessionEnd {
+    fn from(value: ipc::TraceSessionEnd) -> Self {
+        Self {
+            session_id: value.session_id.into_bytes().to_vec(),
+            time_ns: value.time_ns,
+        }
+    }
+}
+impl TryInto<ipc::TraceSessionEnd> for super::TraceSessionEnd {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceSessionEnd, Self::Error> {
+        Ok(ipc::TraceSessionEnd {
+            session_id: Uuid::from_slice(&self.session_id)?,
+            time_ns: self.time_ns,
+        })
+    }
+}
+
+// ===== TraceSessionJoin =====
+impl From<ipc::TraceSessionJoin> for super::TraceSessionJoin {
+    fn from(value: ipc::TraceSessionJoin) -> Self {
+        Self {
+            session_id: value.session_id.into_bytes().to_vec(),
+        }
+    }
+}
+impl TryInto<ipc::TraceSessionJoin> for super::TraceSessionJoin {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceSessionJoin, Self::Error> {
+        Ok(ipc::TraceSessionJoin {
+            session_id: Uuid::from_slice(&self.session_id)?,
+        })
+    }
+}
+
+// ===== TraceAnnotation =====
+impl From<ipc::TraceAnnotation> for super::TraceAnnotation {
+    fn from(value: ipc::TraceAnnotation) -> Self {
+        Self {
+            annotation_id: value.annotation_id.into_bytes().to_vec(),
+            start_time_ns: value.start_time_ns,
+            end_time_ns: value.end_time_ns,
+            text: value.text,
+            tags: value.tags,
+            author: value.author,
+        }
+    }
+}
+impl TryInto<ipc::TraceAnnotation> for super::TraceAnnotation {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceAnnotation, Self::Error> {
+        Ok(ipc::TraceAnnotation {
+            annotation_id: Uuid::from_slice(&self.annotation_id)?,
+            start_time_ns: self.start_time_ns,
+            end_time_ns: self.end_time_ns,
+            text: self.text,
+            tags: self.tags,
+            author: self.author,
+        })
+    }
+}
+
+// ===== LogLevel =====
+impl From<ipc::LogLevel> for super::LogLevel {
+    fn from(value: ipc::LogLevel) -> Self {
+        match value {
+            ipc::LogLevel::Trace => Self::Trace,
+            ipc::LogLevel::Debug => Self::Debug,
+            ipc::LogLevel::Info => Self::Info,
+            ipc::LogLevel::Warn => Self::Warn,
+            ipc::LogLevel::Error => Self::Error,
+        }
+    }
+}
+impl TryInto<ipc::LogLevel> for super::LogLevel {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::LogLevel, Error> {
+        match self {
+            Self::Trace => Ok(ipc::LogLevel::Trace),
+            Self::Debug => Ok(ipc::LogLevel::Debug),
+            Self::Info => Ok(ipc::LogLevel::Info),
+            Self::Warn => Ok(ipc::LogLevel::Warn),
+            Self::Error => Ok(ipc::LogLevel::Error),
+            Self::Unspecified => Err(Error::MissingLogLevel),
+        }
+    }
+}
+
+// ===== TraceLog =====
+impl From<ipc::TraceLog> for super::TraceLog {
+    fn from(value: ipc::TraceLog) -> Self {
+        let level: super::LogLevel = value.level.into();
+        Self {
+            time_ns: value.time_ns,
+            level: level.into(),
+            target: value.target,
+            message: value.message,
+            fields: values_into_entries(value.fields),
+        }
+    }
+}
+impl TryInto<ipc::TraceLog> for super::TraceLog {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceLog, Self::Error> {
+        let level = self.level().try_into()?;
+        Ok(ipc::TraceLog {
+            time_ns: self.time_ns,
+            level,
+            target: self.target,
+            message: self.message,
+            fields: values_from_entries(self.fields)?,
+        })
+    }
+}
+
+// ===== TraceIntervalBegin =====
+impl From<ipc::TraceIntervalBegin> for super::TraceIntervalBegin {
+    fn from(value: ipc::TraceIntervalBegin) -> Self {
+        Self {
+            interval_id: value.interval_id.into_bytes().to_vec(),
+            parent_id: value.parent_id.map(|id| id.into_bytes().to_vec()),
+            time_ns: value.time_ns,
+            name: value.name,
+            fields: values_into_entries(value.fields),
+        }
+    }
+}
+impl TryInto<ipc::TraceIntervalBegin> for super::TraceIntervalBegin {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceIntervalBegin, Self::Error> {
+        Ok(ipc::TraceIntervalBegin {
+            interval_id: Uuid::from_slice(&self.interval_id)?,
+            parent_id: self.parent_id.map(|id| Uuid::from_slice(&id)).transpose()?,
+            time_ns: self.time_ns,
+            name: self.name,
+            fields: values_from_entries(self.fields)?,
+        })
+    }
+}
+
+// ===== TraceIntervalEnd =====
+impl From<ipc::TraceIntervalEnd> for super::TraceIntervalEnd {
+    fn from(value: ipc::TraceIntervalEnd) -> Self {
+        Self {
+            interval_id: value.interval_id.into_bytes().to_vec(),
+            time_ns: value.time_ns,
+            name: value.name,
+            fields: values_into_entries(value.fields),
+        }
+    }
+}
+impl TryInto<ipc::TraceIntervalEnd> for super::TraceIntervalEnd {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceIntervalEnd, Self::Error> {
+        Ok(ipc::TraceIntervalEnd {
+            interval_id: Uuid::from_slice(&self.interval_id)?,
+            time_ns: self.time_ns,
+            name: self.name,
+            fields: values_from_entries(self.fields)?,
+        })
+    }
+}
+
+// ===== TraceActionCall =====
+impl From<ipc::TraceActionCall> for super::TraceActionCall {
+    fn from(value: ipc::TraceActionCall) -> Self {
+        Self {
+            call_id: value.call_id.into_bytes().to_vec(),
+            action_name: value.action_name,
+            arguments: values_into_entries(value.arguments),
+            segment_id: value.segment_id.map(|id| id.into_bytes().to_vec()),
+        }
+    }
+}
+impl TryInto<ipc::TraceActionCall> for super::TraceActionCall {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceActionCall, Self::Error> {
+        Ok(ipc::TraceActionCall {
+            call_id: Uuid::from_slice(&self.call_id)?,
+            action_name: self.action_name,
+            arguments: values_from_entries(self.arguments)?,
+            segment_id: self
+                .segment_id
+                .map(|id| Uuid::from_slice(&id))
+                .transpose()?,
+        })
+    }
+}
+
+// ===== TraceControl =====
+impl From<ipc::TraceControl> for super::TraceControl {
+    fn from(value: ipc::TraceControl) -> Self {
+        Self {
+            source_name: value.source_name,
+            cmd: Some(match value.command {
+                ipc::TraceControlCommand::SetEventEnabled {
+                    event_name,
+                    enabled,
+                } => super::trace_control::Cmd::SetEventEnabled(super::SetEventEnabled {
+                    event_name,
+                    enabled,
+                }),
+                ipc::TraceControlCommand::SetSampleRate {
+                    event_name,
+                    max_rate_hz,
+                } => super::trace_control::Cmd::SetSampleRate(super::SetSampleRate {
+                    event_name,
+                    max_rate_hz,
+                }),
+                ipc::TraceControlCommand::ResendMetadata => {
+                    super::trace_control::Cmd::ResendMetadata(super::ResendMetadata {})
+                }
+                ipc::TraceControlCommand::CallAction(call) => {
+                    super::trace_control::Cmd::CallAction(call.into())
+                }
+                ipc::TraceControlCommand::SetParameter {
+                    name,
+                    value,
+                    call_id,
+                    segment_id,
+                } => super::trace_control::Cmd::SetParameter(super::SetParameter {
+                    name,
+                    value: Some(value.into()),
+                    call_id: call_id.map(|id| id.into_bytes().to_vec()),
+                    segment_id: segment_id.map(|id| id.into_bytes().to_vec()),
+                }),
+            }),
+        }
+    }
+}
+impl TryInto<ipc::TraceControl> for super::TraceControl {
+    type Error = Error;
+
+    fn try_into(self) -> Result<ipc::TraceControl, Self::Error> {
+        let command = match self.cmd.ok_or(Error::MissingOneOf)? {
+            super::trace_control::Cmd::SetEventEnabled(cmd) => {
+                ipc::TraceControlCommand::SetEventEnabled {
+                    event_name: cmd.event_name,
+                    enabled: cmd.enabled,
+                }
+            }
+            super::trace_control::Cmd::SetSampleRate(cmd) => {
+                ipc::TraceControlCommand::SetSampleRate {
+                    event_name: cmd.event_name,
+                    max_rate_hz: cmd.max_rate_hz,
+                }
+            }
+            super::trace_control::Cmd::ResendMetadata(_) => {
+                ipc::TraceControlCommand::ResendMetadata
+            }
+            super::trace_control::Cmd::CallAction(call) => {
+                ipc::TraceControlCommand::CallAction(call.try_into()?)
+            }
+            super::trace_control::Cmd::SetParameter(cmd) => {
+                ipc::TraceControlCommand::SetParameter {
+                    name: cmd.name,
+                    value: cmd.value.ok_or(Error::MissingValue)?.try_into()?,
+                    call_id: cmd.call_id.map(|id| Uuid::from_slice(&id)).transpose()?,
+                    segment_id: cmd.segment_id.map(|id| Uuid::from_slice(&id)).transpose()?,
+                }
+            }
+        };
+        Ok(ipc::TraceControl {
+            source_name: self.source_name,
+            command,
+        })
+    }
+}
+
 // ===== TraceMessage =====
 impl From<ipc::IpcMessageWithId> for super::TraceMessage {
     fn from(value: ipc::IpcMessageWithId) -> Self {
         Self {
             segment_id: value.segment_id.into_bytes().to_vec(),
-            source_name: value.source_name,
+            source_name: value.source_name.to_string(),
             msg: Some(match value.msg {
                 ipc::IpcMessage::TraceEvent(event) => {
                     super::trace_message::Msg::Event(event.into())
@@ -289,6 +1057,31 @@ impl From<ipc::IpcMessageWithId> for super::TraceMessage {
                 ipc::IpcMessage::TraceEventFieldNamedValues(field_named_values) => {
                     super::trace_message::Msg::EventFieldNamedValues(field_named_values.into())
                 }
+                ipc::IpcMessage::TraceActionSchema(action_schema) => {
+                    super::trace_message::Msg::ActionSchema(action_schema.into())
+                }
+                ipc::IpcMessage::TraceActionResult(action_result) => {
+                    super::trace_message::Msg::ActionResult(action_result.into())
+                }
+                ipc::IpcMessage::TraceSessionStart(session_start) => {
+                    super::trace_message::Msg::SessionStart(session_start.into())
+                }
+                ipc::IpcMessage::TraceSessionEnd(session_end) => {
+                    super::trace_message::Msg::SessionEnd(session_end.into())
+                }
+                ipc::IpcMessage::TraceSessionJoin(session_join) => {
+                    super::trace_message::Msg::SessionJoin(session_join.into())
+                }
+                ipc::IpcMessage::Annotation(annotation) => {
+                    super::trace_message::Msg::Annotation(annotation.into())
+                }
+                ipc::IpcMessage::Log(log) => super::trace_message::Msg::Log(log.into()),
+                ipc::IpcMessage::TraceIntervalBegin(interval_begin) => {
+                    super::trace_message::Msg::IntervalBegin(interval_begin.into())
+                }
+                ipc::IpcMessage::TraceIntervalEnd(interval_end) => {
+                    super::trace_message::Msg::IntervalEnd(interval_end.into())
+                }
             }),
         }
     }
@@ -298,7 +1091,7 @@ impl TryInto<ipc::IpcMessageWithId> for super::TraceMessage {
 
     fn try_into(self) -> Result<ipc::IpcMessageWithId, Self::Error> {
         let segment_id = Uuid::from_slice(&self.segment_id)?;
-        let source_name = self.source_name;
+        let source_name = intern(self.source_name);
         let msg = match self.msg.ok_or(Error::MissingMessage)? {
             super::trace_message::Msg::SegmentStart(trace_segment_start) => {
                 ipc::IpcMessage::TraceSegmentStart(trace_segment_start.into())
@@ -317,6 +1110,34 @@ impl TryInto<ipc::IpcMessageWithId> for super::TraceMessage {
             super::trace_message::Msg::Event(trace_event) => {
                 ipc::IpcMessage::TraceEvent(trace_event.try_into()?)
             }
+            super::trace_message::Msg::EventBatch(_) => {
+                return Err(Error::EventBatchWithoutSchema);
+            }
+            super::trace_message::Msg::ActionSchema(action_schema) => {
+                ipc::IpcMessage::TraceActionSchema(action_schema.try_into()?)
+            }
+            super::trace_message::Msg::ActionResult(action_result) => {
+                ipc::IpcMessage::TraceActionResult(action_result.try_into()?)
+            }
+            super::trace_message::Msg::SessionStart(session_start) => {
+                ipc::IpcMessage::TraceSessionStart(session_start.try_into()?)
+            }
+            super::trace_message::Msg::SessionEnd(session_end) => {
+                ipc::IpcMessage::TraceSessionEnd(session_end.try_into()?)
+            }
+            super::trace_message::Msg::SessionJoin(session_join) => {
+                ipc::IpcMessage::TraceSessionJoin(session_join.try_into()?)
+            }
+            super::trace_message::Msg::Annotation(annotation) => {
+                ipc::IpcMessage::Annotation(annotation.try_into()?)
+            }
+            super::trace_message::Msg::Log(log) => ipc::IpcMessage::Log(log.try_into()?),
+            super::trace_message::Msg::IntervalBegin(interval_begin) => {
+                ipc::IpcMessage::TraceIntervalBegin(interval_begin.try_into()?)
+            }
+            super::trace_message::Msg::IntervalEnd(interval_end) => {
+                ipc::IpcMessage::TraceIntervalEnd(interval_end.try_into()?)
+            }
         };
         Ok(ipc::IpcMessageWithId {
             segment_id,
@@ -331,6 +1152,7 @@ impl From<super::TraceSegmentStart> for ipc::TraceSegmentStart {
         ipc::TraceSegmentStart {
             time_ns: val.time_ns,
             source_name: val.source_name,
+            attributes: val.attributes,
         }
     }
 }
@@ -342,3 +1164,33 @@ impl From<super::TraceSegmentEnd> for ipc::TraceSegmentEnd {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_names_are_interned() -> Result<(), Error> {
+        let message = |time_ns| super::super::TraceMessage {
+            segment_id: Uuid::now_v7().into_bytes().to_vec(),
+            source_name: "rig".to_string(),
+            msg: Some(super::super::trace_message::Msg::Event(
+                super::super::TraceEvent {
+                    time_ns,
+                    name: "motor".to_string(),
+                    fields: Vec::new(),
+                },
+            )),
+        };
+        let first: ipc::IpcMessageWithId = message(1).try_into()?;
+        let second: ipc::IpcMessageWithId = message(2).try_into()?;
+        assert!(Arc::ptr_eq(&first.source_name, &second.source_name));
+        let (ipc::IpcMessage::TraceEvent(first), ipc::IpcMessage::TraceEvent(second)) =
+            (first.msg, second.msg)
+        else {
+            panic!("Expected events");
+        };
+        assert!(Arc::ptr_eq(&first.name, &second.name));
+        Ok(())
+    }
+}
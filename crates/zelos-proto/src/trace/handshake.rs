@@ -0,0 +1,93 @@
+use zelos_trace_types::ipc::IpcMessage;
+
+use super::{Capability, Handshake};
+use crate::error::Error;
+
+/// Protocol version spoken by this crate. Version 2 added the handshake and event batches, version 3 the action,
+/// session, annotation, log and interval messages and schema versions.
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// Oldest protocol version this crate still accepts from peers
+pub const MIN_PROTOCOL_VERSION: u32 = 1;
+
+/// Protocol version of peers that do not send a handshake
+pub const LEGACY_PROTOCOL_VERSION: u32 = 1;
+
+/// First protocol version with messages beyond segments, schemas, value tables and events
+const EXTENDED_MESSAGES_PROTOCOL_VERSION: u32 = 3;
+
+impl Handshake {
+    /// Handshake advertising this crate's protocol versions and `capabilities`
+    pub fn local(capabilities: impl IntoIterator<Item = Capability>) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            min_protocol_version: MIN_PROTOCOL_VERSION,
+            capabilities: capabilities.into_iter().map(|c| c.into()).collect(),
+        }
+    }
+
+    /// What is assumed about a peer that does not send a handshake: it speaks version 1, which always had acks
+    pub fn legacy() -> Self {
+        Self {
+            protocol_version: LEGACY_PROTOCOL_VERSION,
+            min_protocol_version: LEGACY_PROTOCOL_VERSION,
+            capabilities: vec![Capability::Acks.into()],
+        }
+    }
+
+    pub fn has(&self, capability: Capability) -> bool {
+        self.capabilities.contains(&capability.into())
+    }
+
+    /// Whether a peer speaking the agreed protocol version can decode `msg`. Older peers fail their stream on message
+    /// types they do not know, so these must not be sent to them.
+    pub fn supports(&self, msg: &IpcMessage) -> bool {
+        match msg {
+            IpcMessage::TraceSegmentStart(_)
+            | IpcMessage::TraceSegmentEnd(_)
+            | IpcMessage::TraceEventSchema(_)
+            | IpcMessage::TraceEventFieldNamedValues(_)
+            | IpcMessage::TraceEvent(_) => true,
+            IpcMessage::TraceActionSchema(_)
+            | IpcMessage::TraceActionResult(_)
+            | IpcMessage::TraceSessionStart(_)
+            | IpcMessage::TraceSessionEnd(_)
+            | IpcMessage::TraceSessionJoin(_)
+            | IpcMessage::Annotation(_)
+            | IpcMessage::Log(_)
+            | IpcMessage::TraceIntervalBegin(_)
+            | IpcMessage::TraceIntervalEnd(_) => {
+                self.protocol_version >= EXTENDED_MESSAGES_PROTOCOL_VERSION
+            }
+        }
+    }
+
+    /// Agree on the newest protocol version and the capabilities both sides support, failing if the peer's versions
+    /// do not overlap ours
+    pub fn negotiate(&self, peer: &Handshake) -> Result<Handshake, Error> {
+        // A peer that does not set a minimum only speaks its own version
+        let peer_min = match peer.min_protocol_version {
+            0 => peer.protocol_version,
+            min => min,
+        };
+        let protocol_version = self.protocol_version.min(peer.protocol_version);
+        if protocol_version < self.min_protocol_version.max(peer_min) {
+            return Err(Error::IncompatibleProtocol {
+                local_min: self.min_protocol_version,
+                local: self.protocol_version,
+                peer_min,
+                peer: peer.protocol_version,
+            });
+        }
+
+        Ok(Handshake {
+            protocol_version,
+            min_protocol_version: protocol_version,
+            capabilities: self
+                .capabilities()
+                .filter(|&capability| capability != Capability::Unspecified && peer.has(capability))
+                .map(|capability| capability.into())
+                .collect(),
+        })
+    }
+}
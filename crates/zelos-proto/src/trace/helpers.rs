@@ -1,6 +1,8 @@
 use zelos_trace_types::ipc;
 
-use super::TraceMessageBatch;
+use super::{
+    Handshake, SubscribeCommandAck, SubscribeCommandError, SubscribeCommandKind, TraceMessageBatch,
+};
 use crate::error::Error;
 
 impl super::SubscribeResponse {
@@ -12,11 +14,48 @@ impl super::SubscribeResponse {
         }
     }
 
+    pub fn command_ack(kind: SubscribeCommandKind, filter: Option<String>) -> Self {
+        Self {
+            msg: Some(super::subscribe_response::Msg::CommandAck(
+                SubscribeCommandAck {
+                    kind: kind.into(),
+                    filter,
+                },
+            )),
+        }
+    }
+
+    pub fn command_error(
+        kind: SubscribeCommandKind,
+        filter: Option<String>,
+        message: String,
+    ) -> Self {
+        Self {
+            msg: Some(super::subscribe_response::Msg::CommandError(
+                SubscribeCommandError {
+                    kind: kind.into(),
+                    filter,
+                    message,
+                },
+            )),
+        }
+    }
+
+    pub fn handshake(handshake: Handshake) -> Self {
+        Self {
+            msg: Some(super::subscribe_response::Msg::Handshake(handshake)),
+        }
+    }
+
+    /// Converts the trace messages in this response, responses without trace messages convert to an empty vec
     pub fn as_ipc(self) -> Result<Vec<ipc::IpcMessageWithId>, Error> {
         match self.msg {
             Some(super::subscribe_response::Msg::TraceMessageBatch(msg)) => {
                 msg.messages.into_iter().map(|msg| msg.try_into()).collect()
             }
+            Some(super::subscribe_response::Msg::CommandAck(_))
+            | Some(super::subscribe_response::Msg::CommandError(_))
+            | Some(super::subscribe_response::Msg::Handshake(_)) => Ok(Vec::new()),
             None => Err(Error::MissingMessage),
         }
     }
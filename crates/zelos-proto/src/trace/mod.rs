@@ -1,5 +1,12 @@
 // Include the autogenerated proto definition
 tonic::include_proto!("zeloscloud.trace");
 
+/// Encoded descriptors of the `zeloscloud.trace` protos, for gRPC server reflection
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    tonic::include_file_descriptor_set!("zeloscloud_trace_descriptor");
+
 mod from_into;
+mod handshake;
 mod helpers;
+
+pub use handshake::{LEGACY_PROTOCOL_VERSION, MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
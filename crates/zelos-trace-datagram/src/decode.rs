@@ -0,0 +1,295 @@
+use crate::{
+    Error, FieldDef, FieldType, FieldValue, HEADER_LEN, KIND_EVENT, KIND_REGISTER,
+    KIND_REGISTER_ACK, KIND_SEGMENT_END, MAGIC, VERSION,
+};
+
+#[derive(Debug, Clone)]
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], Error> {
+        if self.buf.len() < len {
+            return Err(Error::Truncated);
+        }
+        let (head, tail) = self.buf.split_at(len);
+        self.buf = tail;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut out = [0; N];
+        out.copy_from_slice(self.bytes(N)?);
+        Ok(out)
+    }
+
+    fn u8(&mut self) -> Result<u8, Error> {
+        Ok(self.array::<1>()?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, Error> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn i64(&mut self) -> Result<i64, Error> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn str(&mut self, len: usize) -> Result<&'a str, Error> {
+        core::str::from_utf8(self.bytes(len)?).map_err(|_| Error::InvalidUtf8)
+    }
+
+    fn name(&mut self) -> Result<&'a str, Error> {
+        let len = self.u8()? as usize;
+        self.str(len)
+    }
+
+    fn long_bytes(&mut self) -> Result<&'a [u8], Error> {
+        let len = self.u16()? as usize;
+        self.bytes(len)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Header {
+    pub segment_id: [u8; 16],
+    pub sequence: u32,
+}
+
+#[derive(Debug, Clone)]
+pub enum Packet<'a> {
+    Register {
+        schema_id: u16,
+        source_name: &'a str,
+        event_name: &'a str,
+        fields: FieldDefs<'a>,
+    },
+    RegisterAck {
+        schema_id: u16,
+    },
+    Event {
+        schema_id: u16,
+        time_ns: i64,
+        values: Values<'a>,
+    },
+    SegmentEnd {
+        time_ns: i64,
+    },
+}
+
+/// The fields of a registration, decoded as they are iterated
+#[derive(Debug, Clone)]
+pub struct FieldDefs<'a> {
+    reader: Reader<'a>,
+    remaining: u8,
+}
+
+impl FieldDefs<'_> {
+    pub fn len(&self) -> usize {
+        self.remaining as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+impl<'a> Iterator for FieldDefs<'a> {
+    type Item = Result<FieldDef<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let field = (|| {
+            let name = self.reader.name()?;
+            let field_type = FieldType::try_from(self.reader.u8()?)?;
+            let unit = self.reader.name()?;
+            Ok(FieldDef {
+                name,
+                field_type,
+                unit: (!unit.is_empty()).then_some(unit),
+            })
+        })();
+        if field.is_err() {
+            self.remaining = 0;
+        }
+        Some(field)
+    }
+}
+
+/// The packed values of an event. They carry no type information, so they are read with the field types of the
+/// registered schema.
+#[derive(Debug, Clone)]
+pub struct Values<'a> {
+    reader: Reader<'a>,
+}
+
+impl<'a> Values<'a> {
+    pub fn next_value(&mut self, field_type: FieldType) -> Result<FieldValue<'a>, Error> {
+        let r = &mut self.reader;
+        Ok(match field_type {
+            FieldType::Int8 => FieldValue::Int8(i8::from_le_bytes(r.array()?)),
+            FieldType::Int16 => FieldValue::Int16(i16::from_le_bytes(r.array()?)),
+            FieldType::Int32 => FieldValue::Int32(i32::from_le_bytes(r.array()?)),
+            FieldType::Int64 => FieldValue::Int64(r.i64()?),
+            FieldType::UInt8 => FieldValue::UInt8(r.u8()?),
+            FieldType::UInt16 => FieldValue::UInt16(r.u16()?),
+            FieldType::UInt32 => FieldValue::UInt32(u32::from_le_bytes(r.array()?)),
+            FieldType::UInt64 => FieldValue::UInt64(u64::from_le_bytes(r.array()?)),
+            FieldType::Float32 => FieldValue::Float32(f32::from_le_bytes(r.array()?)),
+            FieldType::Float64 => FieldValue::Float64(f64::from_le_bytes(r.array()?)),
+            FieldType::TimestampNs => FieldValue::TimestampNs(r.i64()?),
+            FieldType::Binary => FieldValue::Binary(r.long_bytes()?),
+            FieldType::String => {
+                let len = r.u16()? as usize;
+                FieldValue::String(r.str(len)?)
+            }
+            FieldType::Boolean => match r.u8()? {
+                0 => FieldValue::Boolean(false),
+                1 => FieldValue::Boolean(true),
+                _ => return Err(Error::TypeMismatch),
+            },
+        })
+    }
+
+    /// Whether every value has been read
+    pub fn is_empty(&self) -> bool {
+        self.reader.buf.is_empty()
+    }
+}
+
+/// Decode a datagram, borrowing names and values from it
+pub fn decode(datagram: &[u8]) -> Result<(Header, Packet<'_>), Error> {
+    if datagram.len() < HEADER_LEN {
+        return Err(Error::Truncated);
+    }
+    let mut r = Reader { buf: datagram };
+    if r.u16()? != MAGIC {
+        return Err(Error::BadMagic);
+    }
+    let version = r.u8()?;
+    if version != VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+    let kind = r.u8()?;
+    let header = Header {
+        segment_id: r.array()?,
+        sequence: u32::from_le_bytes(r.array()?),
+    };
+
+    let packet = match kind {
+        KIND_REGISTER => {
+            let schema_id = r.u16()?;
+            let source_name = r.name()?;
+            let event_name = r.name()?;
+            let remaining = r.u8()?;
+            Packet::Register {
+                schema_id,
+                source_name,
+                event_name,
+                fields: FieldDefs {
+                    reader: r,
+                    remaining,
+                },
+            }
+        }
+        KIND_REGISTER_ACK => Packet::RegisterAck {
+            schema_id: r.u16()?,
+        },
+        KIND_EVENT => Packet::Event {
+            schema_id: r.u16()?,
+            time_ns: r.i64()?,
+            values: Values { reader: r },
+        },
+        KIND_SEGMENT_END => Packet::SegmentEnd { time_ns: r.i64()? },
+        kind => return Err(Error::UnknownKind(kind)),
+    };
+    Ok((header, packet))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{encode_register_ack, Encoder, MAX_DATAGRAM_LEN};
+
+    #[test]
+    fn test_roundtrip() -> Result<(), Error> {
+        let mut encoder = Encoder::new([7; 16]);
+        let mut buf = [0; MAX_DATAGRAM_LEN];
+
+        let fields = [
+            FieldDef {
+                name: "rpm",
+                field_type: FieldType::Float32,
+                unit: Some("rpm"),
+            },
+            FieldDef {
+                name: "state",
+                field_type: FieldType::String,
+                unit: None,
+            },
+            FieldDef {
+                name: "ok",
+                field_type: FieldType::Boolean,
+                unit: None,
+            },
+        ];
+        let datagram = encoder.register(&mut buf, 3, "rig", "motor", &fields)?;
+        let (header, packet) = decode(datagram)?;
+        assert_eq!(header.sequence, 0);
+        let Packet::Register {
+            schema_id: 3,
+            source_name: "rig",
+            event_name: "motor",
+            fields: decoded,
+        } = packet
+        else {
+            panic!("Expected a registration, got {:?}", packet);
+        };
+        assert_eq!(decoded.len(), 3);
+        for (decoded, field) in decoded.zip(fields) {
+            assert_eq!(decoded?, field);
+        }
+
+        let values = [
+            FieldValue::Float32(1.5),
+            FieldValue::String("spinning"),
+            FieldValue::Boolean(true),
+        ];
+        let datagram = encoder.event(&mut buf, 3, 42, &values)?;
+        let (header, packet) = decode(datagram)?;
+        assert_eq!(header.segment_id, [7; 16]);
+        assert_eq!(header.sequence, 1);
+        let Packet::Event {
+            schema_id: 3,
+            time_ns: 42,
+            values: mut decoded,
+        } = packet
+        else {
+            panic!("Expected an event, got {:?}", packet);
+        };
+        for value in values {
+            assert_eq!(decoded.next_value(value.field_type())?, value);
+        }
+        assert!(decoded.is_empty());
+
+        // Running out of space does not consume a sequence number
+        assert_eq!(
+            encoder.event(&mut buf[..30], 3, 0, &values).err(),
+            Some(Error::BufferTooSmall)
+        );
+        let (header, _) = decode(encoder.end(&mut buf, 50)?)?;
+        assert_eq!(header.sequence, 2);
+
+        let (_, packet) = decode(encode_register_ack(&mut buf, &[7; 16], 3)?)?;
+        assert!(matches!(packet, Packet::RegisterAck { schema_id: 3 }));
+
+        assert_eq!(decode(&buf[..10]).err(), Some(Error::Truncated));
+        buf[0] = 0;
+        assert_eq!(decode(&buf).err(), Some(Error::BadMagic));
+        Ok(())
+    }
+}
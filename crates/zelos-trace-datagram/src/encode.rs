@@ -0,0 +1,218 @@
+use crate::{
+    Error, FieldType, KIND_EVENT, KIND_REGISTER, KIND_REGISTER_ACK, KIND_SEGMENT_END, MAGIC,
+    VERSION,
+};
+
+/// A field of an event schema
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldDef<'a> {
+    pub name: &'a str,
+    pub field_type: FieldType,
+    pub unit: Option<&'a str>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FieldValue<'a> {
+    Int8(i8),
+    Int16(i16),
+    Int32(i32),
+    Int64(i64),
+    UInt8(u8),
+    UInt16(u16),
+    UInt32(u32),
+    UInt64(u64),
+    Float32(f32),
+    Float64(f64),
+    TimestampNs(i64),
+    Binary(&'a [u8]),
+    String(&'a str),
+    Boolean(bool),
+}
+
+impl FieldValue<'_> {
+    pub fn field_type(&self) -> FieldType {
+        match self {
+            FieldValue::Int8(_) => FieldType::Int8,
+            FieldValue::Int16(_) => FieldType::Int16,
+            FieldValue::Int32(_) => FieldType::Int32,
+            FieldValue::Int64(_) => FieldType::Int64,
+            FieldValue::UInt8(_) => FieldType::UInt8,
+            FieldValue::UInt16(_) => FieldType::UInt16,
+            FieldValue::UInt32(_) => FieldType::UInt32,
+            FieldValue::UInt64(_) => FieldType::UInt64,
+            FieldValue::Float32(_) => FieldType::Float32,
+            FieldValue::Float64(_) => FieldType::Float64,
+            FieldValue::TimestampNs(_) => FieldType::TimestampNs,
+            FieldValue::Binary(_) => FieldType::Binary,
+            FieldValue::String(_) => FieldType::String,
+            FieldValue::Boolean(_) => FieldType::Boolean,
+        }
+    }
+}
+
+struct Writer<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn bytes(&mut self, v: &[u8]) -> Result<(), Error> {
+        let end = self.pos + v.len();
+        self.buf
+            .get_mut(self.pos..end)
+            .ok_or(Error::BufferTooSmall)?
+            .copy_from_slice(v);
+        self.pos = end;
+        Ok(())
+    }
+
+    fn u8(&mut self, v: u8) -> Result<(), Error> {
+        self.bytes(&[v])
+    }
+
+    fn u16(&mut self, v: u16) -> Result<(), Error> {
+        self.bytes(&v.to_le_bytes())
+    }
+
+    fn i64(&mut self, v: i64) -> Result<(), Error> {
+        self.bytes(&v.to_le_bytes())
+    }
+
+    fn name(&mut self, v: &str) -> Result<(), Error> {
+        let len: u8 = v.len().try_into().map_err(|_| Error::TooLong)?;
+        self.u8(len)?;
+        self.bytes(v.as_bytes())
+    }
+
+    fn long_bytes(&mut self, v: &[u8]) -> Result<(), Error> {
+        let len: u16 = v.len().try_into().map_err(|_| Error::TooLong)?;
+        self.u16(len)?;
+        self.bytes(v)
+    }
+
+    fn header(&mut self, kind: u8, segment_id: &[u8; 16], sequence: u32) -> Result<(), Error> {
+        self.u16(MAGIC)?;
+        self.u8(VERSION)?;
+        self.u8(kind)?;
+        self.bytes(segment_id)?;
+        self.bytes(&sequence.to_le_bytes())
+    }
+
+    fn value(&mut self, value: &FieldValue) -> Result<(), Error> {
+        match *value {
+            FieldValue::Int8(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Int16(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Int32(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Int64(v) | FieldValue::TimestampNs(v) => self.i64(v),
+            FieldValue::UInt8(v) => self.u8(v),
+            FieldValue::UInt16(v) => self.u16(v),
+            FieldValue::UInt32(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::UInt64(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Float32(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Float64(v) => self.bytes(&v.to_le_bytes()),
+            FieldValue::Binary(v) => self.long_bytes(v),
+            FieldValue::String(v) => self.long_bytes(v.as_bytes()),
+            FieldValue::Boolean(v) => self.u8(v as u8),
+        }
+    }
+
+    fn finish(self) -> &'a [u8] {
+        let Writer { buf, pos } = self;
+        &buf[..pos]
+    }
+}
+
+/// Encodes the datagrams of one segment, numbering them so the receiver can detect loss
+#[derive(Debug, Clone)]
+pub struct Encoder {
+    segment_id: [u8; 16],
+    sequence: u32,
+}
+
+impl Encoder {
+    /// `segment_id` should be unique for every run of the device, e.g. a UUID derived from a boot counter
+    pub const fn new(segment_id: [u8; 16]) -> Self {
+        Self {
+            segment_id,
+            sequence: 0,
+        }
+    }
+
+    pub fn segment_id(&self) -> &[u8; 16] {
+        &self.segment_id
+    }
+
+    /// Encode a datagram, only consuming a sequence number if it fits in `buf`
+    fn encode<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+        kind: u8,
+        payload: impl FnOnce(&mut Writer<'b>) -> Result<(), Error>,
+    ) -> Result<&'b [u8], Error> {
+        let mut writer = Writer { buf, pos: 0 };
+        writer.header(kind, &self.segment_id, self.sequence)?;
+        payload(&mut writer)?;
+        self.sequence = self.sequence.wrapping_add(1);
+        Ok(writer.finish())
+    }
+
+    /// Register an event schema under `schema_id`. Send it until the receiver acks it, events with an unregistered
+    /// schema id are dropped.
+    pub fn register<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+        schema_id: u16,
+        source_name: &str,
+        event_name: &str,
+        fields: &[FieldDef],
+    ) -> Result<&'b [u8], Error> {
+        let field_count: u8 = fields.len().try_into().map_err(|_| Error::TooLong)?;
+        self.encode(buf, KIND_REGISTER, |w| {
+            w.u16(schema_id)?;
+            w.name(source_name)?;
+            w.name(event_name)?;
+            w.u8(field_count)?;
+            for field in fields {
+                w.name(field.name)?;
+                w.u8(field.field_type as u8)?;
+                w.name(field.unit.unwrap_or(""))?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Encode an event, with one value per field of the schema in registration order. Use [`RECEIVE_TIME`] as
+    /// `time_ns` to have the receiver timestamp it.
+    ///
+    /// [`RECEIVE_TIME`]: crate::RECEIVE_TIME
+    pub fn event<'b>(
+        &mut self,
+        buf: &'b mut [u8],
+        schema_id: u16,
+        time_ns: i64,
+        values: &[FieldValue],
+    ) -> Result<&'b [u8], Error> {
+        self.encode(buf, KIND_EVENT, |w| {
+            w.u16(schema_id)?;
+            w.i64(time_ns)?;
+            values.iter().try_for_each(|value| w.value(value))
+        })
+    }
+
+    /// End the segment, after which the encoder should not be used
+    pub fn end<'b>(&mut self, buf: &'b mut [u8], time_ns: i64) -> Result<&'b [u8], Error> {
+        self.encode(buf, KIND_SEGMENT_END, |w| w.i64(time_ns))
+    }
+}
+
+/// Encode the receiver's answer to a registration
+pub fn encode_register_ack<'b>(
+    buf: &'b mut [u8],
+    segment_id: &[u8; 16],
+    schema_id: u16,
+) -> Result<&'b [u8], Error> {
+    let mut writer = Writer { buf, pos: 0 };
+    writer.header(KIND_REGISTER_ACK, segment_id, 0)?;
+    writer.u16(schema_id)?;
+    Ok(writer.finish())
+}
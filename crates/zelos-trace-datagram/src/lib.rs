@@ -0,0 +1,120 @@
+//! Compact datagram format for publishing trace events from devices that can send UDP but not gRPC.
+//!
+//! Every datagram starts with a 24 byte header: a `u16` magic, a version byte, a kind byte, the 16 byte segment id
+//! and a `u32` sequence number that the sender increments for every datagram of the segment, which lets the receiver
+//! count lost and reordered datagrams. Integers are little-endian and names are prefixed with a `u8` length.
+//!
+//! A device registers each event schema under a `u16` schema id with [`Encoder::register`] and resends the
+//! registration until the receiver answers with a [`Packet::RegisterAck`]. Events then carry only the schema id, a
+//! timestamp and the field values packed in schema order: numbers at their natural width, booleans as one byte, and
+//! strings and binary prefixed with a `u16` length.
+//!
+//! The crate is `no_std` and allocation free so it can run on microcontrollers; enable the `std` feature for
+//! [`std::error::Error`] impls.
+#![no_std]
+#![deny(clippy::expect_used, clippy::unwrap_used)]
+
+#[cfg(feature = "std")]
+extern crate std;
+
+mod decode;
+mod encode;
+
+pub use decode::{decode, FieldDefs, Header, Packet, Values};
+pub use encode::{encode_register_ack, Encoder, FieldDef, FieldValue};
+
+pub const MAGIC: u16 = 0x5a44;
+pub const VERSION: u8 = 1;
+pub const HEADER_LEN: usize = 24;
+
+/// Largest datagram that fits in a single Ethernet frame without IP fragmentation
+pub const MAX_DATAGRAM_LEN: usize = 1472;
+
+/// A timestamp of zero asks the receiver to stamp the message with its arrival time, for devices without a clock
+pub const RECEIVE_TIME: i64 = 0;
+
+pub(crate) const KIND_REGISTER: u8 = 1;
+pub(crate) const KIND_REGISTER_ACK: u8 = 2;
+pub(crate) const KIND_EVENT: u8 = 3;
+pub(crate) const KIND_SEGMENT_END: u8 = 4;
+
+/// Type of a field, using the same tags as the shared-memory codec
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum FieldType {
+    Int8 = 0,
+    Int16 = 1,
+    Int32 = 2,
+    Int64 = 3,
+    UInt8 = 4,
+    UInt16 = 5,
+    UInt32 = 6,
+    UInt64 = 7,
+    Float32 = 8,
+    Float64 = 9,
+    TimestampNs = 10,
+    Binary = 11,
+    String = 12,
+    Boolean = 13,
+}
+
+impl TryFrom<u8> for FieldType {
+    type Error = Error;
+
+    fn try_from(tag: u8) -> Result<Self, Error> {
+        Ok(match tag {
+            0 => FieldType::Int8,
+            1 => FieldType::Int16,
+            2 => FieldType::Int32,
+            3 => FieldType::Int64,
+            4 => FieldType::UInt8,
+            5 => FieldType::UInt16,
+            6 => FieldType::UInt32,
+            7 => FieldType::UInt64,
+            8 => FieldType::Float32,
+            9 => FieldType::Float64,
+            10 => FieldType::TimestampNs,
+            11 => FieldType::Binary,
+            12 => FieldType::String,
+            13 => FieldType::Boolean,
+            _ => return Err(Error::UnknownFieldType(tag)),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// The output buffer is too small for the datagram
+    BufferTooSmall,
+    /// A name is longer than 255 bytes, or a string or binary value longer than 65535 bytes
+    TooLong,
+    /// The datagram ended before the message did
+    Truncated,
+    /// The datagram does not start with [`MAGIC`]
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownKind(u8),
+    UnknownFieldType(u8),
+    /// A value does not match the type of its field
+    TypeMismatch,
+    InvalidUtf8,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Error::BufferTooSmall => write!(f, "Buffer too small for datagram"),
+            Error::TooLong => write!(f, "Name or value too long"),
+            Error::Truncated => write!(f, "Datagram truncated"),
+            Error::BadMagic => write!(f, "Not a zelos datagram"),
+            Error::UnsupportedVersion(v) => write!(f, "Unsupported datagram version {}", v),
+            Error::UnknownKind(k) => write!(f, "Unknown datagram kind {}", k),
+            Error::UnknownFieldType(t) => write!(f, "Unknown field type {}", t),
+            Error::TypeMismatch => write!(f, "Value does not match its field type"),
+            Error::InvalidUtf8 => write!(f, "Invalid UTF-8"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -1,4 +1,8 @@
-use std::{collections::HashMap, sync::LazyLock, time::Duration};
+use std::{
+    collections::HashMap,
+    sync::{Arc, LazyLock},
+    time::Duration,
+};
 
 use anyhow::{anyhow, Result};
 use tokio::task::JoinHandle;
@@ -8,15 +12,20 @@ use tonic::transport::Server;
 use tracing::level_filters::LevelFilter;
 use tracing_subscriber::{filter::Targets, layer::SubscriberExt, util::SubscriberInitExt, Layer};
 use uuid::Uuid;
-use zelos_trace::TraceRouter;
-use zelos_trace_grpc::publish::{
-    TracePublishClient, TracePublishClientConfig, TracePublishService,
+use zelos_trace::{TraceRouter, TraceSource};
+use zelos_trace_grpc::{
+    publish::{TracePublishClient, TracePublishClientConfig, TracePublishService},
+    BackoffConfig,
 };
 use zelos_trace_types::{
-    ipc::{IpcMessage, IpcMessageWithId, Sender},
-    Value,
+    ipc::{FieldKind, IpcMessage, IpcMessageWithId, Sender, TraceEventFieldMetadata},
+    DataType, Value,
 };
 
+// Count allocations alongside timings
+#[global_allocator]
+static ALLOC: divan::AllocProfiler = divan::AllocProfiler::system();
+
 static RUNTIME: LazyLock<tokio::runtime::Runtime> = LazyLock::new(|| {
     tokio::runtime::Builder::new_multi_thread()
         .worker_threads(4)
@@ -101,7 +110,12 @@ async fn run_server(
     Ok((format!("grpc://{}", addr), task_server))
 }
 
-fn create_test_message(name: String, seq: u64, fields_per_event: usize) -> IpcMessageWithId {
+fn create_test_message(
+    source_name: Arc<str>,
+    name: Arc<str>,
+    seq: u64,
+    fields_per_event: usize,
+) -> IpcMessageWithId {
     let mut fields = HashMap::new();
     fields.insert("seq".to_string(), Value::UInt64(seq));
 
@@ -113,12 +127,12 @@ fn create_test_message(name: String, seq: u64, fields_per_event: usize) -> IpcMe
     let message = IpcMessage::TraceEvent(zelos_trace_types::ipc::TraceEvent {
         time_ns: zelos_trace::time::now_time_ns(),
         name,
-        fields,
+        fields: fields.into(),
     });
 
     IpcMessageWithId {
         segment_id: Uuid::now_v7(),
-        source_name: "bench".to_string(),
+        source_name,
         msg: message,
     }
 }
@@ -144,7 +158,11 @@ mod router {
                 url: server_url,
                 batch_size: 1000,
                 batch_timeout: Duration::from_millis(100),
-                reconnect_delay: Duration::from_millis(100),
+                reconnect: BackoffConfig {
+                    initial_delay: Duration::from_millis(100),
+                    ..Default::default()
+                },
+                ..Default::default()
             };
 
             // Create the router
@@ -166,11 +184,17 @@ mod router {
 
             // Send messages
             let sender = router.sender();
-            let name = format!("bench-{}", config.name);
+            let source_name: Arc<str> = "bench".into();
+            let name: Arc<str> = format!("bench-{}", config.name).into();
             let fields_per_event = config.fields_per_event;
             let send_task = tokio::spawn(async move {
                 for i in 0..total_messages {
-                    let message = create_test_message(name.clone(), i as u64, fields_per_event);
+                    let message = create_test_message(
+                        source_name.clone(),
+                        name.clone(),
+                        i as u64,
+                        fields_per_event,
+                    );
                     sender
                         .send_async(message)
                         .await
@@ -236,6 +260,7 @@ mod direct {
                 .chunks_timeout(1000, Duration::from_millis(100))
                 .map(|m| zelos_proto::trace::PublishRequest {
                     trace_messages: m.into_iter().map(|msg| msg.into()).collect(),
+                    handshake: None,
                 });
 
             // Publish the stream to the server
@@ -249,11 +274,17 @@ mod direct {
             let total_messages = config.num_segments * config.events_per_segment;
 
             // Send messages
-            let name = format!("bench-{}", config.name);
+            let source_name: Arc<str> = "bench".into();
+            let name: Arc<str> = format!("bench-{}", config.name).into();
             let fields_per_event = config.fields_per_event;
             let send_task = tokio::spawn(async move {
                 for i in 0..total_messages {
-                    let message = create_test_message(name.clone(), i as u64, fields_per_event);
+                    let message = create_test_message(
+                        source_name.clone(),
+                        name.clone(),
+                        i as u64,
+                        fields_per_event,
+                    );
                     sender
                         .send_async(message)
                         .await
@@ -278,3 +309,70 @@ mod direct {
         divan::black_box(())
     }
 }
+
+/// Emitting a single event from a source, by field name and by schema index
+mod emit {
+    use super::*;
+
+    const FIELDS_PER_EVENT: &[usize] = &[5, 10, 20];
+
+    fn field_name(i: usize) -> String {
+        format!("field_{}", i)
+    }
+
+    fn source_event(
+        fields_per_event: usize,
+    ) -> (
+        TraceSource,
+        Arc<zelos_trace::source::TraceSourceEvent>,
+        flume::Receiver<IpcMessageWithId>,
+    ) {
+        let (sender, receiver) = flume::unbounded();
+        let source = TraceSource::new("bench", sender);
+        let event = source
+            .add_event(
+                "bench",
+                (0..fields_per_event).map(|i| TraceEventFieldMetadata {
+                    name: field_name(i),
+                    data_type: DataType::Int32,
+                    unit: None,
+                    kind: FieldKind::Gauge,
+                    display: Default::default(),
+                }),
+            )
+            .expect("Failed to add event");
+        // Drop the segment start and schema
+        receiver.drain();
+        (source, event, receiver)
+    }
+
+    #[divan::bench(args = FIELDS_PER_EVENT)]
+    fn named(bencher: divan::Bencher, fields_per_event: usize) {
+        let (_source, event, receiver) = source_event(fields_per_event);
+        bencher.bench_local(|| {
+            event
+                .emit(
+                    0,
+                    (0..fields_per_event).map(|i| (field_name(i), Value::Int32(i as i32))),
+                )
+                .expect("Failed to emit");
+            receiver.try_recv()
+        });
+    }
+
+    #[divan::bench(args = FIELDS_PER_EVENT)]
+    fn indexed(bencher: divan::Bencher, fields_per_event: usize) {
+        let (_source, event, receiver) = source_event(fields_per_event);
+        bencher.bench_local(|| {
+            event
+                .emit_values(
+                    0,
+                    (0..fields_per_event)
+                        .map(|i| Some(Value::Int32(i as i32)))
+                        .collect(),
+                )
+                .expect("Failed to emit");
+            receiver.try_recv()
+        });
+    }
+}
@@ -0,0 +1,65 @@
+use std::{collections::HashMap, time::Duration};
+
+use anyhow::{anyhow, Result};
+use tonic::transport::Channel;
+use uuid::Uuid;
+use zelos_proto::trace::{trace_action_client, CallActionRequest};
+use zelos_trace_types::Value;
+
+use crate::transport;
+
+/// Client for calling the actions of sources connected to a trace server
+#[derive(Debug, Clone)]
+pub struct TraceActionClient {
+    client: trace_action_client::TraceActionClient<Channel>,
+}
+
+impl TraceActionClient {
+    /// Connect to the action service at `url`
+    pub async fn connect(url: String) -> Result<Self> {
+        let channel = transport::connect(&url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to action service: {}", e))?;
+        Ok(Self {
+            client: trace_action_client::TraceActionClient::new(channel),
+        })
+    }
+
+    /// Call `action_name` on the source named `source_name`, returning the values of its result. The call runs on the
+    /// segment `segment_id`, or the only live segment of the source with the action if `None`. The server waits up to
+    /// `timeout` for the source to answer, or its default if `None`.
+    pub async fn call(
+        &mut self,
+        source_name: &str,
+        action_name: &str,
+        arguments: HashMap<String, Value>,
+        segment_id: Option<Uuid>,
+        timeout: Option<Duration>,
+    ) -> Result<HashMap<String, Value>> {
+        let response = self
+            .client
+            .call(CallActionRequest {
+                source_name: source_name.to_string(),
+                action_name: action_name.to_string(),
+                arguments: arguments.into_iter().map(|entry| entry.into()).collect(),
+                timeout_ms: timeout.map(|timeout| timeout.as_millis() as u64),
+                segment_id: segment_id.map(|id| id.into_bytes().to_vec()),
+            })
+            .await
+            .map_err(|e| {
+                anyhow!(
+                    "Failed to call {}/{}: {}",
+                    source_name,
+                    action_name,
+                    e.message()
+                )
+            })?
+            .into_inner();
+
+        Ok(response
+            .values
+            .into_iter()
+            .map(|entry| entry.try_into())
+            .collect::<Result<_, _>>()?)
+    }
+}
@@ -0,0 +1,6 @@
+mod client;
+mod service;
+
+pub use client::TraceActionClient;
+pub use service::TraceActionService;
+pub(crate) use service::{call_status, request_timeout};
@@ -0,0 +1,86 @@
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+use zelos_proto::trace::{
+    trace_action_server::{TraceAction, TraceActionServer},
+    CallActionRequest, CallActionResponse,
+};
+use zelos_trace::{action::ActionCallError, TraceRouter};
+
+/// How long to wait for a result when the request does not say
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Upper bound on the timeout a caller can ask for
+const MAX_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long to wait for a source to answer a request with `timeout_ms`
+pub(crate) fn request_timeout(timeout_ms: Option<u64>) -> Duration {
+    timeout_ms
+        .map_or(DEFAULT_TIMEOUT, Duration::from_millis)
+        .min(MAX_TIMEOUT)
+}
+
+/// The status for a request a source did not answer successfully
+pub(crate) fn call_status(e: ActionCallError) -> Status {
+    match e {
+        ActionCallError::NoSources => Status::unavailable(e.to_string()),
+        ActionCallError::NotFound => Status::not_found(e.to_string()),
+        ActionCallError::Ambiguous(_) => Status::failed_precondition(e.to_string()),
+        ActionCallError::TimedOut(_) => Status::deadline_exceeded(e.to_string()),
+        ActionCallError::Failed(_) => Status::aborted(e.to_string()),
+    }
+}
+
+/// Calls actions of the sources publishing to a router
+pub struct TraceActionService {
+    router: Arc<TraceRouter>,
+}
+
+impl TraceActionService {
+    pub fn new(router: Arc<TraceRouter>) -> Self {
+        Self { router }
+    }
+
+    pub fn server(self) -> TraceActionServer<Self> {
+        TraceActionServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl TraceAction for TraceActionService {
+    async fn call(
+        &self,
+        request: Request<CallActionRequest>,
+    ) -> Result<Response<CallActionResponse>, Status> {
+        let request = request.into_inner();
+        let arguments = request
+            .arguments
+            .into_iter()
+            .map(|entry| entry.try_into())
+            .collect::<Result<HashMap<_, _>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid argument: {}", e)))?;
+        let segment_id = request
+            .segment_id
+            .map(|id| Uuid::from_slice(&id))
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid segment id: {}", e)))?;
+        let timeout = request_timeout(request.timeout_ms);
+
+        let values = self
+            .router
+            .call_action(
+                &request.source_name,
+                &request.action_name,
+                arguments,
+                segment_id,
+                timeout,
+            )
+            .await
+            .map_err(call_status)?;
+
+        Ok(Response::new(CallActionResponse {
+            values: values.into_iter().map(|entry| entry.into()).collect(),
+        }))
+    }
+}
@@ -0,0 +1,444 @@
+use std::{
+    fmt,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+#[cfg(unix)]
+use tokio_stream::wrappers::UnixListenerStream;
+use tokio_util::sync::CancellationToken;
+use tonic::transport::Server;
+use zelos_trace::{
+    rate::RateProcessor, router::TraceRouterConfig, MetadataOnlyStore, Store, TraceMetadata,
+    TraceRouter,
+};
+
+use crate::{
+    action::TraceActionService,
+    catalog::TraceCatalogService,
+    control::TraceSourceControlService,
+    health::{self, TraceHealthConfig},
+    publish::TracePublishService,
+    reflection,
+    subscribe::TraceSubscribeService,
+    transport::{self, DEFAULT_UNIX_SOCKET_MODE, UNIX_SCHEME},
+};
+
+const DEFAULT_PORT: u16 = 2300;
+
+/// Where the agent accepts connections, either a TCP address or `unix:///path/to/socket`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum ListenAddr {
+    Tcp(SocketAddr),
+    Unix(PathBuf),
+}
+
+impl FromStr for ListenAddr {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.strip_prefix(UNIX_SCHEME) {
+            Some(path) => Ok(Self::Unix(PathBuf::from(path))),
+            None => {
+                Ok(Self::Tcp(s.parse().map_err(|e| {
+                    anyhow!("Invalid listen address {}: {}", s, e)
+                })?))
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for ListenAddr {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<ListenAddr> for String {
+    fn from(value: ListenAddr) -> Self {
+        value.to_string()
+    }
+}
+
+impl fmt::Display for ListenAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tcp(addr) => write!(f, "{}", addr),
+            Self::Unix(path) => write!(f, "{}{}", UNIX_SCHEME, path.display()),
+        }
+    }
+}
+
+/// The store backing the agent's router
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StoreKind {
+    /// Keep segment metadata in memory, discarding events once they are forwarded
+    #[default]
+    Metadata,
+}
+
+impl FromStr for StoreKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "metadata" => Ok(Self::Metadata),
+            _ => Err(anyhow!("Unknown store kind: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AgentConfig {
+    /// Address to serve the trace services on
+    pub listen: ListenAddr,
+    /// Permissions of the socket file when listening on a Unix domain socket, which control who may connect
+    pub socket_mode: u32,
+    /// The store backing the router
+    pub store: StoreKind,
+    /// Shared-memory rings to create for same-host publishers, requires the `shm` feature
+    pub shm_rings: Vec<PathBuf>,
+    /// Address to serve the HTTP gateway for JSON publishers and subscribers on, requires the `http` feature
+    pub http_listen: Option<SocketAddr>,
+    /// Address to receive trace datagrams from devices on, requires the `udp` feature
+    pub udp_listen: Option<SocketAddr>,
+    /// Derive a `rates/<event>` event from the counter fields of every event
+    pub derive_rates: bool,
+    /// Bytes of recent events and logs kept for subscribers that resume from an earlier time, 0 to keep none
+    pub replay_buffer_bytes: usize,
+}
+
+impl AgentConfig {
+    /// Load a config from a TOML file, using defaults for any missing keys
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&contents)
+    }
+
+    /// Parse a config from a TOML string, using defaults for any missing keys
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        toml::from_str(contents).map_err(|e| anyhow!("Invalid agent config: {}", e))
+    }
+}
+
+impl Default for AgentConfig {
+    fn default() -> Self {
+        Self {
+            listen: ListenAddr::Tcp(SocketAddr::from(([127, 0, 0, 1], DEFAULT_PORT))),
+            socket_mode: DEFAULT_UNIX_SOCKET_MODE,
+            store: StoreKind::default(),
+            shm_rings: Vec::new(),
+            http_listen: None,
+            udp_listen: None,
+            derive_rates: false,
+            replay_buffer_bytes: 0,
+        }
+    }
+}
+
+enum Listener {
+    Tcp(TcpListener),
+    #[cfg(unix)]
+    Unix(tokio::net::UnixListener, PathBuf),
+}
+
+/// A trace router serving the publish, subscribe, catalog, action and source control services
+pub struct Agent {
+    listener: Listener,
+    router: Arc<TraceRouter>,
+    metadata: TraceMetadata,
+    cancellation_token: CancellationToken,
+    router_task: tokio::task::JoinHandle<Result<()>>,
+    derive_rates: bool,
+    #[cfg(feature = "shm")]
+    shm_receivers: Vec<zelos_trace_shm::ShmReceiver>,
+    #[cfg(feature = "http")]
+    http_listener: Option<TcpListener>,
+    #[cfg(feature = "udp")]
+    udp_receiver: Option<zelos_trace_udp::UdpReceiver>,
+}
+
+impl Agent {
+    /// Start the router and bind the listening socket. The agent runs until `cancellation_token` is cancelled.
+    pub async fn bind(config: &AgentConfig, cancellation_token: CancellationToken) -> Result<Self> {
+        let (store, metadata): (Arc<dyn Store>, _) = match config.store {
+            StoreKind::Metadata => {
+                let store = Arc::new(MetadataOnlyStore::new());
+                let metadata = store.metadata().clone();
+                (store, metadata)
+            }
+        };
+        let router_config = TraceRouterConfig {
+            replay_buffer_bytes: config.replay_buffer_bytes,
+        };
+        let (router, router_task) =
+            TraceRouter::new_with_config(store, router_config, cancellation_token.clone());
+        let router_task = tokio::spawn(router_task);
+
+        let listener = match &config.listen {
+            ListenAddr::Tcp(addr) => Listener::Tcp(
+                TcpListener::bind(addr)
+                    .await
+                    .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?,
+            ),
+            #[cfg(unix)]
+            ListenAddr::Unix(path) => Listener::Unix(
+                transport::bind_unix(path, config.socket_mode)?,
+                path.clone(),
+            ),
+            #[cfg(not(unix))]
+            ListenAddr::Unix(_) => {
+                return Err(anyhow!(
+                    "Unix domain sockets are not supported on this platform"
+                ));
+            }
+        };
+
+        #[cfg(feature = "shm")]
+        let shm_receivers = config
+            .shm_rings
+            .iter()
+            .map(|path| {
+                zelos_trace_shm::ShmReceiver::new(
+                    path,
+                    router.sender(),
+                    zelos_trace_shm::ShmReceiverConfig::default(),
+                )
+            })
+            .collect::<Result<_>>()?;
+        #[cfg(not(feature = "shm"))]
+        if !config.shm_rings.is_empty() {
+            return Err(anyhow!(
+                "Shared-memory rings require the shm feature of zelos-trace-grpc"
+            ));
+        }
+
+        #[cfg(feature = "http")]
+        let http_listener = match config.http_listen {
+            Some(addr) => Some(
+                TcpListener::bind(addr)
+                    .await
+                    .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?,
+            ),
+            None => None,
+        };
+        #[cfg(not(feature = "http"))]
+        if config.http_listen.is_some() {
+            return Err(anyhow!(
+                "The HTTP gateway requires the http feature of zelos-trace-grpc"
+            ));
+        }
+
+        #[cfg(feature = "udp")]
+        let udp_receiver = match config.udp_listen {
+            Some(addr) => Some(
+                zelos_trace_udp::UdpReceiver::bind(
+                    addr,
+                    router.sender(),
+                    zelos_trace_udp::UdpReceiverConfig::default(),
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        #[cfg(not(feature = "udp"))]
+        if config.udp_listen.is_some() {
+            return Err(anyhow!(
+                "Receiving datagrams requires the udp feature of zelos-trace-grpc"
+            ));
+        }
+
+        Ok(Self {
+            listener,
+            router,
+            metadata,
+            cancellation_token,
+            router_task,
+            derive_rates: config.derive_rates,
+            #[cfg(feature = "shm")]
+            shm_receivers,
+            #[cfg(feature = "http")]
+            http_listener,
+            #[cfg(feature = "udp")]
+            udp_receiver,
+        })
+    }
+
+    /// The address the agent is listening on
+    pub fn local_addr(&self) -> Result<ListenAddr> {
+        match &self.listener {
+            Listener::Tcp(listener) => Ok(ListenAddr::Tcp(listener.local_addr()?)),
+            #[cfg(unix)]
+            Listener::Unix(_, path) => Ok(ListenAddr::Unix(path.clone())),
+        }
+    }
+
+    /// The router that published messages are forwarded to
+    pub fn router(&self) -> Arc<TraceRouter> {
+        self.router.clone()
+    }
+
+    /// Serve until cancelled, then wait for the router to drain its remaining messages. Health checking and
+    /// reflection are served alongside the trace services.
+    pub async fn run(self) -> Result<()> {
+        tracing::info!("Agent listening on {}", self.local_addr()?);
+
+        let publish =
+            TracePublishService::new(self.router.sender(), self.cancellation_token.clone())
+                .with_controls(self.router.control_sender())
+                .with_router_metadata(self.router.metadata());
+        let subscribe = TraceSubscribeService::new(self.router.clone());
+        let catalog = TraceCatalogService::new(self.metadata);
+        let action = TraceActionService::new(self.router.clone());
+        let control = TraceSourceControlService::new(self.router.clone());
+        let (health, health_task) = health::health_service(
+            self.router.clone(),
+            TraceHealthConfig::default(),
+            self.cancellation_token.clone(),
+        );
+        tokio::spawn(health_task);
+
+        if self.derive_rates {
+            let router = self.router.clone();
+            let cancellation_token = self.cancellation_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = RateProcessor::run(router, cancellation_token).await {
+                    tracing::error!("Rate processor failed: {}", e);
+                }
+            });
+        }
+
+        #[cfg(feature = "shm")]
+        for receiver in self.shm_receivers {
+            let cancellation_token = self.cancellation_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = receiver.run(cancellation_token).await {
+                    tracing::error!("Shared-memory receiver failed: {}", e);
+                }
+            });
+        }
+
+        #[cfg(feature = "http")]
+        if let Some(listener) = self.http_listener {
+            let gateway = zelos_trace_http::TraceHttpGateway::new(
+                self.router.clone(),
+                self.cancellation_token.clone(),
+            );
+            tokio::spawn(async move {
+                if let Err(e) = gateway.serve(listener).await {
+                    tracing::error!("HTTP gateway failed: {}", e);
+                }
+            });
+        }
+
+        #[cfg(feature = "udp")]
+        if let Some(receiver) = self.udp_receiver {
+            let cancellation_token = self.cancellation_token.clone();
+            tokio::spawn(async move {
+                if let Err(e) = receiver.run(cancellation_token).await {
+                    tracing::error!("UDP receiver failed: {}", e);
+                }
+            });
+        }
+
+        let server = Server::builder()
+            .add_service(publish.server())
+            .add_service(subscribe.server())
+            .add_service(catalog.server())
+            .add_service(action.server())
+            .add_service(control.server())
+            .add_service(health)
+            .add_service(reflection::service()?)
+            .add_service(reflection::service_v1alpha()?);
+
+        let shutdown = self.cancellation_token.cancelled();
+        match self.listener {
+            Listener::Tcp(listener) => {
+                server
+                    .serve_with_incoming_shutdown(TcpListenerStream::new(listener), shutdown)
+                    .await?
+            }
+            #[cfg(unix)]
+            Listener::Unix(listener, path) => {
+                let result = server
+                    .serve_with_incoming_shutdown(UnixListenerStream::new(listener), shutdown)
+                    .await;
+                // Clean up the socket file rather than leaving it for the next agent to replace
+                if let Err(e) = std::fs::remove_file(&path) {
+                    tracing::warn!("Failed to remove {}: {}", path.display(), e);
+                }
+                result?
+            }
+        }
+
+        tracing::info!("Agent stopped serving, waiting for the router to drain");
+        self.router_task.await?
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_config_defaults() -> Result<()> {
+        assert_eq!(AgentConfig::from_toml("")?, AgentConfig::default());
+
+        let config = AgentConfig::from_toml(
+            r#"
+            listen = "0.0.0.0:2400"
+            store = "metadata"
+            "#,
+        )?;
+        assert_eq!(config.listen, "0.0.0.0:2400".parse()?);
+        assert_eq!(config.store, StoreKind::Metadata);
+
+        let config = AgentConfig::from_toml(
+            r#"
+            listen = "unix:///run/zelos/agent.sock"
+            socket_mode = 0o600
+            "#,
+        )?;
+        assert_eq!(
+            config.listen,
+            ListenAddr::Unix(PathBuf::from("/run/zelos/agent.sock"))
+        );
+        assert_eq!(config.listen.to_string(), "unix:///run/zelos/agent.sock");
+        assert_eq!(config.socket_mode, 0o600);
+
+        let config = AgentConfig::from_toml(r#"shm_rings = ["/dev/shm/zelos-high-frequency"]"#)?;
+        assert_eq!(
+            config.shm_rings,
+            vec![PathBuf::from("/dev/shm/zelos-high-frequency")]
+        );
+
+        let config = AgentConfig::from_toml(r#"http_listen = "127.0.0.1:2301""#)?;
+        assert_eq!(config.http_listen, Some("127.0.0.1:2301".parse()?));
+
+        let config = AgentConfig::from_toml(r#"udp_listen = "0.0.0.0:2302""#)?;
+        assert_eq!(config.udp_listen, Some("0.0.0.0:2302".parse()?));
+
+        let config = AgentConfig::from_toml("derive_rates = true")?;
+        assert!(config.derive_rates);
+
+        let config = AgentConfig::from_toml("replay_buffer_bytes = 67108864")?;
+        assert_eq!(config.replay_buffer_bytes, 64 * 1024 * 1024);
+
+        assert!(AgentConfig::from_toml("store = \"duckdb\"").is_err());
+        assert!(AgentConfig::from_toml("port = 2300").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use anyhow::Result;
+use clap::Parser;
+use tokio_util::sync::CancellationToken;
+use zelos_trace_grpc::agent::{Agent, AgentConfig, ListenAddr, StoreKind};
+
+#[derive(Parser, Debug, Clone)]
+struct Args {
+    /// Path to a TOML config file, flags take precedence over its values
+    #[clap(short, long)]
+    config: Option<PathBuf>,
+
+    /// Address to serve the trace services on, a TCP address or unix:///path/to/socket [default: 127.0.0.1:2300]
+    #[clap(short, long)]
+    listen: Option<ListenAddr>,
+
+    /// Octal permissions of the socket file when listening on a Unix domain socket [default: 660]
+    #[clap(long, value_parser = parse_mode)]
+    socket_mode: Option<u32>,
+
+    /// Store backing the router [default: metadata]
+    #[clap(short, long)]
+    store: Option<StoreKind>,
+
+    /// Create a shared-memory ring at this path for a same-host publisher, may be repeated
+    #[clap(long = "shm-ring")]
+    shm_rings: Vec<PathBuf>,
+
+    /// Serve an HTTP gateway for JSON publishers and subscribers on this address
+    #[clap(long)]
+    http_listen: Option<SocketAddr>,
+
+    /// Receive trace datagrams from devices on this UDP address
+    #[clap(long)]
+    udp_listen: Option<SocketAddr>,
+
+    /// Derive a rates/<event> event from the counter fields of every event
+    #[clap(long)]
+    derive_rates: bool,
+
+    /// Bytes of recent events and logs kept for subscribers that resume from an earlier time [default: 0]
+    #[clap(long)]
+    replay_buffer_bytes: Option<usize>,
+}
+
+fn parse_mode(s: &str) -> Result<u32, std::num::ParseIntError> {
+    u32::from_str_radix(s.trim_start_matches("0o"), 8)
+}
+
+impl Args {
+    fn into_config(self) -> Result<AgentConfig> {
+        let mut config = match &self.config {
+            Some(path) => AgentConfig::from_file(path)?,
+            None => AgentConfig::default(),
+        };
+        if let Some(listen) = self.listen {
+            config.listen = listen;
+        }
+        if let Some(socket_mode) = self.socket_mode {
+            config.socket_mode = socket_mode;
+        }
+        if let Some(store) = self.store {
+            config.store = store;
+        }
+        config.shm_rings.extend(self.shm_rings);
+        if let Some(http_listen) = self.http_listen {
+            config.http_listen = Some(http_listen);
+        }
+        if let Some(udp_listen) = self.udp_listen {
+            config.udp_listen = Some(udp_listen);
+        }
+        if self.derive_rates {
+            config.derive_rates = true;
+        }
+        if let Some(replay_buffer_bytes) = self.replay_buffer_bytes {
+            config.replay_buffer_bytes = replay_buffer_bytes;
+        }
+        Ok(config)
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+    let config = Args::parse().into_config()?;
+    let cancellation_token = CancellationToken::new();
+
+    let agent = Agent::bind(&config, cancellation_token.clone()).await?;
+    println!("Agent listening on {}", agent.local_addr()?);
+
+    let shutdown_token = cancellation_token.clone();
+    tokio::spawn(async move {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to listen for ctrl_c");
+        println!("\nReceived Ctrl+C, shutting down...");
+        shutdown_token.cancel();
+    });
+
+    agent.run().await
+}
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use clap::Parser;
 use tokio_util::sync::CancellationToken;
-use zelos_trace_grpc::subscribe::TraceSubscribeClient;
+use zelos_trace_grpc::subscribe::{TraceSubscribeClient, TraceSubscribeClientConfig};
 use zelos_trace_types::ipc::IpcMessageWithId;
 
 #[derive(Debug, Parser)]
@@ -36,7 +36,11 @@ async fn main() -> Result<()> {
     // Connect to the server
     let addr = format!("grpc://{}", args.host);
     eprintln!("[*] Connecting to gRPC server at {}", addr);
-    let (client, task_client) = TraceSubscribeClient::new(sender, shutdown.clone(), addr).await?;
+    let (client, task_client) = TraceSubscribeClient::new(
+        sender,
+        shutdown.clone(),
+        TraceSubscribeClientConfig::new_with_url(addr),
+    );
     let client_task = tokio::spawn(task_client);
 
     // Send a subscription request for the specified filter (or None)
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use tonic::transport::Channel;
+use uuid::Uuid;
+use zelos_proto::trace::{
+    trace_catalog_client, GetSegmentRequest, ListSegmentsRequest, ListSessionsRequest,
+    ListSignalsRequest,
+};
+use zelos_trace::segment::TraceSegment;
+use zelos_trace_types::{ipc, Signal};
+
+use crate::transport;
+
+/// Summary of a segment, as returned by [`TraceCatalogClient::list_segments`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SegmentInfo {
+    pub id: Uuid,
+    pub source: String,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub attributes: HashMap<String, String>,
+}
+
+impl TryFrom<zelos_proto::trace::SegmentInfo> for SegmentInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: zelos_proto::trace::SegmentInfo) -> Result<Self> {
+        Ok(Self {
+            id: Uuid::from_slice(&value.segment_id)?,
+            source: value.source_name,
+            start_time: value.start_time_ns.map(DateTime::from_timestamp_nanos),
+            end_time: value.end_time_ns.map(DateTime::from_timestamp_nanos),
+            attributes: value.attributes,
+        })
+    }
+}
+
+/// Summary of a session, as returned by [`TraceCatalogClient::list_sessions`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionInfo {
+    pub id: Uuid,
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    pub join_attributes: HashMap<String, String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub segment_ids: Vec<Uuid>,
+}
+
+impl TryFrom<zelos_proto::trace::SessionInfo> for SessionInfo {
+    type Error = anyhow::Error;
+
+    fn try_from(value: zelos_proto::trace::SessionInfo) -> Result<Self> {
+        Ok(Self {
+            id: Uuid::from_slice(&value.session_id)?,
+            name: value.name,
+            attributes: value.attributes,
+            join_attributes: value.join_attributes,
+            start_time: DateTime::from_timestamp_nanos(value.start_time_ns),
+            end_time: value.end_time_ns.map(DateTime::from_timestamp_nanos),
+            segment_ids: value
+                .segment_ids
+                .iter()
+                .map(|id| Uuid::from_slice(id))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Client for querying the segments and signals known to a trace server
+#[derive(Debug, Clone)]
+pub struct TraceCatalogClient {
+    client: trace_catalog_client::TraceCatalogClient<Channel>,
+}
+
+impl TraceCatalogClient {
+    /// Connect to the catalog service at `url`
+    pub async fn connect(url: String) -> Result<Self> {
+        let channel = transport::connect(&url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to catalog service: {}", e))?;
+        Ok(Self {
+            client: trace_catalog_client::TraceCatalogClient::new(channel),
+        })
+    }
+
+    /// List all known segments, optionally only those produced by `source_name`
+    pub async fn list_segments(&mut self, source_name: Option<String>) -> Result<Vec<SegmentInfo>> {
+        self.list_segments_matching(ListSegmentsRequest {
+            source_name,
+            session_id: None,
+        })
+        .await
+    }
+
+    /// List the segments that joined the session `session_id`
+    pub async fn list_session_segments(&mut self, session_id: Uuid) -> Result<Vec<SegmentInfo>> {
+        self.list_segments_matching(ListSegmentsRequest {
+            source_name: None,
+            session_id: Some(session_id.into_bytes().to_vec()),
+        })
+        .await
+    }
+
+    async fn list_segments_matching(
+        &mut self,
+        request: ListSegmentsRequest,
+    ) -> Result<Vec<SegmentInfo>> {
+        let response = self.client.list_segments(request).await?.into_inner();
+        response
+            .segments
+            .into_iter()
+            .map(|s| s.try_into())
+            .collect()
+    }
+
+    /// Get a segment with all of its schemas, value tables and actions
+    pub async fn get_segment(&mut self, id: Uuid) -> Result<TraceSegment> {
+        let response = self
+            .client
+            .get_segment(GetSegmentRequest {
+                segment_id: id.into_bytes().to_vec(),
+            })
+            .await?
+            .into_inner();
+        let info: SegmentInfo = response
+            .segment
+            .ok_or_else(|| anyhow!("Missing segment info in response"))?
+            .try_into()?;
+
+        let mut segment = TraceSegment::empty(info.id, info.source);
+        segment.start_time = info.start_time;
+        segment.end_time = info.end_time;
+        segment.attributes = info.attributes;
+        for schema in response.schemas {
+            let schema: ipc::TraceEventSchema = schema.try_into()?;
+            segment.update_mut(&schema.into())?;
+        }
+        for value_table in response.value_tables {
+            let value_table: ipc::TraceEventFieldNamedValues = value_table.try_into()?;
+            segment.update_mut(&value_table.into())?;
+        }
+        for action in response.actions {
+            let action: ipc::TraceActionSchema = action.try_into()?;
+            segment.update_mut(&action.into())?;
+        }
+
+        Ok(segment)
+    }
+
+    /// List the signals matching any of `signal_keys`, or all signals if no keys are given
+    pub async fn list_signals(&mut self, signal_keys: Vec<String>) -> Result<Vec<Signal>> {
+        let response = self
+            .client
+            .list_signals(ListSignalsRequest { signal_keys })
+            .await?
+            .into_inner();
+        response
+            .signals
+            .into_iter()
+            .map(|s| s.try_into().map_err(|e| anyhow!("Invalid signal: {}", e)))
+            .collect()
+    }
+
+    /// List all known sessions with the segments that joined them
+    pub async fn list_sessions(&mut self) -> Result<Vec<SessionInfo>> {
+        let response = self
+            .client
+            .list_sessions(ListSessionsRequest {})
+            .await?
+            .into_inner();
+        response
+            .sessions
+            .into_iter()
+            .map(|s| s.try_into())
+            .collect()
+    }
+}
@@ -0,0 +1,5 @@
+mod client;
+mod service;
+
+pub use client::{SegmentInfo, TraceCatalogClient};
+pub use service::TraceCatalogService;
@@ -0,0 +1,244 @@
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+use zelos_proto::trace::{
+    trace_catalog_server::{TraceCatalog, TraceCatalogServer},
+    GetSegmentRequest, GetSegmentResponse, ListSegmentsRequest, ListSegmentsResponse,
+    ListSessionsRequest, ListSessionsResponse, ListSignalsRequest, ListSignalsResponse,
+    SegmentInfo, SessionInfo,
+};
+use zelos_trace::{segment::TraceSegment, session::TraceSession, TraceMetadata};
+use zelos_trace_types::{ipc::IpcMessage, SignalKey};
+
+/// Read-only queries against the segments and sessions a router has seen
+pub struct TraceCatalogService {
+    metadata: TraceMetadata,
+}
+
+impl TraceCatalogService {
+    pub fn new(metadata: TraceMetadata) -> Self {
+        Self { metadata }
+    }
+
+    pub fn server(self) -> TraceCatalogServer<Self> {
+        TraceCatalogServer::new(self)
+    }
+}
+
+fn segment_info(segment: &TraceSegment) -> SegmentInfo {
+    SegmentInfo {
+        segment_id: segment.id.into_bytes().to_vec(),
+        source_name: segment.source.clone(),
+        start_time_ns: segment.start_time.and_then(|t| t.timestamp_nanos_opt()),
+        end_time_ns: segment.end_time.and_then(|t| t.timestamp_nanos_opt()),
+        attributes: segment.attributes.clone(),
+    }
+}
+
+fn session_info(session: &TraceSession) -> SessionInfo {
+    let mut segment_ids: Vec<_> = session.segments.iter().collect();
+    segment_ids.sort();
+    SessionInfo {
+        session_id: session.id.into_bytes().to_vec(),
+        name: session.name.clone(),
+        attributes: session.attributes.clone(),
+        join_attributes: session.join_attributes.clone(),
+        start_time_ns: session.start_time.timestamp_nanos_opt().unwrap_or_default(),
+        end_time_ns: session.end_time.and_then(|t| t.timestamp_nanos_opt()),
+        segment_ids: segment_ids
+            .into_iter()
+            .map(|id| id.into_bytes().to_vec())
+            .collect(),
+    }
+}
+
+#[tonic::async_trait]
+impl TraceCatalog for TraceCatalogService {
+    async fn list_segments(
+        &self,
+        request: Request<ListSegmentsRequest>,
+    ) -> Result<Response<ListSegmentsResponse>, Status> {
+        let request = request.into_inner();
+        let source_name = request.source_name;
+        let session = match request.session_id {
+            Some(session_id) => {
+                let session_id = Uuid::from_slice(&session_id)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid session id: {}", e)))?;
+                let session = self.metadata.get_session(&session_id).ok_or_else(|| {
+                    Status::not_found(format!("Session {} not found", session_id))
+                })?;
+                Some(session)
+            }
+            None => None,
+        };
+
+        // Segment ids are UUIDv7, so sorting by id sorts by creation time
+        let mut segments: Vec<_> = self
+            .metadata
+            .segments_iter()
+            .filter(|s| source_name.as_ref().is_none_or(|name| &s.source == name))
+            .filter(|s| {
+                session
+                    .as_ref()
+                    .is_none_or(|session| session.segments.contains(&s.id))
+            })
+            .collect();
+        segments.sort_by_key(|s| s.id);
+
+        Ok(Response::new(ListSegmentsResponse {
+            segments: segments.iter().map(segment_info).collect(),
+        }))
+    }
+
+    async fn get_segment(
+        &self,
+        request: Request<GetSegmentRequest>,
+    ) -> Result<Response<GetSegmentResponse>, Status> {
+        let segment_id = Uuid::from_slice(&request.into_inner().segment_id)
+            .map_err(|e| Status::invalid_argument(format!("Invalid segment id: {}", e)))?;
+        let segment = self
+            .metadata
+            .get_segment(&segment_id)
+            .ok_or_else(|| Status::not_found(format!("Segment {} not found", segment_id)))?;
+
+        let mut response = GetSegmentResponse {
+            segment: Some(segment_info(&segment)),
+            ..Default::default()
+        };
+        for msg in segment.as_ipc() {
+            match msg {
+                IpcMessage::TraceEventSchema(m) => response.schemas.push(m.into()),
+                IpcMessage::TraceEventFieldNamedValues(m) => response.value_tables.push(m.into()),
+                IpcMessage::TraceActionSchema(m) => response.actions.push(m.into()),
+                _ => {}
+            }
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn list_signals(
+        &self,
+        request: Request<ListSignalsRequest>,
+    ) -> Result<Response<ListSignalsResponse>, Status> {
+        let signal_keys = request
+            .into_inner()
+            .signal_keys
+            .iter()
+            .map(|k| SignalKey::try_parse(k))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid signal key: {}", e)))?;
+
+        let signals = self
+            .metadata
+            .segments_iter()
+            .flat_map(|segment| {
+                if signal_keys.is_empty() {
+                    segment.signals().collect::<Vec<_>>()
+                } else {
+                    segment.signals_matching(&signal_keys).collect()
+                }
+            })
+            .map(|signal| signal.into())
+            .collect();
+
+        Ok(Response::new(ListSignalsResponse { signals }))
+    }
+
+    async fn list_sessions(
+        &self,
+        _request: Request<ListSessionsRequest>,
+    ) -> Result<Response<ListSessionsResponse>, Status> {
+        // Session ids are UUIDv7, so sorting by id sorts by creation time
+        let mut sessions: Vec<_> = self.metadata.sessions().into_values().collect();
+        sessions.sort_by_key(|s| s.id);
+
+        Ok(Response::new(ListSessionsResponse {
+            sessions: sessions.iter().map(session_info).collect(),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use zelos_trace_types::{
+        ipc::{
+            FieldDisplay, FieldKind, IpcMessageWithId, TraceEventFieldMetadata, TraceEventSchema,
+            TraceSegmentStart,
+        },
+        DataType,
+    };
+
+    use super::*;
+    use crate::catalog::TraceCatalogClient;
+
+    #[tokio::test]
+    async fn test_catalog_queries() -> Result<()> {
+        let metadata = TraceMetadata::new();
+        let segment_id = Uuid::now_v7();
+        for msg in [
+            IpcMessage::TraceSegmentStart(TraceSegmentStart {
+                time_ns: 1_000,
+                source_name: "rig".to_string(),
+                attributes: [("host".to_string(), "bench".to_string())].into(),
+            }),
+            IpcMessage::TraceEventSchema(TraceEventSchema {
+                name: "motor".to_string(),
+                version: 0,
+                fields: vec![TraceEventFieldMetadata {
+                    name: "rpm".to_string(),
+                    data_type: DataType::Float64,
+                    unit: Some("rpm".to_string()),
+                    kind: FieldKind::Gauge,
+                    display: FieldDisplay::default(),
+                }],
+            }),
+        ] {
+            metadata.update(&IpcMessageWithId {
+                segment_id,
+                source_name: "rig".into(),
+                msg,
+            })?;
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceCatalogService::new(metadata).server())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut client = TraceCatalogClient::connect(format!("grpc://{}", addr)).await?;
+
+        let segments = client.list_segments(None).await?;
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].id, segment_id);
+        assert_eq!(segments[0].source, "rig");
+        assert_eq!(segments[0].attributes["host"], "bench");
+        assert!(client
+            .list_segments(Some("other".to_string()))
+            .await?
+            .is_empty());
+
+        let segment = client.get_segment(segment_id).await?;
+        let Some(schema) = segment.schemas.get("motor") else {
+            panic!("Missing the motor schema in {:?}", segment);
+        };
+        assert_eq!(schema.as_ipc().fields[0].name, "rpm");
+
+        let signals = client.list_signals(Vec::new()).await?;
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal, "rpm");
+        let signals = client.list_signals(vec![signals[0].key_string()]).await?;
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].unit.as_deref(), Some("rpm"));
+
+        assert!(client.get_segment(Uuid::now_v7()).await.is_err());
+        assert!(client.list_sessions().await?.is_empty());
+
+        server.abort();
+        Ok(())
+    }
+}
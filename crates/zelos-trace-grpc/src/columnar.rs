@@ -0,0 +1,494 @@
+use std::{collections::HashMap, sync::Arc};
+
+use uuid::Uuid;
+use zelos_proto::{
+    error::Error,
+    trace::{trace_message::Msg, TraceEventBatch, TraceMessage},
+};
+use zelos_trace::TraceMetadata;
+use zelos_trace_types::ipc::{IpcMessage, IpcMessageWithId, TraceEvent, TraceEventFieldMetadata};
+
+/// Events of one schema waiting to be packed
+struct EventGroup {
+    segment_id: Uuid,
+    source_name: Arc<str>,
+    name: Arc<str>,
+    events: Vec<TraceEvent>,
+}
+
+/// Event schemas seen on a publish or subscribe stream, used to pack events into [`TraceEventBatch`]es on the sending
+/// side and unpack them on the receiving side. Only events whose schema was sent earlier on the same stream are
+/// packed, so the receiver always has the schema of every batch.
+#[derive(Default)]
+pub(crate) struct StreamSchemas {
+    segments: HashMap<Uuid, HashMap<String, Arc<[TraceEventFieldMetadata]>>>,
+    /// The router's metadata, for unpacking batches whose schema was sent on an earlier stream
+    router_metadata: Option<TraceMetadata>,
+}
+
+impl StreamSchemas {
+    /// Unpack batches of schemas this stream has not seen with those the router knows, see [`TraceRouter::metadata`]
+    ///
+    /// [`TraceRouter::metadata`]: zelos_trace::TraceRouter::metadata
+    pub(crate) fn with_router_metadata(router_metadata: Option<TraceMetadata>) -> Self {
+        Self {
+            router_metadata,
+            ..Default::default()
+        }
+    }
+
+    fn update(&mut self, msg: &IpcMessageWithId) {
+        match &msg.msg {
+            IpcMessage::TraceEventSchema(schema) => {
+                self.segments
+                    .entry(msg.segment_id)
+                    .or_default()
+                    .insert(schema.name.clone(), schema.fields.as_slice().into());
+            }
+            IpcMessage::TraceSegmentEnd(_) => {
+                self.segments.remove(&msg.segment_id);
+            }
+            _ => {}
+        }
+    }
+
+    fn fields(&self, segment_id: &Uuid, name: &str) -> Option<&Arc<[TraceEventFieldMetadata]>> {
+        self.segments.get(segment_id)?.get(name)
+    }
+
+    /// The fields of a schema this stream has not seen, from the router's metadata, kept for the rest of the stream
+    fn router_fields(
+        &mut self,
+        segment_id: &Uuid,
+        name: &str,
+    ) -> Option<Arc<[TraceEventFieldMetadata]>> {
+        let segment = self.router_metadata.as_ref()?.get_segment(segment_id)?;
+        let fields: Arc<[TraceEventFieldMetadata]> =
+            segment.schemas.get(name)?.as_ipc().fields.into();
+        self.segments
+            .entry(*segment_id)
+            .or_default()
+            .insert(name.to_string(), fields.clone());
+        Some(fields)
+    }
+
+    /// Convert each group to a batch, or to individual events if it is a single event or cannot be packed
+    fn flush(&self, groups: &mut Vec<EventGroup>, out: &mut Vec<TraceMessage>) {
+        for group in groups.drain(..) {
+            let events = match self.fields(&group.segment_id, &group.name) {
+                Some(fields) if group.events.len() > 1 => {
+                    match TraceEventBatch::pack(group.name.to_string(), fields, group.events) {
+                        Ok(batch) => {
+                            out.push(TraceMessage {
+                                segment_id: group.segment_id.into_bytes().to_vec(),
+                                source_name: group.source_name.to_string(),
+                                msg: Some(Msg::EventBatch(batch)),
+                            });
+                            continue;
+                        }
+                        Err(events) => events,
+                    }
+                }
+                _ => group.events,
+            };
+            out.extend(events.into_iter().map(|event| {
+                IpcMessageWithId {
+                    segment_id: group.segment_id,
+                    source_name: group.source_name.clone(),
+                    msg: IpcMessage::TraceEvent(event),
+                }
+                .into()
+            }));
+        }
+    }
+
+    /// Convert messages to protos, packing the events of each segment and schema into a batch. Events are grouped up
+    /// to the next non-event message, so they keep their order relative to schemas and segment boundaries, and to
+    /// other events of the same schema, but not to events of other schemas.
+    pub(crate) fn pack(&mut self, msgs: Vec<IpcMessageWithId>) -> Vec<TraceMessage> {
+        let mut out = Vec::with_capacity(msgs.len());
+        let mut groups: Vec<EventGroup> = Vec::new();
+        for msg in msgs {
+            match msg {
+                IpcMessageWithId {
+                    segment_id,
+                    source_name,
+                    msg: IpcMessage::TraceEvent(event),
+                } => {
+                    match groups
+                        .iter_mut()
+                        .find(|group| group.segment_id == segment_id && group.name == event.name)
+                    {
+                        Some(group) => group.events.push(event),
+                        None => groups.push(EventGroup {
+                            segment_id,
+                            source_name,
+                            name: event.name.clone(),
+                            events: vec![event],
+                        }),
+                    }
+                }
+                msg => {
+                    self.flush(&mut groups, &mut out);
+                    self.update(&msg);
+                    out.push(msg.into());
+                }
+            }
+        }
+        self.flush(&mut groups, &mut out);
+        out
+    }
+
+    /// Convert messages to protos, packing events only if `pack` is set. Schemas are tracked either way, so packing can
+    /// be turned on partway through a stream once the peer confirms it supports event batches.
+    pub(crate) fn encode(&mut self, msgs: Vec<IpcMessageWithId>, pack: bool) -> Vec<TraceMessage> {
+        if pack {
+            return self.pack(msgs);
+        }
+        msgs.into_iter()
+            .map(|msg| {
+                self.update(&msg);
+                msg.into()
+            })
+            .collect()
+    }
+
+    /// Convert a proto to messages, unpacking event batches with the schemas seen earlier on the stream
+    pub(crate) fn unpack(&mut self, mut msg: TraceMessage) -> Result<Vec<IpcMessageWithId>, Error> {
+        match msg.msg.take() {
+            Some(Msg::EventBatch(batch)) => {
+                let segment_id = Uuid::from_slice(&msg.segment_id)?;
+                let source_name: Arc<str> = msg.source_name.into();
+                let fields = match self.fields(&segment_id, &batch.name) {
+                    Some(fields) => fields.clone(),
+                    None => self
+                        .router_fields(&segment_id, &batch.name)
+                        .ok_or_else(|| Error::MissingEventSchema(batch.name.clone()))?,
+                };
+                Ok(batch
+                    .unpack(&fields)?
+                    .into_iter()
+                    .map(|event| IpcMessageWithId {
+                        segment_id,
+                        source_name: source_name.clone(),
+                        msg: IpcMessage::TraceEvent(event),
+                    })
+                    .collect())
+            }
+            other => {
+                msg.msg = other;
+                let msg: IpcMessageWithId = msg.try_into()?;
+                self.update(&msg);
+                Ok(vec![msg])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use zelos_trace_types::{
+        ipc::{FieldKind, TraceEventSchema, TraceSegmentEnd, TraceSegmentStart},
+        DataType, Value,
+    };
+
+    use super::*;
+
+    fn msg(segment_id: Uuid, msg: IpcMessage) -> IpcMessageWithId {
+        IpcMessageWithId {
+            segment_id,
+            source_name: "rig".into(),
+            msg,
+        }
+    }
+
+    fn event(time_ns: i64, name: &str, fields: &[(&str, Value)]) -> IpcMessage {
+        IpcMessage::TraceEvent(TraceEvent {
+            time_ns,
+            name: name.into(),
+            fields: fields
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect(),
+        })
+    }
+
+    /// Describe a message with its event fields sorted, since they are unordered
+    fn describe(msg: &IpcMessageWithId) -> String {
+        match &msg.msg {
+            IpcMessage::TraceEvent(event) => {
+                let mut fields: Vec<_> = event.fields.iter().collect();
+                fields.sort_by(|a, b| a.0.cmp(b.0));
+                format!("{} {} {:?}", event.time_ns, event.name, fields)
+            }
+            msg => format!("{:?}", msg),
+        }
+    }
+
+    #[test]
+    fn test_pack_roundtrip() -> Result<()> {
+        let segment_id = Uuid::now_v7();
+        let field = |name: &str, data_type| TraceEventFieldMetadata {
+            name: name.to_string(),
+            data_type,
+            unit: None,
+            kind: FieldKind::Gauge,
+            display: Default::default(),
+        };
+        let msgs = vec![
+            msg(
+                segment_id,
+                IpcMessage::TraceSegmentStart(TraceSegmentStart {
+                    time_ns: 0,
+                    source_name: "rig".to_string(),
+                    attributes: HashMap::new(),
+                }),
+            ),
+            msg(
+                segment_id,
+                IpcMessage::TraceEventSchema(TraceEventSchema {
+                    name: "motor".to_string(),
+                    version: 0,
+                    fields: vec![
+                        field("rpm", DataType::Float32),
+                        field("state", DataType::String),
+                        field("fault", DataType::Int8),
+                    ],
+                }),
+            ),
+            msg(
+                segment_id,
+                event(
+                    1,
+                    "motor",
+                    &[
+                        ("rpm", Value::Float32(1.5)),
+                        ("state", Value::String("idle".to_string())),
+                    ],
+                ),
+            ),
+            // An event without a schema is sent as is
+            msg(segment_id, event(2, "other", &[("x", Value::Int64(1))])),
+            msg(
+                segment_id,
+                event(
+                    3,
+                    "motor",
+                    &[
+                        ("rpm", Value::Float32(2.5)),
+                        ("state", Value::String("spinning".to_string())),
+                        ("fault", Value::Int8(-3)),
+                    ],
+                ),
+            ),
+            msg(
+                segment_id,
+                IpcMessage::TraceSegmentEnd(TraceSegmentEnd { time_ns: 4 }),
+            ),
+        ];
+
+        let mut packer = StreamSchemas::default();
+        let packed = packer.pack(msgs.clone());
+        let kinds: Vec<_> = packed
+            .iter()
+            .map(|msg| match &msg.msg {
+                Some(Msg::SegmentStart(_)) => "start",
+                Some(Msg::EventSchema(_)) => "schema",
+                Some(Msg::Event(_)) => "event",
+                Some(Msg::EventBatch(_)) => "batch",
+                Some(Msg::SegmentEnd(_)) => "end",
+                _ => "other",
+            })
+            .collect();
+        assert_eq!(kinds, ["start", "schema", "batch", "event", "end"]);
+        let Some(Msg::EventBatch(batch)) = &packed[2].msg else {
+            anyhow::bail!("Expected an event batch");
+        };
+        assert_eq!(batch.time_ns, [1, 3]);
+        let fault = batch
+            .columns
+            .iter()
+            .find(|column| column.field_index == 2)
+            .ok_or_else(|| anyhow::anyhow!("Missing fault column"))?;
+        assert_eq!(fault.present, [1]);
+        assert_eq!(fault.ints, [-3]);
+
+        let mut unpacker = StreamSchemas::default();
+        let mut unpacked = Vec::new();
+        for msg in packed {
+            unpacked.extend(unpacker.unpack(msg)?);
+        }
+        let expected = [&msgs[..3], &msgs[4..5], &msgs[3..4], &msgs[5..]].concat();
+        assert!(unpacked.iter().all(|msg| msg.segment_id == segment_id));
+        assert_eq!(
+            unpacked.iter().map(describe).collect::<Vec<_>>(),
+            expected.iter().map(describe).collect::<Vec<_>>()
+        );
+
+        // Batches for a schema the stream has not seen are rejected
+        let batch = packer.pack(msgs[..3].iter().chain(&msgs[2..3]).cloned().collect());
+        let mut unpacker = StreamSchemas::default();
+        assert!(unpacker.unpack(batch[2].clone()).is_err());
+        Ok(())
+    }
+
+    fn schema(segment_id: Uuid, name: &str, fields: &[(&str, DataType)]) -> IpcMessageWithId {
+        msg(
+            segment_id,
+            IpcMessage::TraceEventSchema(TraceEventSchema {
+                name: name.to_string(),
+                version: 0,
+                fields: fields
+                    .iter()
+                    .map(|(name, data_type)| TraceEventFieldMetadata {
+                        name: name.to_string(),
+                        data_type: data_type.clone(),
+                        unit: None,
+                        kind: FieldKind::Gauge,
+                        display: Default::default(),
+                    })
+                    .collect(),
+            }),
+        )
+    }
+
+    fn kinds(packed: &[TraceMessage]) -> Vec<&'static str> {
+        packed
+            .iter()
+            .map(|msg| match &msg.msg {
+                Some(Msg::EventSchema(_)) => "schema",
+                Some(Msg::Event(_)) => "event",
+                Some(Msg::EventBatch(_)) => "batch",
+                _ => "other",
+            })
+            .collect()
+    }
+
+    fn roundtrip(packed: Vec<TraceMessage>) -> Result<Vec<String>> {
+        let mut unpacker = StreamSchemas::default();
+        let mut unpacked = Vec::new();
+        for msg in packed {
+            unpacked.extend(unpacker.unpack(msg)?);
+        }
+        Ok(unpacked.iter().map(describe).collect())
+    }
+
+    #[test]
+    fn test_pack_mixed_schemas() -> Result<()> {
+        let (first, second) = (Uuid::now_v7(), Uuid::now_v7());
+        let mut msgs = vec![
+            schema(first, "motor", &[("rpm", DataType::Float64)]),
+            schema(first, "battery", &[("volts", DataType::Float32)]),
+            schema(second, "motor", &[("rpm", DataType::Float64)]),
+        ];
+        for time_ns in 0..3 {
+            msgs.push(msg(
+                first,
+                event(time_ns, "motor", &[("rpm", Value::Float64(1.0))]),
+            ));
+            msgs.push(msg(
+                first,
+                event(time_ns, "battery", &[("volts", Value::Float32(12.0))]),
+            ));
+            msgs.push(msg(
+                second,
+                event(time_ns, "motor", &[("rpm", Value::Float64(2.0))]),
+            ));
+        }
+
+        // A batch per segment and schema, in the order each group first appeared
+        let packed = StreamSchemas::default().pack(msgs.clone());
+        assert_eq!(
+            kinds(&packed),
+            ["schema", "schema", "schema", "batch", "batch", "batch"]
+        );
+        let names: Vec<_> = packed[3..]
+            .iter()
+            .map(|msg| match &msg.msg {
+                Some(Msg::EventBatch(batch)) => {
+                    (Uuid::from_slice(&msg.segment_id).ok(), batch.name.clone())
+                }
+                _ => (None, String::new()),
+            })
+            .collect();
+        assert_eq!(
+            names,
+            [
+                (Some(first), "motor".to_string()),
+                (Some(first), "battery".to_string()),
+                (Some(second), "motor".to_string()),
+            ]
+        );
+
+        // Each schema's events keep their order
+        let mut expected: Vec<_> = msgs[..3].iter().map(describe).collect();
+        for i in 0..3 {
+            expected.extend((0..3).map(|time_ns| describe(&msgs[3 + time_ns * 3 + i])));
+        }
+        assert_eq!(roundtrip(packed)?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pack_fields_that_do_not_fit() -> Result<()> {
+        let segment_id = Uuid::now_v7();
+        let msgs = vec![
+            schema(segment_id, "motor", &[("rpm", DataType::Float64)]),
+            msg(
+                segment_id,
+                event(1, "motor", &[("rpm", Value::Float64(1.0))]),
+            ),
+            // A field the schema does not have
+            msg(
+                segment_id,
+                event(
+                    2,
+                    "motor",
+                    &[("rpm", Value::Float64(2.0)), ("torque", Value::Int32(5))],
+                ),
+            ),
+            // A value of the wrong type
+            msg(segment_id, event(3, "motor", &[("rpm", Value::Int64(3))])),
+        ];
+
+        // Events that can't be packed are sent as they are, none are lost or changed
+        let packed = StreamSchemas::default().pack(msgs.clone());
+        assert_eq!(kinds(&packed), ["schema", "event", "event", "event"]);
+        assert_eq!(
+            roundtrip(packed)?,
+            msgs.iter().map(describe).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_unpack_with_router_schemas() -> Result<()> {
+        let segment_id = Uuid::now_v7();
+        let motor = schema(segment_id, "motor", &[("rpm", DataType::Float64)]);
+        let events: Vec<_> = (0..2)
+            .map(|time_ns| {
+                msg(
+                    segment_id,
+                    event(time_ns, "motor", &[("rpm", Value::Float64(1.0))]),
+                )
+            })
+            .collect();
+        let mut packer = StreamSchemas::default();
+        packer.pack(vec![motor.clone()]);
+        let packed = packer.pack(events.clone());
+        assert_eq!(kinds(&packed), ["batch"]);
+
+        // The schema was sent on an earlier stream, which only the router remembers
+        assert!(StreamSchemas::default().unpack(packed[0].clone()).is_err());
+        let metadata = TraceMetadata::new();
+        metadata.update(&motor)?;
+        let unpacked =
+            StreamSchemas::with_router_metadata(Some(metadata)).unpack(packed[0].clone())?;
+        assert_eq!(
+            unpacked.iter().map(describe).collect::<Vec<_>>(),
+            events.iter().map(describe).collect::<Vec<_>>()
+        );
+        Ok(())
+    }
+}
@@ -1,7 +1,50 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tokio::sync::watch;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum ConnectionStatus {
     Disconnected,
     Connecting,
     Connected,
     Error,
+    /// The client gave up reconnecting, this status is terminal
+    Failed,
+}
+
+/// Wait until `connection_status` reports connected or the timeout expires
+pub(crate) async fn wait_until_connected(
+    connection_status: &watch::Receiver<ConnectionStatus>,
+    timeout: Duration,
+) -> Result<()> {
+    let mut connection_status = connection_status.clone();
+    tokio::time::timeout(timeout, async move {
+        loop {
+            // Borrow and get the current value, this also updates the receiver if needed
+            let status = *connection_status.borrow_and_update();
+
+            // Check if we are already connected
+            if status == ConnectionStatus::Connected {
+                return Ok(()); // We are connected, success
+            }
+
+            // Check if the client gave up on connecting
+            if status == ConnectionStatus::Failed {
+                return Err(anyhow!("Client gave up connecting"));
+            }
+
+            // If not connected, wait for the next status change.
+            // This returns an error if the sender is dropped (client shut down).
+            connection_status.changed().await?;
+        }
+    })
+    .await // Await the timeout future
+    // If the timeout occurs, map the Elapsed error to an anyhow error
+    .map_err(|_| {
+        anyhow!(
+            "Timed out waiting for connection to become connected within {:?}",
+            timeout
+        )
+    })?
 }
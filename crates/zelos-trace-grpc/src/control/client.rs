@@ -0,0 +1,116 @@
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use tonic::transport::Channel;
+use uuid::Uuid;
+use zelos_proto::trace::{trace_source_control_client, SendControlRequest, SetParameterRequest};
+use zelos_trace_types::{
+    ipc::{TraceControl, TraceControlCommand},
+    Value,
+};
+
+use crate::transport;
+
+/// Client for sending control commands to the sources connected to a trace server
+#[derive(Debug, Clone)]
+pub struct TraceSourceControlClient {
+    client: trace_source_control_client::TraceSourceControlClient<Channel>,
+}
+
+impl TraceSourceControlClient {
+    /// Connect to the source control service at `url`
+    pub async fn connect(url: String) -> Result<Self> {
+        let channel = transport::connect(&url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to source control service: {}", e))?;
+        Ok(Self {
+            client: trace_source_control_client::TraceSourceControlClient::new(channel),
+        })
+    }
+
+    /// Send `control`, returning how many publishers and local sources received it
+    pub async fn send(&mut self, control: TraceControl) -> Result<usize> {
+        let response = self
+            .client
+            .send(SendControlRequest {
+                control: Some(control.into()),
+            })
+            .await
+            .map_err(|e| anyhow!("Failed to send control: {}", e.message()))?
+            .into_inner();
+        Ok(response.receivers as usize)
+    }
+
+    /// Enable or disable `event_name` of `source_name`, or every event or source if `None`
+    pub async fn set_event_enabled(
+        &mut self,
+        source_name: Option<&str>,
+        event_name: Option<&str>,
+        enabled: bool,
+    ) -> Result<usize> {
+        self.send(TraceControl {
+            source_name: source_name.map(str::to_string),
+            command: TraceControlCommand::SetEventEnabled {
+                event_name: event_name.map(str::to_string),
+                enabled,
+            },
+        })
+        .await
+    }
+
+    /// Emit `event_name` of `source_name` at most `max_rate_hz` times per second, or without a limit if `None`
+    pub async fn set_sample_rate(
+        &mut self,
+        source_name: Option<&str>,
+        event_name: Option<&str>,
+        max_rate_hz: Option<f64>,
+    ) -> Result<usize> {
+        self.send(TraceControl {
+            source_name: source_name.map(str::to_string),
+            command: TraceControlCommand::SetSampleRate {
+                event_name: event_name.map(str::to_string),
+                max_rate_hz,
+            },
+        })
+        .await
+    }
+
+    /// Ask `source_name`, or every source if `None`, to emit its metadata again
+    pub async fn resend_metadata(&mut self, source_name: Option<&str>) -> Result<usize> {
+        self.send(TraceControl {
+            source_name: source_name.map(str::to_string),
+            command: TraceControlCommand::ResendMetadata,
+        })
+        .await
+    }
+
+    /// Set the parameter `name` of the source named `source_name`, returning the value the source accepted. It is set
+    /// on the segment `segment_id`, or the only live segment of the source with the parameter if `None`. The server
+    /// waits up to `timeout` for the source to answer, or its default if `None`.
+    pub async fn set_parameter(
+        &mut self,
+        source_name: &str,
+        name: &str,
+        value: Value,
+        segment_id: Option<Uuid>,
+        timeout: Option<Duration>,
+    ) -> Result<Value> {
+        let response = self
+            .client
+            .set_parameter(SetParameterRequest {
+                source_name: source_name.to_string(),
+                name: name.to_string(),
+                value: Some(value.into()),
+                segment_id: segment_id.map(|id| id.into_bytes().to_vec()),
+                timeout_ms: timeout.map(|timeout| timeout.as_millis() as u64),
+            })
+            .await
+            .map_err(|e| anyhow!("Failed to set {}/{}: {}", source_name, name, e.message()))?
+            .into_inner();
+
+        Ok(response
+            .value
+            .ok_or_else(|| anyhow!("Missing the accepted value"))?
+            .try_into()?)
+    }
+}
@@ -0,0 +1,5 @@
+mod client;
+mod service;
+
+pub use client::TraceSourceControlClient;
+pub use service::TraceSourceControlService;
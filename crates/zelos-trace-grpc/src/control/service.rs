@@ -0,0 +1,179 @@
+use std::sync::Arc;
+
+use tonic::{Request, Response, Status};
+use uuid::Uuid;
+use zelos_proto::trace::{
+    trace_source_control_server::{TraceSourceControl, TraceSourceControlServer},
+    SendControlRequest, SendControlResponse, SetParameterRequest, SetParameterResponse,
+};
+use zelos_trace::TraceRouter;
+use zelos_trace_types::ipc::{TraceControl, TraceControlCommand};
+
+use crate::action::{call_status, request_timeout};
+
+/// Sends control commands to the sources publishing to a router
+pub struct TraceSourceControlService {
+    router: Arc<TraceRouter>,
+}
+
+impl TraceSourceControlService {
+    pub fn new(router: Arc<TraceRouter>) -> Self {
+        Self { router }
+    }
+
+    pub fn server(self) -> TraceSourceControlServer<Self> {
+        TraceSourceControlServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl TraceSourceControl for TraceSourceControlService {
+    async fn send(
+        &self,
+        request: Request<SendControlRequest>,
+    ) -> Result<Response<SendControlResponse>, Status> {
+        let control: TraceControl = request
+            .into_inner()
+            .control
+            .ok_or_else(|| Status::invalid_argument("Missing control"))?
+            .try_into()
+            .map_err(|e| Status::invalid_argument(format!("Invalid control: {}", e)))?;
+
+        // Calls are answered through the router's pending calls, which only the action service registers
+        if let TraceControlCommand::CallAction(_) = control.command {
+            return Err(Status::invalid_argument(
+                "Actions are called through the TraceAction service",
+            ));
+        }
+
+        let receivers = self.router.send_control(control);
+        if receivers == 0 {
+            return Err(Status::unavailable("No sources are following controls"));
+        }
+        Ok(Response::new(SendControlResponse {
+            receivers: receivers as u32,
+        }))
+    }
+
+    async fn set_parameter(
+        &self,
+        request: Request<SetParameterRequest>,
+    ) -> Result<Response<SetParameterResponse>, Status> {
+        let request = request.into_inner();
+        let value = request
+            .value
+            .ok_or_else(|| Status::invalid_argument("Missing value"))?
+            .try_into()
+            .map_err(|e| Status::invalid_argument(format!("Invalid value: {}", e)))?;
+        let segment_id = request
+            .segment_id
+            .map(|id| Uuid::from_slice(&id))
+            .transpose()
+            .map_err(|e| Status::invalid_argument(format!("Invalid segment id: {}", e)))?;
+
+        let value = self
+            .router
+            .set_parameter(
+                &request.source_name,
+                &request.name,
+                value,
+                segment_id,
+                request_timeout(request.timeout_ms),
+            )
+            .await
+            .map_err(call_status)?;
+
+        Ok(Response::new(SetParameterResponse {
+            value: Some(value.into()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tokio_util::sync::CancellationToken;
+    use zelos_trace::TraceSource;
+    use zelos_trace_types::{ipc::TraceActionCall, Value};
+
+    use super::*;
+    use crate::control::TraceSourceControlClient;
+
+    #[tokio::test]
+    async fn test_send_control() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = tokio::spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceSourceControlService::new(router.clone()).server())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+
+        let mut client = TraceSourceControlClient::connect(format!("grpc://{}", addr)).await?;
+
+        // Nothing is following the router's controls yet
+        assert!(client.resend_metadata(None).await.is_err());
+
+        let mut controls = router.controls();
+        let receivers = client
+            .set_event_enabled(Some("rig"), Some("motor"), false)
+            .await?;
+        assert_eq!(receivers, 1);
+        assert_eq!(
+            controls.recv().await?,
+            TraceControl {
+                source_name: Some("rig".to_string()),
+                command: TraceControlCommand::SetEventEnabled {
+                    event_name: Some("motor".to_string()),
+                    enabled: false,
+                },
+            }
+        );
+
+        // Calls have to go through the action service to get their results
+        let call = client
+            .send(TraceControl {
+                source_name: Some("rig".to_string()),
+                command: TraceControlCommand::CallAction(TraceActionCall {
+                    call_id: uuid::Uuid::now_v7(),
+                    action_name: "reset".to_string(),
+                    arguments: Default::default(),
+                    segment_id: None,
+                }),
+            })
+            .await;
+        assert!(call.is_err());
+        assert!(controls.try_recv().is_err());
+
+        // Parameters are set through the router, which waits for the source's answer
+        let source = Arc::new(TraceSource::new("rig", router.sender()));
+        source.add_parameter("gain", Value::Float64(0.5), None, None, None)?;
+        tokio::spawn(source.follow_controls(router.controls()));
+        let value = client
+            .set_parameter(
+                "rig",
+                "gain",
+                Value::Float64(0.75),
+                Some(source.id),
+                Some(Duration::from_secs(1)),
+            )
+            .await?;
+        assert_eq!(value, Value::Float64(0.75));
+        assert!(client
+            .set_parameter("rig", "gain", Value::Int32(1), Some(source.id), None)
+            .await
+            .is_err());
+
+        server.abort();
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+}
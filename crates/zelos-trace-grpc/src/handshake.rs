@@ -0,0 +1,187 @@
+use parking_lot::RwLock;
+use tokio::sync::Notify;
+use tonic::{
+    metadata::{MetadataMap, MetadataValue},
+    Code, Status,
+};
+use zelos_proto::{
+    error::Error,
+    trace::{Capability, Handshake, TraceMessage},
+};
+use zelos_trace_types::ipc::IpcMessageWithId;
+
+use crate::columnar::StreamSchemas;
+
+/// Capabilities of the publish and subscribe services. gzip is not offered since tonic is built without compression.
+pub(crate) const SERVICE_CAPABILITIES: [Capability; 3] = [
+    Capability::EventBatch,
+    Capability::Acks,
+    Capability::Control,
+];
+
+/// Metadata set on the status of a stream failed because of the handshake, so clients can tell it apart from other
+/// FAILED_PRECONDITION errors
+const REJECTED_KEY: &str = "zelos-handshake";
+const REJECTED_VALUE: &str = "rejected";
+
+/// The handshake agreed with the peer of a stream, shared between the task that reads the peer's handshake and the
+/// stream that encodes messages for it. Until a handshake is agreed the peer is treated as legacy.
+#[derive(Debug)]
+pub(crate) struct Agreed {
+    handshake: RwLock<Handshake>,
+    settled: Notify,
+}
+
+impl Agreed {
+    pub(crate) fn legacy() -> Self {
+        Self {
+            handshake: RwLock::new(Handshake::legacy()),
+            settled: Notify::new(),
+        }
+    }
+
+    pub(crate) fn set(&self, agreed: Handshake) {
+        *self.handshake.write() = agreed;
+        self.settled.notify_one();
+    }
+
+    /// Wait until the handshake is first [`set`](Self::set), e.g. to legacy once the peer answers without one
+    pub(crate) async fn settled(&self) {
+        self.settled.notified().await
+    }
+
+    /// Convert messages to protos for the peer, see [`encode`]
+    pub(crate) fn encode(
+        &self,
+        schemas: &mut StreamSchemas,
+        msgs: Vec<IpcMessageWithId>,
+    ) -> Vec<TraceMessage> {
+        let agreed = self.handshake.read().clone();
+        encode(&agreed, schemas, msgs)
+    }
+}
+
+/// Convert messages to protos for a peer that agreed on `agreed`, leaving out those its protocol version cannot
+/// decode and packing events once it agreed to event batches
+pub(crate) fn encode(
+    agreed: &Handshake,
+    schemas: &mut StreamSchemas,
+    mut msgs: Vec<IpcMessageWithId>,
+) -> Vec<TraceMessage> {
+    msgs.retain(|msg| agreed.supports(&msg.msg));
+    schemas.encode(msgs, agreed.has(Capability::EventBatch))
+}
+
+/// Negotiate with a client's handshake, failing the stream with FAILED_PRECONDITION if its versions are incompatible
+pub(crate) fn negotiate(client: &Handshake) -> Result<Handshake, Status> {
+    Handshake::local(SERVICE_CAPABILITIES)
+        .negotiate(client)
+        .map_err(|e| {
+            tracing::warn!("Rejecting client: {}", e);
+            let mut metadata = MetadataMap::new();
+            metadata.insert(REJECTED_KEY, MetadataValue::from_static(REJECTED_VALUE));
+            Status::with_metadata(Code::FailedPrecondition, e.to_string(), metadata)
+        })
+}
+
+/// Check the server's reply to our handshake, returning the capabilities to use
+pub(crate) fn accept(local: &Handshake, server: &Handshake) -> anyhow::Result<Handshake> {
+    Ok(local.negotiate(server)?)
+}
+
+/// Whether a client error means the server's protocol is incompatible with ours, in which case reconnecting will not
+/// help
+pub(crate) fn is_incompatible(e: &anyhow::Error) -> bool {
+    let rejected = e.downcast_ref::<Status>().is_some_and(|status| {
+        status.code() == Code::FailedPrecondition
+            && status
+                .metadata()
+                .get(REJECTED_KEY)
+                .is_some_and(|value| value == REJECTED_VALUE)
+    });
+    let incompatible = matches!(
+        e.downcast_ref::<Error>(),
+        Some(Error::IncompatibleProtocol { .. })
+    );
+    rejected || incompatible
+}
+
+#[cfg(test)]
+mod test {
+    use anyhow::Result;
+    use zelos_proto::trace::{MIN_PROTOCOL_VERSION, PROTOCOL_VERSION};
+    use zelos_trace_types::ipc::{IpcMessage, LogLevel, TraceLog, TraceSegmentEnd};
+
+    use super::*;
+
+    #[test]
+    fn test_negotiate() -> Result<()> {
+        // A client with fewer capabilities gets only those
+        let agreed = negotiate(&Handshake::local([Capability::Acks, Capability::Gzip]))?;
+        assert_eq!(agreed.protocol_version, PROTOCOL_VERSION);
+        assert!(agreed.has(Capability::Acks));
+        assert!(!agreed.has(Capability::Gzip));
+        assert!(!agreed.has(Capability::EventBatch));
+
+        // An older client is downgraded to its version
+        let old = Handshake {
+            protocol_version: MIN_PROTOCOL_VERSION,
+            min_protocol_version: 0,
+            capabilities: vec![Capability::EventBatch.into()],
+        };
+        let agreed = negotiate(&old)?;
+        assert_eq!(agreed.protocol_version, MIN_PROTOCOL_VERSION);
+        assert!(agreed.has(Capability::EventBatch));
+
+        // A newer client that cannot go back to our version is rejected, and the client recognises the rejection
+        let newer = Handshake {
+            protocol_version: PROTOCOL_VERSION + 2,
+            min_protocol_version: PROTOCOL_VERSION + 1,
+            capabilities: vec![],
+        };
+        let status = negotiate(&newer)
+            .err()
+            .ok_or_else(|| anyhow::anyhow!("Expected a rejection"))?;
+        assert!(is_incompatible(&status.into()));
+        let local = Handshake::local(SERVICE_CAPABILITIES);
+        let e = accept(&local, &newer)
+            .err()
+            .ok_or_else(|| anyhow::anyhow!("Expected an error"))?;
+        assert!(is_incompatible(&e));
+        assert!(!is_incompatible(&anyhow::anyhow!("Connection reset")));
+
+        // Other failed preconditions are not a handshake rejection
+        let other = Status::failed_precondition("Not ready");
+        assert!(!is_incompatible(&other.into()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_agreed_encode() -> Result<()> {
+        let msg = |msg| IpcMessageWithId {
+            segment_id: uuid::Uuid::now_v7(),
+            source_name: "rig".into(),
+            msg,
+        };
+        let msgs = vec![
+            msg(IpcMessage::TraceSegmentEnd(TraceSegmentEnd { time_ns: 1 })),
+            msg(IpcMessage::Log(TraceLog {
+                time_ns: 1,
+                level: LogLevel::Info,
+                target: "rig".to_string(),
+                message: "started".to_string(),
+                fields: Default::default(),
+            })),
+        ];
+
+        // Peers that predate the handshake cannot decode logs
+        let agreed = Agreed::legacy();
+        let encoded = agreed.encode(&mut StreamSchemas::default(), msgs.clone());
+        assert_eq!(encoded.len(), 1);
+
+        agreed.set(negotiate(&Handshake::local(SERVICE_CAPABILITIES))?);
+        let encoded = agreed.encode(&mut StreamSchemas::default(), msgs);
+        assert_eq!(encoded.len(), 2);
+        Ok(())
+    }
+}
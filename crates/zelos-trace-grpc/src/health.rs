@@ -0,0 +1,98 @@
+use std::{future::Future, sync::Arc, time::Duration};
+
+use tokio_util::sync::CancellationToken;
+use tonic::server::NamedService;
+use tonic_health::{
+    pb::health_server::{Health, HealthServer},
+    server::HealthReporter,
+    ServingStatus,
+};
+use zelos_proto::trace::{
+    trace_catalog_server::TraceCatalogServer, trace_publish_server::TracePublishServer,
+    trace_subscribe_server::TraceSubscribeServer,
+};
+use zelos_trace::TraceRouter;
+
+use crate::{
+    catalog::TraceCatalogService, publish::TracePublishService, subscribe::TraceSubscribeService,
+};
+
+const DEFAULT_CHECK_INTERVAL_MS: u64 = 1000;
+const DEFAULT_SATURATION_THRESHOLD: f64 = 0.9;
+
+/// Services whose status follows the router, the empty name is the status of the server as a whole
+const ROUTER_SERVICES: &[&str] = &[
+    "",
+    <TracePublishServer<TracePublishService> as NamedService>::NAME,
+    <TraceSubscribeServer<TraceSubscribeService> as NamedService>::NAME,
+];
+
+#[derive(Debug, Clone)]
+pub struct TraceHealthConfig {
+    /// How often the router is checked
+    pub check_interval: Duration,
+    /// The router is reported as not serving once its input channel is fuller than this fraction
+    pub saturation_threshold: f64,
+}
+
+impl Default for TraceHealthConfig {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_millis(DEFAULT_CHECK_INTERVAL_MS),
+            saturation_threshold: DEFAULT_SATURATION_THRESHOLD,
+        }
+    }
+}
+
+/// The status of the router: serving while its task is running and its channel is not saturated
+pub fn router_status(router: &TraceRouter, saturation_threshold: f64) -> ServingStatus {
+    if router.is_running() && router.saturation() < saturation_threshold {
+        ServingStatus::Serving
+    } else {
+        ServingStatus::NotServing
+    }
+}
+
+async fn set_router_status(reporter: &mut HealthReporter, status: ServingStatus) {
+    for service in ROUTER_SERVICES {
+        reporter.set_service_status(service, status).await;
+    }
+}
+
+/// Create a `grpc.health.v1` service for the trace services, and the task that keeps it up to date with the
+/// router's status until cancelled.
+pub fn health_service(
+    router: Arc<TraceRouter>,
+    config: TraceHealthConfig,
+    cancellation_token: CancellationToken,
+) -> (HealthServer<impl Health>, impl Future<Output = ()>) {
+    let (mut reporter, server) = tonic_health::server::health_reporter();
+
+    let task = async move {
+        // The catalog only reads metadata, so it is available for as long as we are
+        reporter
+            .set_serving::<TraceCatalogServer<TraceCatalogService>>()
+            .await;
+
+        let mut last_status = None;
+        let mut interval = tokio::time::interval(config.check_interval);
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                _ = cancellation_token.cancelled() => break,
+            }
+
+            let status = router_status(&router, config.saturation_threshold);
+            if last_status != Some(status) {
+                tracing::debug!("Router health changed to {:?}", status);
+                set_router_status(&mut reporter, status).await;
+                last_status = Some(status);
+            }
+        }
+
+        // Let clients know we are going away while the server drains
+        set_router_status(&mut reporter, ServingStatus::NotServing).await;
+    };
+
+    (server, task)
+}
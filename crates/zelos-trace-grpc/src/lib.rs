@@ -1,4 +1,21 @@
+// tonic::Status is large, but it is the error type every gRPC handler has to return
+#![allow(clippy::result_large_err)]
+
+mod columnar;
 mod connection_status;
+mod handshake;
+mod reconnect;
 
+pub mod action;
+#[cfg(feature = "agent")]
+pub mod agent;
+pub mod catalog;
+pub mod control;
+pub mod health;
 pub mod publish;
+pub mod reflection;
 pub mod subscribe;
+pub mod transport;
+
+pub use connection_status::ConnectionStatus;
+pub use reconnect::{BackoffConfig, ConnectionStats, UrlSelection};
@@ -2,30 +2,46 @@ use std::{sync::Arc, time::Duration};
 
 use anyhow::{anyhow, Result};
 use tokio::{sync::watch, time::Instant};
-use tokio_stream::StreamExt;
-use tonic::{Code, Request};
+use tokio_stream::{self as stream, StreamExt};
+use tonic::{Code, Request, Streaming};
 use zelos_proto::trace::{
-    trace_publish_client::TracePublishClient as GrpcClient, PublishRequest, PublishStatus,
+    trace_publish_client::TracePublishClient as GrpcClient, Capability, Handshake, PublishRequest,
+    PublishResponse, PublishStatus,
 };
 use zelos_trace::TraceRouter;
 
-use crate::connection_status::ConnectionStatus;
+use crate::{
+    columnar::StreamSchemas,
+    connection_status::{self, ConnectionStatus},
+    handshake::{self, Agreed},
+    reconnect::{
+        Backoff, BackoffConfig, ConnectionCounters, ConnectionStats, Endpoints, UrlSelection,
+    },
+    transport,
+};
 
 const DEFAULT_BATCH_SIZE: usize = 1000;
 const DEFAULT_BATCH_TIMEOUT_MS: u64 = 100;
 const DEFAULT_URL: &str = "grpc://localhost:2300";
-const DEFAULT_RECONNECT_DELAY_MS: u64 = 1000;
 
 #[derive(Debug, Clone)]
 pub struct TracePublishClientConfig {
-    /// URL of the trace publish service
+    /// URL of the trace publish service, e.g. `grpc://127.0.0.1:2300` or `unix:///run/zelos/agent.sock`
     pub url: String,
+    /// Additional URLs of trace publish services to use when `url` is unavailable
+    pub fallback_urls: Vec<String>,
+    /// How to choose between `url` and `fallback_urls` when reconnecting
+    pub url_selection: UrlSelection,
     /// Maximum number of messages to batch together in a single request
     pub batch_size: usize,
     /// Maximum time to wait before sending a batch (even if not full)
     pub batch_timeout: Duration,
-    /// Minimum delay between connection attempts
-    pub reconnect_delay: Duration,
+    /// Pack the events of each batch into columns per event schema, which is much smaller on the wire. Events are only
+    /// packed once the server's handshake agrees to event batches, servers that predate them or don't send a handshake
+    /// get individual events, so this is safe to leave on.
+    pub columnar: bool,
+    /// Backoff policy between connection attempts
+    pub reconnect: BackoffConfig,
 }
 
 impl TracePublishClientConfig {
@@ -41,9 +57,12 @@ impl Default for TracePublishClientConfig {
     fn default() -> Self {
         Self {
             url: DEFAULT_URL.to_string(),
+            fallback_urls: Vec::new(),
+            url_selection: UrlSelection::default(),
             batch_size: DEFAULT_BATCH_SIZE,
             batch_timeout: Duration::from_millis(DEFAULT_BATCH_TIMEOUT_MS),
-            reconnect_delay: Duration::from_millis(DEFAULT_RECONNECT_DELAY_MS),
+            columnar: true,
+            reconnect: BackoffConfig::default(),
         }
     }
 }
@@ -57,6 +76,9 @@ pub struct TracePublishClient {
 
     /// The last publish status from the connection
     publish_status: watch::Receiver<Option<PublishStatus>>,
+
+    /// Counters for connection attempts
+    counters: Arc<ConnectionCounters>,
 }
 
 impl TracePublishClient {
@@ -68,13 +90,21 @@ impl TracePublishClient {
         let (tx_connection_status, connection_status) =
             watch::channel(ConnectionStatus::Disconnected);
         let (tx_publish_status, publish_status) = watch::channel(None);
+        let counters = Arc::new(ConnectionCounters::default());
 
         let client = Self {
             config: config.clone(),
             connection_status,
             publish_status,
+            counters: counters.clone(),
         };
-        let task = Self::run(router, config, tx_publish_status, tx_connection_status);
+        let task = Self::run(
+            router,
+            config,
+            tx_publish_status,
+            tx_connection_status,
+            counters,
+        );
 
         (client, task)
     }
@@ -86,27 +116,57 @@ impl TracePublishClient {
         Self::new(router, TracePublishClientConfig::default())
     }
 
-    /// Connect to the endpoint specified in the config, returning with an error on failure.
-    async fn connect(
-        router: Arc<TraceRouter>,
-        config: TracePublishClientConfig,
-        tx_publish_status: &watch::Sender<Option<PublishStatus>>,
-        tx_connection_status: &watch::Sender<ConnectionStatus>,
-    ) -> Result<()> {
+    /// The handshake this client opens each publish stream with
+    fn local_handshake(config: &TracePublishClientConfig) -> Handshake {
+        let mut capabilities = vec![Capability::Acks, Capability::Control];
+        if config.columnar {
+            capabilities.push(Capability::EventBatch);
+        }
+        Handshake::local(capabilities)
+    }
+
+    /// Connect to `url` and start publishing all router messages, returning the server's response stream and the
+    /// handshake to set once the server answers.
+    async fn establish(
+        router: &Arc<TraceRouter>,
+        url: &str,
+        config: &TracePublishClientConfig,
+    ) -> Result<(Streaming<PublishResponse>, Arc<Agreed>)> {
         // Attempt to connect to the grpc server
-        tracing::info!("Trace client connecting to {}", &config.url);
-        let mut client = GrpcClient::connect(config.url.clone())
+        tracing::info!("Trace client connecting to {}", url);
+        let channel = transport::connect(url)
             .await
             .map_err(|e| anyhow!("Failed to connect to publish service: {}", e))?;
+        let mut client = GrpcClient::new(channel);
 
-        // Subscribe to all messages and get it back as a stream
-        let stream = router
+        // Subscribe to all messages and get it back as a stream. The subscription replays metadata first, so the schemas
+        // of every packed event are sent on this stream before it. Nothing is sent until the server answers, with its
+        // handshake or, for servers that predate it, a status, so older servers never get messages they cannot decode.
+        let agreed = Arc::new(Agreed::legacy());
+        let encoder = agreed.clone();
+        let mut schemas = StreamSchemas::default();
+        let messages = router
             .subscribe_all_blocking_stream()
             .await?
             .chunks_timeout(config.batch_size, config.batch_timeout)
-            .map(|m| PublishRequest {
-                trace_messages: m.into_iter().map(|msg| msg.into()).collect(),
+            .map(move |m| PublishRequest {
+                trace_messages: encoder.encode(&mut schemas, m),
+                handshake: None,
             });
+        let settled = agreed.clone();
+        let stream = stream::once(PublishRequest {
+            trace_messages: vec![],
+            handshake: Some(Self::local_handshake(config)),
+        })
+        .chain(
+            stream::once(())
+                .then(move |()| {
+                    let settled = settled.clone();
+                    async move { settled.settled().await }
+                })
+                .filter_map(|()| None::<PublishRequest>),
+        )
+        .chain(messages);
 
         // Call our rpc to publish to the server
         let response = client
@@ -114,16 +174,43 @@ impl TracePublishClient {
             .await
             .map_err(|e| anyhow!("Failed to establish publish stream: {e}"))?;
         tracing::debug!("Successfully established new gRPC publish stream.");
-        tx_connection_status.send(ConnectionStatus::Connected)?;
 
-        // Process status messages sent back from the server
-        let mut response_stream = response.into_inner();
+        Ok((response.into_inner(), agreed))
+    }
+
+    /// Process status messages sent back from the server until the stream ends, passing control commands on to the
+    /// router's sources
+    async fn process_responses(
+        mut response_stream: Streaming<PublishResponse>,
+        router: &TraceRouter,
+        tx_publish_status: &watch::Sender<Option<PublishStatus>>,
+        local: &Handshake,
+        agreed: &Agreed,
+    ) -> Result<()> {
+        let mut answered = false;
         loop {
             match response_stream.message().await {
                 Ok(Some(resp)) => {
+                    if let Some(reply) = resp.handshake {
+                        let reply = handshake::accept(local, &reply)?;
+                        tracing::debug!("Publish stream agreed on {:?}", reply);
+                        agreed.set(reply);
+                    } else if !answered {
+                        tracing::debug!("Publish service predates the handshake");
+                        agreed.set(Handshake::legacy());
+                    }
+                    answered = true;
                     if let Some(status) = resp.status {
                         tx_publish_status.send(Some(status))?;
                     }
+                    if let Some(control) = resp.control {
+                        match control.try_into() {
+                            Ok(control) => {
+                                router.send_control(control);
+                            }
+                            Err(e) => tracing::warn!("Ignoring invalid control command: {}", e),
+                        }
+                    }
                     tracing::trace!("Publish status: {:?}", tx_publish_status);
                 }
                 Ok(None) => {
@@ -133,45 +220,84 @@ impl TracePublishClient {
                 Err(status) => {
                     return match status.code() {
                         Code::Ok => Ok(()),
-                        _ => Err(anyhow!("Received error status: {}", status)),
+                        _ => Err(status.into()),
                     };
                 }
             }
         }
     }
 
-    /// The main task loop, which attempts to reconnect repeatedly while waiting for reconnect_delay
+    /// The main task loop, which attempts to reconnect repeatedly, backing off between failed attempts and giving up
+    /// once the configured maximum number of attempts is reached.
     async fn run(
         router: Arc<TraceRouter>,
         config: TracePublishClientConfig,
         tx_publish_status: watch::Sender<Option<PublishStatus>>,
         tx_connection_status: watch::Sender<ConnectionStatus>,
+        counters: Arc<ConnectionCounters>,
     ) -> Result<()> {
-        let mut last_connection_time: Instant;
+        let mut backoff = Backoff::new(config.reconnect.clone());
+        let mut endpoints =
+            Endpoints::new(&config.url, &config.fallback_urls, config.url_selection);
+        let local = Self::local_handshake(&config);
         loop {
             // Attempt to connect
-            last_connection_time = Instant::now();
+            counters.record_attempt();
             tx_connection_status.send(ConnectionStatus::Connecting)?;
-            if let Err(e) = Self::connect(
-                router.clone(),
-                config.clone(),
-                &tx_publish_status,
-                &tx_connection_status,
-            )
-            .await
-            {
-                tracing::error!("Error forwarding trace events: {}", e);
-                tx_connection_status.send(ConnectionStatus::Error)?;
-            } else {
-                tx_connection_status.send(ConnectionStatus::Disconnected)?;
-            }
 
-            // If our reconnect attempt was too recent, sleep until our reconnect delay is up
-            let elapsed = last_connection_time.elapsed();
-            if elapsed < config.reconnect_delay {
-                let remaining = config.reconnect_delay - elapsed;
-                tokio::time::sleep(remaining).await;
-            }
+            let url = endpoints.current().to_string();
+            let delay = match Self::establish(&router, &url, &config).await {
+                Ok((response_stream, agreed)) => {
+                    let connected_at = Instant::now();
+                    counters.record_connected();
+                    backoff.connected();
+                    tx_connection_status.send(ConnectionStatus::Connected)?;
+
+                    if let Err(e) = Self::process_responses(
+                        response_stream,
+                        &router,
+                        &tx_publish_status,
+                        &local,
+                        &agreed,
+                    )
+                    .await
+                    {
+                        // Reconnecting to a server that speaks an incompatible protocol will not help
+                        if handshake::is_incompatible(&e) {
+                            tracing::error!("Publish service at {} is incompatible: {}", url, e);
+                            tx_connection_status.send(ConnectionStatus::Failed)?;
+                            return Err(anyhow!("Giving up on incompatible publish service: {e}"));
+                        }
+                        tracing::error!("Error forwarding trace events to {}: {}", url, e);
+                        tx_connection_status.send(ConnectionStatus::Error)?;
+                    } else {
+                        tx_connection_status.send(ConnectionStatus::Disconnected)?;
+                    }
+                    endpoints.record_disconnect();
+
+                    // Back off further if the connection did not stay up for long
+                    backoff.disconnected(connected_at.elapsed())
+                }
+                Err(e) => {
+                    tracing::error!("Error connecting to {}: {}", url, e);
+                    counters.record_failure();
+                    endpoints.record_failure();
+                    tx_connection_status.send(ConnectionStatus::Error)?;
+
+                    match backoff.next_delay() {
+                        Some(delay) => delay,
+                        None => {
+                            tx_connection_status.send(ConnectionStatus::Failed)?;
+                            return Err(anyhow!(
+                                "Giving up after {} consecutive failed connection attempts",
+                                counters.snapshot().consecutive_failures
+                            ));
+                        }
+                    }
+                }
+            };
+
+            tokio::time::sleep(delay).await;
         }
     }
 
@@ -187,30 +313,7 @@ impl TracePublishClient {
 
     /// Wait until connected or the timeout expires
     pub async fn wait_until_connected(&self, timeout: Duration) -> Result<()> {
-        let mut connection_status = self.connection_status.clone();
-        tokio::time::timeout(timeout, async move {
-            loop {
-                // Borrow and get the current value, this also updates the receiver if needed
-                let status = *connection_status.borrow_and_update();
-
-                // Check if we are already connected
-                if status == ConnectionStatus::Connected {
-                    return Ok(()); // We are connected, success
-                }
-
-                // If not connected, wait for the next status change.
-                // This returns an error if the sender is dropped (client shut down).
-                connection_status.changed().await?;
-            }
-        })
-        .await // Await the timeout future
-        // If the timeout occurs, map the Elapsed error to an anyhow error
-        .map_err(|_| {
-            anyhow!(
-                "Timed out waiting for connection to become connected within {:?}",
-                timeout
-            )
-        })?
+        connection_status::wait_until_connected(&self.connection_status, timeout).await
     }
 
     /// Gets a clone of the publish status receiver
@@ -222,4 +325,9 @@ impl TracePublishClient {
     pub async fn last_publish_status(&self) -> Option<PublishStatus> {
         self.publish_status.borrow().clone()
     }
+
+    /// Gets a snapshot of the connection attempt counters
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.counters.snapshot()
+    }
 }
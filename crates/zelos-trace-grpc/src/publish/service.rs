@@ -1,46 +1,94 @@
 use std::pin::Pin;
 
-use tokio::{sync::mpsc, time::Duration};
+use tokio::{
+    sync::{broadcast, mpsc},
+    time::Duration,
+};
 use tokio_stream::{wrappers::ReceiverStream, Stream};
 use tokio_util::sync::CancellationToken;
 use tonic::{Request, Response, Status, Streaming};
-use zelos_proto::trace::{
-    trace_publish_server::{TracePublish, TracePublishServer},
-    PublishRequest, PublishResponse, PublishStatus,
+use zelos_proto::{
+    error::Error,
+    trace::{
+        trace_publish_server::{TracePublish, TracePublishServer},
+        Capability, Handshake, PublishRequest, PublishResponse, PublishStatus, PROTOCOL_VERSION,
+    },
 };
-use zelos_trace_types::ipc::Sender;
+use zelos_trace::TraceMetadata;
+use zelos_trace_types::ipc::{Sender, TraceControl};
+
+use crate::{columnar::StreamSchemas, handshake};
+
+/// Number of control commands kept for streams that fall behind, when the service owns its control channel
+const CONTROL_CHANNEL_SIZE: usize = 64;
 
 pub struct TracePublishService {
     sender: Sender,
+    controls: broadcast::Sender<TraceControl>,
+    router_metadata: Option<TraceMetadata>,
     cancellation_token: CancellationToken,
 }
 
 impl TracePublishService {
     pub fn new(sender: Sender, cancellation_token: CancellationToken) -> Self {
+        let (controls, _) = broadcast::channel(CONTROL_CHANNEL_SIZE);
         Self {
             sender,
+            controls,
+            router_metadata: None,
             cancellation_token,
         }
     }
 
+    /// Unpack event batches whose schema the client sent on an earlier stream with the schemas in `metadata`, e.g.
+    /// [`zelos_trace::TraceRouter::metadata`], rather than rejecting them
+    pub fn with_router_metadata(mut self, metadata: Option<TraceMetadata>) -> Self {
+        self.router_metadata = metadata;
+        self
+    }
+
+    /// Forward the control commands sent on `controls`, e.g. [`zelos_trace::TraceRouter::control_sender`], to every
+    /// connected client that supports them
+    pub fn with_controls(mut self, controls: broadcast::Sender<TraceControl>) -> Self {
+        self.controls = controls;
+        self
+    }
+
+    /// The sender for control commands forwarded to connected clients
+    pub fn controls(&self) -> broadcast::Sender<TraceControl> {
+        self.controls.clone()
+    }
+
     pub fn server(self) -> TracePublishServer<Self> {
         TracePublishServer::new(self)
     }
 }
 
-/// Forward all of the messages in req to the router sender, returning a grpc error on failure
-async fn forward_request_messages(req: PublishRequest, sender: &Sender) -> Result<usize, Status> {
-    let count = req.trace_messages.len();
+/// Forward all of the messages in req to the router sender, unpacking event batches with the schemas seen on the
+/// stream and returning a grpc error on failure
+async fn forward_request_messages(
+    req: PublishRequest,
+    schemas: &mut StreamSchemas,
+    sender: &Sender,
+) -> Result<usize, Status> {
+    let mut count = 0;
     for msg in req.trace_messages {
-        // If try_into fails, we have an invalid proto message that we cannot understand
-        let ipc = msg
-            .try_into()
-            .map_err(|e| Status::invalid_argument(format!("Error converting message: {}", e)))?;
-        // If send_async fails, the router has shutdown and we cannot send any more messages
-        sender
-            .send_async(ipc)
-            .await
-            .map_err(|e| Status::unavailable(format!("Error sending message: {}", e)))?;
+        // If unpacking fails, we have an invalid proto message that we cannot understand
+        let msgs = schemas.unpack(msg).map_err(|e| match e {
+            Error::MissingMessage => Status::invalid_argument(format!(
+                "Unknown or missing message type, the client may speak a newer protocol than version {}",
+                PROTOCOL_VERSION
+            )),
+            e => Status::invalid_argument(format!("Error converting message: {}", e)),
+        })?;
+        for ipc in msgs {
+            // If send_async fails, the router has shutdown and we cannot send any more messages
+            sender
+                .send_async(ipc)
+                .await
+                .map_err(|e| Status::unavailable(format!("Error sending message: {}", e)))?;
+            count += 1;
+        }
     }
     Ok(count)
 }
@@ -60,8 +108,18 @@ impl TracePublish for TracePublishService {
         let mut stream = request.into_inner();
         let router_sender = self.sender.clone();
         let shutdown = self.cancellation_token.clone();
+        let mut controls = self.controls.subscribe();
+        let router_metadata = self.router_metadata.clone();
         tokio::spawn(async move {
             let mut msg_count = 0;
+            let mut schemas = StreamSchemas::with_router_metadata(router_metadata);
+
+            // Clients that predate the handshake never send one
+            let mut negotiated = Handshake::legacy();
+            let mut controls_open = true;
+            // Clients hold their messages until the first response, which must be the handshake reply if they sent
+            // one, so statuses only start once the first request was read
+            let mut started = false;
 
             // Send a heartbeat message to the client once per second
             let mut status_interval = tokio::time::interval(Duration::from_secs(1));
@@ -72,8 +130,28 @@ impl TracePublish for TracePublishService {
                 tokio::select! {
                     msg = stream.message() => {
                         match msg {
-                            Ok(Some(req)) => {
-                                match forward_request_messages(req, &router_sender).await {
+                            Ok(Some(mut req)) => {
+                                started = true;
+                                if let Some(client) = req.handshake.take() {
+                                    match handshake::negotiate(&client) {
+                                        Ok(agreed) => {
+                                            negotiated = agreed.clone();
+                                            let response = PublishResponse {
+                                                status: None,
+                                                handshake: Some(agreed),
+                                                control: None,
+                                            };
+                                            if tx.send(Ok(response)).await.is_err() {
+                                                return;
+                                            }
+                                        }
+                                        Err(e) => {
+                                            let _ = tx.send(Err(e)).await;
+                                            return;
+                                        }
+                                    }
+                                }
+                                match forward_request_messages(req, &mut schemas, &router_sender).await {
                                     Ok(count) => msg_count += count as u64,
                                     Err(e) => {
                                         // We had an error forwarding the request, attempt to send that error to the
@@ -95,7 +173,7 @@ impl TracePublish for TracePublishService {
                             }
                         }
                     }
-                    _ = status_interval.tick() => {
+                    _ = status_interval.tick(), if started && negotiated.has(Capability::Acks) => {
                         // Send a heartbeat message to the client
                         // NOTE(jbott): we close the connection on failure rather than sending an error because there is
                         // no way to recover.
@@ -104,11 +182,29 @@ impl TracePublish for TracePublishService {
                             successful_messages: msg_count,
                             failed_messages: 0,
                             last_error: "".to_string(),
-                        }) })).is_err() {
+                        }), handshake: None, control: None })).is_err() {
                             // Client disconnected, exit
                             return;
                         }
                     }
+                    control = controls.recv(), if controls_open && negotiated.has(Capability::Control) => {
+                        match control {
+                            Ok(control) => {
+                                let response = PublishResponse {
+                                    status: None,
+                                    handshake: None,
+                                    control: Some(control.into()),
+                                };
+                                if tx.send(Ok(response)).await.is_err() {
+                                    return;
+                                }
+                            }
+                            Err(broadcast::error::RecvError::Lagged(count)) => {
+                                tracing::warn!("Dropped {} control commands for a slow client", count);
+                            }
+                            Err(broadcast::error::RecvError::Closed) => controls_open = false,
+                        }
+                    }
                     _ = shutdown.cancelled() => {
                         // Server shutting down, inform the client and exit
                         let _ = tx.try_send(Err(Status::unavailable("Server shutting down".to_string())));
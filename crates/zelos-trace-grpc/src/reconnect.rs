@@ -0,0 +1,296 @@
+use std::{
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+    time::Duration,
+};
+
+const DEFAULT_INITIAL_DELAY_MS: u64 = 1000;
+const DEFAULT_MAX_DELAY_MS: u64 = 30_000;
+const DEFAULT_MULTIPLIER: f64 = 2.0;
+const DEFAULT_JITTER: f64 = 0.5;
+const DEFAULT_STABLE_AFTER_MS: u64 = 10_000;
+
+/// Policy for spacing out connection attempts
+#[derive(Debug, Clone)]
+pub struct BackoffConfig {
+    /// Delay after the first failed attempt, and the minimum delay between attempts
+    pub initial_delay: Duration,
+    /// Upper bound for the delay between attempts
+    pub max_delay: Duration,
+    /// Factor applied to the delay after every consecutive failure
+    pub multiplier: f64,
+    /// Fraction of each delay that is randomized, from 0.0 (none) to 1.0 (anywhere between zero and the delay)
+    pub jitter: f64,
+    /// Give up after this many consecutive failed attempts, or retry forever when unset
+    pub max_attempts: Option<u32>,
+    /// How long a connection must stay up before the delay goes back to `initial_delay`. Connections that end
+    /// sooner keep growing the delay, so a server that accepts and then drops every connection is not retried in a
+    /// tight loop.
+    pub stable_after: Duration,
+}
+
+impl Default for BackoffConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::from_millis(DEFAULT_INITIAL_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_MAX_DELAY_MS),
+            multiplier: DEFAULT_MULTIPLIER,
+            jitter: DEFAULT_JITTER,
+            max_attempts: None,
+            stable_after: Duration::from_millis(DEFAULT_STABLE_AFTER_MS),
+        }
+    }
+}
+
+/// Exponential backoff state for a single client
+#[derive(Debug)]
+pub(crate) struct Backoff {
+    config: BackoffConfig,
+    /// Failed attempts since the last established connection, counted towards `max_attempts`
+    failures: u32,
+    /// Delays since the last stable connection, which set how long the next one is
+    delays: u32,
+}
+
+impl Backoff {
+    pub(crate) fn new(config: BackoffConfig) -> Self {
+        Self {
+            config,
+            failures: 0,
+            delays: 0,
+        }
+    }
+
+    /// Record that a connection has been established, so earlier failed attempts no longer count towards giving up
+    pub(crate) fn connected(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Record that a connection ended after being up for `uptime`, returning how long to wait before the next attempt.
+    /// The delay starts over only if the connection was stable.
+    pub(crate) fn disconnected(&mut self, uptime: Duration) -> Duration {
+        if uptime >= self.config.stable_after {
+            self.delays = 0;
+        }
+        self.delay()
+    }
+
+    /// Record a failed attempt, returning how long to wait before the next one, or None if we should give up
+    pub(crate) fn next_delay(&mut self) -> Option<Duration> {
+        self.failures = self.failures.saturating_add(1);
+        if matches!(self.config.max_attempts, Some(max) if self.failures >= max) {
+            return None;
+        }
+        Some(self.delay())
+    }
+
+    fn delay(&mut self) -> Duration {
+        self.delays = self.delays.saturating_add(1);
+        let exponent = self.delays.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let base = self.config.initial_delay.as_secs_f64() * self.config.multiplier.powi(exponent);
+        let capped = base.min(self.config.max_delay.as_secs_f64());
+        let jitter = self.config.jitter.clamp(0.0, 1.0) * rand::random::<f64>();
+
+        // A negative or NaN multiplier or jitter gives a delay that is not a duration, wait the longest instead
+        Duration::try_from_secs_f64(capped * (1.0 - jitter)).unwrap_or(self.config.max_delay)
+    }
+}
+
+/// How a client chooses which URL to connect to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum UrlSelection {
+    /// Stay on the current URL while it works, moving to the next one in the list only after a failed attempt
+    #[default]
+    Failover,
+    /// Move to the next URL in the list on every attempt
+    RoundRobin,
+}
+
+/// The list of URLs a client may connect to, and which one is next
+#[derive(Debug)]
+pub(crate) struct Endpoints {
+    urls: Vec<String>,
+    selection: UrlSelection,
+    index: usize,
+}
+
+impl Endpoints {
+    pub(crate) fn new(url: &str, fallback_urls: &[String], selection: UrlSelection) -> Self {
+        let urls = std::iter::once(url.to_string())
+            .chain(fallback_urls.iter().cloned())
+            .collect();
+        Self {
+            urls,
+            selection,
+            index: 0,
+        }
+    }
+
+    /// The URL to use for the next attempt
+    pub(crate) fn current(&self) -> &str {
+        &self.urls[self.index]
+    }
+
+    fn advance(&mut self) {
+        self.index = (self.index + 1) % self.urls.len();
+    }
+
+    /// Record that an attempt against the current URL failed
+    pub(crate) fn record_failure(&mut self) {
+        self.advance();
+    }
+
+    /// Record that the connection to the current URL ended after being established
+    pub(crate) fn record_disconnect(&mut self) {
+        if self.selection == UrlSelection::RoundRobin {
+            self.advance();
+        }
+    }
+}
+
+/// A snapshot of the connection counters of a client
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    /// Total number of connection attempts
+    pub attempts: u64,
+    /// Number of attempts that failed to establish a connection
+    pub failures: u64,
+    /// Number of attempts that established a connection
+    pub connections: u64,
+    /// Number of failed attempts since the last established connection
+    pub consecutive_failures: u32,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct ConnectionCounters {
+    attempts: AtomicU64,
+    failures: AtomicU64,
+    connections: AtomicU64,
+    consecutive_failures: AtomicU32,
+}
+
+impl ConnectionCounters {
+    pub(crate) fn record_attempt(&self) {
+        self.attempts.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_failure(&self) {
+        self.failures.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_connected(&self) {
+        self.connections.fetch_add(1, Ordering::Relaxed);
+        self.consecutive_failures.store(0, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> ConnectionStats {
+        ConnectionStats {
+            attempts: self.attempts.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            connections: self.connections.load(Ordering::Relaxed),
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_backoff_grows_and_caps() {
+        let mut backoff = Backoff::new(BackoffConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(350),
+            multiplier: 2.0,
+            jitter: 0.0,
+            max_attempts: None,
+            stable_after: Duration::from_secs(1),
+        });
+
+        let delays: Vec<_> = (0..4).filter_map(|_| backoff.next_delay()).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(350),
+                Duration::from_millis(350),
+            ]
+        );
+
+        // Connections that drop right away keep growing the delay, a stable one starts it over
+        backoff.connected();
+        assert_eq!(
+            backoff.disconnected(Duration::from_millis(10)),
+            Duration::from_millis(350)
+        );
+        backoff.connected();
+        assert_eq!(
+            backoff.disconnected(Duration::from_secs(1)),
+            Duration::from_millis(100)
+        );
+        assert_eq!(backoff.next_delay(), Some(Duration::from_millis(200)));
+    }
+
+    #[test]
+    fn test_backoff_jitter_and_give_up() {
+        let mut backoff = Backoff::new(BackoffConfig {
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(1),
+            multiplier: 1.0,
+            jitter: 0.5,
+            max_attempts: Some(3),
+            stable_after: Duration::from_secs(1),
+        });
+
+        for _ in 0..2 {
+            let delay = backoff.next_delay().expect("should retry");
+            assert!(delay >= Duration::from_millis(50) && delay <= Duration::from_millis(100));
+        }
+        assert_eq!(backoff.next_delay(), None);
+    }
+
+    #[test]
+    fn test_backoff_invalid_config() {
+        for (multiplier, jitter) in [
+            (-3.0, 0.0),
+            (f64::NAN, 0.0),
+            (f64::INFINITY, 0.0),
+            (2.0, f64::NAN),
+        ] {
+            let mut backoff = Backoff::new(BackoffConfig {
+                initial_delay: Duration::from_millis(100),
+                max_delay: Duration::from_secs(1),
+                multiplier,
+                jitter,
+                max_attempts: None,
+                stable_after: Duration::from_secs(1),
+            });
+            for _ in 0..3 {
+                let delay = backoff.next_delay().expect("should retry");
+                assert!(delay <= Duration::from_secs(1));
+            }
+        }
+    }
+
+    #[test]
+    fn test_endpoints_selection() {
+        let fallbacks = vec!["b".to_string(), "c".to_string()];
+
+        let mut failover = Endpoints::new("a", &fallbacks, UrlSelection::Failover);
+        failover.record_disconnect();
+        assert_eq!(failover.current(), "a");
+        failover.record_failure();
+        assert_eq!(failover.current(), "b");
+        failover.record_failure();
+        failover.record_failure();
+        assert_eq!(failover.current(), "a");
+
+        let mut round_robin = Endpoints::new("a", &fallbacks, UrlSelection::RoundRobin);
+        round_robin.record_disconnect();
+        assert_eq!(round_robin.current(), "b");
+        round_robin.record_failure();
+        assert_eq!(round_robin.current(), "c");
+    }
+}
@@ -1,54 +1,259 @@
-use anyhow::Result;
-use tokio::sync::mpsc::Sender;
-use tokio_stream::wrappers::ReceiverStream;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use tokio::{
+    sync::{mpsc, watch},
+    time::Instant,
+};
+use tokio_stream::wrappers::UnboundedReceiverStream;
 use tokio_util::sync::CancellationToken;
 use tonic::Streaming;
+use uuid::Uuid;
 use zelos_proto::trace::{
-    subscribe_request, trace_subscribe_client, SubscribeCommand, SubscribeRequest,
-    SubscribeResponse, UnsubscribeCommand,
+    subscribe_request, subscribe_response, trace_subscribe_client, Capability, Handshake,
+    SubscribeCommand, SubscribeCommandKind, SubscribeRequest, SubscribeResponse,
+    UnsubscribeCommand,
+};
+use zelos_trace_types::ipc::{IpcMessage, IpcMessageWithId};
+
+use crate::{
+    columnar::StreamSchemas,
+    connection_status::{self, ConnectionStatus},
+    handshake,
+    reconnect::{Backoff, BackoffConfig, ConnectionCounters, ConnectionStats},
+    transport,
 };
 
+const DEFAULT_URL: &str = "grpc://localhost:2300";
+
+/// Capabilities this client asks the subscribe service for
+const CLIENT_CAPABILITIES: [Capability; 2] = [Capability::EventBatch, Capability::Acks];
+
+#[derive(Debug, Clone)]
+pub struct TraceSubscribeClientConfig {
+    /// URL of the trace subscribe service, e.g. `grpc://127.0.0.1:2300` or `unix:///run/zelos/agent.sock`
+    pub url: String,
+    /// Backoff policy between connection attempts
+    pub reconnect: BackoffConfig,
+}
+
+impl TraceSubscribeClientConfig {
+    pub fn new_with_url(url: String) -> Self {
+        Self {
+            url,
+            ..Default::default()
+        }
+    }
+}
+
+impl Default for TraceSubscribeClientConfig {
+    fn default() -> Self {
+        Self {
+            url: DEFAULT_URL.to_string(),
+            reconnect: BackoffConfig::default(),
+        }
+    }
+}
+
+/// A subscribe command that is re-issued every time the client reconnects
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Subscription {
+    filter: Option<String>,
+    start_time: Option<i64>,
+}
+
+impl Subscription {
+    /// Build the subscribe request for this subscription, resuming from `resume_time_ns` if it is later than the
+    /// requested start time. The service replays from that time inclusive, so messages at exactly `resume_time_ns` may
+    /// be received twice.
+    fn request(&self, resume_time_ns: Option<i64>) -> SubscribeRequest {
+        let start_time = match (self.start_time, resume_time_ns) {
+            (Some(start), Some(resume)) => Some(start.max(resume)),
+            (start, resume) => start.or(resume),
+        };
+        SubscribeRequest {
+            cmd: Some(subscribe_request::Cmd::Subscribe(SubscribeCommand {
+                filter: self.filter.clone(),
+                start_time,
+            })),
+        }
+    }
+}
+
+/// State shared between the client handle and its connection task
+#[derive(Debug, Default)]
+struct SharedState {
+    /// The subscriptions that are currently active
+    subscriptions: Vec<Subscription>,
+    /// The request sender of the current connection, if we are connected
+    req_sender: Option<mpsc::UnboundedSender<SubscribeRequest>>,
+    /// The latest timestamp received from each open segment, used to fill the gap after reconnecting
+    segment_times_ns: HashMap<Uuid, i64>,
+    /// The latest timestamp received from any segment, to resume from once every segment ended
+    last_time_ns: Option<i64>,
+}
+
+impl SharedState {
+    /// Record the messages received from the service, forgetting segments once they end. Annotations can be placed
+    /// anywhere on a segment's timeline and sessions are not segments, so neither moves the resume point.
+    fn record(&mut self, msgs: &[IpcMessageWithId]) {
+        for msg in msgs {
+            let Some(time_ns) = msg.msg.time_ns() else {
+                continue;
+            };
+            match msg.msg {
+                IpcMessage::Annotation(_)
+                | IpcMessage::TraceSessionStart(_)
+                | IpcMessage::TraceSessionEnd(_) => continue,
+                IpcMessage::TraceSegmentEnd(_) => {
+                    self.segment_times_ns.remove(&msg.segment_id);
+                }
+                _ => {
+                    let last = self
+                        .segment_times_ns
+                        .entry(msg.segment_id)
+                        .or_insert(time_ns);
+                    *last = (*last).max(time_ns);
+                }
+            }
+            self.last_time_ns = Some(self.last_time_ns.map_or(time_ns, |t| t.max(time_ns)));
+        }
+    }
+
+    /// Where to resume after reconnecting: the earliest of the open segments' latest timestamps, so a segment whose
+    /// clock lags behind the others misses nothing. Messages of the other segments after that are received again.
+    fn resume_time_ns(&self) -> Option<i64> {
+        self.segment_times_ns
+            .values()
+            .copied()
+            .min()
+            .or(self.last_time_ns)
+    }
+}
+
 pub struct TraceSubscribeClient {
-    /// The sender for subscribe requests.
-    req_sender: Sender<SubscribeRequest>,
+    /// The config, retained for external visibility
+    pub config: TraceSubscribeClientConfig,
+
+    /// Subscriptions and the current connection
+    state: Arc<Mutex<SharedState>>,
+
+    /// The last connection status
+    connection_status: watch::Receiver<ConnectionStatus>,
+
+    /// Counters for connection attempts
+    counters: Arc<ConnectionCounters>,
 }
 
 impl TraceSubscribeClient {
-    /// Create a new TraceSubscribeClient and connect to the given address.
-    pub async fn new(
+    /// Create a new TraceSubscribeClient, the returned task connects to the configured address and forwards received
+    /// messages to `sender`, reconnecting and restoring subscriptions whenever the stream fails.
+    pub fn new(
         sender: zelos_trace_types::ipc::Sender,
         cancellation_token: CancellationToken,
-        address: String,
-    ) -> Result<(Self, impl Future<Output = Result<()>>)> {
-        // Connect to the gRPC server
-        let mut client = trace_subscribe_client::TraceSubscribeClient::connect(address).await?;
+        config: TraceSubscribeClientConfig,
+    ) -> (Self, impl Future<Output = Result<()>>) {
+        let (tx_connection_status, connection_status) =
+            watch::channel(ConnectionStatus::Disconnected);
+        let state = Arc::new(Mutex::new(SharedState::default()));
+        let counters = Arc::new(ConnectionCounters::default());
+
+        let client = Self {
+            config: config.clone(),
+            state: state.clone(),
+            connection_status,
+            counters: counters.clone(),
+        };
+        let task = Self::run(
+            sender,
+            cancellation_token,
+            config,
+            state,
+            tx_connection_status,
+            counters,
+        );
 
-        // Initialize a channel for sending subscribe requests
-        let (req_sender, req_receiver) = tokio::sync::mpsc::channel(1);
+        (client, task)
+    }
 
-        // Attempt to call the subscribe streaming method, exiting early if we fail
-        let request_stream = tonic::Request::new(ReceiverStream::new(req_receiver));
-        let resp = client.subscribe(request_stream).await?;
+    /// Connect to `url` and open the subscribe stream, re-issuing all active subscriptions.
+    async fn establish(
+        url: &str,
+        state: &Mutex<SharedState>,
+    ) -> Result<Streaming<SubscribeResponse>> {
+        // Connect to the gRPC server
+        tracing::info!("Trace subscribe client connecting to {}", url);
+        let channel = transport::connect(url)
+            .await
+            .map_err(|e| anyhow!("Failed to connect to subscribe service: {}", e))?;
+        let mut client = trace_subscribe_client::TraceSubscribeClient::new(channel);
 
-        // Run our task to forward from the response stream to the sender
-        let future = Self::run(resp.into_inner(), sender.clone(), cancellation_token);
+        // Open with our handshake, then queue up our active subscriptions, and make the channel visible for any new ones
+        let (req_sender, req_receiver) = mpsc::unbounded_channel();
+        req_sender.send(SubscribeRequest {
+            cmd: Some(subscribe_request::Cmd::Handshake(Handshake::local(
+                CLIENT_CAPABILITIES,
+            ))),
+        })?;
+        {
+            let mut state = state.lock();
+            for subscription in &state.subscriptions {
+                req_sender.send(subscription.request(state.resume_time_ns()))?;
+            }
+            state.req_sender = Some(req_sender);
+        }
 
-        Ok((Self { req_sender }, future))
+        // Attempt to call the subscribe streaming method
+        let request_stream = tonic::Request::new(UnboundedReceiverStream::new(req_receiver));
+        let resp = client
+            .subscribe(request_stream)
+            .await
+            .map_err(|e| anyhow!("Failed to establish subscribe stream: {e}"))?;
+
+        Ok(resp.into_inner())
     }
 
-    /// Run a task to forward from the response stream to the sender
-    async fn run(
+    /// Forward from the response stream to the sender until the stream ends or we are cancelled
+    async fn forward(
         mut stream: Streaming<SubscribeResponse>,
-        sender: zelos_trace_types::ipc::Sender,
-        cancellation_token: CancellationToken,
+        sender: &zelos_trace_types::ipc::Sender,
+        state: &Mutex<SharedState>,
+        cancellation_token: &CancellationToken,
     ) -> Result<()> {
+        let local = Handshake::local(CLIENT_CAPABILITIES);
+        let mut schemas = StreamSchemas::default();
         loop {
             tokio::select! {
                 msg = stream.message() => {
                     match msg {
+                        Ok(Some(SubscribeResponse { msg: Some(subscribe_response::Msg::Handshake(reply)) })) => {
+                            let agreed = handshake::accept(&local, &reply)?;
+                            tracing::debug!("Subscribe stream agreed on {:?}", agreed);
+                        }
+                        Ok(Some(SubscribeResponse { msg: Some(subscribe_response::Msg::CommandAck(ack)) })) => {
+                            tracing::debug!("Subscribe command acknowledged: {:?}", ack);
+                        }
+                        Ok(Some(SubscribeResponse { msg: Some(subscribe_response::Msg::CommandError(err)) })) => {
+                            // Drop rejected subscriptions so we don't re-issue them on every reconnect
+                            tracing::warn!("Subscribe command rejected: {}", err.message);
+                            if err.kind() == SubscribeCommandKind::Subscribe {
+                                state.lock().subscriptions.retain(|s| s.filter != err.filter);
+                            }
+                        }
                         Ok(Some(response)) => {
-                            // Forward the message to the router
-                            let ipc = response.as_ipc()?;
+                            // Forward the message to the router, keeping track of the latest timestamp
+                            let ipc = match response.msg {
+                                Some(subscribe_response::Msg::TraceMessageBatch(batch)) => {
+                                    let mut ipc = Vec::with_capacity(batch.messages.len());
+                                    for msg in batch.messages {
+                                        ipc.extend(schemas.unpack(msg)?);
+                                    }
+                                    ipc
+                                }
+                                _ => response.as_ipc()?,
+                            };
+                            state.lock().record(&ipc);
                             for m in ipc {
                                 sender.send_async(m).await?;
                             }
@@ -58,7 +263,7 @@ impl TraceSubscribeClient {
                             return Ok(());
                         }
                         Err(e) => {
-                            // Error from the client
+                            // Error from the server
                             return Err(e.into());
                         }
                     }
@@ -68,29 +273,127 @@ impl TraceSubscribeClient {
         }
     }
 
+    /// The main task loop, which reconnects with backoff until cancelled or the maximum number of attempts is reached
+    async fn run(
+        sender: zelos_trace_types::ipc::Sender,
+        cancellation_token: CancellationToken,
+        config: TraceSubscribeClientConfig,
+        state: Arc<Mutex<SharedState>>,
+        tx_connection_status: watch::Sender<ConnectionStatus>,
+        counters: Arc<ConnectionCounters>,
+    ) -> Result<()> {
+        let mut backoff = Backoff::new(config.reconnect.clone());
+        loop {
+            // Attempt to connect
+            counters.record_attempt();
+            tx_connection_status.send(ConnectionStatus::Connecting)?;
+
+            let result = tokio::select! {
+                result = Self::establish(&config.url, &state) => result,
+                _ = cancellation_token.cancelled() => return Ok(()),
+            };
+            let delay = match result {
+                Ok(stream) => {
+                    let connected_at = Instant::now();
+                    counters.record_connected();
+                    backoff.connected();
+                    tx_connection_status.send(ConnectionStatus::Connected)?;
+
+                    let result = Self::forward(stream, &sender, &state, &cancellation_token).await;
+                    state.lock().req_sender = None;
+
+                    // If the receiving side has gone away there is no point in reconnecting
+                    if sender.is_disconnected() {
+                        tx_connection_status.send(ConnectionStatus::Disconnected)?;
+                        return Err(anyhow!("Subscriber channel closed"));
+                    }
+
+                    match result {
+                        Ok(()) => tx_connection_status.send(ConnectionStatus::Disconnected)?,
+                        // Reconnecting to a server that speaks an incompatible protocol will not help
+                        Err(e) if handshake::is_incompatible(&e) => {
+                            tracing::error!(
+                                "Subscribe service at {} is incompatible: {}",
+                                config.url,
+                                e
+                            );
+                            tx_connection_status.send(ConnectionStatus::Failed)?;
+                            return Err(anyhow!(
+                                "Giving up on incompatible subscribe service: {e}"
+                            ));
+                        }
+                        Err(e) => {
+                            tracing::error!("Error receiving trace messages: {}", e);
+                            tx_connection_status.send(ConnectionStatus::Error)?;
+                        }
+                    }
+                    if cancellation_token.is_cancelled() {
+                        return Ok(());
+                    }
+
+                    // Back off further if the connection did not stay up for long
+                    backoff.disconnected(connected_at.elapsed())
+                }
+                Err(e) => {
+                    tracing::error!("Error connecting to {}: {}", config.url, e);
+                    state.lock().req_sender = None;
+                    counters.record_failure();
+                    tx_connection_status.send(ConnectionStatus::Error)?;
+
+                    match backoff.next_delay() {
+                        Some(delay) => delay,
+                        None => {
+                            tx_connection_status.send(ConnectionStatus::Failed)?;
+                            return Err(anyhow!(
+                                "Giving up after {} consecutive failed connection attempts",
+                                counters.snapshot().consecutive_failures
+                            ));
+                        }
+                    }
+                }
+            };
+
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = cancellation_token.cancelled() => return Ok(()),
+            }
+        }
+    }
+
+    /// Send `req` on the current connection, if there is one. Requests made while disconnected are applied when the
+    /// client reconnects.
+    fn send_if_connected(state: &SharedState, req: SubscribeRequest) {
+        let Some(req_sender) = &state.req_sender else {
+            return;
+        };
+        if req_sender.send(req).is_err() {
+            tracing::debug!("Subscribe stream closed, request will be sent on reconnect");
+        }
+    }
+
     /// Send a subscribe command with the given filter and start time
     pub async fn subscribe(&self, filter: Option<String>, start_time: Option<i64>) -> Result<()> {
-        self.req_sender
-            .send(SubscribeRequest {
-                cmd: Some(subscribe_request::Cmd::Subscribe(SubscribeCommand {
-                    filter,
-                    start_time,
-                })),
-            })
-            .await?;
+        let subscription = Subscription { filter, start_time };
+
+        let mut state = self.state.lock();
+        Self::send_if_connected(&state, subscription.request(None));
+        state.subscriptions.push(subscription);
 
         Ok(())
     }
 
     /// Send an unsubscribe command with the given filter
     pub async fn unsubscribe(&self, filter: Option<String>) -> Result<()> {
-        self.req_sender
-            .send(SubscribeRequest {
+        let mut state = self.state.lock();
+        state.subscriptions.retain(|s| s.filter != filter);
+        Self::send_if_connected(
+            &state,
+            SubscribeRequest {
                 cmd: Some(subscribe_request::Cmd::Unsubscribe(UnsubscribeCommand {
                     filter,
                 })),
-            })
-            .await?;
+            },
+        );
 
         Ok(())
     }
@@ -104,4 +407,34 @@ impl TraceSubscribeClient {
     pub async fn unsubscribe_all(&self) -> Result<()> {
         self.unsubscribe(None).await
     }
+
+    /// Gets the filters of all active subscriptions
+    pub async fn active_filters(&self) -> Vec<Option<String>> {
+        self.state
+            .lock()
+            .subscriptions
+            .iter()
+            .map(|s| s.filter.clone())
+            .collect()
+    }
+
+    /// Gets a clone of the connection status receiver
+    pub async fn connection_status(&self) -> watch::Receiver<ConnectionStatus> {
+        self.connection_status.clone()
+    }
+
+    /// Gets the last value the connection status receiver has seen
+    pub async fn last_connection_status(&self) -> ConnectionStatus {
+        *self.connection_status.borrow()
+    }
+
+    /// Wait until connected or the timeout expires
+    pub async fn wait_until_connected(&self, timeout: Duration) -> Result<()> {
+        connection_status::wait_until_connected(&self.connection_status, timeout).await
+    }
+
+    /// Gets a snapshot of the connection attempt counters
+    pub fn connection_stats(&self) -> ConnectionStats {
+        self.counters.snapshot()
+    }
 }
@@ -1,5 +1,5 @@
 mod client;
 mod service;
 
-pub use client::TraceSubscribeClient;
+pub use client::{TraceSubscribeClient, TraceSubscribeClientConfig};
 pub use service::TraceSubscribeService;
@@ -1,16 +1,30 @@
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+    time::Duration,
+};
 
-use tokio_stream::{Stream, StreamExt};
+use tokio::sync::mpsc;
+use tokio_stream::{wrappers::ReceiverStream, Stream, StreamExt};
+use tokio_util::sync::{CancellationToken, DropGuard};
 use tonic::{Request, Response, Status, Streaming};
 use zelos_proto::trace::{
     subscribe_request::Cmd,
     trace_subscribe_server::{TraceSubscribe, TraceSubscribeServer},
-    SubscribeRequest, SubscribeResponse,
+    Capability, Handshake, SubscribeCommandKind, SubscribeRequest, SubscribeResponse,
 };
-use zelos_trace::{filter::Filter, TraceRouter};
+use zelos_trace::{SinkCommand, TraceRouter, TraceSink};
+use zelos_trace_types::ipc::IpcMessageWithId;
+
+use crate::{columnar::StreamSchemas, handshake};
 
 const CHUNK_SIZE: usize = 1024;
 const CHUNK_TIMEOUT: Duration = Duration::from_millis(10);
+const RESPONSE_CHANNEL_SIZE: usize = 16;
+
+type ResponseStream =
+    Pin<Box<dyn Stream<Item = Result<SubscribeResponse, Status>> + Send + 'static>>;
 
 pub struct TraceSubscribeService {
     router: Arc<TraceRouter>,
@@ -26,12 +40,144 @@ impl TraceSubscribeService {
     }
 }
 
+/// A response stream that cancels a token when it is dropped, which tonic does once the client goes away
+struct DisconnectOnDrop {
+    inner: ResponseStream,
+    _guard: DropGuard,
+}
+
+impl Stream for DisconnectOnDrop {
+    type Item = Result<SubscribeResponse, Status>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Apply a single command to the sink, returning the ack or error response for the client along with the messages
+/// replayed for a subscription with a start time
+async fn handle_command(
+    sink: &TraceSink,
+    cmd: Option<Cmd>,
+) -> (SubscribeResponse, Vec<IpcMessageWithId>) {
+    let (kind, command) = match cmd {
+        Some(Cmd::Subscribe(subscribe)) => (
+            SubscribeCommandKind::Subscribe,
+            SinkCommand::Subscribe {
+                filter: subscribe.filter,
+                start_time_ns: subscribe.start_time,
+            },
+        ),
+        Some(Cmd::Unsubscribe(unsubscribe)) => (
+            SubscribeCommandKind::Unsubscribe,
+            SinkCommand::Unsubscribe {
+                filter: unsubscribe.filter,
+            },
+        ),
+        Some(Cmd::Handshake(_)) | None => {
+            let error = SubscribeResponse::command_error(
+                SubscribeCommandKind::Unspecified,
+                None,
+                "Missing command".to_string(),
+            );
+            return (error, Vec::new());
+        }
+    };
+    let filter = command.filter().cloned();
+
+    match sink.apply(command).await {
+        Ok(replay) => (SubscribeResponse::command_ack(kind, filter), replay),
+        Err(e) => {
+            tracing::debug!("Failed to apply subscribe command: {}", e);
+            let error = SubscribeResponse::command_error(kind, filter, e.to_string());
+            (error, Vec::new())
+        }
+    }
+}
+
+/// Stream trace messages to the client and apply its commands until it disconnects. Messages replayed for a command
+/// are sent before any live message that follows it, and both are encoded the same way, leaving out the message
+/// types the client's protocol version predates. The sink is dropped on return, which removes it from the router.
+async fn handle_requests(
+    mut req_stream: Streaming<SubscribeRequest>,
+    sink: TraceSink,
+    messages: impl Stream<Item = IpcMessageWithId>,
+    responses: mpsc::Sender<Result<SubscribeResponse, Status>>,
+    disconnected: CancellationToken,
+) {
+    // Clients that do not send a handshake speak the original protocol
+    let mut negotiated = Handshake::legacy();
+    let mut schemas = StreamSchemas::default();
+    let batches = messages.chunks_timeout(CHUNK_SIZE, CHUNK_TIMEOUT);
+    tokio::pin!(batches);
+    let mut requests_open = true;
+    loop {
+        let req = tokio::select! {
+            batch = batches.next() => {
+                let Some(batch) = batch else {
+                    break;
+                };
+                let messages = handshake::encode(&negotiated, &mut schemas, batch);
+                if !messages.is_empty()
+                    && responses.send(Ok(SubscribeResponse::from_ipc(messages))).await.is_err()
+                {
+                    break;
+                }
+                continue;
+            }
+            req = req_stream.message(), if requests_open => req,
+            _ = disconnected.cancelled() => break,
+        };
+
+        match req {
+            Ok(Some(SubscribeRequest {
+                cmd: Some(Cmd::Handshake(client)),
+            })) => {
+                let response = handshake::negotiate(&client).map(|agreed| {
+                    negotiated = agreed.clone();
+                    SubscribeResponse::handshake(agreed)
+                });
+                let rejected = response.is_err();
+                if responses.send(response).await.is_err() || rejected {
+                    break;
+                }
+            }
+            Ok(Some(req)) => {
+                let (response, replay) = handle_command(&sink, req.cmd).await;
+                for chunk in replay.chunks(CHUNK_SIZE) {
+                    let messages = handshake::encode(&negotiated, &mut schemas, chunk.to_vec());
+                    if !messages.is_empty()
+                        && responses
+                            .send(Ok(SubscribeResponse::from_ipc(messages)))
+                            .await
+                            .is_err()
+                    {
+                        return;
+                    }
+                }
+                if !negotiated.has(Capability::Acks) {
+                    continue;
+                }
+                if responses.send(Ok(response)).await.is_err() {
+                    break;
+                }
+            }
+            Ok(None) => {
+                // The client is done sending commands, keep streaming until it goes away
+                requests_open = false;
+            }
+            Err(e) => {
+                tracing::debug!("Subscriber disconnected: {}", e);
+                break;
+            }
+        }
+    }
+}
+
 #[tonic::async_trait]
 impl TraceSubscribe for TraceSubscribeService {
-    type SubscribeStream =
-        Pin<Box<dyn Stream<Item = Result<SubscribeResponse, Status>> + Send + 'static>>;
+    type SubscribeStream = ResponseStream;
 
-    // TODO(jbott): handle disconnects
     async fn subscribe(
         &self,
         request: Request<Streaming<SubscribeRequest>>,
@@ -43,47 +189,326 @@ impl TraceSubscribe for TraceSubscribeService {
             .await
             .map_err(|e| Status::internal(format!("Failed to subscribe: {}", e)))?;
 
-        let stream = stream.chunks_timeout(CHUNK_SIZE, CHUNK_TIMEOUT).map(|m| {
-            Ok(SubscribeResponse::from_ipc(
-                m.into_iter().map(|msg| msg.into()).collect(),
-            ))
-        });
-
-        // Handle messages from the client
-        let mut req_stream = request.into_inner();
-        tokio::task::spawn(async move {
-            while let Some(req) = req_stream.message().await? {
-                if let Some(cmd) = req.cmd {
-                    match cmd {
-                        Cmd::Subscribe(subscribe) => {
-                            let filter = match &subscribe.filter {
-                                Some(f) => Filter::parse(f),
-                                None => Ok(Filter::any()),
-                            };
-
-                            match filter {
-                                Ok(f) => sink.subscribe(f).await,
-                                Err(e) => tracing::error!("Failed to parse filter: {}", e),
-                            }
-                        }
-                        Cmd::Unsubscribe(unsubscribe) => {
-                            let filter = match &unsubscribe.filter {
-                                Some(f) => Filter::parse(f),
-                                None => Ok(Filter::any()),
-                            };
-
-                            match filter {
-                                Ok(f) => sink.unsubscribe(f).await,
-                                Err(e) => tracing::error!("Failed to parse filter: {}", e),
-                            }
-                        }
-                    }
+        // Trace messages and the responses to the client's commands are sent from one task, so they stay in order.
+        // Events are packed into batches once the client's handshake agrees to them.
+        let (response_sender, response_receiver) = mpsc::channel(RESPONSE_CHANNEL_SIZE);
+
+        // Handle messages from the client, tearing down the sink when the client goes away
+        let disconnected = CancellationToken::new();
+        tokio::task::spawn(handle_requests(
+            request.into_inner(),
+            sink,
+            stream,
+            response_sender,
+            disconnected.clone(),
+        ));
+
+        Ok(Response::new(Box::pin(DisconnectOnDrop {
+            inner: Box::pin(ReceiverStream::new(response_receiver)),
+            _guard: disconnected.drop_guard(),
+        })))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{collections::HashSet, net::SocketAddr};
+
+    use anyhow::Result;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use uuid::Uuid;
+    use zelos_trace_types::{
+        ipc::{
+            FieldDisplay, FieldKind, IpcMessage, LogLevel, TraceEvent, TraceEventFieldMetadata,
+            TraceEventSchema, TraceLog, TraceSegmentStart,
+        },
+        DataType, Value,
+    };
+
+    use zelos_trace::{
+        router::{TraceRouterConfig, DEFAULT_REPLAY_BUFFER_BYTES},
+        MetadataOnlyStore,
+    };
+
+    use super::*;
+    use crate::{
+        reconnect::BackoffConfig,
+        subscribe::{TraceSubscribeClient, TraceSubscribeClientConfig},
+    };
+
+    /// Serve `router` on `addr` from a runtime of its own, so dropping the runtime drops every connection at once
+    async fn serve(router: Arc<TraceRouter>, addr: SocketAddr) -> Result<tokio::runtime::Runtime> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .enable_all()
+            .build()?;
+        let listener = runtime
+            .spawn(async move { tokio::net::TcpListener::bind(addr).await })
+            .await??;
+        runtime.spawn(
+            tonic::transport::Server::builder()
+                .add_service(TraceSubscribeService::new(router).server())
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        Ok(runtime)
+    }
+
+    /// Start a segment with a `tick` event that has an `index` field
+    async fn start_segment(sender: &zelos_trace_types::ipc::Sender) -> Result<Uuid> {
+        let segment_id = Uuid::now_v7();
+        let metadata = |msg| IpcMessageWithId {
+            segment_id,
+            source_name: "rig".into(),
+            msg,
+        };
+        sender
+            .send_async(metadata(IpcMessage::TraceSegmentStart(TraceSegmentStart {
+                time_ns: 0,
+                source_name: "rig".to_string(),
+                attributes: Default::default(),
+            })))
+            .await?;
+        sender
+            .send_async(metadata(IpcMessage::TraceEventSchema(TraceEventSchema {
+                name: "tick".to_string(),
+                version: 0,
+                fields: vec![TraceEventFieldMetadata {
+                    name: "index".to_string(),
+                    data_type: DataType::UInt64,
+                    unit: None,
+                    kind: FieldKind::Gauge,
+                    display: FieldDisplay::default(),
+                }],
+            })))
+            .await?;
+        Ok(segment_id)
+    }
+
+    fn event(segment_id: Uuid, index: u64) -> IpcMessageWithId {
+        event_at(segment_id, index, index as i64)
+    }
+
+    fn event_at(segment_id: Uuid, index: u64, time_ns: i64) -> IpcMessageWithId {
+        IpcMessageWithId {
+            segment_id,
+            source_name: "rig".into(),
+            msg: IpcMessage::TraceEvent(TraceEvent {
+                time_ns,
+                name: "tick".into(),
+                fields: [("index".to_string(), Value::UInt64(index))]
+                    .into_iter()
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Subscribe to everything on `addr` from the start, reconnecting quickly
+    async fn connect(
+        addr: SocketAddr,
+        cancellation_token: &CancellationToken,
+    ) -> Result<(TraceSubscribeClient, flume::Receiver<IpcMessageWithId>)> {
+        let (received_sender, received) = flume::unbounded();
+        let (client, task) = TraceSubscribeClient::new(
+            received_sender,
+            cancellation_token.clone(),
+            TraceSubscribeClientConfig {
+                url: format!("grpc://{}", addr),
+                reconnect: BackoffConfig {
+                    initial_delay: Duration::from_millis(20),
+                    max_delay: Duration::from_millis(100),
+                    ..Default::default()
+                },
+            },
+        );
+        tokio::spawn(task);
+        client.subscribe(None, Some(0)).await?;
+        client.wait_until_connected(Duration::from_secs(5)).await?;
+        Ok((client, received))
+    }
+
+    /// Collect the indices of received events in the order they arrive until there are `count` distinct ones
+    async fn receive_until(
+        received: &flume::Receiver<IpcMessageWithId>,
+        indices: &mut Vec<u64>,
+        count: usize,
+    ) -> Result<()> {
+        while indices.iter().collect::<HashSet<_>>().len() < count {
+            let msg = tokio::time::timeout(Duration::from_secs(5), received.recv_async()).await??;
+            let IpcMessage::TraceEvent(event) = msg.msg else {
+                continue;
+            };
+            if let Some(Value::UInt64(index)) = event.fields.get("index") {
+                indices.push(*index);
+            }
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_disconnect_has_no_gaps() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new_with_config(
+            Arc::new(MetadataOnlyStore::new()),
+            TraceRouterConfig {
+                replay_buffer_bytes: DEFAULT_REPLAY_BUFFER_BYTES,
+            },
+            cancellation_token.clone(),
+        );
+        tokio::spawn(run);
+        let sender = router.sender();
+        let segment_id = start_segment(&sender).await?;
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let server = serve(router.clone(), addr).await?;
+
+        let (_client, received) = connect(addr, &cancellation_token).await?;
+
+        let mut indices = Vec::new();
+        for index in 1..=100 {
+            sender.send_async(event(segment_id, index)).await?;
+        }
+        receive_until(&received, &mut indices, 100).await?;
+
+        // Drop the connection mid-stream and keep publishing while the client is away
+        server.shutdown_background();
+        for index in 101..=200 {
+            sender.send_async(event(segment_id, index)).await?;
+        }
+
+        let server = serve(router.clone(), addr).await?;
+        for index in 201..=300 {
+            sender.send_async(event(segment_id, index)).await?;
+        }
+        receive_until(&received, &mut indices, 300).await?;
+        assert_eq!(
+            indices.iter().copied().collect::<HashSet<_>>(),
+            (1..=300).collect()
+        );
+
+        // The replay arrives before live events, only the event the client resumed from is received twice
+        assert!(indices.windows(2).all(|pair| pair[0] <= pair[1]));
+
+        server.shutdown_background();
+        cancellation_token.cancel();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resume_with_lagging_segment() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new_with_config(
+            Arc::new(MetadataOnlyStore::new()),
+            TraceRouterConfig {
+                replay_buffer_bytes: DEFAULT_REPLAY_BUFFER_BYTES,
+            },
+            cancellation_token.clone(),
+        );
+        tokio::spawn(run);
+        let sender = router.sender();
+        let (lagging, leading) = (start_segment(&sender).await?, start_segment(&sender).await?);
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let server = serve(router.clone(), addr).await?;
+        let (_client, received) = connect(addr, &cancellation_token).await?;
+
+        // The leading segment's clock is far ahead of the lagging one's
+        let publish = |indices: std::ops::RangeInclusive<u64>| {
+            let sender = sender.clone();
+            async move {
+                for index in indices {
+                    sender.send_async(event(lagging, index)).await?;
+                    sender
+                        .send_async(event_at(leading, index + 1000, 1_000_000 + index as i64))
+                        .await?;
                 }
+                anyhow::Ok(())
             }
+        };
+        let mut indices = Vec::new();
+        publish(1..=50).await?;
+        receive_until(&received, &mut indices, 100).await?;
 
-            Ok::<_, anyhow::Error>(())
-        });
+        // Both segments keep publishing while the client is away, it resumes from the lagging segment's last event
+        server.shutdown_background();
+        publish(51..=100).await?;
+        let server = serve(router.clone(), addr).await?;
+        receive_until(&received, &mut indices, 200).await?;
+        let expected: HashSet<u64> = (1..=100).chain(1001..=1100).collect();
+        assert_eq!(indices.iter().copied().collect::<HashSet<_>>(), expected);
+
+        server.shutdown_background();
+        cancellation_token.cancel();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_legacy_subscriber_gets_original_messages() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        tokio::spawn(run);
+        let sender = router.sender();
+        let segment_id = start_segment(&sender).await?;
+
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")?.local_addr()?;
+        let server = serve(router.clone(), addr).await?;
+
+        // Subscribe like a client that predates the handshake, without acks to tell when the command is applied
+        let channel = crate::transport::connect(&format!("grpc://{}", addr)).await?;
+        let mut client =
+            zelos_proto::trace::trace_subscribe_client::TraceSubscribeClient::new(channel);
+        let subscribe = SubscribeRequest {
+            cmd: Some(Cmd::Subscribe(zelos_proto::trace::SubscribeCommand {
+                filter: None,
+                start_time: None,
+            })),
+        };
+        let mut responses = client
+            .subscribe(tokio_stream::iter([subscribe]))
+            .await?
+            .into_inner();
+
+        // Keep publishing a log and an event until events come through, the log must never reach the client
+        let mut received = Vec::new();
+        for index in 1..=100 {
+            let log = IpcMessage::Log(TraceLog {
+                time_ns: index as i64,
+                level: LogLevel::Info,
+                target: "rig".to_string(),
+                message: "tick".to_string(),
+                fields: Default::default(),
+            });
+            sender
+                .send_async(IpcMessageWithId {
+                    segment_id,
+                    source_name: "rig".into(),
+                    msg: log,
+                })
+                .await?;
+            sender.send_async(event(segment_id, index)).await?;
+            while let Ok(response) =
+                tokio::time::timeout(Duration::from_millis(50), responses.message()).await
+            {
+                let Some(response) = response? else {
+                    break;
+                };
+                received.extend(response.as_ipc()?);
+            }
+            if received
+                .iter()
+                .any(|msg| matches!(msg.msg, IpcMessage::TraceEvent(_)))
+            {
+                break;
+            }
+        }
+        assert!(received
+            .iter()
+            .any(|msg| matches!(msg.msg, IpcMessage::TraceEvent(_))));
+        assert!(received
+            .iter()
+            .all(|msg| !matches!(msg.msg, IpcMessage::Log(_))));
 
-        Ok(Response::new(Box::pin(stream)))
+        server.shutdown_background();
+        cancellation_token.cancel();
+        Ok(())
     }
 }
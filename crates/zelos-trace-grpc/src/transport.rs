@@ -0,0 +1,147 @@
+#[cfg(unix)]
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+#[cfg(unix)]
+use tonic::transport::Uri;
+use tonic::transport::{Channel, Endpoint};
+
+/// URL scheme for Unix domain sockets, e.g. `unix:///run/zelos/agent.sock`
+pub const UNIX_SCHEME: &str = "unix://";
+
+/// Default permissions for Unix domain sockets created by [`bind_unix`], read/write for the owner and group
+pub const DEFAULT_UNIX_SOCKET_MODE: u32 = 0o660;
+
+/// Open a channel to `url`. In addition to the schemes tonic understands, `unix:///path/to/socket` connects to a
+/// Unix domain socket.
+pub async fn connect(url: &str) -> Result<Channel> {
+    match url.strip_prefix(UNIX_SCHEME) {
+        Some(path) => connect_unix(path).await,
+        None => Ok(Endpoint::from_shared(url.to_string())?.connect().await?),
+    }
+}
+
+#[cfg(unix)]
+async fn connect_unix(path: &str) -> Result<Channel> {
+    let path = PathBuf::from(path);
+
+    // tonic requires a URI, but the connector ignores it and always dials the socket
+    let channel = Endpoint::from_static("http://localhost")
+        .connect_with_connector(tower::service_fn(move |_: Uri| {
+            let path = path.clone();
+            async move {
+                let stream = tokio::net::UnixStream::connect(path).await?;
+                Ok::<_, std::io::Error>(hyper_util::rt::TokioIo::new(stream))
+            }
+        }))
+        .await?;
+    Ok(channel)
+}
+
+#[cfg(not(unix))]
+async fn connect_unix(_path: &str) -> Result<Channel> {
+    Err(anyhow!(
+        "Unix domain sockets are not supported on this platform"
+    ))
+}
+
+/// Listen on a Unix domain socket at `path`, restricting access to it with the file permissions in `mode`. A stale
+/// socket left behind by a previous process is replaced, but one that is still accepting connections, or a file that
+/// is not a socket, is an error.
+///
+/// The socket is bound in a private directory next to `path` and moved into place once its permissions are set, so
+/// it is never reachable with looser permissions than `mode`.
+#[cfg(unix)]
+pub fn bind_unix(path: &Path, mode: u32) -> Result<tokio::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if !metadata.file_type().is_socket() => {
+            return Err(anyhow!(
+                "{} exists and is not a socket, refusing to replace it",
+                path.display()
+            ));
+        }
+        Ok(_) => {
+            if std::os::unix::net::UnixStream::connect(path).is_ok() {
+                return Err(anyhow!("{} is already in use", path.display()));
+            }
+            std::fs::remove_file(path)
+                .map_err(|e| anyhow!("Failed to remove stale socket {}: {}", path.display(), e))?;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(anyhow!("Failed to inspect {}: {}", path.display(), e)),
+    }
+
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let private_dir = parent.join(format!(".zelos-{}", uuid::Uuid::now_v7()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .map_err(|e| anyhow!("Failed to create {}: {}", private_dir.display(), e))?;
+
+    let private_path = private_dir.join("socket");
+    let result = tokio::net::UnixListener::bind(&private_path)
+        .map_err(|e| anyhow!("Failed to listen on {}: {}", path.display(), e))
+        .and_then(|listener| {
+            std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(mode))
+                .map_err(|e| anyhow!("Failed to set permissions on {}: {}", path.display(), e))?;
+            std::fs::rename(&private_path, path)
+                .map_err(|e| anyhow!("Failed to move socket to {}: {}", path.display(), e))?;
+            Ok(listener)
+        });
+
+    let _ = std::fs::remove_file(&private_path);
+    let _ = std::fs::remove_dir(&private_dir);
+    result
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use std::os::unix::fs::PermissionsExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_bind_unix_replaces_stale_socket() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("zelos-{}.sock", uuid::Uuid::now_v7()));
+
+        let listener = bind_unix(&path, 0o600)?;
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o600
+        );
+
+        // A live socket must not be taken over
+        assert!(bind_unix(&path, 0o600).is_err());
+
+        // Once the listener is gone, the leftover file is replaced
+        drop(listener);
+        let _listener = bind_unix(&path, DEFAULT_UNIX_SOCKET_MODE)?;
+        assert_eq!(
+            std::fs::metadata(&path)?.permissions().mode() & 0o777,
+            0o660
+        );
+
+        // The socket accepts connections once it is moved into place
+        std::os::unix::net::UnixStream::connect(&path)?;
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bind_unix_keeps_other_files() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("zelos-{}.sock", uuid::Uuid::now_v7()));
+        std::fs::write(&path, "not a socket")?;
+
+        assert!(bind_unix(&path, 0o600).is_err());
+        assert_eq!(std::fs::read_to_string(&path)?, "not a socket");
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
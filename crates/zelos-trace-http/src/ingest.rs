@@ -0,0 +1,333 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::State,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    Json,
+};
+use zelos_trace::segment::TraceSegment;
+use zelos_trace_types::{
+    ipc::IpcMessage,
+    json::{JsonIngestError, JsonIngestResponse, JsonIpcMessageWithId},
+};
+
+use crate::GatewayState;
+
+const NDJSON_CONTENT_TYPES: &[&str] = &["application/x-ndjson", "application/jsonl"];
+
+/// A message's index in the request, and the message or why it could not be parsed
+type ParsedMessage = (usize, Result<JsonIpcMessageWithId, String>);
+
+fn is_ndjson(headers: &HeaderMap) -> bool {
+    headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|value| NDJSON_CONTENT_TYPES.contains(&value.trim()))
+}
+
+/// Split a request body into messages, keeping parse errors so they can be reported alongside validation errors.
+/// Messages are indexed by their position in a JSON array, or by their (zero-based) line in NDJSON.
+fn parse_messages(body: &str, ndjson: bool) -> Result<Vec<ParsedMessage>, String> {
+    if ndjson {
+        return Ok(body
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| (index, serde_json::from_str(line).map_err(|e| e.to_string())))
+            .collect());
+    }
+
+    let value: serde_json::Value =
+        serde_json::from_str(body).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let values = match value {
+        serde_json::Value::Array(values) => values,
+        value => vec![value],
+    };
+    Ok(values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            (
+                index,
+                serde_json::from_value(value).map_err(|e| e.to_string()),
+            )
+        })
+        .collect())
+}
+
+/// Accept a JSON message, an array of them or NDJSON, converting event fields with the schemas registered by earlier
+/// messages and forwarding everything valid to the router. Schemas in the request type the messages after them
+/// before the router has applied them, without writing to the router's metadata.
+pub(crate) async fn ingest(
+    State(state): State<GatewayState>,
+    headers: HeaderMap,
+    body: String,
+) -> (StatusCode, Json<JsonIngestResponse>) {
+    let messages = match parse_messages(&body, is_ndjson(&headers)) {
+        Ok(messages) => messages,
+        Err(message) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(JsonIngestResponse {
+                    accepted: 0,
+                    errors: vec![JsonIngestError { index: 0, message }],
+                }),
+            );
+        }
+    };
+
+    // Segments whose schemas changed in this request, the router's store is only updated by the router itself
+    let mut overlay = HashMap::new();
+    let mut response = JsonIngestResponse::default();
+    for (index, msg) in messages {
+        let msg = msg.and_then(|msg| {
+            let shared;
+            let segment = match overlay.get(&msg.segment_id) {
+                Some(segment) => Some(segment),
+                None => {
+                    shared = state.metadata.get_segment(&msg.segment_id);
+                    shared.as_ref()
+                }
+            };
+            msg.try_into_ipc(|event_name, field_name| {
+                let schema = segment?.schemas.get(event_name)?;
+                let field = schema.get_field(field_name)?;
+                Some(field.metadata.data_type.clone())
+            })
+            .map_err(|e| e.to_string())
+        });
+        let msg = match msg {
+            Ok(msg) => msg,
+            Err(message) => {
+                tracing::debug!("Rejected ingested message {}: {}", index, message);
+                response.errors.push(JsonIngestError { index, message });
+                continue;
+            }
+        };
+
+        // Apply schemas to the overlay right away so later messages in this request can be typed, and to the
+        // gateway's own metadata if the router keeps none. Schemas that change a field's type are rejected.
+        let applied = match &msg.msg {
+            IpcMessage::TraceEventSchema(_) => {
+                let mut segment = match overlay.get(&msg.segment_id) {
+                    Some(segment) => Some(segment.clone()),
+                    None => state.metadata.get_segment(&msg.segment_id),
+                }
+                .unwrap_or_else(|| {
+                    TraceSegment::empty(msg.segment_id, msg.source_name.to_string())
+                });
+                let applied = segment.update_mut(&msg.msg).and_then(|()| {
+                    if !state.shared_metadata {
+                        state.metadata.update(&msg)?;
+                    }
+                    Ok(())
+                });
+                if applied.is_ok() {
+                    overlay.insert(msg.segment_id, segment);
+                }
+                applied.map_err(|e| e.to_string())
+            }
+            IpcMessage::TraceSegmentEnd(_) if !state.shared_metadata => {
+                state.metadata.remove_segment(&msg.segment_id);
+                Ok(())
+            }
+            _ => Ok(()),
+        };
+        if let Err(message) = applied {
+            tracing::debug!("Rejected ingested message {}: {}", index, message);
+            response.errors.push(JsonIngestError { index, message });
+            continue;
+        }
+        if state.sender.send_async(msg).await.is_err() {
+            return (StatusCode::SERVICE_UNAVAILABLE, Json(response));
+        }
+        response.accepted += 1;
+    }
+
+    let status = if response.errors.is_empty() {
+        StatusCode::OK
+    } else {
+        StatusCode::UNPROCESSABLE_ENTITY
+    };
+    (status, Json(response))
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use anyhow::Result;
+    use axum::{body::Body, http::Request};
+    use tokio_util::sync::CancellationToken;
+    use tower::ServiceExt;
+    use zelos_trace::TraceRouter;
+    use zelos_trace_types::Value;
+
+    use super::*;
+    use crate::TraceHttpGateway;
+
+    #[tokio::test]
+    async fn test_ingest_ndjson() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let router_task = tokio::spawn(run);
+        let (receiver, _metadata) = router.subscribe_all_blocking().await?;
+        let app = TraceHttpGateway::new(router.clone(), cancellation_token.clone()).into_router();
+
+        let segment_id = uuid::Uuid::now_v7();
+        let body = [
+            r#"{"type": "trace_segment_start", "time_ns": 0}"#,
+            r#"{"type": "trace_event_schema", "name": "motor", "fields": [{"name": "rpm", "data_type": "float64"}]}"#,
+            "",
+            r#"{"type": "trace_event", "time_ns": 1, "name": "motor", "fields": {"rpm": 1200.5}}"#,
+            r#"{"type": "trace_event", "time_ns": 2, "name": "motor", "fields": {"rpm": "fast"}}"#,
+            r#"{"type": "trace_event", "time_ns": 3, "name": "motor", "fields": {"torque": 1}}"#,
+            r#"{"type": "trace_event", "time_ns": 4}"#,
+        ]
+        .map(|line| match line {
+            "" => String::new(),
+            line => format!(
+                r#"{{"segment_id": "{}", "source_name": "board", {}"#,
+                segment_id,
+                &line[1..]
+            ),
+        })
+        .join("\n");
+
+        let response = app
+            .oneshot(
+                Request::post("/ingest")
+                    .header(CONTENT_TYPE, "application/x-ndjson")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let response: JsonIngestResponse =
+            serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(response.accepted, 3);
+        assert_eq!(
+            response
+                .errors
+                .iter()
+                .map(|error| error.index)
+                .collect::<Vec<_>>(),
+            vec![4, 5, 6]
+        );
+
+        let mut events = Vec::new();
+        while events.is_empty() {
+            let msg = tokio::time::timeout(Duration::from_secs(1), receiver.recv_async()).await??;
+            assert_eq!(msg.segment_id, segment_id);
+            if let IpcMessage::TraceEvent(event) = msg.msg {
+                events.push(event);
+            }
+        }
+        assert_eq!(events[0].fields.get("rpm"), Some(&Value::Float64(1200.5)));
+
+        cancellation_token.cancel();
+        router_task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_with_router_schemas() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let router_task = tokio::spawn(run);
+        let (receiver, _metadata) = router.subscribe_all_blocking().await?;
+        let app = TraceHttpGateway::new(router.clone(), cancellation_token.clone()).into_router();
+
+        // The schema is published to the router directly, not through the gateway
+        let source = zelos_trace::TraceSource::new("board", router.sender());
+        source
+            .build_event("motor")
+            .add_f64_field("rpm", None)
+            .build()?;
+        let segment_id = loop {
+            let msg = tokio::time::timeout(Duration::from_secs(1), receiver.recv_async()).await??;
+            if let IpcMessage::TraceEventSchema(_) = msg.msg {
+                break msg.segment_id;
+            }
+        };
+
+        let body = format!(
+            r#"{{"segment_id": "{}", "source_name": "board", "type": "trace_event", "time_ns": 1, "name": "motor", "fields": {{"rpm": 3}}}}"#,
+            segment_id
+        );
+        let response = app
+            .oneshot(Request::post("/ingest").body(Body::from(body))?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let event = loop {
+            let msg = tokio::time::timeout(Duration::from_secs(1), receiver.recv_async()).await??;
+            if let IpcMessage::TraceEvent(event) = msg.msg {
+                break event;
+            }
+        };
+        assert_eq!(event.fields.get("rpm"), Some(&Value::Float64(3.0)));
+
+        cancellation_token.cancel();
+        router_task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_leaves_router_metadata() -> Result<()> {
+        // The router is not running, so only the request's own schemas can type its events
+        let (router, _run) = TraceRouter::new(CancellationToken::new());
+        let app = TraceHttpGateway::new(router.clone(), CancellationToken::new()).into_router();
+
+        let segment_id = uuid::Uuid::now_v7();
+        let body = [
+            r#"{"type": "trace_event_schema", "name": "motor", "fields": [{"name": "rpm", "data_type": "float64"}]}"#,
+            r#"{"type": "trace_event", "time_ns": 1, "name": "motor", "fields": {"rpm": 1200.5}}"#,
+            r#"{"type": "trace_event_schema", "name": "motor", "version": 1, "fields": [{"name": "rpm", "data_type": "string"}]}"#,
+        ]
+        .map(|line| {
+            format!(
+                r#"{{"segment_id": "{}", "source_name": "board", {}"#,
+                segment_id,
+                &line[1..]
+            )
+        })
+        .join("\n");
+        let response = app
+            .oneshot(
+                Request::post("/ingest")
+                    .header(CONTENT_TYPE, "application/x-ndjson")
+                    .body(Body::from(body))?,
+            )
+            .await?;
+        let response: JsonIngestResponse =
+            serde_json::from_slice(&axum::body::to_bytes(response.into_body(), usize::MAX).await?)?;
+        assert_eq!(response.accepted, 2);
+        assert_eq!(
+            response
+                .errors
+                .iter()
+                .map(|error| error.index)
+                .collect::<Vec<_>>(),
+            vec![2]
+        );
+        assert!(response.errors[0].message.contains("cannot change type"));
+
+        let Some(metadata) = router.metadata() else {
+            panic!("Expected the router to keep metadata");
+        };
+        assert!(metadata.get_segment(&segment_id).is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ingest_invalid_json() -> Result<()> {
+        let (router, _run) = TraceRouter::new(CancellationToken::new());
+        let app = TraceHttpGateway::new(router, CancellationToken::new()).into_router();
+        let response = app
+            .oneshot(Request::post("/ingest").body(Body::from("[{"))?)
+            .await?;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+}
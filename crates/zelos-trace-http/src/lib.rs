@@ -0,0 +1,81 @@
+//! HTTP gateway for clients that can't speak gRPC, such as browsers.
+//!
+//! Subscribers connect a WebSocket to `/subscribe` and send [`JsonSubscribeRequest`]s as text frames, equivalent to
+//! the commands of the gRPC subscribe service. Each command is answered with an ack or error, and trace messages
+//! matching the active filters are streamed as [`JsonSubscribeResponse`] batches. The wire types live in
+//! [`zelos_trace_types::json`], and TypeScript definitions for them are exported by its `ts-rs` feature.
+//!
+//! Publishers POST [`JsonIpcMessageWithId`]s to `/ingest`, as a single JSON message, an array of them, or NDJSON when
+//! sent with an `application/x-ndjson` content type. Event fields and value tables are typed with the schemas sent
+//! earlier for the same segment, and a [`JsonIngestResponse`] reports which messages were rejected and why.
+//!
+//! [`JsonSubscribeRequest`]: zelos_trace_types::json::JsonSubscribeRequest
+//! [`JsonSubscribeResponse`]: zelos_trace_types::json::JsonSubscribeResponse
+//! [`JsonIpcMessageWithId`]: zelos_trace_types::json::JsonIpcMessageWithId
+//! [`JsonIngestResponse`]: zelos_trace_types::json::JsonIngestResponse
+#![deny(clippy::expect_used, clippy::unwrap_used)]
+
+use std::sync::Arc;
+
+use anyhow::Result;
+use axum::{
+    routing::{get, post},
+    Router,
+};
+use tokio::net::TcpListener;
+use tokio_util::sync::CancellationToken;
+use zelos_trace::{TraceMetadata, TraceRouter};
+use zelos_trace_types::ipc::Sender;
+
+mod ingest;
+mod subscribe;
+
+#[derive(Clone)]
+pub(crate) struct GatewayState {
+    router: Arc<TraceRouter>,
+    sender: Sender,
+    /// The router's segment metadata, used to type the events published through `/ingest`
+    metadata: TraceMetadata,
+    /// Whether `metadata` is the router's, rather than kept by the gateway for a router whose store keeps none
+    shared_metadata: bool,
+    cancellation_token: CancellationToken,
+}
+
+/// Serves a trace router over HTTP
+pub struct TraceHttpGateway {
+    state: GatewayState,
+}
+
+impl TraceHttpGateway {
+    /// Open WebSockets are closed once `cancellation_token` is cancelled. Events are typed with the schemas in the
+    /// router's store, or only with those published through the gateway if the store keeps no metadata.
+    pub fn new(router: Arc<TraceRouter>, cancellation_token: CancellationToken) -> Self {
+        Self {
+            state: GatewayState {
+                sender: router.sender(),
+                shared_metadata: router.metadata().is_some(),
+                metadata: router.metadata().unwrap_or_default(),
+                router,
+                cancellation_token,
+            },
+        }
+    }
+
+    /// The gateway's routes, for merging into an existing axum application
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/subscribe", get(subscribe::subscribe))
+            .route("/ingest", post(ingest::ingest))
+            .with_state(self.state)
+    }
+
+    /// Serve on `listener` until cancelled
+    pub async fn serve(self, listener: TcpListener) -> Result<()> {
+        let cancellation_token = self.state.cancellation_token.clone();
+        tracing::info!("HTTP gateway listening on {}", listener.local_addr()?);
+        axum::serve(listener, self.into_router())
+            .with_graceful_shutdown(cancellation_token.cancelled_owned())
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,301 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use axum::{
+    extract::{
+        ws::{Message, WebSocket},
+        State, WebSocketUpgrade,
+    },
+    response::Response,
+};
+use tokio_stream::StreamExt;
+use zelos_trace::{SinkCommand, TraceSink};
+use zelos_trace_types::{
+    ipc::IpcMessageWithId,
+    json::{JsonSubscribeCommandKind, JsonSubscribeRequest, JsonSubscribeResponse},
+};
+
+use crate::GatewayState;
+
+const CHUNK_SIZE: usize = 1024;
+const CHUNK_TIMEOUT: Duration = Duration::from_millis(10);
+
+pub(crate) async fn subscribe(ws: WebSocketUpgrade, State(state): State<GatewayState>) -> Response {
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = handle_socket(socket, state).await {
+            tracing::debug!("Subscriber disconnected: {}", e);
+        }
+    })
+}
+
+/// Apply a single command to the sink, returning the ack or error response for the client along with the messages
+/// replayed for a subscription with a start time
+async fn handle_command(
+    sink: &TraceSink,
+    text: &str,
+) -> (JsonSubscribeResponse, Vec<IpcMessageWithId>) {
+    let request: JsonSubscribeRequest = match serde_json::from_str(text) {
+        Ok(request) => request,
+        Err(e) => {
+            let error = JsonSubscribeResponse::CommandError {
+                kind: JsonSubscribeCommandKind::Unspecified,
+                filter: None,
+                message: format!("Invalid command: {}", e),
+            };
+            return (error, Vec::new());
+        }
+    };
+    let kind = request.kind();
+    let filter = request.filter().cloned();
+    let command = match request {
+        JsonSubscribeRequest::Subscribe { filter, start_time } => SinkCommand::Subscribe {
+            filter,
+            start_time_ns: start_time,
+        },
+        JsonSubscribeRequest::Unsubscribe { filter } => SinkCommand::Unsubscribe { filter },
+    };
+
+    match sink.apply(command).await {
+        Ok(replay) => (JsonSubscribeResponse::CommandAck { kind, filter }, replay),
+        Err(e) => {
+            tracing::debug!("Failed to apply subscribe command: {}", e);
+            let error = JsonSubscribeResponse::CommandError {
+                kind,
+                filter,
+                message: e.to_string(),
+            };
+            (error, Vec::new())
+        }
+    }
+}
+
+async fn send(socket: &mut WebSocket, response: &JsonSubscribeResponse) -> Result<()> {
+    socket
+        .send(Message::Text(serde_json::to_string(response)?))
+        .await?;
+    Ok(())
+}
+
+/// Stream trace messages to the client and apply its commands until it goes away. The sink is dropped on return,
+/// which removes it from the router.
+async fn handle_socket(mut socket: WebSocket, state: GatewayState) -> Result<()> {
+    let (sink, stream) = state.router.subscribe_stream().await?;
+    let batches = stream.chunks_timeout(CHUNK_SIZE, CHUNK_TIMEOUT);
+    tokio::pin!(batches);
+
+    loop {
+        tokio::select! {
+            batch = batches.next() => {
+                let Some(batch) = batch else {
+                    break;
+                };
+                send(&mut socket, &JsonSubscribeResponse::from_ipc(batch)).await?;
+            }
+            msg = socket.recv() => {
+                let (response, replay) = match msg {
+                    Some(Ok(Message::Text(text))) => handle_command(&sink, &text).await,
+                    Some(Ok(Message::Binary(_))) => (
+                        JsonSubscribeResponse::CommandError {
+                            kind: JsonSubscribeCommandKind::Unspecified,
+                            filter: None,
+                            message: "Commands must be sent as text frames".to_string(),
+                        },
+                        Vec::new(),
+                    ),
+                    // Pings are answered by axum
+                    Some(Ok(Message::Ping(_) | Message::Pong(_))) => continue,
+                    Some(Ok(Message::Close(_))) | None => break,
+                    Some(Err(e)) => return Err(e.into()),
+                };
+                for chunk in replay.chunks(CHUNK_SIZE) {
+                    send(&mut socket, &JsonSubscribeResponse::from_ipc(chunk.to_vec())).await?;
+                }
+                send(&mut socket, &response).await?;
+            }
+            _ = state.cancellation_token.cancelled() => {
+                socket.send(Message::Close(None)).await?;
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use futures_util::{SinkExt, StreamExt};
+    use tokio_tungstenite::tungstenite;
+    use tokio_util::sync::CancellationToken;
+    use zelos_trace::{
+        router::{TraceRouterConfig, DEFAULT_REPLAY_BUFFER_BYTES},
+        MetadataOnlyStore, TraceRouter, TraceSource,
+    };
+    use zelos_trace_types::json::JsonIpcMessage;
+
+    use super::*;
+    use crate::TraceHttpGateway;
+
+    type Client = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    async fn command(client: &mut Client, command: &str) -> Result<JsonSubscribeResponse> {
+        client
+            .send(tungstenite::Message::Text(command.to_string()))
+            .await?;
+        next_response(client).await
+    }
+
+    async fn next_response(client: &mut Client) -> Result<JsonSubscribeResponse> {
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(1), client.next())
+                .await?
+                .ok_or(anyhow::anyhow!("Socket closed"))??;
+            if let tungstenite::Message::Text(text) = msg {
+                return Ok(serde_json::from_str(&text)?);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_over_websocket() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let router_task = tokio::spawn(run);
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let gateway = TraceHttpGateway::new(router.clone(), cancellation_token.clone());
+        let server = tokio::spawn(gateway.serve(listener));
+
+        let (mut client, _) =
+            tokio_tungstenite::connect_async(format!("ws://{}/subscribe", addr)).await?;
+
+        let response = command(&mut client, r#"{"type": "subscribe", "filter": "bad"}"#).await?;
+        assert!(matches!(
+            response,
+            JsonSubscribeResponse::CommandError {
+                kind: JsonSubscribeCommandKind::Subscribe,
+                ..
+            }
+        ));
+        let response = command(&mut client, r#"{"type": "resubscribe"}"#).await?;
+        assert!(matches!(
+            response,
+            JsonSubscribeResponse::CommandError {
+                kind: JsonSubscribeCommandKind::Unspecified,
+                ..
+            }
+        ));
+        let response = command(
+            &mut client,
+            r#"{"type": "subscribe", "filter": "*/*/hello"}"#,
+        )
+        .await?;
+        assert_eq!(
+            response,
+            JsonSubscribeResponse::CommandAck {
+                kind: JsonSubscribeCommandKind::Subscribe,
+                filter: Some("*/*/hello".to_string()),
+            }
+        );
+
+        let source = TraceSource::new("s", router.sender());
+        let event = source
+            .build_event("hello")
+            .add_f64_field("x", Some("m".to_string()))
+            .build()?;
+        event.build().try_insert_f64("x", 1.5)?.emit()?;
+        event.build().try_insert_f64("x", f64::NAN)?.emit()?;
+
+        let mut values = Vec::new();
+        while values.len() < 2 {
+            let JsonSubscribeResponse::TraceMessageBatch { messages } =
+                next_response(&mut client).await?
+            else {
+                continue;
+            };
+            for message in messages {
+                assert_eq!(message.source_name, "s");
+                if let JsonIpcMessage::TraceEvent {
+                    name, mut fields, ..
+                } = message.msg
+                {
+                    assert_eq!(name, "hello");
+                    values.push(fields.remove("x"));
+                }
+            }
+        }
+        // Values JSON can't represent are sent as null
+        assert_eq!(
+            values,
+            vec![Some(1.5.into()), Some(serde_json::Value::Null)]
+        );
+
+        cancellation_token.cancel();
+        server.await??;
+        router_task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_from_start_time() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new_with_config(
+            Arc::new(MetadataOnlyStore::new()),
+            TraceRouterConfig {
+                replay_buffer_bytes: DEFAULT_REPLAY_BUFFER_BYTES,
+            },
+            cancellation_token.clone(),
+        );
+        let router_task = tokio::spawn(run);
+
+        // Publish before anyone subscribes, waiting until the router has forwarded the event
+        let (routed, _) = router.subscribe_all_blocking().await?;
+        let source = TraceSource::new("s", router.sender());
+        let event = source
+            .build_event("hello")
+            .add_f64_field("x", None)
+            .build()?;
+        event.build().try_insert_f64("x", 1.5)?.emit()?;
+        while !matches!(
+            routed.recv_async().await?.msg,
+            zelos_trace_types::ipc::IpcMessage::TraceEvent(_)
+        ) {}
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let gateway = TraceHttpGateway::new(router.clone(), cancellation_token.clone());
+        let server = tokio::spawn(gateway.serve(listener));
+        let (mut client, _) =
+            tokio_tungstenite::connect_async(format!("ws://{}/subscribe", addr)).await?;
+
+        // The replayed event arrives ahead of the ack
+        client
+            .send(tungstenite::Message::Text(
+                r#"{"type": "subscribe", "filter": "*/*/hello", "start_time": 0}"#.to_string(),
+            ))
+            .await?;
+        let mut replayed = 0;
+        loop {
+            match next_response(&mut client).await? {
+                JsonSubscribeResponse::TraceMessageBatch { messages } => {
+                    replayed += messages
+                        .iter()
+                        .filter(|m| matches!(m.msg, JsonIpcMessage::TraceEvent { .. }))
+                        .count();
+                }
+                JsonSubscribeResponse::CommandAck { .. } => break,
+                response => panic!("Unexpected response {:?}", response),
+            }
+        }
+        assert_eq!(replayed, 1);
+
+        cancellation_token.cancel();
+        server.await??;
+        router_task.await??;
+        Ok(())
+    }
+}
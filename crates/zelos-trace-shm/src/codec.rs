@@ -0,0 +1,884 @@
+//! Compact binary encoding of [`IpcMessageWithId`] for the ring.
+//!
+//! Every message starts with a kind byte, the segment id and the source name. Integers are little-endian, names are
+//! prefixed with a `u16` length and values with a type tag matching [`DataType`].
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use uuid::Uuid;
+use zelos_trace_types::{
+    ipc::{
+        DisplayHint, FieldDisplay, FieldKind, IpcMessage, IpcMessageWithId, LogLevel,
+        TraceActionResult, TraceActionSchema, TraceAnnotation, TraceEvent, TraceEventFieldMetadata,
+        TraceEventFieldNamedValues, TraceEventSchema, TraceIntervalBegin, TraceIntervalEnd,
+        TraceLog, TraceSegmentEnd, TraceSegmentStart, TraceSessionEnd, TraceSessionJoin,
+        TraceSessionStart,
+    },
+    DataType, Value,
+};
+
+const KIND_SEGMENT_START: u8 = 0;
+const KIND_SEGMENT_END: u8 = 1;
+const KIND_EVENT_SCHEMA: u8 = 2;
+const KIND_FIELD_NAMED_VALUES: u8 = 3;
+const KIND_EVENT: u8 = 4;
+const KIND_ACTION_SCHEMA: u8 = 5;
+const KIND_ACTION_RESULT: u8 = 6;
+const KIND_SESSION_START: u8 = 7;
+const KIND_SESSION_END: u8 = 8;
+const KIND_SESSION_JOIN: u8 = 9;
+const KIND_ANNOTATION: u8 = 10;
+const KIND_LOG: u8 = 11;
+const KIND_INTERVAL_BEGIN: u8 = 12;
+const KIND_INTERVAL_END: u8 = 13;
+
+fn data_type_tag(data_type: &DataType) -> u8 {
+    match data_type {
+        DataType::Int8 => 0,
+        DataType::Int16 => 1,
+        DataType::Int32 => 2,
+        DataType::Int64 => 3,
+        DataType::UInt8 => 4,
+        DataType::UInt16 => 5,
+        DataType::UInt32 => 6,
+        DataType::UInt64 => 7,
+        DataType::Float32 => 8,
+        DataType::Float64 => 9,
+        DataType::TimestampNs => 10,
+        DataType::Binary => 11,
+        DataType::String => 12,
+        DataType::Boolean => 13,
+    }
+}
+
+fn data_type_from_tag(tag: u8) -> Result<DataType> {
+    Ok(match tag {
+        0 => DataType::Int8,
+        1 => DataType::Int16,
+        2 => DataType::Int32,
+        3 => DataType::Int64,
+        4 => DataType::UInt8,
+        5 => DataType::UInt16,
+        6 => DataType::UInt32,
+        7 => DataType::UInt64,
+        8 => DataType::Float32,
+        9 => DataType::Float64,
+        10 => DataType::TimestampNs,
+        11 => DataType::Binary,
+        12 => DataType::String,
+        13 => DataType::Boolean,
+        _ => return Err(anyhow!("Unknown data type tag {}", tag)),
+    })
+}
+
+fn field_kind_tag(kind: FieldKind) -> u8 {
+    match kind {
+        FieldKind::Gauge => 0,
+        FieldKind::Counter => 1,
+        FieldKind::Delta => 2,
+        FieldKind::State => 3,
+    }
+}
+
+fn field_kind_from_tag(tag: u8) -> Result<FieldKind> {
+    Ok(match tag {
+        0 => FieldKind::Gauge,
+        1 => FieldKind::Counter,
+        2 => FieldKind::Delta,
+        3 => FieldKind::State,
+        _ => return Err(anyhow!("Unknown field kind tag {}", tag)),
+    })
+}
+
+fn display_hint_tag(hint: DisplayHint) -> u8 {
+    match hint {
+        DisplayHint::Line => 0,
+        DisplayHint::Step => 1,
+        DisplayHint::Scatter => 2,
+        DisplayHint::Bar => 3,
+        DisplayHint::Text => 4,
+    }
+}
+
+fn display_hint_from_tag(tag: u8) -> Result<DisplayHint> {
+    Ok(match tag {
+        0 => DisplayHint::Line,
+        1 => DisplayHint::Step,
+        2 => DisplayHint::Scatter,
+        3 => DisplayHint::Bar,
+        4 => DisplayHint::Text,
+        _ => return Err(anyhow!("Unknown display hint tag {}", tag)),
+    })
+}
+
+// Flags of the parts of a field display that are present, which follow in this order
+const DISPLAY_DESCRIPTION: u8 = 1 << 0;
+const DISPLAY_MIN: u8 = 1 << 1;
+const DISPLAY_MAX: u8 = 1 << 2;
+const DISPLAY_PRECISION: u8 = 1 << 3;
+const DISPLAY_HINT: u8 = 1 << 4;
+
+fn log_level_tag(level: LogLevel) -> u8 {
+    match level {
+        LogLevel::Trace => 0,
+        LogLevel::Debug => 1,
+        LogLevel::Info => 2,
+        LogLevel::Warn => 3,
+        LogLevel::Error => 4,
+    }
+}
+
+fn log_level_from_tag(tag: u8) -> Result<LogLevel> {
+    Ok(match tag {
+        0 => LogLevel::Trace,
+        1 => LogLevel::Debug,
+        2 => LogLevel::Info,
+        3 => LogLevel::Warn,
+        4 => LogLevel::Error,
+        _ => return Err(anyhow!("Unknown log level tag {}", tag)),
+    })
+}
+
+struct Writer<'a> {
+    buf: &'a mut Vec<u8>,
+}
+
+impl Writer<'_> {
+    fn u8(&mut self, v: u8) {
+        self.buf.push(v);
+    }
+
+    fn bytes(&mut self, v: &[u8]) {
+        self.buf.extend_from_slice(v);
+    }
+
+    fn name(&mut self, v: &str) -> Result<()> {
+        let len: u16 = v
+            .len()
+            .try_into()
+            .map_err(|_| anyhow!("Name of {} bytes is too long", v.len()))?;
+        self.bytes(&len.to_le_bytes());
+        self.bytes(v.as_bytes());
+        Ok(())
+    }
+
+    fn optional_name(&mut self, v: Option<&str>) -> Result<()> {
+        match v {
+            Some(v) => {
+                self.u8(1);
+                self.name(v)
+            }
+            None => {
+                self.u8(0);
+                Ok(())
+            }
+        }
+    }
+
+    fn blob(&mut self, v: &[u8]) -> Result<()> {
+        let len: u32 = v
+            .len()
+            .try_into()
+            .map_err(|_| anyhow!("Value of {} bytes is too long", v.len()))?;
+        self.bytes(&len.to_le_bytes());
+        self.bytes(v);
+        Ok(())
+    }
+
+    fn count(&mut self, v: usize) -> Result<()> {
+        let count: u16 = v
+            .try_into()
+            .map_err(|_| anyhow!("Too many entries: {}", v))?;
+        self.bytes(&count.to_le_bytes());
+        Ok(())
+    }
+
+    fn fields(&mut self, fields: &[TraceEventFieldMetadata]) -> Result<()> {
+        self.count(fields.len())?;
+        for field in fields {
+            self.name(&field.name)?;
+            self.u8(data_type_tag(&field.data_type));
+            self.optional_name(field.unit.as_deref())?;
+            self.u8(field_kind_tag(field.kind));
+            self.display(&field.display)?;
+        }
+        Ok(())
+    }
+
+    fn display(&mut self, display: &FieldDisplay) -> Result<()> {
+        let flags = [
+            (display.description.is_some(), DISPLAY_DESCRIPTION),
+            (display.min.is_some(), DISPLAY_MIN),
+            (display.max.is_some(), DISPLAY_MAX),
+            (display.precision.is_some(), DISPLAY_PRECISION),
+            (display.hint.is_some(), DISPLAY_HINT),
+        ]
+        .into_iter()
+        .filter(|(present, _)| *present)
+        .fold(0, |flags, (_, flag)| flags | flag);
+        self.u8(flags);
+        if let Some(description) = &display.description {
+            self.blob(description.as_bytes())?;
+        }
+        if let Some(min) = display.min {
+            self.bytes(&min.to_le_bytes());
+        }
+        if let Some(max) = display.max {
+            self.bytes(&max.to_le_bytes());
+        }
+        if let Some(precision) = display.precision {
+            self.bytes(&precision.to_le_bytes());
+        }
+        if let Some(hint) = display.hint {
+            self.u8(display_hint_tag(hint));
+        }
+        Ok(())
+    }
+
+    fn attributes(&mut self, attributes: &HashMap<String, String>) -> Result<()> {
+        self.count(attributes.len())?;
+        for (key, value) in attributes {
+            self.name(key)?;
+            self.blob(value.as_bytes())?;
+        }
+        Ok(())
+    }
+
+    fn named_values(&mut self, values: &HashMap<String, Value>) -> Result<()> {
+        self.count(values.len())?;
+        for (name, value) in values {
+            self.name(name)?;
+            self.value(value)?;
+        }
+        Ok(())
+    }
+
+    fn value(&mut self, v: &Value) -> Result<()> {
+        self.u8(data_type_tag(&v.data_type()));
+        match v {
+            Value::Int8(v) => self.bytes(&v.to_le_bytes()),
+            Value::Int16(v) => self.bytes(&v.to_le_bytes()),
+            Value::Int32(v) => self.bytes(&v.to_le_bytes()),
+            Value::Int64(v) => self.bytes(&v.to_le_bytes()),
+            Value::UInt8(v) => self.bytes(&v.to_le_bytes()),
+            Value::UInt16(v) => self.bytes(&v.to_le_bytes()),
+            Value::UInt32(v) => self.bytes(&v.to_le_bytes()),
+            Value::UInt64(v) => self.bytes(&v.to_le_bytes()),
+            Value::Float32(v) => self.bytes(&v.to_le_bytes()),
+            Value::Float64(v) => self.bytes(&v.to_le_bytes()),
+            Value::TimestampNs(v) => self.bytes(&v.to_le_bytes()),
+            Value::Binary(v) => self.blob(v)?,
+            Value::String(v) => self.blob(v.as_bytes())?,
+            Value::Boolean(v) => self.u8(*v as u8),
+        }
+        Ok(())
+    }
+}
+
+struct Reader<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.buf.len() < len {
+            return Err(anyhow!("Message is truncated"));
+        }
+        let (head, rest) = self.buf.split_at(len);
+        self.buf = rest;
+        Ok(head)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        let mut array = [0u8; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_le_bytes(self.array()?))
+    }
+
+    fn string(&mut self, len: usize) -> Result<String> {
+        Ok(std::str::from_utf8(self.take(len)?)?.to_string())
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let len = u16::from_le_bytes(self.array()?) as usize;
+        self.string(len)
+    }
+
+    fn optional_name(&mut self) -> Result<Option<String>> {
+        match self.u8()? {
+            0 => Ok(None),
+            _ => Ok(Some(self.name()?)),
+        }
+    }
+
+    fn blob(&mut self) -> Result<&'a [u8]> {
+        let len = u32::from_le_bytes(self.array()?) as usize;
+        self.take(len)
+    }
+
+    fn count(&mut self) -> Result<usize> {
+        Ok(u16::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn fields(&mut self) -> Result<Vec<TraceEventFieldMetadata>> {
+        (0..self.count()?)
+            .map(|_| {
+                Ok(TraceEventFieldMetadata {
+                    name: self.name()?,
+                    data_type: data_type_from_tag(self.u8()?)?,
+                    unit: self.optional_name()?,
+                    kind: field_kind_from_tag(self.u8()?)?,
+                    display: self.display()?,
+                })
+            })
+            .collect()
+    }
+
+    fn display(&mut self) -> Result<FieldDisplay> {
+        let flags = self.u8()?;
+        let mut display = FieldDisplay::default();
+        if flags & DISPLAY_DESCRIPTION != 0 {
+            display.description = Some(std::str::from_utf8(self.blob()?)?.to_string());
+        }
+        if flags & DISPLAY_MIN != 0 {
+            display.min = Some(f64::from_le_bytes(self.array()?));
+        }
+        if flags & DISPLAY_MAX != 0 {
+            display.max = Some(f64::from_le_bytes(self.array()?));
+        }
+        if flags & DISPLAY_PRECISION != 0 {
+            display.precision = Some(u32::from_le_bytes(self.array()?));
+        }
+        if flags & DISPLAY_HINT != 0 {
+            display.hint = Some(display_hint_from_tag(self.u8()?)?);
+        }
+        Ok(display)
+    }
+
+    fn attributes(&mut self) -> Result<HashMap<String, String>> {
+        (0..self.count()?)
+            .map(|_| Ok((self.name()?, std::str::from_utf8(self.blob()?)?.to_string())))
+            .collect()
+    }
+
+    fn named_values(&mut self) -> Result<HashMap<String, Value>> {
+        (0..self.count()?)
+            .map(|_| Ok((self.name()?, self.value()?)))
+            .collect()
+    }
+
+    fn value(&mut self) -> Result<Value> {
+        Ok(match data_type_from_tag(self.u8()?)? {
+            DataType::Int8 => Value::Int8(i8::from_le_bytes(self.array()?)),
+            DataType::Int16 => Value::Int16(i16::from_le_bytes(self.array()?)),
+            DataType::Int32 => Value::Int32(i32::from_le_bytes(self.array()?)),
+            DataType::Int64 => Value::Int64(i64::from_le_bytes(self.array()?)),
+            DataType::UInt8 => Value::UInt8(self.u8()?),
+            DataType::UInt16 => Value::UInt16(u16::from_le_bytes(self.array()?)),
+            DataType::UInt32 => Value::UInt32(u32::from_le_bytes(self.array()?)),
+            DataType::UInt64 => Value::UInt64(u64::from_le_bytes(self.array()?)),
+            DataType::Float32 => Value::Float32(f32::from_le_bytes(self.array()?)),
+            DataType::Float64 => Value::Float64(f64::from_le_bytes(self.array()?)),
+            DataType::TimestampNs => Value::TimestampNs(i64::from_le_bytes(self.array()?)),
+            DataType::Binary => Value::Binary(self.blob()?.to_vec()),
+            DataType::String => Value::String(std::str::from_utf8(self.blob()?)?.to_string()),
+            DataType::Boolean => Value::Boolean(self.u8()? != 0),
+        })
+    }
+}
+
+/// Encode `msg` into `buf`, replacing its contents
+pub fn encode(msg: &IpcMessageWithId, buf: &mut Vec<u8>) -> Result<()> {
+    buf.clear();
+    let mut w = Writer { buf };
+
+    let kind = match &msg.msg {
+        IpcMessage::TraceSegmentStart(_) => KIND_SEGMENT_START,
+        IpcMessage::TraceSegmentEnd(_) => KIND_SEGMENT_END,
+        IpcMessage::TraceEventSchema(_) => KIND_EVENT_SCHEMA,
+        IpcMessage::TraceEventFieldNamedValues(_) => KIND_FIELD_NAMED_VALUES,
+        IpcMessage::TraceEvent(_) => KIND_EVENT,
+        IpcMessage::TraceActionSchema(_) => KIND_ACTION_SCHEMA,
+        IpcMessage::TraceActionResult(_) => KIND_ACTION_RESULT,
+        IpcMessage::TraceSessionStart(_) => KIND_SESSION_START,
+        IpcMessage::TraceSessionEnd(_) => KIND_SESSION_END,
+        IpcMessage::TraceSessionJoin(_) => KIND_SESSION_JOIN,
+        IpcMessage::Annotation(_) => KIND_ANNOTATION,
+        IpcMessage::Log(_) => KIND_LOG,
+        IpcMessage::TraceIntervalBegin(_) => KIND_INTERVAL_BEGIN,
+        IpcMessage::TraceIntervalEnd(_) => KIND_INTERVAL_END,
+    };
+    w.u8(kind);
+    w.bytes(msg.segment_id.as_bytes());
+    w.name(&msg.source_name)?;
+
+    match &msg.msg {
+        IpcMessage::TraceSegmentStart(m) => {
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.name(&m.source_name)?;
+            w.attributes(&m.attributes)?;
+        }
+        IpcMessage::TraceSegmentEnd(m) => {
+            w.bytes(&m.time_ns.to_le_bytes());
+        }
+        IpcMessage::TraceEventSchema(m) => {
+            w.name(&m.name)?;
+            w.bytes(&m.version.to_le_bytes());
+            w.fields(&m.fields)?;
+        }
+        IpcMessage::TraceEventFieldNamedValues(m) => {
+            w.name(&m.event_name)?;
+            w.name(&m.field_name)?;
+            w.count(m.values.len())?;
+            for (value, name) in &m.values {
+                w.value(value)?;
+                w.name(name)?;
+            }
+        }
+        IpcMessage::TraceEvent(m) => {
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.name(&m.name)?;
+            w.count(m.fields.len())?;
+            for (name, value) in &m.fields {
+                w.name(name)?;
+                w.value(value)?;
+            }
+        }
+        IpcMessage::TraceActionSchema(m) => {
+            w.name(&m.name)?;
+            w.fields(&m.parameters)?;
+            w.fields(&m.returns)?;
+        }
+        IpcMessage::TraceActionResult(m) => {
+            w.bytes(m.call_id.as_bytes());
+            w.name(&m.action_name)?;
+            match &m.result {
+                Ok(values) => {
+                    w.u8(1);
+                    w.count(values.len())?;
+                    for (name, value) in values {
+                        w.name(name)?;
+                        w.value(value)?;
+                    }
+                }
+                Err(error) => {
+                    w.u8(0);
+                    w.blob(error.as_bytes())?;
+                }
+            }
+        }
+        IpcMessage::TraceSessionStart(m) => {
+            w.bytes(m.session_id.as_bytes());
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.name(&m.name)?;
+            w.attributes(&m.attributes)?;
+            w.attributes(&m.join_attributes)?;
+        }
+        IpcMessage::TraceSessionEnd(m) => {
+            w.bytes(m.session_id.as_bytes());
+            w.bytes(&m.time_ns.to_le_bytes());
+        }
+        IpcMessage::TraceSessionJoin(m) => {
+            w.bytes(m.session_id.as_bytes());
+        }
+        IpcMessage::Annotation(m) => {
+            w.bytes(m.annotation_id.as_bytes());
+            w.bytes(&m.start_time_ns.to_le_bytes());
+            w.bytes(&m.end_time_ns.to_le_bytes());
+            w.blob(m.text.as_bytes())?;
+            w.count(m.tags.len())?;
+            for tag in &m.tags {
+                w.name(tag)?;
+            }
+            w.optional_name(m.author.as_deref())?;
+        }
+        IpcMessage::Log(m) => {
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.u8(log_level_tag(m.level));
+            w.name(&m.target)?;
+            w.blob(m.message.as_bytes())?;
+            w.named_values(&m.fields)?;
+        }
+        IpcMessage::TraceIntervalBegin(m) => {
+            w.bytes(m.interval_id.as_bytes());
+            match m.parent_id {
+                Some(parent_id) => {
+                    w.u8(1);
+                    w.bytes(parent_id.as_bytes());
+                }
+                None => w.u8(0),
+            }
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.name(&m.name)?;
+            w.named_values(&m.fields)?;
+        }
+        IpcMessage::TraceIntervalEnd(m) => {
+            w.bytes(m.interval_id.as_bytes());
+            w.bytes(&m.time_ns.to_le_bytes());
+            w.name(&m.name)?;
+            w.named_values(&m.fields)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Decode a message produced by [`encode`]
+pub fn decode(buf: &[u8]) -> Result<IpcMessageWithId> {
+    let mut r = Reader { buf };
+
+    let kind = r.u8()?;
+    let segment_id = Uuid::from_bytes(r.array()?);
+    let source_name = r.name()?;
+
+    let msg = match kind {
+        KIND_SEGMENT_START => IpcMessage::TraceSegmentStart(TraceSegmentStart {
+            time_ns: r.i64()?,
+            source_name: r.name()?,
+            attributes: r.attributes()?,
+        }),
+        KIND_SEGMENT_END => IpcMessage::TraceSegmentEnd(TraceSegmentEnd { time_ns: r.i64()? }),
+        KIND_EVENT_SCHEMA => IpcMessage::TraceEventSchema(TraceEventSchema {
+            name: r.name()?,
+            version: u32::from_le_bytes(r.array()?),
+            fields: r.fields()?,
+        }),
+        KIND_FIELD_NAMED_VALUES => {
+            let event_name = r.name()?;
+            let field_name = r.name()?;
+            let values = (0..r.count()?)
+                .map(|_| Ok((r.value()?, r.name()?)))
+                .collect::<Result<HashMap<_, _>>>()?;
+            IpcMessage::TraceEventFieldNamedValues(TraceEventFieldNamedValues {
+                event_name,
+                field_name,
+                values,
+            })
+        }
+        KIND_EVENT => {
+            let time_ns = r.i64()?;
+            let name = r.name()?;
+            let fields = (0..r.count()?)
+                .map(|_| Ok((r.name()?, r.value()?)))
+                .collect::<Result<_>>()?;
+            IpcMessage::TraceEvent(TraceEvent {
+                time_ns,
+                name: name.into(),
+                fields,
+            })
+        }
+        KIND_ACTION_SCHEMA => IpcMessage::TraceActionSchema(TraceActionSchema {
+            name: r.name()?,
+            parameters: r.fields()?,
+            returns: r.fields()?,
+        }),
+        KIND_ACTION_RESULT => {
+            let call_id = Uuid::from_bytes(r.array()?);
+            let action_name = r.name()?;
+            let result = match r.u8()? {
+                0 => Err(std::str::from_utf8(r.blob()?)?.to_string()),
+                _ => Ok((0..r.count()?)
+                    .map(|_| Ok((r.name()?, r.value()?)))
+                    .collect::<Result<_>>()?),
+            };
+            IpcMessage::TraceActionResult(TraceActionResult {
+                call_id,
+                action_name,
+                result,
+            })
+        }
+        KIND_SESSION_START => IpcMessage::TraceSessionStart(TraceSessionStart {
+            session_id: Uuid::from_bytes(r.array()?),
+            time_ns: r.i64()?,
+            name: r.name()?,
+            attributes: r.attributes()?,
+            join_attributes: r.attributes()?,
+        }),
+        KIND_SESSION_END => IpcMessage::TraceSessionEnd(TraceSessionEnd {
+            session_id: Uuid::from_bytes(r.array()?),
+            time_ns: r.i64()?,
+        }),
+        KIND_SESSION_JOIN => IpcMessage::TraceSessionJoin(TraceSessionJoin {
+            session_id: Uuid::from_bytes(r.array()?),
+        }),
+        KIND_ANNOTATION => IpcMessage::Annotation(TraceAnnotation {
+            annotation_id: Uuid::from_bytes(r.array()?),
+            start_time_ns: r.i64()?,
+            end_time_ns: r.i64()?,
+            text: std::str::from_utf8(r.blob()?)?.to_string(),
+            tags: (0..r.count()?).map(|_| r.name()).collect::<Result<_>>()?,
+            author: r.optional_name()?,
+        }),
+        KIND_LOG => IpcMessage::Log(TraceLog {
+            time_ns: r.i64()?,
+            level: log_level_from_tag(r.u8()?)?,
+            target: r.name()?,
+            message: std::str::from_utf8(r.blob()?)?.to_string(),
+            fields: r.named_values()?,
+        }),
+        KIND_INTERVAL_BEGIN => IpcMessage::TraceIntervalBegin(TraceIntervalBegin {
+            interval_id: Uuid::from_bytes(r.array()?),
+            parent_id: match r.u8()? {
+                0 => None,
+                _ => Some(Uuid::from_bytes(r.array()?)),
+            },
+            time_ns: r.i64()?,
+            name: r.name()?,
+            fields: r.named_values()?,
+        }),
+        KIND_INTERVAL_END => IpcMessage::TraceIntervalEnd(TraceIntervalEnd {
+            interval_id: Uuid::from_bytes(r.array()?),
+            time_ns: r.i64()?,
+            name: r.name()?,
+            fields: r.named_values()?,
+        }),
+        _ => return Err(anyhow!("Unknown message kind {}", kind)),
+    };
+
+    if !r.buf.is_empty() {
+        return Err(anyhow!("{} trailing bytes after message", r.buf.len()));
+    }
+
+    Ok(IpcMessageWithId {
+        segment_id,
+        source_name: source_name.into(),
+        msg,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn roundtrip(msg: IpcMessage) -> Result<IpcMessage> {
+        let msg = IpcMessageWithId {
+            segment_id: Uuid::now_v7(),
+            source_name: "source".into(),
+            msg,
+        };
+        let mut buf = Vec::new();
+        encode(&msg, &mut buf)?;
+        let decoded = decode(&buf)?;
+        assert_eq!(decoded.segment_id, msg.segment_id);
+        assert_eq!(decoded.source_name, msg.source_name);
+
+        // Every byte matters, so any truncation must be caught
+        for len in 0..buf.len() {
+            assert!(decode(&buf[..len]).is_err());
+        }
+        Ok(decoded.msg)
+    }
+
+    #[test]
+    fn test_roundtrip() -> Result<()> {
+        let fields: HashMap<String, Value> = [
+            Value::Int8(-1),
+            Value::Int16(-2),
+            Value::Int32(-3),
+            Value::Int64(-4),
+            Value::UInt8(1),
+            Value::UInt16(2),
+            Value::UInt32(3),
+            Value::UInt64(4),
+            Value::Float32(1.5),
+            Value::Float64(f64::NAN),
+            Value::TimestampNs(1_700_000_000_000_000_000),
+            Value::Binary(vec![0, 1, 2]),
+            Value::String("hello".to_string()),
+            Value::Boolean(true),
+        ]
+        .into_iter()
+        .map(|v| (format!("{:?}", v.data_type()), v))
+        .collect();
+
+        let IpcMessage::TraceEvent(event) = roundtrip(
+            TraceEvent {
+                time_ns: 42,
+                name: "event".into(),
+                fields: fields.clone().into(),
+            }
+            .into(),
+        )?
+        else {
+            panic!("Expected an event");
+        };
+        assert_eq!(event.time_ns, 42);
+        assert_eq!(&*event.name, "event");
+        assert_eq!(event.fields.into_named(), fields);
+
+        let IpcMessage::TraceEventSchema(schema) = roundtrip(
+            TraceEventSchema {
+                name: "event".to_string(),
+                version: 3,
+                fields: vec![
+                    TraceEventFieldMetadata {
+                        name: "a".to_string(),
+                        data_type: DataType::Float64,
+                        unit: Some("V".to_string()),
+                        kind: FieldKind::Gauge,
+                        display: FieldDisplay::default()
+                            .with_description("Bus voltage")
+                            .with_range(0.0, 48.0)
+                            .with_precision(2)
+                            .with_hint(DisplayHint::Step),
+                    },
+                    TraceEventFieldMetadata {
+                        name: "b".to_string(),
+                        data_type: DataType::UInt32,
+                        unit: None,
+                        kind: FieldKind::Counter,
+                        display: FieldDisplay::default(),
+                    },
+                ],
+            }
+            .into(),
+        )?
+        else {
+            panic!("Expected a schema");
+        };
+        assert_eq!(schema.version, 3);
+        assert_eq!(schema.fields[0].unit.as_deref(), Some("V"));
+        assert_eq!(
+            schema.fields[0].display.description.as_deref(),
+            Some("Bus voltage")
+        );
+        assert_eq!(schema.fields[0].display.max, Some(48.0));
+        assert_eq!(schema.fields[0].display.precision, Some(2));
+        assert_eq!(schema.fields[0].display.hint, Some(DisplayHint::Step));
+        assert_eq!(schema.fields[1].kind, FieldKind::Counter);
+        assert!(schema.fields[1].display.is_empty());
+
+        let values: HashMap<Value, String> = [(Value::UInt8(0), "off".to_string())].into();
+        let IpcMessage::TraceEventFieldNamedValues(named) = roundtrip(
+            TraceEventFieldNamedValues {
+                event_name: "event".to_string(),
+                field_name: "state".to_string(),
+                values: values.clone(),
+            }
+            .into(),
+        )?
+        else {
+            panic!("Expected named values");
+        };
+        assert_eq!(named.values, values);
+
+        let attributes: HashMap<String, String> =
+            [("host".to_string(), "bench-1".to_string())].into();
+        let IpcMessage::TraceSegmentStart(start) = roundtrip(
+            TraceSegmentStart {
+                time_ns: 7,
+                source_name: "source".to_string(),
+                attributes: attributes.clone(),
+            }
+            .into(),
+        )?
+        else {
+            panic!("Expected a segment start");
+        };
+        assert_eq!(start.attributes, attributes);
+
+        let session = TraceSessionStart {
+            session_id: Uuid::now_v7(),
+            time_ns: 8,
+            name: "run".to_string(),
+            attributes: attributes.clone(),
+            join_attributes: [("run".to_string(), "42".to_string())].into(),
+        };
+        let IpcMessage::TraceSessionStart(decoded) = roundtrip(session.clone().into())? else {
+            panic!("Expected a session start");
+        };
+        assert_eq!(decoded, session);
+
+        let annotation = TraceAnnotation {
+            annotation_id: Uuid::now_v7(),
+            start_time_ns: 9,
+            end_time_ns: 10,
+            text: "brake test started".to_string(),
+            tags: vec!["brakes".to_string()],
+            author: Some("operator".to_string()),
+        };
+        let IpcMessage::Annotation(decoded) = roundtrip(annotation.clone().into())? else {
+            panic!("Expected an annotation");
+        };
+        assert_eq!(decoded, annotation);
+
+        let log = TraceLog {
+            time_ns: 11,
+            level: LogLevel::Warn,
+            target: "motor::can".to_string(),
+            message: "Bus off, restarting".to_string(),
+            fields: [("errors".to_string(), Value::UInt32(12))].into(),
+        };
+        let IpcMessage::Log(decoded) = roundtrip(log.clone().into())? else {
+            panic!("Expected a log");
+        };
+        assert_eq!(decoded, log);
+
+        for parent_id in [None, Some(Uuid::now_v7())] {
+            let begin = TraceIntervalBegin {
+                interval_id: Uuid::now_v7(),
+                parent_id,
+                time_ns: 12,
+                name: "RUNNING".to_string(),
+                fields: [("mode".to_string(), Value::String("auto".to_string()))].into(),
+            };
+            let IpcMessage::TraceIntervalBegin(decoded) = roundtrip(begin.clone().into())? else {
+                panic!("Expected an interval begin");
+            };
+            assert_eq!(decoded, begin);
+        }
+
+        let end = TraceIntervalEnd {
+            interval_id: Uuid::now_v7(),
+            time_ns: 13,
+            name: "RUNNING".to_string(),
+            fields: [("cycles".to_string(), Value::UInt32(40))].into(),
+        };
+        let IpcMessage::TraceIntervalEnd(decoded) = roundtrip(end.clone().into())? else {
+            panic!("Expected an interval end");
+        };
+        assert_eq!(decoded, end);
+
+        let action = TraceActionSchema {
+            name: "reset".to_string(),
+            parameters: vec![TraceEventFieldMetadata {
+                name: "channel".to_string(),
+                data_type: DataType::UInt8,
+                unit: None,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay::default(),
+            }],
+            returns: vec![],
+        };
+        let IpcMessage::TraceActionSchema(decoded) = roundtrip(action.clone().into())? else {
+            panic!("Expected an action schema");
+        };
+        assert_eq!(decoded, action);
+
+        for result in [
+            Ok([("count".to_string(), Value::UInt32(3))].into()),
+            Err("Channel is busy".to_string()),
+        ] {
+            let result = TraceActionResult {
+                call_id: Uuid::now_v7(),
+                action_name: "reset".to_string(),
+                result,
+            };
+            let IpcMessage::TraceActionResult(decoded) = roundtrip(result.clone().into())? else {
+                panic!("Expected an action result");
+            };
+            assert_eq!(decoded, result);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,28 @@
+//! Shared-memory transport between a publisher process and the router in another process on the same host.
+//!
+//! A [`ShmReceiver`] creates a ring buffer in a memory-mapped file and forwards everything written to it into a
+//! router's [`Sender`](zelos_trace_types::ipc::Sender). A [`ShmPublisher`] in the publishing process attaches to the
+//! ring and writes every message from its local router into it, using a compact binary encoding rather than protobuf.
+#![deny(clippy::expect_used, clippy::unwrap_used)]
+
+pub mod codec;
+mod publisher;
+mod receiver;
+pub mod ring;
+
+pub use publisher::{ShmPublisher, ShmPublisherConfig};
+pub use receiver::{ShmReceiver, ShmReceiverConfig};
+
+/// Written by a publisher when it attaches, so the receiver can end segments left open by a previous publisher
+pub(crate) const ATTACH_RECORD: &[u8] = &[u8::MAX];
+
+/// Default location for a ring named `name`, in memory-backed storage where the platform has it
+pub fn default_path(name: &str) -> std::path::PathBuf {
+    let dir = std::path::Path::new("/dev/shm");
+    let dir = if dir.is_dir() {
+        dir.to_path_buf()
+    } else {
+        std::env::temp_dir()
+    };
+    dir.join(format!("zelos-{}", name))
+}
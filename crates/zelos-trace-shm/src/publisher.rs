@@ -0,0 +1,169 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use anyhow::{anyhow, Result};
+use tokio_util::sync::CancellationToken;
+use zelos_trace::{time::now_time_ns, TraceRouter};
+use zelos_trace_types::ipc::IpcMessageWithId;
+
+use crate::{codec, ring::ShmRing, ATTACH_RECORD};
+
+const DEFAULT_HEARTBEAT_INTERVAL_MS: u64 = 100;
+const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
+const DEFAULT_PRODUCER_TIMEOUT_MS: u64 = 1000;
+const FULL_RETRY_DELAY: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone)]
+pub struct ShmPublisherConfig {
+    /// How often we tell the receiver we are still alive
+    pub heartbeat_interval: Duration,
+    /// Delay between attempts to attach to the ring
+    pub retry_delay: Duration,
+    /// A producer that has not sent a heartbeat for this long is considered dead, and may be replaced
+    pub producer_timeout: Duration,
+}
+
+impl Default for ShmPublisherConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval: Duration::from_millis(DEFAULT_HEARTBEAT_INTERVAL_MS),
+            retry_delay: Duration::from_millis(DEFAULT_RETRY_DELAY_MS),
+            producer_timeout: Duration::from_millis(DEFAULT_PRODUCER_TIMEOUT_MS),
+        }
+    }
+}
+
+/// Writes every message from a local router into a ring created by a [`ShmReceiver`](crate::ShmReceiver)
+pub struct ShmPublisher {
+    router: Arc<TraceRouter>,
+    path: PathBuf,
+    config: ShmPublisherConfig,
+}
+
+struct Attached {
+    ring: ShmRing,
+    pid: u32,
+    buf: Vec<u8>,
+}
+
+impl Attached {
+    /// Write a message, waiting for the receiver to make room if the ring is full
+    async fn push(&mut self, msg: &IpcMessageWithId) -> Result<()> {
+        if let Err(e) = codec::encode(msg, &mut self.buf) {
+            tracing::warn!("Dropping message that cannot be encoded: {}", e);
+            return Ok(());
+        }
+        if self.buf.len() > self.ring.max_payload_len() {
+            tracing::warn!(
+                "Dropping message of {} bytes, which does not fit in the ring",
+                self.buf.len()
+            );
+            return Ok(());
+        }
+
+        while !self.ring.try_push(&self.buf)? {
+            if self.ring.is_closed() {
+                return Err(anyhow!("Ring was closed by the receiver"));
+            }
+            self.ring.heartbeat(now_time_ns() as u64);
+            tokio::time::sleep(FULL_RETRY_DELAY).await;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for Attached {
+    fn drop(&mut self) {
+        self.ring.detach_producer(self.pid);
+    }
+}
+
+impl ShmPublisher {
+    pub fn new(
+        router: Arc<TraceRouter>,
+        path: impl Into<PathBuf>,
+        config: ShmPublisherConfig,
+    ) -> Self {
+        Self {
+            router,
+            path: path.into(),
+            config,
+        }
+    }
+
+    fn attach(&self) -> Result<Attached> {
+        let ring = ShmRing::open(&self.path)?;
+        if ring.is_closed() {
+            return Err(anyhow!("Ring at {} is closed", self.path.display()));
+        }
+
+        let pid = std::process::id();
+        let now_ns = now_time_ns() as u64;
+        let stale_before_ns = now_ns.saturating_sub(self.config.producer_timeout.as_nanos() as u64);
+        let generation = ring.attach_producer(pid, now_ns, stale_before_ns)?;
+        tracing::info!(
+            "Attached to ring at {} as producer generation {}",
+            self.path.display(),
+            generation
+        );
+
+        Ok(Attached {
+            ring,
+            pid,
+            buf: Vec::new(),
+        })
+    }
+
+    /// Forward messages until cancelled or the ring is closed
+    async fn forward(
+        &self,
+        attached: &mut Attached,
+        cancellation_token: &CancellationToken,
+    ) -> Result<()> {
+        // Mark the start of our messages, then replay the metadata the router has seen so far
+        while !attached.ring.try_push(ATTACH_RECORD)? {
+            tokio::time::sleep(FULL_RETRY_DELAY).await;
+        }
+        let (receiver, metadata) = self.router.subscribe_all_blocking().await?;
+        for msg in &metadata {
+            attached.push(msg).await?;
+        }
+
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat_interval);
+        loop {
+            tokio::select! {
+                msg = receiver.recv_async() => attached.push(&msg?).await?,
+                _ = heartbeat.tick() => {
+                    if attached.ring.is_closed() {
+                        return Err(anyhow!("Ring was closed by the receiver"));
+                    }
+                    attached.ring.heartbeat(now_time_ns() as u64);
+                }
+                _ = cancellation_token.cancelled() => {
+                    // Flush whatever the router has already handed us
+                    for msg in receiver.drain() {
+                        attached.push(&msg).await?;
+                    }
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Attach to the ring and forward messages until cancelled, re-attaching whenever the receiver replaces the ring
+    pub async fn run(self, cancellation_token: CancellationToken) -> Result<()> {
+        loop {
+            match self.attach() {
+                Ok(mut attached) => match self.forward(&mut attached, &cancellation_token).await {
+                    Ok(()) => return Ok(()),
+                    Err(e) => tracing::warn!("Lost ring at {}: {}", self.path.display(), e),
+                },
+                Err(e) => tracing::debug!("Failed to attach to ring: {}", e),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.config.retry_delay) => {}
+                _ = cancellation_token.cancelled() => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Result;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use zelos_trace::time::now_time_ns;
+use zelos_trace_types::ipc::{IpcMessage, IpcMessageWithId, Sender, TraceSegmentEnd};
+
+use crate::{codec, ring::ShmRing, ATTACH_RECORD};
+
+const DEFAULT_CAPACITY: usize = 8 * 1024 * 1024;
+const DEFAULT_POLL_INTERVAL_MS: u64 = 1;
+const DEFAULT_PRODUCER_TIMEOUT_MS: u64 = 1000;
+
+#[derive(Debug, Clone)]
+pub struct ShmReceiverConfig {
+    /// Size of the ring's data region in bytes
+    pub capacity: usize,
+    /// How long to wait before checking an empty ring again
+    pub poll_interval: Duration,
+    /// A producer that has not sent a heartbeat for this long is considered dead, and its segments are ended
+    pub producer_timeout: Duration,
+}
+
+impl Default for ShmReceiverConfig {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_CAPACITY,
+            poll_interval: Duration::from_millis(DEFAULT_POLL_INTERVAL_MS),
+            producer_timeout: Duration::from_millis(DEFAULT_PRODUCER_TIMEOUT_MS),
+        }
+    }
+}
+
+/// Reads messages from a ring and forwards them to a router
+pub struct ShmReceiver {
+    ring: ShmRing,
+    path: PathBuf,
+    sender: Sender,
+    config: ShmReceiverConfig,
+
+    /// Segments we have seen start but not end, and their source names
+    open_segments: HashMap<Uuid, Arc<str>>,
+}
+
+impl ShmReceiver {
+    /// Create the ring at `path`, replacing any ring a previous receiver left behind
+    pub fn new(
+        path: impl Into<PathBuf>,
+        sender: Sender,
+        config: ShmReceiverConfig,
+    ) -> Result<Self> {
+        let path = path.into();
+        let ring = ShmRing::create(&path, config.capacity)?;
+        Ok(Self {
+            ring,
+            path,
+            sender,
+            config,
+            open_segments: HashMap::new(),
+        })
+    }
+
+    /// The path of the ring publishers attach to
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// End every open segment, used when the producer that owns them is gone
+    async fn end_open_segments(&mut self) -> Result<()> {
+        let time_ns = now_time_ns();
+        for (segment_id, source_name) in self.open_segments.drain() {
+            tracing::debug!(
+                "Ending segment {} of {} left open by producer",
+                segment_id,
+                source_name
+            );
+            self.sender
+                .send_async(IpcMessageWithId {
+                    segment_id,
+                    source_name,
+                    msg: TraceSegmentEnd { time_ns }.into(),
+                })
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Forward everything currently in the ring, returning whether there was anything
+    async fn drain(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        let mut received = false;
+        while self.ring.try_pop(buf)? {
+            received = true;
+            if buf.as_slice() == ATTACH_RECORD {
+                // A new producer is starting, so the previous one will not end its segments
+                self.end_open_segments().await?;
+                continue;
+            }
+
+            let msg = match codec::decode(buf) {
+                Ok(msg) => msg,
+                Err(e) => {
+                    tracing::warn!("Dropping message that cannot be decoded: {}", e);
+                    continue;
+                }
+            };
+            match &msg.msg {
+                IpcMessage::TraceSegmentEnd(_) => {
+                    self.open_segments.remove(&msg.segment_id);
+                }
+                _ => {
+                    self.open_segments
+                        .entry(msg.segment_id)
+                        .or_insert_with(|| msg.source_name.clone());
+                }
+            }
+            self.sender.send_async(msg).await?;
+        }
+        Ok(received)
+    }
+
+    /// Whether the producer has gone away, either by detaching or by missing its heartbeats
+    fn producer_is_gone(&self) -> bool {
+        let Some(pid) = self.ring.producer_pid() else {
+            return true;
+        };
+        let stale_before_ns =
+            (now_time_ns() as u64).saturating_sub(self.config.producer_timeout.as_nanos() as u64);
+        if self.ring.heartbeat_ns() < stale_before_ns {
+            tracing::warn!("Producer {} stopped sending heartbeats", pid);
+            self.ring.detach_producer(pid);
+            return true;
+        }
+        false
+    }
+
+    /// Forward everything in the ring, and end the segments of a producer that went away. Returns whether there was
+    /// anything in the ring.
+    async fn poll(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        if self.drain(buf).await? {
+            return Ok(true);
+        }
+
+        // Only look at the producer once the ring is empty, so we never end a segment before its last message
+        if !self.open_segments.is_empty() && self.producer_is_gone() {
+            self.end_open_segments().await?;
+        }
+        Ok(false)
+    }
+
+    /// Forward messages until cancelled, then close and remove the ring
+    pub async fn run(mut self, cancellation_token: CancellationToken) -> Result<()> {
+        tracing::info!(
+            "Receiving trace messages from ring at {}",
+            self.path.display()
+        );
+
+        let mut buf = Vec::new();
+        let result = loop {
+            match self.poll(&mut buf).await {
+                Ok(true) => continue,
+                Ok(false) => {}
+                Err(e) => break Err(e),
+            }
+
+            tokio::select! {
+                _ = tokio::time::sleep(self.config.poll_interval) => {}
+                _ = cancellation_token.cancelled() => break self.drain(&mut buf).await.map(|_| ()),
+            }
+        };
+
+        self.ring.close();
+        if let Err(e) = std::fs::remove_file(&self.path) {
+            tracing::warn!("Failed to remove {}: {}", self.path.display(), e);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zelos_trace::{TraceRouter, TraceSource};
+    use zelos_trace_types::ipc::TraceSegmentStart;
+
+    use super::*;
+    use crate::{ShmPublisher, ShmPublisherConfig};
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("zelos-receiver-{}", Uuid::now_v7()))
+    }
+
+    async fn recv(receiver: &flume::Receiver<IpcMessageWithId>) -> Result<IpcMessageWithId> {
+        Ok(tokio::time::timeout(Duration::from_secs(5), receiver.recv_async()).await??)
+    }
+
+    #[tokio::test]
+    async fn test_publisher_to_receiver() -> Result<()> {
+        let path = temp_path();
+        let cancellation_token = CancellationToken::new();
+        let (sender, receiver) = flume::unbounded();
+        let shm_receiver = ShmReceiver::new(&path, sender, ShmReceiverConfig::default())?;
+        let receiver_task = tokio::spawn(shm_receiver.run(cancellation_token.clone()));
+
+        let (router, router_task) = TraceRouter::new(cancellation_token.clone());
+        tokio::spawn(router_task);
+        let source = TraceSource::new("shm", router.sender());
+        let event = source
+            .build_event("data")
+            .add_u64_field("n", None)
+            .build()?;
+
+        // Once the publisher attaches it replays the schema, after which events are forwarded as they are emitted
+        let publisher = ShmPublisher::new(router.clone(), &path, ShmPublisherConfig::default());
+        tokio::spawn(publisher.run(cancellation_token.clone()));
+        while !matches!(recv(&receiver).await?.msg, IpcMessage::TraceEventSchema(_)) {}
+
+        for n in 0..100 {
+            event.build().try_insert_u64("n", n)?.emit()?;
+        }
+
+        let mut received = 0;
+        while received < 100 {
+            if let IpcMessage::TraceEvent(e) = recv(&receiver).await?.msg {
+                assert_eq!(
+                    e.fields.get("n"),
+                    Some(&zelos_trace_types::Value::UInt64(received))
+                );
+                received += 1;
+            }
+        }
+
+        cancellation_token.cancel();
+        receiver_task.await??;
+        assert!(!path.exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_crashed_producer_segments_are_ended() -> Result<()> {
+        let path = temp_path();
+        let cancellation_token = CancellationToken::new();
+        let (sender, receiver) = flume::unbounded();
+        let config = ShmReceiverConfig {
+            producer_timeout: Duration::from_millis(50),
+            ..Default::default()
+        };
+        let shm_receiver = ShmReceiver::new(&path, sender, config)?;
+        let receiver_task = tokio::spawn(shm_receiver.run(cancellation_token.clone()));
+
+        // Attach and start a segment by hand, then stop sending heartbeats as if the process had died
+        let mut producer = ShmRing::open(&path)?;
+        producer.attach_producer(std::process::id(), now_time_ns() as u64, 0)?;
+        let start = IpcMessageWithId {
+            segment_id: Uuid::now_v7(),
+            source_name: "crashed".into(),
+            msg: TraceSegmentStart {
+                time_ns: now_time_ns(),
+                source_name: "crashed".to_string(),
+                attributes: HashMap::new(),
+            }
+            .into(),
+        };
+        let mut buf = Vec::new();
+        codec::encode(&start, &mut buf)?;
+        assert!(producer.try_push(&buf)?);
+
+        assert!(matches!(
+            recv(&receiver).await?.msg,
+            IpcMessage::TraceSegmentStart(_)
+        ));
+        let end = recv(&receiver).await?;
+        assert_eq!(end.segment_id, start.segment_id);
+        assert!(matches!(end.msg, IpcMessage::TraceSegmentEnd(_)));
+
+        // The dead producer's slot is released for the next one
+        assert_eq!(producer.producer_pid(), None);
+
+        cancellation_token.cancel();
+        receiver_task.await??;
+        Ok(())
+    }
+}
@@ -0,0 +1,455 @@
+//! A single-producer single-consumer ring buffer in a memory-mapped file.
+//!
+//! The file starts with a header holding the read and write positions and the liveness information of both sides,
+//! followed by the data region. Records are a little-endian `u32` length in an 8-byte slot followed by the payload,
+//! padded to 8 bytes. A record never wraps around the end of the data region, the producer writes a wrap marker and
+//! continues at the start instead.
+
+use std::{
+    fs::OpenOptions,
+    path::Path,
+    ptr,
+    sync::atomic::{AtomicU32, AtomicU64, Ordering},
+};
+
+use anyhow::{anyhow, Result};
+use memmap2::MmapMut;
+
+const MAGIC: u64 = u64::from_le_bytes(*b"ZELOSSHM");
+/// Bumped whenever the header layout, the record framing or the record codec in [`crate::codec`] changes, so peers
+/// built against another format refuse the ring instead of misreading it
+const VERSION: u32 = 2;
+
+// Header layout, with the fields written by each side on their own cache line
+const MAGIC_OFFSET: usize = 0;
+const VERSION_OFFSET: usize = 8;
+const CAPACITY_OFFSET: usize = 16;
+const CLOSED_OFFSET: usize = 24;
+const PRODUCER_PID_OFFSET: usize = 64;
+const GENERATION_OFFSET: usize = 72;
+const HEARTBEAT_OFFSET: usize = 80;
+const HEAD_OFFSET: usize = 128;
+const TAIL_OFFSET: usize = 192;
+const HEADER_SIZE: usize = 256;
+
+const RECORD_HEADER_SIZE: usize = 8;
+const WRAP_MARKER: u32 = u32::MAX;
+
+/// Smallest data region we will create
+pub const MIN_CAPACITY: usize = 4096;
+
+fn padded(len: usize) -> usize {
+    len.next_multiple_of(RECORD_HEADER_SIZE)
+}
+
+pub struct ShmRing {
+    // Keeps the mapping alive for as long as we hold pointers into it
+    mmap: MmapMut,
+    base: *mut u8,
+    capacity: usize,
+}
+
+// SAFETY: the mapping is owned by the ring and only accessed through atomics or by the side that owns a region
+unsafe impl Send for ShmRing {}
+
+impl ShmRing {
+    /// Create a new, empty ring at `path` with a data region of at least `capacity` bytes, replacing any existing one.
+    /// A producer still attached to a replaced ring sees it as closed.
+    pub fn create(path: &Path, capacity: usize) -> Result<Self> {
+        let capacity = padded(capacity.max(MIN_CAPACITY));
+
+        if let Ok(old) = Self::open(path) {
+            old.close();
+        }
+        // Unlink rather than truncate, so anyone still mapping the old file keeps valid memory
+        if path.exists() {
+            std::fs::remove_file(path)
+                .map_err(|e| anyhow!("Failed to remove {}: {}", path.display(), e))?;
+        }
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .map_err(|e| anyhow!("Failed to create {}: {}", path.display(), e))?;
+        file.set_len((HEADER_SIZE + capacity) as u64)?;
+
+        // SAFETY: the file was just created by us, other processes only access it through this same layout
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        let base = mmap.as_mut_ptr();
+        let ring = Self {
+            mmap,
+            base,
+            capacity,
+        };
+
+        // Write the magic last, so a producer never sees a half-initialized header
+        ring.write_u32(VERSION_OFFSET, VERSION);
+        ring.write_u64(CAPACITY_OFFSET, capacity as u64);
+        ring.atomic_u64(MAGIC_OFFSET)
+            .store(MAGIC, Ordering::Release);
+
+        Ok(ring)
+    }
+
+    /// Open an existing ring at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|e| anyhow!("Failed to open {}: {}", path.display(), e))?;
+        if (file.metadata()?.len() as usize) < HEADER_SIZE {
+            return Err(anyhow!("{} is not a trace ring", path.display()));
+        }
+
+        // SAFETY: the header is validated below before anything else is read
+        let mut mmap = unsafe { MmapMut::map_mut(&file)? };
+        let base = mmap.as_mut_ptr();
+        let mut ring = Self {
+            mmap,
+            base,
+            capacity: 0,
+        };
+
+        if ring.atomic_u64(MAGIC_OFFSET).load(Ordering::Acquire) != MAGIC {
+            return Err(anyhow!("{} is not a trace ring", path.display()));
+        }
+        let version = ring.read_u32(VERSION_OFFSET);
+        if version != VERSION {
+            return Err(anyhow!(
+                "{} has version {}, expected {}",
+                path.display(),
+                version,
+                VERSION
+            ));
+        }
+        let capacity = ring.read_u64(CAPACITY_OFFSET) as usize;
+        if capacity < MIN_CAPACITY
+            || !capacity.is_multiple_of(RECORD_HEADER_SIZE)
+            || HEADER_SIZE + capacity > ring.mmap.len()
+        {
+            return Err(anyhow!("{} has an invalid capacity", path.display()));
+        }
+        ring.capacity = capacity;
+
+        Ok(ring)
+    }
+
+    /// Size of the data region in bytes
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Largest payload that fits in the ring
+    pub fn max_payload_len(&self) -> usize {
+        self.capacity / 2 - RECORD_HEADER_SIZE
+    }
+
+    fn atomic_u64(&self, offset: usize) -> &AtomicU64 {
+        // SAFETY: header offsets are 8-byte aligned within the page-aligned mapping
+        unsafe { AtomicU64::from_ptr(self.base.add(offset).cast()) }
+    }
+
+    fn atomic_u32(&self, offset: usize) -> &AtomicU32 {
+        // SAFETY: header offsets are 8-byte aligned within the page-aligned mapping
+        unsafe { AtomicU32::from_ptr(self.base.add(offset).cast()) }
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        let mut bytes = [0u8; 4];
+        // SAFETY: callers only pass offsets within the mapping
+        unsafe { ptr::copy_nonoverlapping(self.base.add(offset), bytes.as_mut_ptr(), 4) };
+        u32::from_le_bytes(bytes)
+    }
+
+    fn write_u32(&self, offset: usize, value: u32) {
+        // SAFETY: callers only pass offsets within the mapping
+        unsafe { ptr::copy_nonoverlapping(value.to_le_bytes().as_ptr(), self.base.add(offset), 4) };
+    }
+
+    fn read_u64(&self, offset: usize) -> u64 {
+        let mut bytes = [0u8; 8];
+        // SAFETY: callers only pass offsets within the mapping
+        unsafe { ptr::copy_nonoverlapping(self.base.add(offset), bytes.as_mut_ptr(), 8) };
+        u64::from_le_bytes(bytes)
+    }
+
+    fn write_u64(&self, offset: usize, value: u64) {
+        // SAFETY: callers only pass offsets within the mapping
+        unsafe { ptr::copy_nonoverlapping(value.to_le_bytes().as_ptr(), self.base.add(offset), 8) };
+    }
+
+    /// Check the positions read from the shared header, which the other side may have corrupted
+    fn check_positions(&self, head: u64, tail: u64) -> Result<()> {
+        if tail > head
+            || head - tail > self.capacity as u64
+            || !head.is_multiple_of(RECORD_HEADER_SIZE as u64)
+            || !tail.is_multiple_of(RECORD_HEADER_SIZE as u64)
+        {
+            return Err(anyhow!(
+                "Corrupt ring positions, head {} and tail {}",
+                head,
+                tail
+            ));
+        }
+        Ok(())
+    }
+
+    fn data_offset(&self, position: u64) -> usize {
+        HEADER_SIZE + (position % self.capacity as u64) as usize
+    }
+
+    /// Append a record, returning false if there is not enough free space. Must only be called by the producer.
+    pub fn try_push(&mut self, payload: &[u8]) -> Result<bool> {
+        if payload.len() > self.max_payload_len() {
+            return Err(anyhow!(
+                "Message of {} bytes exceeds the ring's limit of {} bytes",
+                payload.len(),
+                self.max_payload_len()
+            ));
+        }
+
+        let head = self.atomic_u64(HEAD_OFFSET).load(Ordering::Relaxed);
+        let tail = self.atomic_u64(TAIL_OFFSET).load(Ordering::Acquire);
+        self.check_positions(head, tail)?;
+
+        // Skip to the start of the data region if the record would not fit before the end
+        let record_len = RECORD_HEADER_SIZE + padded(payload.len());
+        let until_end = self.capacity - (head % self.capacity as u64) as usize;
+        let skip = if record_len > until_end { until_end } else { 0 };
+
+        let new_head = head + (skip + record_len) as u64;
+        if new_head - tail > self.capacity as u64 {
+            return Ok(false);
+        }
+
+        if skip > 0 {
+            self.write_u32(self.data_offset(head), WRAP_MARKER);
+        }
+        let start = self.data_offset(head + skip as u64);
+        self.write_u32(start, payload.len() as u32);
+        // SAFETY: the record fits before the end of the data region, and the consumer does not read past head
+        unsafe {
+            ptr::copy_nonoverlapping(
+                payload.as_ptr(),
+                self.base.add(start + RECORD_HEADER_SIZE),
+                payload.len(),
+            )
+        };
+
+        self.atomic_u64(HEAD_OFFSET)
+            .store(new_head, Ordering::Release);
+        Ok(true)
+    }
+
+    /// Take the next record into `buf`, returning false if the ring is empty. Must only be called by the consumer.
+    pub fn try_pop(&mut self, buf: &mut Vec<u8>) -> Result<bool> {
+        let mut tail = self.atomic_u64(TAIL_OFFSET).load(Ordering::Relaxed);
+        let head = self.atomic_u64(HEAD_OFFSET).load(Ordering::Acquire);
+
+        loop {
+            self.check_positions(head, tail)?;
+            if tail == head {
+                return Ok(false);
+            }
+
+            let start = self.data_offset(tail);
+            let len = self.read_u32(start);
+            if len == WRAP_MARKER {
+                tail += (self.capacity - (tail % self.capacity as u64) as usize) as u64;
+                continue;
+            }
+
+            // A record never wraps, so one running past the end of the data region or past head is corrupt
+            let len = len as usize;
+            let record_len = RECORD_HEADER_SIZE + padded(len);
+            if record_len as u64 > head - tail || start + record_len > HEADER_SIZE + self.capacity {
+                return Err(anyhow!("Corrupt record of {} bytes in ring", len));
+            }
+
+            buf.clear();
+            buf.reserve(len);
+            // SAFETY: the producer published this record before advancing head, and it lies within the data region
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    self.base.add(start + RECORD_HEADER_SIZE),
+                    buf.as_mut_ptr(),
+                    len,
+                );
+                buf.set_len(len);
+            }
+
+            self.atomic_u64(TAIL_OFFSET)
+                .store(tail + record_len as u64, Ordering::Release);
+            return Ok(true);
+        }
+    }
+
+    /// Whether there are records waiting to be read
+    pub fn is_empty(&self) -> bool {
+        self.atomic_u64(HEAD_OFFSET).load(Ordering::Acquire)
+            == self.atomic_u64(TAIL_OFFSET).load(Ordering::Acquire)
+    }
+
+    /// Mark the ring as abandoned by the consumer, telling the producer to look for a new one
+    pub fn close(&self) {
+        self.atomic_u32(CLOSED_OFFSET).store(1, Ordering::Release);
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.atomic_u32(CLOSED_OFFSET).load(Ordering::Acquire) != 0
+    }
+
+    /// Claim the producer side for `pid`, taking it over if the current producer's heartbeat is older than
+    /// `stale_before_ns`. Returns the new generation.
+    pub fn attach_producer(&self, pid: u32, now_ns: u64, stale_before_ns: u64) -> Result<u64> {
+        let producer_pid = self.atomic_u32(PRODUCER_PID_OFFSET);
+        let current = producer_pid.load(Ordering::Acquire);
+        if current != 0 && self.heartbeat_ns() >= stale_before_ns {
+            return Err(anyhow!("Ring is in use by process {}", current));
+        }
+        producer_pid
+            .compare_exchange(current, pid, Ordering::AcqRel, Ordering::Acquire)
+            .map_err(|other| anyhow!("Ring is in use by process {}", other))?;
+
+        self.heartbeat(now_ns);
+        Ok(self
+            .atomic_u64(GENERATION_OFFSET)
+            .fetch_add(1, Ordering::AcqRel)
+            + 1)
+    }
+
+    /// Release the producer side, so the consumer knows the producer went away cleanly
+    pub fn detach_producer(&self, pid: u32) {
+        let _ = self.atomic_u32(PRODUCER_PID_OFFSET).compare_exchange(
+            pid,
+            0,
+            Ordering::AcqRel,
+            Ordering::Acquire,
+        );
+    }
+
+    /// The process currently attached as producer, if any
+    pub fn producer_pid(&self) -> Option<u32> {
+        match self.atomic_u32(PRODUCER_PID_OFFSET).load(Ordering::Acquire) {
+            0 => None,
+            pid => Some(pid),
+        }
+    }
+
+    /// Incremented every time a producer attaches
+    pub fn generation(&self) -> u64 {
+        self.atomic_u64(GENERATION_OFFSET).load(Ordering::Acquire)
+    }
+
+    /// Record that the producer is alive at `now_ns`
+    pub fn heartbeat(&self, now_ns: u64) {
+        self.atomic_u64(HEARTBEAT_OFFSET)
+            .store(now_ns, Ordering::Release);
+    }
+
+    /// The last time the producer reported being alive
+    pub fn heartbeat_ns(&self) -> u64 {
+        self.atomic_u64(HEARTBEAT_OFFSET).load(Ordering::Acquire)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path() -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("zelos-ring-{}", uuid::Uuid::now_v7()))
+    }
+
+    #[test]
+    fn test_push_pop_wraps() -> Result<()> {
+        let path = temp_path();
+        let mut consumer = ShmRing::create(&path, MIN_CAPACITY)?;
+        let mut producer = ShmRing::open(&path)?;
+        assert_eq!(producer.capacity(), MIN_CAPACITY);
+
+        // Push odd-sized records through the ring several times over, so records wrap at different offsets
+        let mut buf = Vec::new();
+        for i in 0..1000u32 {
+            let payload = vec![i as u8; (i % 300) as usize + 1];
+            assert!(producer.try_push(&payload)?);
+            assert!(consumer.try_pop(&mut buf)?);
+            assert_eq!(buf, payload);
+        }
+        assert!(!consumer.try_pop(&mut buf)?);
+
+        // Fill the ring until it refuses, then drain it
+        let payload = [7u8; 100];
+        let mut pushed = 0;
+        while producer.try_push(&payload)? {
+            pushed += 1;
+        }
+        assert!(pushed >= MIN_CAPACITY / (RECORD_HEADER_SIZE + 104) - 1);
+        while consumer.try_pop(&mut buf)? {
+            pushed -= 1;
+        }
+        assert_eq!(pushed, 0);
+        assert!(consumer.is_empty());
+
+        assert!(producer.try_push(&vec![0u8; MIN_CAPACITY]).is_err());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupt_header_is_rejected() -> Result<()> {
+        let path = temp_path();
+        let mut consumer = ShmRing::create(&path, MIN_CAPACITY)?;
+        let mut producer = ShmRing::open(&path)?;
+        let mut buf = Vec::new();
+
+        // Tail ahead of head
+        consumer.write_u64(TAIL_OFFSET, 64);
+        assert!(consumer.try_pop(&mut buf).is_err());
+        assert!(producer.try_push(b"payload").is_err());
+
+        // Head further ahead than the ring holds
+        consumer.write_u64(TAIL_OFFSET, 0);
+        consumer.write_u64(HEAD_OFFSET, 2 * MIN_CAPACITY as u64);
+        assert!(consumer.try_pop(&mut buf).is_err());
+
+        // A record whose length runs past the end of the data region
+        let last = (MIN_CAPACITY - RECORD_HEADER_SIZE) as u64;
+        consumer.write_u64(TAIL_OFFSET, last);
+        consumer.write_u64(HEAD_OFFSET, last + MIN_CAPACITY as u64 / 2);
+        consumer.write_u32(consumer.data_offset(last), 64);
+        assert!(consumer.try_pop(&mut buf).is_err());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_producer_attach_and_close() -> Result<()> {
+        let path = temp_path();
+        let consumer = ShmRing::create(&path, MIN_CAPACITY)?;
+        let producer = ShmRing::open(&path)?;
+
+        assert_eq!(producer.attach_producer(1, 100, 0)?, 1);
+        assert_eq!(consumer.producer_pid(), Some(1));
+
+        // A second producer is refused while the first is alive, but may take over once it is stale
+        assert!(producer.attach_producer(2, 150, 50).is_err());
+        assert_eq!(producer.attach_producer(2, 250, 200)?, 2);
+        assert_eq!(consumer.producer_pid(), Some(2));
+
+        producer.detach_producer(2);
+        assert_eq!(consumer.producer_pid(), None);
+
+        // Replacing the ring closes the old one
+        let _replacement = ShmRing::create(&path, MIN_CAPACITY)?;
+        assert!(producer.is_closed());
+
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+}
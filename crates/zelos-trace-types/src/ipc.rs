@@ -1,8 +1,17 @@
 // Trace-related messages that are sent between processes (or inside the same process).
 
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+    sync::Arc,
+};
 
 use derive_more::From;
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
 use uuid::Uuid;
 
 use crate::{DataType, Value};
@@ -11,6 +20,8 @@ use crate::{DataType, Value};
 pub struct TraceSegmentStart {
     pub time_ns: i64,
     pub source_name: String,
+    /// Where the segment was produced, e.g. host, pid and version, and any tags added by the application
+    pub attributes: HashMap<String, String>,
 }
 
 #[derive(Debug, Clone)]
@@ -18,19 +29,182 @@ pub struct TraceSegmentEnd {
     pub time_ns: i64,
 }
 
+/// How the values of a field relate to each other over time
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+pub enum FieldKind {
+    /// A measurement at the time of the event, such as a temperature
+    #[default]
+    Gauge,
+    /// A monotonically increasing count, such as packets received, which may wrap around or reset to zero
+    Counter,
+    /// The change since the previous event, such as packets received since the last sample
+    Delta,
+    /// One of a set of states, usually named by a value table
+    State,
+}
+
+impl FieldKind {
+    pub fn is_gauge(&self) -> bool {
+        *self == FieldKind::Gauge
+    }
+}
+
+/// How a dashboard should plot a field by default
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+pub enum DisplayHint {
+    /// A line between samples, for continuous measurements
+    Line,
+    /// A step held until the next sample, for states and setpoints
+    Step,
+    /// Unconnected points, for sparse or noisy samples
+    Scatter,
+    /// A bar per sample, for counts and deltas
+    Bar,
+    /// The latest value as text
+    Text,
+}
+
+/// Describes a field to the people looking at it, so dashboards can configure axes and tooltips without outside
+/// knowledge of the source. Every part is optional.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+pub struct FieldDisplay {
+    /// What the field measures
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub description: Option<String>,
+    /// The lowest value expected, e.g. the bottom of a sensor's range
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub min: Option<f64>,
+    /// The highest value expected
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub max: Option<f64>,
+    /// Number of decimal places to show
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub precision: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub hint: Option<DisplayHint>,
+}
+
+impl FieldDisplay {
+    pub fn with_description(mut self, description: &str) -> Self {
+        self.description = Some(description.to_string());
+        self
+    }
+
+    pub fn with_range(mut self, min: f64, max: f64) -> Self {
+        self.min = Some(min);
+        self.max = Some(max);
+        self
+    }
+
+    pub fn with_precision(mut self, precision: u32) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    pub fn with_hint(mut self, hint: DisplayHint) -> Self {
+        self.hint = Some(hint);
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+// The range is compared by the bits of its bounds, so displays, and the fields holding them, are `Eq` and `Hash`
+impl PartialEq for FieldDisplay {
+    fn eq(&self, other: &Self) -> bool {
+        self.description == other.description
+            && self.min.map(f64::to_bits) == other.min.map(f64::to_bits)
+            && self.max.map(f64::to_bits) == other.max.map(f64::to_bits)
+            && self.precision == other.precision
+            && self.hint == other.hint
+    }
+}
+
+impl Eq for FieldDisplay {}
+
+impl Hash for FieldDisplay {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.description.hash(state);
+        self.min.map(f64::to_bits).hash(state);
+        self.max.map(f64::to_bits).hash(state);
+        self.precision.hash(state);
+        self.hint.hash(state);
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct TraceEventFieldMetadata {
     pub name: String,
     pub data_type: DataType,
     pub unit: Option<String>,
+    pub kind: FieldKind,
+    pub display: FieldDisplay,
 }
 
 #[derive(Debug, Clone)]
 pub struct TraceEventSchema {
     pub name: String,
+    /// Starts at 0 and increases each time the event's fields change within a segment. Events are read with the latest
+    /// version sent before them.
+    pub version: u32,
     pub fields: Vec<TraceEventFieldMetadata>,
 }
 
+impl TraceEventSchema {
+    /// Check that `next` can replace this schema as a later version. Fields may be added and removed, but a field that
+    /// is kept must keep its data type, since its earlier values could not be read as the new type.
+    pub fn check_evolution(&self, next: &TraceEventSchema) -> anyhow::Result<()> {
+        if next.version <= self.version {
+            return Err(anyhow::anyhow!(
+                "Version {} of event {} does not follow version {}",
+                next.version,
+                self.name,
+                self.version
+            ));
+        }
+        for field in &next.fields {
+            let Some(previous) = self.fields.iter().find(|f| f.name == field.name) else {
+                continue;
+            };
+            if previous.data_type != field.data_type {
+                return Err(anyhow::anyhow!(
+                    "Field {} of event {} cannot change type from {:?} in version {} to {:?}, add a field with a new \
+                     name instead",
+                    field.name,
+                    self.name,
+                    previous.data_type,
+                    self.version,
+                    field.data_type
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check `next` against every earlier version of the event rather than only the latest one, so a field that was
+    /// removed cannot come back with another data type.
+    pub fn check_evolution_from<'a>(
+        versions: impl IntoIterator<Item = &'a TraceEventSchema>,
+        next: &TraceEventSchema,
+    ) -> anyhow::Result<()> {
+        versions
+            .into_iter()
+            .try_for_each(|version| version.check_evolution(next))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TraceEventFieldNamedValues {
     pub event_name: String,
@@ -38,10 +212,259 @@ pub struct TraceEventFieldNamedValues {
     pub values: HashMap<Value, String>,
 }
 
+/// The field values of a trace event
+#[derive(Debug, Clone)]
+pub enum TraceEventFields {
+    /// Values keyed by field name
+    Named(HashMap<String, Value>),
+    /// Values by index into the fields of the event's schema, `None` for fields without a value. The schema is shared
+    /// with every event emitted from it, so no field names are allocated per event.
+    Indexed {
+        schema: Arc<[TraceEventFieldMetadata]>,
+        values: Vec<Option<Value>>,
+    },
+}
+
+impl TraceEventFields {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        match self {
+            TraceEventFields::Named(fields) => fields.get(name),
+            TraceEventFields::Indexed { schema, values } => {
+                let index = schema.iter().position(|field| field.name == name)?;
+                values.get(index)?.as_ref()
+            }
+        }
+    }
+
+    /// Number of fields with a value
+    pub fn len(&self) -> usize {
+        match self {
+            TraceEventFields::Named(fields) => fields.len(),
+            TraceEventFields::Indexed { values, .. } => values.iter().flatten().count(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterate over the fields with a value, without allocating their names
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Value)> {
+        let (named, indexed) = match self {
+            TraceEventFields::Named(fields) => (Some(fields), None),
+            TraceEventFields::Indexed { schema, values } => (None, Some((schema, values))),
+        };
+        let named = named
+            .into_iter()
+            .flatten()
+            .map(|(name, value)| (name.as_str(), value));
+        let indexed = indexed.into_iter().flat_map(|(schema, values)| {
+            schema
+                .iter()
+                .zip(values)
+                .filter_map(|(field, value)| Some((field.name.as_str(), value.as_ref()?)))
+        });
+        named.chain(indexed)
+    }
+
+    /// Convert to the name-keyed form
+    pub fn into_named(self) -> HashMap<String, Value> {
+        self.into_iter().collect()
+    }
+}
+
+impl Default for TraceEventFields {
+    fn default() -> Self {
+        TraceEventFields::Named(HashMap::new())
+    }
+}
+
+impl From<HashMap<String, Value>> for TraceEventFields {
+    fn from(fields: HashMap<String, Value>) -> Self {
+        TraceEventFields::Named(fields)
+    }
+}
+
+impl FromIterator<(String, Value)> for TraceEventFields {
+    fn from_iter<T: IntoIterator<Item = (String, Value)>>(iter: T) -> Self {
+        TraceEventFields::Named(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for TraceEventFields {
+    type Item = (String, Value);
+    type IntoIter = Box<dyn Iterator<Item = (String, Value)> + Send>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            TraceEventFields::Named(fields) => Box::new(fields.into_iter()),
+            TraceEventFields::Indexed { schema, values } => Box::new(
+                values
+                    .into_iter()
+                    .enumerate()
+                    .filter_map(move |(i, value)| Some((schema.get(i)?.name.clone(), value?))),
+            ),
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a TraceEventFields {
+    type Item = (&'a str, &'a Value);
+    type IntoIter = Box<dyn Iterator<Item = (&'a str, &'a Value)> + 'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Box::new(self.iter())
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TraceEvent {
     pub time_ns: i64,
+    pub name: Arc<str>,
+    pub fields: TraceEventFields,
+}
+
+/// An action a source can be asked to run, with the fields of its arguments and of its result
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceActionSchema {
     pub name: String,
+    pub parameters: Vec<TraceEventFieldMetadata>,
+    pub returns: Vec<TraceEventFieldMetadata>,
+}
+
+/// The outcome of a call to an action, sent by the source that ran it
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceActionResult {
+    pub call_id: Uuid,
+    pub action_name: String,
+    /// The returned values, or why the call failed
+    pub result: Result<HashMap<String, Value>, String>,
+}
+
+/// Start of a session grouping the segments of e.g. a test run. Sent with the session id as the segment id and the
+/// session name as the source name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSessionStart {
+    pub session_id: Uuid,
+    pub time_ns: i64,
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    /// Segments that start while the session is open with attributes including all of these join it, none do if it is
+    /// empty
+    pub join_attributes: HashMap<String, String>,
+}
+
+impl TraceSessionStart {
+    /// Whether a segment with `attributes` joins this session by its rule
+    pub fn admits(&self, attributes: &HashMap<String, String>) -> bool {
+        !self.join_attributes.is_empty()
+            && self
+                .join_attributes
+                .iter()
+                .all(|(key, value)| attributes.get(key) == Some(value))
+    }
+}
+
+/// End of a session, sent like its [`TraceSessionStart`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSessionEnd {
+    pub session_id: Uuid,
+    pub time_ns: i64,
+}
+
+/// Adds the segment this message is sent with to a session
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceSessionJoin {
+    pub session_id: Uuid,
+}
+
+/// A bookmark on the timeline such as "brake test started", covering `start_time_ns..=end_time_ns`, which are equal
+/// for a single point in time
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceAnnotation {
+    pub annotation_id: Uuid,
+    pub start_time_ns: i64,
+    pub end_time_ns: i64,
+    pub text: String,
+    pub tags: Vec<String>,
+    /// Who added the annotation, if known
+    pub author: Option<String>,
+}
+
+/// Start of a named interval of a source, such as a state of a state machine, which lasts until the
+/// [`TraceIntervalEnd`] with the same `interval_id`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceIntervalBegin {
+    pub interval_id: Uuid,
+    /// The interval this one is nested in, if any
+    pub parent_id: Option<Uuid>,
+    pub time_ns: i64,
+    pub name: String,
+    pub fields: HashMap<String, Value>,
+}
+
+/// End of an interval, with the name of its [`TraceIntervalBegin`] so it can be filtered the same way
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceIntervalEnd {
+    pub interval_id: Uuid,
+    pub time_ns: i64,
+    pub name: String,
+    pub fields: HashMap<String, Value>,
+}
+
+/// Severity of a [`TraceLog`], ordered from least to most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "ts-rs", derive(TS))]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+}
+
+impl LogLevel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "trace",
+            LogLevel::Debug => "debug",
+            LogLevel::Info => "info",
+            LogLevel::Warn => "warn",
+            LogLevel::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for LogLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "trace" => Ok(LogLevel::Trace),
+            "debug" => Ok(LogLevel::Debug),
+            "info" => Ok(LogLevel::Info),
+            "warn" | "warning" => Ok(LogLevel::Warn),
+            "error" => Ok(LogLevel::Error),
+            _ => Err(anyhow::anyhow!("Unknown log level '{}'", s)),
+        }
+    }
+}
+
+/// A structured log line of a source, routed and filtered like its events
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceLog {
+    pub time_ns: i64,
+    pub level: LogLevel,
+    /// The module or component that logged, e.g. `motor::can`
+    pub target: String,
+    pub message: String,
     pub fields: HashMap<String, Value>,
 }
 
@@ -52,14 +475,238 @@ pub enum IpcMessage {
     TraceEventSchema(TraceEventSchema),
     TraceEventFieldNamedValues(TraceEventFieldNamedValues),
     TraceEvent(TraceEvent),
+    TraceActionSchema(TraceActionSchema),
+    TraceActionResult(TraceActionResult),
+    TraceSessionStart(TraceSessionStart),
+    TraceSessionEnd(TraceSessionEnd),
+    TraceSessionJoin(TraceSessionJoin),
+    Annotation(TraceAnnotation),
+    Log(TraceLog),
+    TraceIntervalBegin(TraceIntervalBegin),
+    TraceIntervalEnd(TraceIntervalEnd),
+}
+
+impl IpcMessage {
+    /// Whether this message describes a session rather than a segment, in which case its segment id is the session id
+    pub fn is_session(&self) -> bool {
+        matches!(
+            self,
+            IpcMessage::TraceSessionStart(_) | IpcMessage::TraceSessionEnd(_)
+        )
+    }
+
+    /// The timestamp carried by this message, if it has one
+    pub fn time_ns(&self) -> Option<i64> {
+        match self {
+            IpcMessage::TraceSegmentStart(m) => Some(m.time_ns),
+            IpcMessage::TraceSegmentEnd(m) => Some(m.time_ns),
+            IpcMessage::TraceEvent(m) => Some(m.time_ns),
+            IpcMessage::TraceSessionStart(m) => Some(m.time_ns),
+            IpcMessage::TraceSessionEnd(m) => Some(m.time_ns),
+            IpcMessage::Annotation(m) => Some(m.start_time_ns),
+            IpcMessage::Log(m) => Some(m.time_ns),
+            IpcMessage::TraceIntervalBegin(m) => Some(m.time_ns),
+            IpcMessage::TraceIntervalEnd(m) => Some(m.time_ns),
+            IpcMessage::TraceEventSchema(_)
+            | IpcMessage::TraceEventFieldNamedValues(_)
+            | IpcMessage::TraceActionSchema(_)
+            | IpcMessage::TraceActionResult(_)
+            | IpcMessage::TraceSessionJoin(_) => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct IpcMessageWithId {
     pub segment_id: Uuid,
-    pub source_name: String,
+    /// Shared by every message of a source, so it is not copied per message
+    pub source_name: Arc<str>,
     pub msg: IpcMessage,
 }
 
+/// A command sent from a trace server back to publishers, e.g. to turn up diagnostics on a live system
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceControl {
+    /// The source to apply the command to, or every source if `None`
+    pub source_name: Option<String>,
+    pub command: TraceControlCommand,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceControlCommand {
+    /// Enable or disable emitting an event, or every event of the source except its parameters' events if
+    /// `event_name` is `None`
+    SetEventEnabled {
+        event_name: Option<String>,
+        enabled: bool,
+    },
+    /// Emit an event at most `max_rate_hz` times per second, or without a limit if `None`. Like
+    /// [`TraceControlCommand::SetEventEnabled`], a `None` event name leaves the parameters' events alone.
+    SetSampleRate {
+        event_name: Option<String>,
+        max_rate_hz: Option<f64>,
+    },
+    /// Emit the source's event schemas, value tables and action schemas again
+    ResendMetadata,
+    /// Run one of the source's actions, which answers with a [`TraceActionResult`]
+    CallAction(TraceActionCall),
+    /// Set one of the source's parameters. Values with the wrong type or out of range are rejected, and if `call_id`
+    /// is set the source answers with a [`TraceActionResult`] holding the accepted value or why it was rejected.
+    SetParameter {
+        name: String,
+        value: Value,
+        call_id: Option<Uuid>,
+        /// The segment of the source instance to set it on, or every instance with the source name if `None`
+        segment_id: Option<Uuid>,
+    },
+}
+
+/// A request to run an action, answered by a [`TraceActionResult`] with the same `call_id`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TraceActionCall {
+    pub call_id: Uuid,
+    pub action_name: String,
+    pub arguments: HashMap<String, Value>,
+    /// The segment of the source instance that runs the call, or every instance with the source name if `None`
+    pub segment_id: Option<Uuid>,
+}
+
+impl TraceControl {
+    /// Whether this command applies to the source named `source_name`
+    pub fn targets(&self, source_name: &str) -> bool {
+        self.source_name
+            .as_deref()
+            .is_none_or(|name| name == source_name)
+    }
+}
+
 pub type Sender = flume::Sender<IpcMessageWithId>;
 pub type Receiver = flume::Receiver<IpcMessageWithId>;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn schema() -> Arc<[TraceEventFieldMetadata]> {
+        ["rpm", "state", "fault"]
+            .into_iter()
+            .map(|name| TraceEventFieldMetadata {
+                name: name.to_string(),
+                data_type: DataType::Int32,
+                unit: None,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay::default(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_named_fields() {
+        let fields: TraceEventFields = [
+            ("rpm".to_string(), Value::Int32(1200)),
+            ("state".to_string(), Value::Int32(2)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(fields.get("rpm"), Some(&Value::Int32(1200)));
+        assert_eq!(fields.get("fault"), None);
+        assert_eq!(fields.len(), 2);
+        assert!(!fields.is_empty());
+        assert!(TraceEventFields::default().is_empty());
+
+        let mut iterated: Vec<_> = (&fields).into_iter().collect();
+        iterated.sort_by_key(|(name, _)| *name);
+        assert_eq!(
+            iterated,
+            [("rpm", &Value::Int32(1200)), ("state", &Value::Int32(2))]
+        );
+        assert_eq!(
+            fields.into_named(),
+            HashMap::from([
+                ("rpm".to_string(), Value::Int32(1200)),
+                ("state".to_string(), Value::Int32(2)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_indexed_fields() {
+        let fields = TraceEventFields::Indexed {
+            schema: schema(),
+            values: vec![Some(Value::Int32(1200)), None, Some(Value::Int32(-3))],
+        };
+
+        // Fields without a value are skipped everywhere
+        assert_eq!(fields.get("rpm"), Some(&Value::Int32(1200)));
+        assert_eq!(fields.get("state"), None);
+        assert_eq!(fields.get("fault"), Some(&Value::Int32(-3)));
+        assert_eq!(fields.get("missing"), None);
+        assert_eq!(fields.len(), 2);
+
+        // In schema order
+        assert_eq!(
+            fields.iter().collect::<Vec<_>>(),
+            [("rpm", &Value::Int32(1200)), ("fault", &Value::Int32(-3))]
+        );
+        assert_eq!(
+            fields.clone().into_iter().collect::<Vec<_>>(),
+            [
+                ("rpm".to_string(), Value::Int32(1200)),
+                ("fault".to_string(), Value::Int32(-3)),
+            ]
+        );
+
+        let empty = TraceEventFields::Indexed {
+            schema: schema(),
+            values: vec![None, None, None],
+        };
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().count(), 0);
+    }
+
+    #[test]
+    fn test_field_display_eq() {
+        let display = FieldDisplay::default()
+            .with_range(0.0, 100.0)
+            .with_hint(DisplayHint::Line);
+        let mut fields: Vec<_> = schema().to_vec();
+        fields[0].display = display.clone();
+
+        // Fields with displays can be deduplicated and used as keys
+        let unique: std::collections::HashSet<_> =
+            fields.iter().chain(fields.iter()).cloned().collect();
+        assert_eq!(unique.len(), 3);
+        assert!(unique.contains(&fields[0]));
+
+        // NaN bounds are still equal to themselves
+        let nan = FieldDisplay::default().with_range(f64::NAN, 1.0);
+        assert_eq!(nan, nan.clone());
+        assert_ne!(nan, display);
+        assert!(FieldDisplay::default().is_empty());
+        assert!(!display.is_empty());
+    }
+
+    #[test]
+    fn test_check_evolution_from() {
+        let version = |version: u32, fields: &[&TraceEventFieldMetadata]| TraceEventSchema {
+            name: "motor".to_string(),
+            version,
+            fields: fields.iter().map(|field| (*field).clone()).collect(),
+        };
+        let fields = schema();
+        let mut retyped = fields[2].clone();
+        retyped.data_type = DataType::String;
+
+        // Dropping "fault" is allowed, bringing it back as a string is not
+        let v0 = version(0, &[&fields[0], &fields[2]]);
+        let v1 = version(1, &[&fields[0]]);
+        let v2 = version(2, &[&fields[0], &retyped]);
+        assert!(v1.check_evolution(&v2).is_ok());
+        assert!(TraceEventSchema::check_evolution_from([&v0, &v1], &v2).is_err());
+
+        // Every earlier version must come before the next one
+        let restored = version(2, &[&fields[0], &fields[2]]);
+        assert!(TraceEventSchema::check_evolution_from([&v0, &v1], &restored).is_ok());
+        assert!(TraceEventSchema::check_evolution_from([&v0, &restored], &v1).is_err());
+    }
+}
@@ -0,0 +1,675 @@
+// JSON representations of trace messages and subscribe commands, for clients that can't speak gRPC (e.g. browsers).
+
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "ts-rs")]
+use ts_rs::TS;
+use uuid::Uuid;
+
+use crate::{
+    ipc::{
+        FieldDisplay, FieldKind, IpcMessage, IpcMessageWithId, LogLevel, TraceActionResult,
+        TraceActionSchema, TraceAnnotation, TraceEvent, TraceEventFieldMetadata,
+        TraceEventFieldNamedValues, TraceEventSchema, TraceIntervalBegin, TraceIntervalEnd,
+        TraceLog, TraceSegmentEnd, TraceSegmentStart, TraceSessionEnd, TraceSessionJoin,
+        TraceSessionStart,
+    },
+    DataType, Value,
+};
+
+/// Convert a value to JSON, mapping values JSON can't represent (non-finite floats) to null
+fn value_to_json(value: Value) -> serde_json::Value {
+    Value::try_to_serde_json(value).unwrap_or(serde_json::Value::Null)
+}
+
+/// Convert values to JSON, for fields without a schema
+fn fields_to_json(fields: HashMap<String, Value>) -> HashMap<String, serde_json::Value> {
+    fields
+        .into_iter()
+        .map(|(name, value)| (name, value_to_json(value)))
+        .collect()
+}
+
+/// Convert JSON values to values of the closest type, for fields without a schema
+fn fields_from_json(fields: HashMap<String, serde_json::Value>) -> HashMap<String, Value> {
+    fields
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value_from_json(value)?)))
+        .collect()
+}
+
+/// Convert a JSON value without a schema to the value of the closest type. Nulls have no value, and arrays and objects
+/// are kept as JSON text.
+fn value_from_json(value: serde_json::Value) -> Option<Value> {
+    match value {
+        serde_json::Value::Null => None,
+        serde_json::Value::Bool(v) => Some(Value::Boolean(v)),
+        serde_json::Value::Number(v) => v
+            .as_i64()
+            .map(Value::Int64)
+            .or_else(|| v.as_u64().map(Value::UInt64))
+            .or_else(|| v.as_f64().map(Value::Float64)),
+        serde_json::Value::String(v) => Some(Value::String(v)),
+        value => Some(Value::String(value.to_string())),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub struct JsonFieldMetadata {
+    pub name: String,
+    pub data_type: DataType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub unit: Option<String>,
+    #[serde(default)]
+    pub kind: FieldKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub display: Option<FieldDisplay>,
+}
+
+/// A single entry of a value table. Tables are sent as a list since JSON object keys must be strings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub struct JsonNamedValue {
+    pub value: serde_json::Value,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub enum JsonIpcMessage {
+    TraceSegmentStart {
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        attributes: HashMap<String, String>,
+    },
+    TraceSegmentEnd {
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+    },
+    TraceEventSchema {
+        name: String,
+        #[serde(default)]
+        version: u32,
+        fields: Vec<JsonFieldMetadata>,
+    },
+    TraceEventFieldNamedValues {
+        event_name: String,
+        field_name: String,
+        values: Vec<JsonNamedValue>,
+    },
+    TraceEvent {
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        name: String,
+        fields: HashMap<String, serde_json::Value>,
+    },
+    TraceActionSchema {
+        name: String,
+        parameters: Vec<JsonFieldMetadata>,
+        returns: Vec<JsonFieldMetadata>,
+    },
+    TraceActionResult {
+        call_id: Uuid,
+        action_name: String,
+        values: HashMap<String, serde_json::Value>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        error: Option<String>,
+    },
+    TraceSessionStart {
+        session_id: Uuid,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        name: String,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        attributes: HashMap<String, String>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        join_attributes: HashMap<String, String>,
+    },
+    TraceSessionEnd {
+        session_id: Uuid,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+    },
+    TraceSessionJoin {
+        session_id: Uuid,
+    },
+    Annotation {
+        annotation_id: Uuid,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        start_time_ns: i64,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        end_time_ns: i64,
+        text: String,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tags: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        author: Option<String>,
+    },
+    TraceLog {
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        level: LogLevel,
+        target: String,
+        message: String,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        fields: HashMap<String, serde_json::Value>,
+    },
+    TraceIntervalBegin {
+        interval_id: Uuid,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        parent_id: Option<Uuid>,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        name: String,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        fields: HashMap<String, serde_json::Value>,
+    },
+    TraceIntervalEnd {
+        interval_id: Uuid,
+        #[cfg_attr(feature = "ts-rs", ts(type = "number"))]
+        time_ns: i64,
+        name: String,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        fields: HashMap<String, serde_json::Value>,
+    },
+}
+
+/// A trace message as sent over JSON transports, e.g. `{"segment_id": "...", "source_name": "motor", "type":
+/// "trace_event", "time_ns": 0, "name": "status", "fields": {"rpm": 1200}}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub struct JsonIpcMessageWithId {
+    pub segment_id: Uuid,
+    pub source_name: String,
+    #[serde(flatten)]
+    pub msg: JsonIpcMessage,
+}
+
+impl From<TraceEventFieldMetadata> for JsonFieldMetadata {
+    fn from(field: TraceEventFieldMetadata) -> Self {
+        Self {
+            name: field.name,
+            data_type: field.data_type,
+            unit: field.unit,
+            kind: field.kind,
+            display: Some(field.display).filter(|display| !display.is_empty()),
+        }
+    }
+}
+
+impl From<JsonFieldMetadata> for TraceEventFieldMetadata {
+    fn from(field: JsonFieldMetadata) -> Self {
+        Self {
+            name: field.name,
+            data_type: field.data_type,
+            unit: field.unit,
+            kind: field.kind,
+            display: field.display.unwrap_or_default(),
+        }
+    }
+}
+
+impl From<IpcMessage> for JsonIpcMessage {
+    fn from(msg: IpcMessage) -> Self {
+        match msg {
+            IpcMessage::TraceSegmentStart(TraceSegmentStart {
+                time_ns,
+                attributes,
+                ..
+            }) => JsonIpcMessage::TraceSegmentStart {
+                time_ns,
+                attributes,
+            },
+            IpcMessage::TraceSegmentEnd(TraceSegmentEnd { time_ns }) => {
+                JsonIpcMessage::TraceSegmentEnd { time_ns }
+            }
+            IpcMessage::TraceEventSchema(TraceEventSchema {
+                name,
+                version,
+                fields,
+            }) => JsonIpcMessage::TraceEventSchema {
+                name,
+                version,
+                fields: fields.into_iter().map(Into::into).collect(),
+            },
+            IpcMessage::TraceEventFieldNamedValues(TraceEventFieldNamedValues {
+                event_name,
+                field_name,
+                values,
+            }) => {
+                let mut values: Vec<_> = values
+                    .into_iter()
+                    .map(|(value, name)| JsonNamedValue {
+                        value: value_to_json(value),
+                        name,
+                    })
+                    .collect();
+                // Keep the output stable, the table itself is unordered
+                values.sort_by(|a, b| a.name.cmp(&b.name));
+                JsonIpcMessage::TraceEventFieldNamedValues {
+                    event_name,
+                    field_name,
+                    values,
+                }
+            }
+            IpcMessage::TraceEvent(TraceEvent {
+                time_ns,
+                name,
+                fields,
+            }) => JsonIpcMessage::TraceEvent {
+                time_ns,
+                name: name.to_string(),
+                fields: fields
+                    .into_iter()
+                    .map(|(name, value)| (name, value_to_json(value)))
+                    .collect(),
+            },
+            IpcMessage::TraceActionSchema(TraceActionSchema {
+                name,
+                parameters,
+                returns,
+            }) => JsonIpcMessage::TraceActionSchema {
+                name,
+                parameters: parameters.into_iter().map(Into::into).collect(),
+                returns: returns.into_iter().map(Into::into).collect(),
+            },
+            IpcMessage::TraceActionResult(TraceActionResult {
+                call_id,
+                action_name,
+                result,
+            }) => {
+                let (values, error) = match result {
+                    Ok(values) => (values, None),
+                    Err(error) => (HashMap::new(), Some(error)),
+                };
+                JsonIpcMessage::TraceActionResult {
+                    call_id,
+                    action_name,
+                    values: values
+                        .into_iter()
+                        .map(|(name, value)| (name, value_to_json(value)))
+                        .collect(),
+                    error,
+                }
+            }
+            IpcMessage::TraceSessionStart(TraceSessionStart {
+                session_id,
+                time_ns,
+                name,
+                attributes,
+                join_attributes,
+            }) => JsonIpcMessage::TraceSessionStart {
+                session_id,
+                time_ns,
+                name,
+                attributes,
+                join_attributes,
+            },
+            IpcMessage::TraceSessionEnd(TraceSessionEnd {
+                session_id,
+                time_ns,
+            }) => JsonIpcMessage::TraceSessionEnd {
+                session_id,
+                time_ns,
+            },
+            IpcMessage::TraceSessionJoin(TraceSessionJoin { session_id }) => {
+                JsonIpcMessage::TraceSessionJoin { session_id }
+            }
+            IpcMessage::Annotation(TraceAnnotation {
+                annotation_id,
+                start_time_ns,
+                end_time_ns,
+                text,
+                tags,
+                author,
+            }) => JsonIpcMessage::Annotation {
+                annotation_id,
+                start_time_ns,
+                end_time_ns,
+                text,
+                tags,
+                author,
+            },
+            IpcMessage::Log(TraceLog {
+                time_ns,
+                level,
+                target,
+                message,
+                fields,
+            }) => JsonIpcMessage::TraceLog {
+                time_ns,
+                level,
+                target,
+                message,
+                fields: fields_to_json(fields),
+            },
+            IpcMessage::TraceIntervalBegin(TraceIntervalBegin {
+                interval_id,
+                parent_id,
+                time_ns,
+                name,
+                fields,
+            }) => JsonIpcMessage::TraceIntervalBegin {
+                interval_id,
+                parent_id,
+                time_ns,
+                name,
+                fields: fields_to_json(fields),
+            },
+            IpcMessage::TraceIntervalEnd(TraceIntervalEnd {
+                interval_id,
+                time_ns,
+                name,
+                fields,
+            }) => JsonIpcMessage::TraceIntervalEnd {
+                interval_id,
+                time_ns,
+                name,
+                fields: fields_to_json(fields),
+            },
+        }
+    }
+}
+
+impl From<IpcMessageWithId> for JsonIpcMessageWithId {
+    fn from(msg: IpcMessageWithId) -> Self {
+        Self {
+            segment_id: msg.segment_id,
+            source_name: msg.source_name.to_string(),
+            msg: msg.msg.into(),
+        }
+    }
+}
+
+impl JsonIpcMessageWithId {
+    /// Convert to an IPC message, typing event fields and value tables with `field_type`, which looks up the data
+    /// type of a field from the event and field names of the message's segment
+    pub fn try_into_ipc(
+        self,
+        field_type: impl Fn(&str, &str) -> Option<DataType>,
+    ) -> Result<IpcMessageWithId> {
+        let field_type = |event_name: &str, field_name: &str| {
+            field_type(event_name, field_name).ok_or_else(|| {
+                anyhow!(
+                    "Unknown field {}.{} in segment {}",
+                    event_name,
+                    field_name,
+                    self.segment_id
+                )
+            })
+        };
+
+        let msg = match self.msg {
+            JsonIpcMessage::TraceSegmentStart {
+                time_ns,
+                attributes,
+            } => TraceSegmentStart {
+                time_ns,
+                source_name: self.source_name.clone(),
+                attributes,
+            }
+            .into(),
+            JsonIpcMessage::TraceSegmentEnd { time_ns } => TraceSegmentEnd { time_ns }.into(),
+            JsonIpcMessage::TraceEventSchema {
+                name,
+                version,
+                fields,
+            } => TraceEventSchema {
+                name,
+                version,
+                fields: fields.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+            JsonIpcMessage::TraceEventFieldNamedValues {
+                event_name,
+                field_name,
+                values,
+            } => {
+                let data_type = field_type(&event_name, &field_name)?;
+                let values = values
+                    .into_iter()
+                    .map(|JsonNamedValue { value, name }| {
+                        Ok((Value::try_from_serde_json_as_type(value, &data_type)?, name))
+                    })
+                    .collect::<Result<_>>()?;
+                TraceEventFieldNamedValues {
+                    event_name,
+                    field_name,
+                    values,
+                }
+                .into()
+            }
+            JsonIpcMessage::TraceEvent {
+                time_ns,
+                name,
+                fields,
+            } => {
+                let fields = fields
+                    .into_iter()
+                    .map(|(field_name, value)| {
+                        let data_type = field_type(&name, &field_name)?;
+                        let value =
+                            Value::try_from_serde_json_as_type(value, &data_type).map_err(|e| {
+                                anyhow!("Invalid value for {}.{}: {}", name, field_name, e)
+                            })?;
+                        Ok((field_name, value))
+                    })
+                    .collect::<Result<_>>()?;
+                TraceEvent {
+                    time_ns,
+                    name: name.into(),
+                    fields,
+                }
+                .into()
+            }
+            JsonIpcMessage::TraceActionSchema {
+                name,
+                parameters,
+                returns,
+            } => TraceActionSchema {
+                name,
+                parameters: parameters.into_iter().map(Into::into).collect(),
+                returns: returns.into_iter().map(Into::into).collect(),
+            }
+            .into(),
+            JsonIpcMessage::TraceActionResult { action_name, .. } => {
+                // The returned values can't be typed without the action's schema
+                return Err(anyhow!(
+                    "Results of action {} can't be sent as JSON",
+                    action_name
+                ));
+            }
+            JsonIpcMessage::TraceSessionStart {
+                session_id,
+                time_ns,
+                name,
+                attributes,
+                join_attributes,
+            } => TraceSessionStart {
+                session_id,
+                time_ns,
+                name,
+                attributes,
+                join_attributes,
+            }
+            .into(),
+            JsonIpcMessage::TraceSessionEnd {
+                session_id,
+                time_ns,
+            } => TraceSessionEnd {
+                session_id,
+                time_ns,
+            }
+            .into(),
+            JsonIpcMessage::TraceSessionJoin { session_id } => {
+                TraceSessionJoin { session_id }.into()
+            }
+            JsonIpcMessage::Annotation {
+                annotation_id,
+                start_time_ns,
+                end_time_ns,
+                text,
+                tags,
+                author,
+            } => TraceAnnotation {
+                annotation_id,
+                start_time_ns,
+                end_time_ns,
+                text,
+                tags,
+                author,
+            }
+            .into(),
+            JsonIpcMessage::TraceLog {
+                time_ns,
+                level,
+                target,
+                message,
+                fields,
+            } => TraceLog {
+                time_ns,
+                level,
+                target,
+                message,
+                // Log fields have no schema, so their types come from the JSON values
+                fields: fields_from_json(fields),
+            }
+            .into(),
+            JsonIpcMessage::TraceIntervalBegin {
+                interval_id,
+                parent_id,
+                time_ns,
+                name,
+                fields,
+            } => TraceIntervalBegin {
+                interval_id,
+                parent_id,
+                time_ns,
+                name,
+                fields: fields_from_json(fields),
+            }
+            .into(),
+            JsonIpcMessage::TraceIntervalEnd {
+                interval_id,
+                time_ns,
+                name,
+                fields,
+            } => TraceIntervalEnd {
+                interval_id,
+                time_ns,
+                name,
+                fields: fields_from_json(fields),
+            }
+            .into(),
+        };
+
+        Ok(IpcMessageWithId {
+            segment_id: self.segment_id,
+            source_name: self.source_name.into(),
+            msg,
+        })
+    }
+}
+
+/// A command sent by a JSON subscriber, mirroring `SubscribeCommand` / `UnsubscribeCommand` in subscribe.proto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub enum JsonSubscribeRequest {
+    Subscribe {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        filter: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional, type = "number"))]
+        start_time: Option<i64>,
+    },
+    Unsubscribe {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        filter: Option<String>,
+    },
+}
+
+impl JsonSubscribeRequest {
+    pub fn kind(&self) -> JsonSubscribeCommandKind {
+        match self {
+            JsonSubscribeRequest::Subscribe { .. } => JsonSubscribeCommandKind::Subscribe,
+            JsonSubscribeRequest::Unsubscribe { .. } => JsonSubscribeCommandKind::Unsubscribe,
+        }
+    }
+
+    pub fn filter(&self) -> Option<&String> {
+        match self {
+            JsonSubscribeRequest::Subscribe { filter, .. }
+            | JsonSubscribeRequest::Unsubscribe { filter } => filter.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub enum JsonSubscribeCommandKind {
+    Unspecified,
+    Subscribe,
+    Unsubscribe,
+}
+
+/// A message sent to a JSON subscriber, mirroring `SubscribeResponse` in subscribe.proto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub enum JsonSubscribeResponse {
+    TraceMessageBatch {
+        messages: Vec<JsonIpcMessageWithId>,
+    },
+    CommandAck {
+        kind: JsonSubscribeCommandKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        filter: Option<String>,
+    },
+    CommandError {
+        kind: JsonSubscribeCommandKind,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        #[cfg_attr(feature = "ts-rs", ts(optional))]
+        filter: Option<String>,
+        message: String,
+    },
+}
+
+impl JsonSubscribeResponse {
+    pub fn from_ipc(messages: Vec<IpcMessageWithId>) -> Self {
+        JsonSubscribeResponse::TraceMessageBatch {
+            messages: messages.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+/// A message that could not be ingested, identified by its position in the request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub struct JsonIngestError {
+    pub index: usize,
+    pub message: String,
+}
+
+/// The result of posting messages to an ingest endpoint. Messages are validated independently, so valid messages are
+/// forwarded even when others in the same request are rejected.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "ts-rs", derive(TS), ts(export))]
+pub struct JsonIngestResponse {
+    pub accepted: usize,
+    pub errors: Vec<JsonIngestError>,
+}
@@ -1,4 +1,5 @@
 mod data_type;
+pub mod json;
 mod latest;
 mod signal;
 mod signal_key;
@@ -10,7 +10,7 @@ use serde_json::Number;
 use ts_rs::TS;
 use uuid::Uuid;
 
-use crate::{DataType, SignalKey};
+use crate::{ipc::FieldDisplay, DataType, SignalKey};
 
 #[cfg(feature = "duckdb")]
 fn parse_uuid_from_db(value: duckdb::types::Value) -> Result<Uuid> {
@@ -22,6 +22,16 @@ fn parse_uuid_from_db(value: duckdb::types::Value) -> Result<Uuid> {
     }
 }
 
+/// The value of `column`, or `None` if it is null or the row does not have it
+#[cfg(feature = "duckdb")]
+fn optional_column<T: duckdb::types::FromSql>(row: &Row, column: &str) -> Result<Option<T>> {
+    match row.get::<&str, Option<T>>(column) {
+        Ok(value) => Ok(value),
+        Err(duckdb::Error::InvalidColumnName(_)) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[cfg_attr(feature = "ts-rs", derive(TS))]
 pub struct Signal {
@@ -34,6 +44,10 @@ pub struct Signal {
     pub unit: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value_table: Option<HashMap<Number, String>>,
+    /// How the signal is displayed, from its field's schema, if the schema describes it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "ts-rs", ts(optional))]
+    pub display: Option<FieldDisplay>,
 }
 
 impl Signal {
@@ -62,6 +76,8 @@ impl Signal {
         )
     }
 
+    /// Read a signal from a row of the signals table. The display is read from an optional `display` column holding the
+    /// JSON of a [`FieldDisplay`].
     #[cfg(feature = "duckdb")]
     pub fn from_row(row: &Row, value_table: Option<&HashMap<Number, String>>) -> Result<Signal> {
         Ok(Signal {
@@ -75,6 +91,10 @@ impl Signal {
                 .map_err(|e| e.into())
                 .and_then(|s| DataType::from_duckdb_type(&s))?,
             value_table: value_table.cloned(),
+            display: optional_column::<String>(row, "display")?
+                .map(|display| serde_json::from_str::<FieldDisplay>(&display))
+                .transpose()?
+                .filter(|display| !display.is_empty()),
         })
     }
 }
@@ -0,0 +1,10 @@
+//! Receives trace events from devices publishing the [`zelos_trace_datagram`] format over UDP.
+//!
+//! A [`UdpReceiver`] answers schema registrations, decodes events against the registered schemas and forwards them
+//! into a router's [`Sender`](zelos_trace_types::ipc::Sender). Lost and reordered datagrams are counted per segment
+//! in [`UdpStats`].
+#![deny(clippy::expect_used, clippy::unwrap_used)]
+
+mod receiver;
+
+pub use receiver::{UdpReceiver, UdpReceiverConfig, UdpSegmentStats, UdpStats, MAX_STATS_SEGMENTS};
@@ -0,0 +1,649 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{anyhow, Result};
+use parking_lot::Mutex;
+use tokio::net::UdpSocket;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use zelos_trace::time::now_time_ns;
+use zelos_trace_datagram::{
+    decode, encode_register_ack, FieldType, FieldValue, Packet, Values, HEADER_LEN, RECEIVE_TIME,
+};
+use zelos_trace_types::{
+    ipc::{
+        FieldDisplay, FieldKind, IpcMessage, IpcMessageWithId, Sender, TraceEvent,
+        TraceEventFieldMetadata, TraceEventSchema, TraceSegmentEnd, TraceSegmentStart,
+    },
+    DataType, Value,
+};
+
+const DEFAULT_SEGMENT_TIMEOUT_S: u64 = 60;
+const SWEEP_INTERVAL: Duration = Duration::from_secs(1);
+/// Large enough for any UDP payload
+const RECV_BUF_LEN: usize = 65536;
+/// Most segments counters are kept for, so datagrams with made-up segment ids can't grow them without bound
+pub const MAX_STATS_SEGMENTS: usize = 4096;
+
+#[derive(Debug, Clone)]
+pub struct UdpReceiverConfig {
+    /// A segment that has not sent anything for this long is considered gone and is ended
+    pub segment_timeout: Duration,
+}
+
+impl Default for UdpReceiverConfig {
+    fn default() -> Self {
+        Self {
+            segment_timeout: Duration::from_secs(DEFAULT_SEGMENT_TIMEOUT_S),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UdpSegmentStats {
+    /// Datagrams received for the segment
+    pub received: u64,
+    /// Gaps in the sequence numbers, reduced again when a missing datagram arrives late
+    pub lost: u64,
+    /// Datagrams that arrived after one with a later sequence number
+    pub out_of_order: u64,
+    /// Events dropped because their schema or segment was never registered
+    pub unknown_schema: u64,
+    /// Datagrams dropped because they did not match their schema, or conflicted with an earlier registration
+    pub invalid: u64,
+}
+
+/// Counters of a segment along with when they last changed
+#[derive(Debug, Clone, Copy)]
+struct SegmentCounters {
+    stats: UdpSegmentStats,
+    updated: Instant,
+}
+
+/// Counters of the segments seen recently, shared with the running receiver. Counters are dropped once they have not
+/// changed for the receiver's segment timeout, so those of ended segments can still be read for a while, and the
+/// least recently updated are dropped first beyond [`MAX_STATS_SEGMENTS`].
+#[derive(Debug, Clone, Default)]
+pub struct UdpStats {
+    segments: Arc<Mutex<HashMap<Uuid, SegmentCounters>>>,
+    malformed: Arc<AtomicU64>,
+}
+
+impl UdpStats {
+    pub fn segment(&self, segment_id: &Uuid) -> Option<UdpSegmentStats> {
+        self.segments.lock().get(segment_id).map(|c| c.stats)
+    }
+
+    pub fn segments(&self) -> HashMap<Uuid, UdpSegmentStats> {
+        self.segments
+            .lock()
+            .iter()
+            .map(|(id, c)| (*id, c.stats))
+            .collect()
+    }
+
+    /// Datagrams that could not be decoded at all, and so can't be attributed to a segment
+    pub fn malformed(&self) -> u64 {
+        self.malformed.load(Ordering::Relaxed)
+    }
+
+    fn update(&self, segment_id: Uuid, f: impl FnOnce(&mut UdpSegmentStats)) {
+        let mut segments = self.segments.lock();
+        if segments.len() >= MAX_STATS_SEGMENTS && !segments.contains_key(&segment_id) {
+            let oldest = segments
+                .iter()
+                .min_by_key(|(_, c)| c.updated)
+                .map(|(id, _)| *id);
+            if let Some(oldest) = oldest {
+                segments.remove(&oldest);
+            }
+        }
+        let counters = segments.entry(segment_id).or_insert(SegmentCounters {
+            stats: UdpSegmentStats::default(),
+            updated: Instant::now(),
+        });
+        counters.updated = Instant::now();
+        f(&mut counters.stats)
+    }
+
+    /// Drop the counters that have not changed for `idle`
+    fn evict_idle(&self, idle: Duration) {
+        self.segments
+            .lock()
+            .retain(|_, c| c.updated.elapsed() <= idle);
+    }
+}
+
+struct Segment {
+    source_name: String,
+    schemas: HashMap<u16, TraceEventSchema>,
+    next_sequence: u32,
+    last_seen: Instant,
+}
+
+impl Segment {
+    /// Advance past `sequence`, counting skipped datagrams as lost and older ones as out of order
+    fn track_sequence(&mut self, sequence: u32, stats: &mut UdpSegmentStats) {
+        let ahead = sequence.wrapping_sub(self.next_sequence);
+        if ahead < u32::MAX / 2 {
+            stats.lost += ahead as u64;
+            self.next_sequence = sequence.wrapping_add(1);
+        } else {
+            stats.out_of_order += 1;
+            stats.lost = stats.lost.saturating_sub(1);
+        }
+    }
+}
+
+fn data_type(field_type: FieldType) -> DataType {
+    match field_type {
+        FieldType::Int8 => DataType::Int8,
+        FieldType::Int16 => DataType::Int16,
+        FieldType::Int32 => DataType::Int32,
+        FieldType::Int64 => DataType::Int64,
+        FieldType::UInt8 => DataType::UInt8,
+        FieldType::UInt16 => DataType::UInt16,
+        FieldType::UInt32 => DataType::UInt32,
+        FieldType::UInt64 => DataType::UInt64,
+        FieldType::Float32 => DataType::Float32,
+        FieldType::Float64 => DataType::Float64,
+        FieldType::TimestampNs => DataType::TimestampNs,
+        FieldType::Binary => DataType::Binary,
+        FieldType::String => DataType::String,
+        FieldType::Boolean => DataType::Boolean,
+    }
+}
+
+fn field_type(data_type: &DataType) -> FieldType {
+    match data_type {
+        DataType::Int8 => FieldType::Int8,
+        DataType::Int16 => FieldType::Int16,
+        DataType::Int32 => FieldType::Int32,
+        DataType::Int64 => FieldType::Int64,
+        DataType::UInt8 => FieldType::UInt8,
+        DataType::UInt16 => FieldType::UInt16,
+        DataType::UInt32 => FieldType::UInt32,
+        DataType::UInt64 => FieldType::UInt64,
+        DataType::Float32 => FieldType::Float32,
+        DataType::Float64 => FieldType::Float64,
+        DataType::TimestampNs => FieldType::TimestampNs,
+        DataType::Binary => FieldType::Binary,
+        DataType::String => FieldType::String,
+        DataType::Boolean => FieldType::Boolean,
+    }
+}
+
+fn value(value: FieldValue) -> Value {
+    match value {
+        FieldValue::Int8(v) => Value::Int8(v),
+        FieldValue::Int16(v) => Value::Int16(v),
+        FieldValue::Int32(v) => Value::Int32(v),
+        FieldValue::Int64(v) => Value::Int64(v),
+        FieldValue::UInt8(v) => Value::UInt8(v),
+        FieldValue::UInt16(v) => Value::UInt16(v),
+        FieldValue::UInt32(v) => Value::UInt32(v),
+        FieldValue::UInt64(v) => Value::UInt64(v),
+        FieldValue::Float32(v) => Value::Float32(v),
+        FieldValue::Float64(v) => Value::Float64(v),
+        FieldValue::TimestampNs(v) => Value::TimestampNs(v),
+        FieldValue::Binary(v) => Value::Binary(v.to_vec()),
+        FieldValue::String(v) => Value::String(v.to_string()),
+        FieldValue::Boolean(v) => Value::Boolean(v),
+    }
+}
+
+/// Decode the packed values of an event in schema order
+fn event_fields(schema: &TraceEventSchema, mut values: Values) -> Result<HashMap<String, Value>> {
+    let fields = schema
+        .fields
+        .iter()
+        .map(|field| {
+            let v = values.next_value(field_type(&field.data_type))?;
+            Ok((field.name.clone(), value(v)))
+        })
+        .collect::<Result<_>>()?;
+    if !values.is_empty() {
+        return Err(anyhow!("Event has more values than its schema has fields"));
+    }
+    Ok(fields)
+}
+
+/// Receives datagrams on a UDP socket and forwards them to a router
+pub struct UdpReceiver {
+    socket: UdpSocket,
+    sender: Sender,
+    config: UdpReceiverConfig,
+    stats: UdpStats,
+    segments: HashMap<Uuid, Segment>,
+}
+
+impl UdpReceiver {
+    pub async fn bind(addr: SocketAddr, sender: Sender, config: UdpReceiverConfig) -> Result<Self> {
+        let socket = UdpSocket::bind(addr)
+            .await
+            .map_err(|e| anyhow!("Failed to listen on {}: {}", addr, e))?;
+        Ok(Self {
+            socket,
+            sender,
+            config,
+            stats: UdpStats::default(),
+            segments: HashMap::new(),
+        })
+    }
+
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.socket.local_addr()?)
+    }
+
+    pub fn stats(&self) -> UdpStats {
+        self.stats.clone()
+    }
+
+    async fn send(&self, segment_id: Uuid, source_name: &str, msg: IpcMessage) -> Result<()> {
+        self.sender
+            .send_async(IpcMessageWithId {
+                segment_id,
+                source_name: source_name.into(),
+                msg,
+            })
+            .await
+            .map_err(|_| anyhow!("Router closed"))
+    }
+
+    /// End and forget the segments matching `predicate`
+    async fn end_segments(&mut self, predicate: impl Fn(&Segment) -> bool) -> Result<()> {
+        let ended: Vec<_> = self
+            .segments
+            .iter()
+            .filter(|(_, segment)| predicate(segment))
+            .map(|(id, _)| *id)
+            .collect();
+        let time_ns = now_time_ns();
+        for segment_id in ended {
+            if let Some(segment) = self.segments.remove(&segment_id) {
+                tracing::debug!("Ending segment {} of {}", segment_id, segment.source_name);
+                self.send(
+                    segment_id,
+                    &segment.source_name,
+                    TraceSegmentEnd { time_ns }.into(),
+                )
+                .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Register a schema, starting the segment on its first registration. Returns whether the registration was
+    /// accepted and should be acked.
+    async fn register(
+        &mut self,
+        segment_id: Uuid,
+        sequence: u32,
+        schema_id: u16,
+        source_name: &str,
+        schema: TraceEventSchema,
+    ) -> Result<bool> {
+        if !self.segments.contains_key(&segment_id) {
+            tracing::debug!("Starting segment {} of {}", segment_id, source_name);
+            self.send(
+                segment_id,
+                source_name,
+                TraceSegmentStart {
+                    time_ns: now_time_ns(),
+                    source_name: source_name.to_string(),
+                    attributes: HashMap::new(),
+                }
+                .into(),
+            )
+            .await?;
+            self.segments.insert(
+                segment_id,
+                Segment {
+                    source_name: source_name.to_string(),
+                    schemas: HashMap::new(),
+                    // Datagrams sent before the first one we see can't be counted
+                    next_sequence: sequence,
+                    last_seen: Instant::now(),
+                },
+            );
+        }
+
+        let Some(segment) = self.segments.get_mut(&segment_id) else {
+            return Ok(false);
+        };
+        match segment.schemas.get(&schema_id) {
+            // A retransmission because our ack was lost
+            Some(existing) if existing.name == schema.name && existing.fields == schema.fields => {
+                Ok(true)
+            }
+            Some(existing) => {
+                tracing::warn!(
+                    "Segment {} registered schema {} as {}, ignoring it as {}",
+                    segment_id,
+                    schema_id,
+                    existing.name,
+                    schema.name
+                );
+                Ok(false)
+            }
+            None => {
+                segment.schemas.insert(schema_id, schema.clone());
+                let source_name = segment.source_name.clone();
+                self.send(segment_id, &source_name, schema.into()).await?;
+                Ok(true)
+            }
+        }
+    }
+
+    async fn handle(&mut self, datagram: &[u8], peer: SocketAddr) -> Result<()> {
+        let (header, packet) = match decode(datagram) {
+            Ok(decoded) => decoded,
+            Err(e) => {
+                tracing::debug!("Dropping datagram from {}: {}", peer, e);
+                self.stats.malformed.fetch_add(1, Ordering::Relaxed);
+                return Ok(());
+            }
+        };
+        let segment_id = Uuid::from_bytes(header.segment_id);
+
+        if let Packet::Register {
+            schema_id,
+            source_name,
+            event_name,
+            fields,
+        } = &packet
+        {
+            let fields = fields
+                .clone()
+                .map(|field| {
+                    field.map(|field| TraceEventFieldMetadata {
+                        name: field.name.to_string(),
+                        data_type: data_type(field.field_type),
+                        unit: field.unit.map(str::to_string),
+                        // Datagrams don't declare field kinds or displays
+                        kind: FieldKind::Gauge,
+                        display: FieldDisplay::default(),
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>();
+            let schema = match fields {
+                Ok(fields) => TraceEventSchema {
+                    name: event_name.to_string(),
+                    version: 0,
+                    fields,
+                },
+                Err(e) => {
+                    tracing::debug!("Dropping registration from {}: {}", peer, e);
+                    self.stats.update(segment_id, |stats| stats.invalid += 1);
+                    return Ok(());
+                }
+            };
+            if self
+                .register(segment_id, header.sequence, *schema_id, source_name, schema)
+                .await?
+            {
+                let mut buf = [0; HEADER_LEN + 2];
+                let ack = encode_register_ack(&mut buf, &header.segment_id, *schema_id)?;
+                if let Err(e) = self.socket.send_to(ack, peer).await {
+                    tracing::debug!("Failed to ack registration to {}: {}", peer, e);
+                }
+            } else {
+                self.stats.update(segment_id, |stats| stats.invalid += 1);
+            }
+        }
+
+        let Some(segment) = self.segments.get_mut(&segment_id) else {
+            self.stats.update(segment_id, |stats| {
+                stats.received += 1;
+                stats.unknown_schema += 1;
+            });
+            return Ok(());
+        };
+        segment.last_seen = Instant::now();
+        self.stats.update(segment_id, |stats| {
+            stats.received += 1;
+            segment.track_sequence(header.sequence, stats);
+        });
+        let source_name = segment.source_name.clone();
+
+        match packet {
+            Packet::Register { .. } | Packet::RegisterAck { .. } => {}
+            Packet::Event {
+                schema_id,
+                time_ns,
+                values,
+            } => {
+                let Some(schema) = segment.schemas.get(&schema_id) else {
+                    self.stats
+                        .update(segment_id, |stats| stats.unknown_schema += 1);
+                    return Ok(());
+                };
+                let fields = match event_fields(schema, values) {
+                    Ok(fields) => fields,
+                    Err(e) => {
+                        tracing::debug!("Dropping {} event from {}: {}", schema.name, peer, e);
+                        self.stats.update(segment_id, |stats| stats.invalid += 1);
+                        return Ok(());
+                    }
+                };
+                let event = TraceEvent {
+                    time_ns: match time_ns {
+                        RECEIVE_TIME => now_time_ns(),
+                        time_ns => time_ns,
+                    },
+                    name: schema.name.as_str().into(),
+                    fields: fields.into(),
+                };
+                self.send(segment_id, &source_name, event.into()).await?;
+            }
+            Packet::SegmentEnd { time_ns } => {
+                self.segments.remove(&segment_id);
+                let time_ns = match time_ns {
+                    RECEIVE_TIME => now_time_ns(),
+                    time_ns => time_ns,
+                };
+                self.send(segment_id, &source_name, TraceSegmentEnd { time_ns }.into())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forward datagrams until cancelled, then end every open segment
+    pub async fn run(mut self, cancellation_token: CancellationToken) -> Result<()> {
+        tracing::info!("Receiving trace datagrams on {}", self.local_addr()?);
+
+        let mut buf = vec![0; RECV_BUF_LEN];
+        let mut sweep = tokio::time::interval(SWEEP_INTERVAL.min(self.config.segment_timeout));
+        loop {
+            tokio::select! {
+                received = self.socket.recv_from(&mut buf) => {
+                    let (len, peer) = received?;
+                    self.handle(&buf[..len], peer).await?;
+                }
+                _ = sweep.tick() => {
+                    let timeout = self.config.segment_timeout;
+                    self.end_segments(|segment| segment.last_seen.elapsed() > timeout).await?;
+                    self.stats.evict_idle(timeout);
+                }
+                _ = cancellation_token.cancelled() => break,
+            }
+        }
+        self.end_segments(|_| true).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use zelos_trace_datagram::{Encoder, FieldDef, MAX_DATAGRAM_LEN};
+
+    use super::*;
+
+    async fn recv(receiver: &flume::Receiver<IpcMessageWithId>) -> Result<IpcMessageWithId> {
+        Ok(tokio::time::timeout(Duration::from_secs(5), receiver.recv_async()).await??)
+    }
+
+    #[tokio::test]
+    async fn test_receive_datagrams() -> Result<()> {
+        let (sender, receiver) = flume::unbounded();
+        let udp =
+            UdpReceiver::bind("127.0.0.1:0".parse()?, sender, UdpReceiverConfig::default()).await?;
+        let addr = udp.local_addr()?;
+        let stats = udp.stats();
+        let cancellation_token = CancellationToken::new();
+        let task = tokio::spawn(udp.run(cancellation_token.clone()));
+
+        let device = UdpSocket::bind("127.0.0.1:0").await?;
+        device.connect(addr).await?;
+        let segment_id = Uuid::now_v7();
+        let mut encoder = Encoder::new(*segment_id.as_bytes());
+        let mut buf = [0; MAX_DATAGRAM_LEN];
+
+        // Events before registration are dropped
+        let values = [FieldValue::UInt16(1200), FieldValue::Boolean(true)];
+        device
+            .send(encoder.event(&mut buf, 1, 10, &values)?)
+            .await?;
+
+        let fields = [
+            FieldDef {
+                name: "rpm",
+                field_type: FieldType::UInt16,
+                unit: Some("rpm"),
+            },
+            FieldDef {
+                name: "ok",
+                field_type: FieldType::Boolean,
+                unit: None,
+            },
+        ];
+        device
+            .send(encoder.register(&mut buf, 1, "rig", "motor", &fields)?)
+            .await?;
+        let mut ack = [0; MAX_DATAGRAM_LEN];
+        let len = tokio::time::timeout(Duration::from_secs(5), device.recv(&mut ack)).await??;
+        let (header, packet) = decode(&ack[..len])?;
+        assert_eq!(header.segment_id, *segment_id.as_bytes());
+        assert!(matches!(packet, Packet::RegisterAck { schema_id: 1 }));
+
+        let msg = recv(&receiver).await?;
+        assert!(matches!(msg.msg, IpcMessage::TraceSegmentStart(_)));
+        assert_eq!(&*msg.source_name, "rig");
+        let IpcMessage::TraceEventSchema(schema) = recv(&receiver).await?.msg else {
+            panic!("Expected the schema");
+        };
+        assert_eq!(schema.fields[0].data_type, DataType::UInt16);
+        assert_eq!(schema.fields[0].unit.as_deref(), Some("rpm"));
+
+        // Send the second event after the third, so it arrives out of order
+        device
+            .send(encoder.event(&mut buf, 1, 20, &values)?)
+            .await?;
+        let late = encoder.event(&mut buf, 1, 30, &values)?.to_vec();
+        device
+            .send(encoder.event(&mut buf, 1, RECEIVE_TIME, &values)?)
+            .await?;
+        device.send(&late).await?;
+        // Unknown schema, and too few values for the schema
+        device
+            .send(encoder.event(&mut buf, 2, 50, &values)?)
+            .await?;
+        device
+            .send(encoder.event(&mut buf, 1, 60, &values[..1])?)
+            .await?;
+        device.send(encoder.end(&mut buf, 70)?).await?;
+
+        let mut times = Vec::new();
+        loop {
+            let msg = recv(&receiver).await?;
+            assert_eq!(msg.segment_id, segment_id);
+            match msg.msg {
+                IpcMessage::TraceEvent(event) => {
+                    assert_eq!(event.fields.get("rpm"), Some(&Value::UInt16(1200)));
+                    assert_eq!(event.fields.get("ok"), Some(&Value::Boolean(true)));
+                    times.push(event.time_ns);
+                }
+                IpcMessage::TraceSegmentEnd(end) => {
+                    assert_eq!(end.time_ns, 70);
+                    break;
+                }
+                msg => panic!("Unexpected message {:?}", msg),
+            }
+        }
+        assert_eq!(times.len(), 3);
+        assert_eq!(times[0], 20);
+        // Stamped on receipt
+        assert!(times[1] > 70);
+        assert_eq!(times[2], 30);
+
+        assert_eq!(
+            stats.segment(&segment_id),
+            Some(UdpSegmentStats {
+                received: 8,
+                lost: 0,
+                out_of_order: 1,
+                unknown_schema: 2,
+                invalid: 1,
+            })
+        );
+
+        device.send(&[0; 8]).await?;
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while stats.malformed() == 0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[test]
+    fn test_stats_eviction() {
+        let stats = UdpStats::default();
+        let first = Uuid::now_v7();
+        stats.update(first, |stats| stats.received += 1);
+        std::thread::sleep(Duration::from_millis(1));
+        for _ in 1..MAX_STATS_SEGMENTS {
+            stats.update(Uuid::now_v7(), |stats| stats.received += 1);
+        }
+        assert_eq!(stats.segments().len(), MAX_STATS_SEGMENTS);
+
+        // The least recently updated segment makes room for a new one
+        let newest = Uuid::now_v7();
+        stats.update(newest, |stats| stats.received += 1);
+        assert_eq!(stats.segments().len(), MAX_STATS_SEGMENTS);
+        assert_eq!(stats.segment(&first), None);
+        assert!(stats.segment(&newest).is_some());
+
+        stats.evict_idle(Duration::from_secs(60));
+        assert_eq!(stats.segments().len(), MAX_STATS_SEGMENTS);
+        stats.evict_idle(Duration::ZERO);
+        assert!(stats.segments().is_empty());
+    }
+
+    #[test]
+    fn test_track_sequence() {
+        let mut segment = Segment {
+            source_name: "rig".to_string(),
+            schemas: HashMap::new(),
+            next_sequence: u32::MAX - 1,
+            last_seen: Instant::now(),
+        };
+        let mut stats = UdpSegmentStats::default();
+        for sequence in [u32::MAX - 1, 1, u32::MAX, 0, 2] {
+            segment.track_sequence(sequence, &mut stats);
+        }
+        assert_eq!(stats.lost, 0);
+        assert_eq!(stats.out_of_order, 2);
+    }
+}
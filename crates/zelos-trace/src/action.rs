@@ -0,0 +1,142 @@
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+
+use parking_lot::Mutex;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+use zelos_trace_types::{
+    ipc::{TraceActionCall, TraceActionResult, TraceActionSchema, TraceEventFieldMetadata},
+    Value,
+};
+
+/// Runs an action with its arguments, returning the values of its result fields
+pub type ActionHandler =
+    dyn Fn(HashMap<String, Value>) -> anyhow::Result<HashMap<String, Value>> + Send + Sync;
+
+/// Why a call to an action did not return a result
+#[derive(Debug, Clone, PartialEq)]
+pub enum ActionCallError {
+    /// Nothing is following the router's controls, so no source can receive the call
+    NoSources,
+    /// No live segment of the source has the action or parameter
+    NotFound,
+    /// Several live segments of the source have the action or parameter, so the call has to name one of them
+    Ambiguous(Vec<Uuid>),
+    /// No source answered within the timeout
+    TimedOut(Duration),
+    /// The source rejected the call or the action failed
+    Failed(String),
+}
+
+impl fmt::Display for ActionCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionCallError::NoSources => write!(f, "No sources are connected to receive the call"),
+            ActionCallError::NotFound => write!(
+                f,
+                "No live segment of the source has the action or parameter"
+            ),
+            ActionCallError::Ambiguous(segment_ids) => write!(
+                f,
+                "{} live segments of the source have the action or parameter, pick one of {:?}",
+                segment_ids.len(),
+                segment_ids
+            ),
+            ActionCallError::TimedOut(timeout) => {
+                write!(f, "No result within {:?}", timeout)
+            }
+            ActionCallError::Failed(message) => write!(f, "Action failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ActionCallError {}
+
+/// An action registered on a [`crate::TraceSource`]
+pub struct TraceSourceAction {
+    pub schema: TraceActionSchema,
+    handler: Box<ActionHandler>,
+}
+
+impl fmt::Debug for TraceSourceAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TraceSourceAction")
+            .field("schema", &self.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Check that `values` has exactly the fields in `schema`, with matching types
+fn check_fields(
+    kind: &str,
+    schema: &[TraceEventFieldMetadata],
+    values: &HashMap<String, Value>,
+) -> Result<(), String> {
+    for field in schema {
+        match values.get(&field.name) {
+            Some(value) if value.data_type() != field.data_type => {
+                return Err(format!(
+                    "Type mismatch for {} '{}': expected {:?}, found {:?}",
+                    kind,
+                    field.name,
+                    field.data_type,
+                    value.data_type()
+                ));
+            }
+            Some(_) => {}
+            None => return Err(format!("Missing {} '{}'", kind, field.name)),
+        }
+    }
+    match values
+        .keys()
+        .find(|name| !schema.iter().any(|field| &field.name == *name))
+    {
+        Some(name) => Err(format!("Unknown {} '{}'", kind, name)),
+        None => Ok(()),
+    }
+}
+
+impl TraceSourceAction {
+    pub fn new(schema: TraceActionSchema, handler: Box<ActionHandler>) -> Self {
+        Self { schema, handler }
+    }
+
+    /// Run the action for `call`, checking its arguments and returned values against the schema
+    pub fn call(&self, call: TraceActionCall) -> TraceActionResult {
+        let result = check_fields("argument", &self.schema.parameters, &call.arguments)
+            .and_then(|()| (self.handler)(call.arguments).map_err(|e| e.to_string()))
+            .and_then(|values| {
+                check_fields("returned value", &self.schema.returns, &values)?;
+                Ok(values)
+            });
+        TraceActionResult {
+            call_id: call.call_id,
+            action_name: call.action_name,
+            result,
+        }
+    }
+}
+
+/// Calls sent by a router that are waiting for their result
+#[derive(Clone, Default)]
+pub(crate) struct PendingCalls {
+    calls: Arc<Mutex<HashMap<Uuid, oneshot::Sender<TraceActionResult>>>>,
+}
+
+impl PendingCalls {
+    pub(crate) fn register(&self, call_id: Uuid) -> oneshot::Receiver<TraceActionResult> {
+        let (sender, receiver) = oneshot::channel();
+        self.calls.lock().insert(call_id, sender);
+        receiver
+    }
+
+    pub(crate) fn cancel(&self, call_id: &Uuid) {
+        self.calls.lock().remove(call_id);
+    }
+
+    /// Hand a result to its caller, if it is still waiting. Only the first result of a call is used.
+    pub(crate) fn resolve(&self, result: &TraceActionResult) {
+        if let Some(sender) = self.calls.lock().remove(&result.call_id) {
+            let _ = sender.send(result.clone());
+        }
+    }
+}
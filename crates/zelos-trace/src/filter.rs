@@ -1,12 +1,95 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Arc,
+};
+
 use anyhow::{anyhow, Result};
 use uuid::Uuid;
-use zelos_trace_types::ipc::{IpcMessage, IpcMessageWithId};
+use zelos_trace_types::ipc::{IpcMessage, IpcMessageWithId, LogLevel, TraceSessionStart};
+
+/// What is known about the segment of a message beyond the message itself
+#[derive(Debug, Clone, Default)]
+pub struct SegmentContext {
+    /// Attributes of the segment's start message
+    pub attributes: HashMap<String, String>,
+    /// Sessions the segment joined
+    pub sessions: HashSet<Uuid>,
+}
+
+/// The context of open segments, kept by the router from the messages it forwards
+#[derive(Debug, Default)]
+pub(crate) struct SegmentContexts {
+    segments: HashMap<Uuid, Arc<SegmentContext>>,
+    open_sessions: HashMap<Uuid, TraceSessionStart>,
+    none: Arc<SegmentContext>,
+}
+
+impl SegmentContexts {
+    /// Update the contexts with `msg` before it is forwarded
+    pub(crate) fn update(&mut self, msg: &IpcMessageWithId) {
+        match &msg.msg {
+            IpcMessage::TraceSegmentStart(start) => {
+                let sessions = self
+                    .open_sessions
+                    .values()
+                    .filter(|session| session.admits(&start.attributes))
+                    .map(|session| session.session_id)
+                    .collect();
+                self.segments.insert(
+                    msg.segment_id,
+                    Arc::new(SegmentContext {
+                        attributes: start.attributes.clone(),
+                        sessions,
+                    }),
+                );
+            }
+            IpcMessage::TraceSessionStart(session) => {
+                self.open_sessions
+                    .insert(session.session_id, session.clone());
+            }
+            IpcMessage::TraceSessionJoin(join) => {
+                if let Some(segment) = self.segments.get_mut(&msg.segment_id) {
+                    Arc::make_mut(segment).sessions.insert(join.session_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Forget segments and sessions that ended with `msg`, after it is forwarded
+    pub(crate) fn remove_ended(&mut self, msg: &IpcMessageWithId) {
+        match &msg.msg {
+            IpcMessage::TraceSegmentEnd(_) => {
+                self.segments.remove(&msg.segment_id);
+            }
+            IpcMessage::TraceSessionEnd(end) => {
+                self.open_sessions.remove(&end.session_id);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn get(&self, segment_id: &Uuid) -> &SegmentContext {
+        self.segments.get(segment_id).unwrap_or(&self.none)
+    }
+
+    /// The context of `segment_id` as it is now, for matching a message against filters added later
+    pub(crate) fn snapshot(&self, segment_id: &Uuid) -> Arc<SegmentContext> {
+        self.segments.get(segment_id).unwrap_or(&self.none).clone()
+    }
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Filter {
     pub segment_id: Option<Uuid>,
     pub source_name: Option<String>,
     pub event_name: Option<String>,
+    /// Attributes the message's segment must have, see [`zelos_trace_types::ipc::TraceSegmentStart::attributes`]
+    pub attributes: BTreeMap<String, String>,
+    /// Session the message's segment must have joined, or the session the message describes
+    pub session_id: Option<Uuid>,
+    /// Minimum level of the logs to match, other messages are not affected
+    pub log_level: Option<LogLevel>,
 }
 
 impl Filter {
@@ -19,18 +102,74 @@ impl Filter {
             segment_id,
             source_name,
             event_name,
+            attributes: BTreeMap::new(),
+            session_id: None,
+            log_level: None,
         }
     }
 
+    /// Only match messages of segments that have the attribute `key` set to `value`
+    pub fn with_attribute(mut self, key: &str, value: &str) -> Self {
+        self.attributes.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    /// Only match messages of segments that joined the session `session_id`, and the session's own messages
+    pub fn with_session(mut self, session_id: Uuid) -> Self {
+        self.session_id = Some(session_id);
+        self
+    }
+
+    /// Only match logs of `level` or above
+    pub fn with_log_level(mut self, level: LogLevel) -> Self {
+        self.log_level = Some(level);
+        self
+    }
+
     pub fn any() -> Self {
         Self {
             segment_id: None,
             source_name: None,
             event_name: None,
+            attributes: BTreeMap::new(),
+            session_id: None,
+            log_level: None,
         }
     }
 
+    /// Parse a filter of the form `segment_id/source_name/event_name`, with `*` matching anything, optionally followed
+    /// by segment attributes as `?key=value&key=value`. Keys starting with `$` are options rather than attributes:
+    /// `$session` matches a session id and `$level` sets the minimum log level. Logs match the event name by their
+    /// target, and intervals by their name.
     pub fn parse(filter: &str) -> Result<Self> {
+        // Split off our attributes, if any
+        let (filter, attributes_str) = match filter.split_once("?") {
+            Some((filter, attributes_str)) => (filter, Some(attributes_str)),
+            None => (filter, None),
+        };
+        let mut attributes = attributes_str
+            .into_iter()
+            .flat_map(|attributes_str| attributes_str.split("&"))
+            .map(|attribute| {
+                attribute
+                    .split_once("=")
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .ok_or(anyhow!("Attribute '{}' is not key=value", attribute))
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        // Options are prefixed, so they do not hide segment attributes with the same name
+        let session_id = attributes
+            .remove("$session")
+            .map(|session_id| Uuid::parse_str(&session_id))
+            .transpose()?;
+        let log_level = attributes
+            .remove("$level")
+            .map(|level| level.parse())
+            .transpose()?;
+        if let Some(key) = attributes.keys().find(|key| key.starts_with('$')) {
+            return Err(anyhow!("Unknown filter option '{}'", key));
+        }
+
         // Split our filter string by `/`
         let (uuid_str, rest) = filter.split_once("/").ok_or(anyhow!("Unable to split"))?;
         let (source_name_str, event_name_str) =
@@ -53,28 +192,69 @@ impl Filter {
             segment_id,
             source_name,
             event_name,
+            attributes,
+            session_id,
+            log_level,
         })
     }
 
-    pub fn matches(&self, msg: &IpcMessageWithId) -> bool {
+    /// Whether `msg` matches, given the context of its segment
+    pub fn matches(&self, msg: &IpcMessageWithId, segment: &SegmentContext) -> bool {
         match self.segment_id {
             Some(segment_id) if segment_id != msg.segment_id => return false,
             _ => {}
         }
 
-        if matches!(&self.source_name, Some(match_source_name) if match_source_name != &msg.source_name)
+        if matches!(&self.source_name, Some(match_source_name) if match_source_name.as_str() != &*msg.source_name)
         {
             return false;
         }
 
         match (&self.event_name, &msg.msg) {
-            (Some(event_name), IpcMessage::TraceEvent(e)) => {
-                if event_name != &e.name {
-                    return false;
-                }
+            (Some(event_name), IpcMessage::TraceEvent(e)) if event_name.as_str() != &*e.name => {
+                return false;
             }
+            (Some(_), IpcMessage::TraceEvent(_)) => {}
+            (Some(event_name), IpcMessage::Log(log)) if *event_name != log.target => {
+                return false;
+            }
+            (Some(_), IpcMessage::Log(_)) => {}
+            (Some(event_name), IpcMessage::TraceIntervalBegin(begin))
+                if *event_name != begin.name =>
+            {
+                return false;
+            }
+            (Some(event_name), IpcMessage::TraceIntervalEnd(end)) if *event_name != end.name => {
+                return false;
+            }
+            (Some(_), IpcMessage::TraceIntervalBegin(_) | IpcMessage::TraceIntervalEnd(_)) => {}
             (Some(_), _) => {
-                // If message is not a TraceEvent, it can't match by event name
+                // If message is not a TraceEvent, log or interval, it can't match by event name
+                return false;
+            }
+            _ => {}
+        }
+
+        match (self.log_level, &msg.msg) {
+            (Some(log_level), IpcMessage::Log(log)) if log.level < log_level => return false,
+            _ => {}
+        }
+
+        if self
+            .attributes
+            .iter()
+            .any(|(key, value)| segment.attributes.get(key) != Some(value))
+        {
+            return false;
+        }
+
+        match self.session_id {
+            Some(session_id) if msg.msg.is_session() && session_id != msg.segment_id => {
+                return false;
+            }
+            Some(session_id)
+                if !msg.msg.is_session() && !segment.sessions.contains(&session_id) =>
+            {
                 return false;
             }
             _ => {}
@@ -0,0 +1,104 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use anyhow::Result;
+use uuid::Uuid;
+use zelos_trace_types::{
+    ipc::{IpcMessageWithId, Sender, TraceIntervalBegin, TraceIntervalEnd},
+    Value,
+};
+
+use crate::time::now_time_ns;
+
+/// An open interval of a [`crate::TraceSource`], such as a state of a state machine, which ends when this is dropped.
+/// Created with [`crate::TraceSource::begin_interval`], or [`TraceInterval::begin_child`] for nested intervals.
+#[derive(Debug)]
+pub struct TraceInterval {
+    pub id: Uuid,
+    pub name: Arc<str>,
+    pub parent_id: Option<Uuid>,
+    segment_id: Uuid,
+    source_name: Arc<str>,
+    sender: Sender,
+    ended: AtomicBool,
+}
+
+impl TraceInterval {
+    /// Begin an interval, emitting its begin on `sender`
+    pub(crate) fn begin(
+        segment_id: Uuid,
+        source_name: Arc<str>,
+        sender: Sender,
+        parent_id: Option<Uuid>,
+        name: &str,
+        fields: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<Self> {
+        let interval = Self {
+            id: Uuid::now_v7(),
+            name: name.into(),
+            parent_id,
+            segment_id,
+            source_name,
+            sender,
+            ended: AtomicBool::new(false),
+        };
+        interval.sender.send(IpcMessageWithId {
+            segment_id,
+            source_name: interval.source_name.clone(),
+            msg: TraceIntervalBegin {
+                interval_id: interval.id,
+                parent_id,
+                time_ns: now_time_ns(),
+                name: name.to_string(),
+                fields: fields.into_iter().collect(),
+            }
+            .into(),
+        })?;
+        Ok(interval)
+    }
+
+    /// Begin an interval nested in this one
+    pub fn begin_child(
+        &self,
+        name: &str,
+        fields: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<TraceInterval> {
+        Self::begin(
+            self.segment_id,
+            self.source_name.clone(),
+            self.sender.clone(),
+            Some(self.id),
+            name,
+            fields,
+        )
+    }
+
+    /// End the interval with `fields`, later calls do nothing
+    pub fn end(&self, fields: impl IntoIterator<Item = (String, Value)>) -> Result<()> {
+        if self.ended.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.sender.send(IpcMessageWithId {
+            segment_id: self.segment_id,
+            source_name: self.source_name.clone(),
+            msg: TraceIntervalEnd {
+                interval_id: self.id,
+                time_ns: now_time_ns(),
+                name: self.name.to_string(),
+                fields: fields.into_iter().collect(),
+            }
+            .into(),
+        })?;
+        Ok(())
+    }
+}
+
+impl Drop for TraceInterval {
+    fn drop(&mut self) {
+        if let Err(e) = self.end([]) {
+            tracing::debug!("Error emitting trace interval end: {}", e);
+        }
+    }
+}
@@ -1,9 +1,15 @@
 #![deny(clippy::expect_used, clippy::unwrap_used)]
 
+pub mod action;
 pub mod filter;
+pub mod interval;
+pub mod logger;
 pub mod metadata;
+pub mod parameter;
+pub mod rate;
 pub mod router;
 pub mod segment;
+pub mod session;
 pub mod sink;
 pub mod source;
 pub mod store;
@@ -11,6 +17,6 @@ pub mod time;
 
 pub use metadata::TraceMetadata;
 pub use router::TraceRouter;
-pub use sink::TraceSink;
+pub use sink::{SinkCommand, TraceSink};
 pub use source::TraceSource;
 pub use store::{MetadataOnlyStore, Store};
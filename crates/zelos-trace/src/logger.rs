@@ -0,0 +1,231 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Result;
+use zelos_trace_types::{ipc::LogLevel, Value};
+
+use crate::TraceSource;
+
+/// Crates whose records are not emitted: the router and transports log while routing, so their records coming back
+/// through the router could feed on themselves or wait on the router they are logging about
+const IGNORED_CRATES: &[&str] = &["tonic", "h2", "hyper", "hyper_util"];
+
+/// Whether records of `target` are dropped, see [`IGNORED_CRATES`]. All `zelos*` crates are ignored.
+fn is_ignored(target: &str) -> bool {
+    let krate = target.split("::").next().unwrap_or(target);
+    krate.starts_with("zelos") || IGNORED_CRATES.contains(&krate)
+}
+
+fn log_level(level: log::Level) -> LogLevel {
+    match level {
+        log::Level::Trace => LogLevel::Trace,
+        log::Level::Debug => LogLevel::Debug,
+        log::Level::Info => LogLevel::Info,
+        log::Level::Warn => LogLevel::Warn,
+        log::Level::Error => LogLevel::Error,
+    }
+}
+
+/// Collects the key-values of a record as log fields, keeping numbers and booleans typed
+#[derive(Default)]
+struct Fields(HashMap<String, Value>);
+
+impl<'kvs> log::kv::VisitSource<'kvs> for Fields {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        let value = if let Some(v) = value.to_bool() {
+            Value::Boolean(v)
+        } else if let Some(v) = value.to_i64() {
+            Value::Int64(v)
+        } else if let Some(v) = value.to_u64() {
+            Value::UInt64(v)
+        } else if let Some(v) = value.to_f64() {
+            Value::Float64(v)
+        } else {
+            Value::String(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// A [`log::Log`] implementation that emits records as logs of a [`TraceSource`], so an application's `log` output
+/// is routed alongside its events. Records are dropped rather than waiting when the source's channel is full, and
+/// records of zelos and its transports are never emitted, so logging cannot stall the router.
+pub struct TraceLogger {
+    source: Arc<TraceSource>,
+    level: log::LevelFilter,
+    dropped: AtomicU64,
+}
+
+impl TraceLogger {
+    /// Create a logger emitting every record on `source`
+    pub fn new(source: Arc<TraceSource>) -> Self {
+        Self {
+            source,
+            level: log::LevelFilter::Trace,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    /// Only emit records of `level` or above
+    pub fn with_level(mut self, level: log::LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Number of records dropped because the source's channel was full
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Install this as the global logger, which fails if one is already installed
+    pub fn init(self) -> Result<()> {
+        log::set_max_level(self.level);
+        log::set_boxed_logger(Box::new(self))?;
+        Ok(())
+    }
+}
+
+impl log::Log for TraceLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        metadata.level() <= self.level && !is_ignored(metadata.target())
+    }
+
+    fn log(&self, record: &log::Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let mut fields = Fields::default();
+        // Visiting our own fields can't fail
+        let _ = record.key_values().visit(&mut fields);
+
+        // Logging the failure could recurse into this logger, so it is only counted
+        if self
+            .source
+            .try_log(
+                log_level(record.level()),
+                record.target(),
+                &record.args().to_string(),
+                fields.0,
+            )
+            .is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use log::Log;
+    use tokio_util::sync::CancellationToken;
+    use zelos_trace_types::ipc::IpcMessage;
+
+    use super::*;
+    use crate::{router::DEFAULT_CHANNEL_SIZE, TraceRouter};
+
+    #[test]
+    fn test_logger() -> Result<()> {
+        let (sender, receiver) = flume::unbounded();
+        let source = Arc::new(TraceSource::new("app", sender));
+        let logger = TraceLogger::new(source).with_level(log::LevelFilter::Info);
+        let _start = receiver.try_recv()?;
+
+        let key_values = [("rpm", log::kv::Value::from(1200)), ("bus", "can0".into())];
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Warn)
+                .target("motor::can")
+                .args(format_args!("Bus off, restarting"))
+                .key_values(&key_values)
+                .build(),
+        );
+        logger.log(
+            &log::Record::builder()
+                .level(log::Level::Debug)
+                .args(format_args!("Ignored"))
+                .build(),
+        );
+
+        let IpcMessage::Log(log) = receiver.try_recv()?.msg else {
+            panic!("Expected a log");
+        };
+        assert_eq!(log.level, LogLevel::Warn);
+        assert_eq!(log.target, "motor::can");
+        assert_eq!(log.message, "Bus off, restarting");
+        assert_eq!(log.fields.get("rpm"), Some(&Value::Int64(1200)));
+        assert_eq!(
+            log.fields.get("bus"),
+            Some(&Value::String("can0".to_string()))
+        );
+        assert!(receiver.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_logger_backpressure() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let source = Arc::new(TraceSource::new("app", router.sender()));
+        let logger = TraceLogger::new(source);
+
+        // The router's own records and those of its transports are never emitted
+        for target in [
+            "zelos_trace::router",
+            "zelos_trace_grpc",
+            "h2::codec",
+            "tonic",
+        ] {
+            let metadata = log::Metadata::builder()
+                .level(log::Level::Error)
+                .target(target)
+                .build();
+            assert!(!logger.enabled(&metadata), "{} is enabled", target);
+        }
+
+        // The router is not running, so its channel fills up and later records are dropped instead of blocking
+        let record = |message| {
+            logger.log(
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("app::pump")
+                    .args(format_args!("{}", message))
+                    .build(),
+            )
+        };
+        // The segment start already took one slot
+        for message in 0..DEFAULT_CHANNEL_SIZE {
+            record(message);
+        }
+        let dropped = logger.dropped();
+
+        // Once the router drains its channel, records get through again
+        let task = tokio::spawn(run);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while router.saturation() > 0.0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+        record(0);
+        assert_eq!(dropped, 1);
+        assert_eq!(logger.dropped(), 1);
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+}
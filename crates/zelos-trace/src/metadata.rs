@@ -1,87 +1,146 @@
 use std::{collections::HashMap, sync::Arc};
 
 use arc_swap::ArcSwap;
+use chrono::DateTime;
 use rpds::HashTrieMapSync;
 use uuid::Uuid;
 use zelos_trace_types::ipc;
 
-use crate::segment::TraceSegment;
+use crate::{
+    segment::{TraceEventSchema, TraceSegment},
+    session::TraceSession,
+};
 
 #[derive(Clone)]
 pub struct TraceMetadata {
     segments: Arc<ArcSwap<HashTrieMapSync<Uuid, TraceSegment>>>,
+    sessions: Arc<ArcSwap<HashTrieMapSync<Uuid, TraceSession>>>,
+}
+
+/// Apply a message to `segments` and `sessions`, returning whether it changed them. Fails if the message is a schema
+/// its segment rejects, see [`TraceSegment::update_mut`].
+fn apply(
+    segments: &mut HashTrieMapSync<Uuid, TraceSegment>,
+    sessions: &mut HashTrieMapSync<Uuid, TraceSession>,
+    msg: &ipc::IpcMessageWithId,
+) -> anyhow::Result<bool> {
+    match &msg.msg {
+        ipc::IpcMessage::TraceEvent(_)
+        | ipc::IpcMessage::TraceActionResult(_)
+        | ipc::IpcMessage::Log(_) => {
+            return Ok(false);
+        }
+        ipc::IpcMessage::TraceSessionStart(m) => {
+            // Only segments that start later join by the session's rule, so replaying the session after its segments
+            // in as_ipc doesn't add any
+            sessions.insert_mut(m.session_id, TraceSession::from_ipc(m));
+            return Ok(true);
+        }
+        ipc::IpcMessage::TraceSessionEnd(m) => {
+            return Ok(match sessions.get_mut(&m.session_id) {
+                Some(session) => {
+                    session.end_time = Some(DateTime::from_timestamp_nanos(m.time_ns));
+                    true
+                }
+                None => false,
+            });
+        }
+        ipc::IpcMessage::TraceSessionJoin(m) => {
+            return Ok(match sessions.get_mut(&m.session_id) {
+                Some(session) => {
+                    session.segments.insert_mut(msg.segment_id);
+                    true
+                }
+                None => {
+                    tracing::debug!(
+                        "Segment {} joined unknown session {}",
+                        msg.segment_id,
+                        m.session_id
+                    );
+                    false
+                }
+            });
+        }
+        ipc::IpcMessage::TraceSegmentStart(m) => {
+            let joined: Vec<Uuid> = sessions
+                .values()
+                .filter(|session| session.admits(&m.attributes))
+                .map(|session| session.id)
+                .collect();
+            for session_id in joined {
+                if let Some(session) = sessions.get_mut(&session_id) {
+                    session.segments.insert_mut(msg.segment_id);
+                }
+            }
+        }
+        ipc::IpcMessage::TraceSegmentEnd(_)
+        | ipc::IpcMessage::TraceEventSchema(_)
+        | ipc::IpcMessage::TraceEventFieldNamedValues(_)
+        | ipc::IpcMessage::TraceActionSchema(_)
+        | ipc::IpcMessage::Annotation(_)
+        | ipc::IpcMessage::TraceIntervalBegin(_)
+        | ipc::IpcMessage::TraceIntervalEnd(_) => {}
+    }
+
+    if let Some(seg) = segments.get_mut(&msg.segment_id) {
+        seg.update_mut(&msg.msg)?;
+    } else {
+        let seg = if let ipc::IpcMessage::TraceSegmentStart(m) = &msg.msg {
+            TraceSegment::from_ipc(msg.segment_id, m)
+        } else {
+            TraceSegment::empty(msg.segment_id, msg.source_name.to_string())
+        };
+        segments.insert_mut(msg.segment_id, seg.update(&msg.msg)?);
+    }
+    Ok(true)
 }
 
 impl TraceMetadata {
     pub fn new() -> Self {
         Self {
             segments: Arc::new(ArcSwap::from_pointee(HashTrieMapSync::new_sync())),
+            sessions: Arc::new(ArcSwap::from_pointee(HashTrieMapSync::new_sync())),
         }
     }
 
-    pub fn from(msgs: impl IntoIterator<Item = ipc::IpcMessageWithId>) -> Self {
+    pub fn from(msgs: impl IntoIterator<Item = ipc::IpcMessageWithId>) -> anyhow::Result<Self> {
         let mut segments: HashTrieMapSync<Uuid, TraceSegment> = HashTrieMapSync::new_sync();
+        let mut sessions: HashTrieMapSync<Uuid, TraceSession> = HashTrieMapSync::new_sync();
 
         for msg in msgs {
-            if let Some(seg) = segments.get_mut(&msg.segment_id) {
-                match &msg.msg {
-                    ipc::IpcMessage::TraceSegmentStart(_)
-                    | ipc::IpcMessage::TraceSegmentEnd(_)
-                    | ipc::IpcMessage::TraceEventSchema(_)
-                    | ipc::IpcMessage::TraceEventFieldNamedValues(_) => seg.update_mut(&msg.msg),
-                    ipc::IpcMessage::TraceEvent(_) => {
-                        // Do nothing
-                    }
-                }
-            } else {
-                let seg = if let ipc::IpcMessage::TraceSegmentStart(m) = &msg.msg {
-                    TraceSegment::from_ipc(msg.segment_id, m)
-                } else {
-                    TraceSegment::empty(msg.segment_id, msg.source_name)
-                };
-                segments.insert_mut(msg.segment_id, seg.update(&msg.msg));
-            }
+            apply(&mut segments, &mut sessions, &msg)?;
         }
 
-        Self {
+        Ok(Self {
             segments: Arc::new(ArcSwap::from_pointee(segments)),
-        }
+            sessions: Arc::new(ArcSwap::from_pointee(sessions)),
+        })
     }
 
+    /// Apply a message, failing without changing anything if its segment rejects it, such as a schema version that
+    /// changes the type of a field
     #[tracing::instrument(level = "trace", skip_all)]
-    pub fn update(&self, msg: &ipc::IpcMessageWithId) {
+    pub fn update(&self, msg: &ipc::IpcMessageWithId) -> anyhow::Result<()> {
         // Early return if we see messages we don't care about
-        if let ipc::IpcMessage::TraceEvent(_) = &msg.msg {
-            return;
+        if let ipc::IpcMessage::TraceEvent(_)
+        | ipc::IpcMessage::TraceActionResult(_)
+        | ipc::IpcMessage::Log(_) = &msg.msg
+        {
+            return Ok(());
         }
 
-        let segments = self.segments.load();
-        if let Some(seg) = segments.get(&msg.segment_id) {
-            match &msg.msg {
-                ipc::IpcMessage::TraceSegmentStart(_)
-                | ipc::IpcMessage::TraceSegmentEnd(_)
-                | ipc::IpcMessage::TraceEventSchema(_)
-                | ipc::IpcMessage::TraceEventFieldNamedValues(_) => {
-                    let new = segments.insert(msg.segment_id, seg.update(&msg.msg));
-                    self.segments.store(Arc::new(new));
-                }
-                ipc::IpcMessage::TraceEvent(_) => {
-                    // Do nothing
-                }
-            }
-        } else {
-            let seg = if let ipc::IpcMessage::TraceSegmentStart(m) = &msg.msg {
-                TraceSegment::from_ipc(msg.segment_id, m)
-            } else {
-                TraceSegment::empty(msg.segment_id, msg.source_name.clone())
-            };
-            let new = segments.insert(msg.segment_id, seg.update(&msg.msg));
-            self.segments.store(Arc::new(new));
+        let mut segments = HashTrieMapSync::clone(&self.segments.load());
+        let mut sessions = HashTrieMapSync::clone(&self.sessions.load());
+        if apply(&mut segments, &mut sessions, msg)? {
+            self.segments.store(Arc::new(segments));
+            self.sessions.store(Arc::new(sessions));
         }
+        Ok(())
     }
 
     pub fn as_ipc(&self) -> Vec<ipc::IpcMessageWithId> {
         let segments = self.segments.load();
+        let sessions = self.sessions.load();
         segments
             .iter()
             .flat_map(|(id, seg)| {
@@ -89,10 +148,16 @@ impl TraceMetadata {
                     .into_iter()
                     .map(move |msg| ipc::IpcMessageWithId {
                         segment_id: *id,
-                        source_name: seg.source.clone(),
+                        source_name: seg.source.as_str().into(),
                         msg,
                     })
             })
+            // Sessions follow the segments that join them
+            .chain(
+                sessions
+                    .values()
+                    .flat_map(|session| session.as_ipc(&segments)),
+            )
             .collect()
     }
 
@@ -119,6 +184,65 @@ impl TraceMetadata {
         segments.get(id).cloned()
     }
 
+    /// Returns a clone of all sessions
+    pub fn sessions(&self) -> HashMap<Uuid, TraceSession> {
+        let sessions = self.sessions.load();
+        sessions
+            .iter()
+            .map(|(id, session)| (*id, session.clone()))
+            .collect()
+    }
+
+    /// Returns a clone of a single session
+    pub fn get_session(&self, id: &Uuid) -> Option<TraceSession> {
+        let sessions = self.sessions.load();
+        sessions.get(id).cloned()
+    }
+
+    /// Returns a clone of the segments that joined a session
+    pub fn session_segments(&self, id: &Uuid) -> Vec<TraceSegment> {
+        let segments = self.segments.load();
+        self.get_session(id)
+            .map(|session| {
+                session
+                    .segments
+                    .iter()
+                    .filter_map(|segment_id| segments.get(segment_id).cloned())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns a clone of every version of an event's schema in a segment, oldest first and ending with the current one
+    pub fn schema_history(&self, segment_id: &Uuid, event_name: &str) -> Vec<TraceEventSchema> {
+        self.segments
+            .load()
+            .get(segment_id)
+            .map(|segment| {
+                segment
+                    .schema_versions(event_name)
+                    .into_iter()
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Returns a clone of the annotations of all segments with the id of their segment, ordered by start time
+    pub fn annotations(&self) -> Vec<(Uuid, ipc::TraceAnnotation)> {
+        let segments = self.segments.load();
+        let mut annotations: Vec<_> = segments
+            .iter()
+            .flat_map(|(id, seg)| {
+                seg.annotations
+                    .values()
+                    .map(move |annotation| (*id, annotation.clone()))
+            })
+            .collect();
+        annotations.sort_by_key(|(_, annotation)| annotation.start_time_ns);
+        annotations
+    }
+
     #[tracing::instrument(level = "trace", skip_all)]
     pub fn remove_segment(&self, id: &Uuid) {
         let segments = self.segments.load();
@@ -136,11 +260,12 @@ impl Default for TraceMetadata {
 #[cfg(test)]
 mod test {
     use chrono::DateTime;
+    use zelos_trace_types::{DataType, Value};
 
     use super::*;
 
     #[test]
-    fn test_basic() {
+    fn test_basic() -> anyhow::Result<()> {
         let metadata = TraceMetadata::new();
 
         let segment_id = match Uuid::try_parse("0196c84d-6eb8-7c46-83b1-e4cac73ba9b6") {
@@ -151,12 +276,13 @@ mod test {
         let start = ipc::TraceSegmentStart {
             time_ns: 0,
             source_name: source_name.to_string(),
+            attributes: [("host".to_string(), "rig-1".to_string())].into(),
         };
         metadata.update(&ipc::IpcMessageWithId {
             segment_id,
-            source_name: source_name.to_string(),
+            source_name: source_name.into(),
             msg: start.into(),
-        });
+        })?;
 
         {
             let seg = match metadata.get_segment(&segment_id) {
@@ -167,14 +293,25 @@ mod test {
             assert_eq!(seg.source, "src");
             assert_eq!(seg.start_time, Some(DateTime::from_timestamp_nanos(0)));
             assert_eq!(seg.end_time, None);
+            assert_eq!(
+                seg.attributes.get("host").map(String::as_str),
+                Some("rig-1")
+            );
         }
 
+        // Attributes are replayed with the segment start
+        let replayed = metadata.as_ipc();
+        assert!(matches!(&replayed[..], [ipc::IpcMessageWithId {
+            msg: ipc::IpcMessage::TraceSegmentStart(start),
+            ..
+        }] if start.attributes.len() == 1));
+
         let end = ipc::TraceSegmentEnd { time_ns: 1 };
         metadata.update(&ipc::IpcMessageWithId {
             segment_id,
-            source_name: source_name.to_string(),
+            source_name: source_name.into(),
             msg: end.into(),
-        });
+        })?;
 
         {
             let seg = match metadata.get_segment(&segment_id) {
@@ -183,5 +320,218 @@ mod test {
             };
             assert_eq!(seg.end_time, Some(DateTime::from_timestamp_nanos(1)));
         }
+        Ok(())
+    }
+
+    #[test]
+    fn test_sessions() -> anyhow::Result<()> {
+        let metadata = TraceMetadata::new();
+        let msg = |segment_id, source_name: &str, msg: ipc::IpcMessage| ipc::IpcMessageWithId {
+            segment_id,
+            source_name: source_name.into(),
+            msg,
+        };
+        let segment_start = |segment_id, attributes: &[(&str, &str)]| {
+            msg(
+                segment_id,
+                "src",
+                ipc::TraceSegmentStart {
+                    time_ns: 0,
+                    source_name: "src".to_string(),
+                    attributes: attributes
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect(),
+                }
+                .into(),
+            )
+        };
+
+        // Segments starting while the session is open join by its rule
+        let (open, later, other, explicit) = (
+            Uuid::now_v7(),
+            Uuid::now_v7(),
+            Uuid::now_v7(),
+            Uuid::now_v7(),
+        );
+        metadata.update(&segment_start(open, &[("run", "42")]))?;
+        let session_id = Uuid::now_v7();
+        metadata.update(&msg(
+            session_id,
+            "hil",
+            ipc::TraceSessionStart {
+                session_id,
+                time_ns: 1,
+                name: "hil".to_string(),
+                attributes: [("bench".to_string(), "2".to_string())].into(),
+                join_attributes: [("run".to_string(), "42".to_string())].into(),
+            }
+            .into(),
+        ))?;
+        metadata.update(&segment_start(later, &[("run", "42")]))?;
+        metadata.update(&segment_start(other, &[("run", "43")]))?;
+
+        // Other segments join explicitly
+        metadata.update(&segment_start(explicit, &[]))?;
+        metadata.update(&msg(
+            explicit,
+            "src",
+            ipc::TraceSessionJoin { session_id }.into(),
+        ))?;
+
+        // Session messages don't create segments
+        assert_eq!(metadata.segments().len(), 4);
+        let session = match metadata.get_session(&session_id) {
+            Some(session) => session,
+            None => panic!("session not found after start"),
+        };
+        let mut joined: Vec<_> = session.segments.iter().copied().collect();
+        joined.sort();
+        assert_eq!(joined, [later, explicit]);
+        assert_eq!(metadata.session_segments(&session_id).len(), 2);
+
+        // Segments starting after the session ended don't join
+        metadata.update(&msg(
+            session_id,
+            "hil",
+            ipc::TraceSessionEnd {
+                session_id,
+                time_ns: 2,
+            }
+            .into(),
+        ))?;
+        metadata.update(&segment_start(Uuid::now_v7(), &[("run", "42")]))?;
+        let session = metadata.get_session(&session_id);
+        assert_eq!(session.as_ref().map(|s| s.segments.size()), Some(2));
+        assert_eq!(
+            session.and_then(|s| s.end_time),
+            Some(DateTime::from_timestamp_nanos(2))
+        );
+
+        // Sessions and their members are replayed
+        let replayed = TraceMetadata::from(metadata.as_ipc())?;
+        let session = replayed.get_session(&session_id);
+        assert_eq!(session.as_ref().map(|s| s.segments.size()), Some(2));
+        assert_eq!(session.map(|s| s.attributes.len()), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_annotations() -> anyhow::Result<()> {
+        let metadata = TraceMetadata::new();
+        let segment_id = Uuid::now_v7();
+        let annotation = |start_time_ns, text: &str| ipc::IpcMessageWithId {
+            segment_id,
+            source_name: "src".into(),
+            msg: ipc::TraceAnnotation {
+                annotation_id: Uuid::now_v7(),
+                start_time_ns,
+                end_time_ns: start_time_ns,
+                text: text.to_string(),
+                tags: vec!["brakes".to_string()],
+                author: None,
+            }
+            .into(),
+        };
+        metadata.update(&annotation(5, "anomaly seen"))?;
+        metadata.update(&annotation(2, "brake test started"))?;
+
+        let texts = |metadata: &TraceMetadata| {
+            metadata
+                .annotations()
+                .into_iter()
+                .map(|(id, annotation)| (id, annotation.text))
+                .collect::<Vec<_>>()
+        };
+        let expected = [
+            (segment_id, "brake test started".to_string()),
+            (segment_id, "anomaly seen".to_string()),
+        ];
+        assert_eq!(texts(&metadata), expected);
+
+        // Annotations are replayed with their segment
+        assert_eq!(texts(&TraceMetadata::from(metadata.as_ipc())?), expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_schema_history() -> anyhow::Result<()> {
+        let metadata = TraceMetadata::new();
+        let segment_id = Uuid::now_v7();
+        let update = |msg: ipc::IpcMessage| {
+            metadata.update(&ipc::IpcMessageWithId {
+                segment_id,
+                source_name: "src".into(),
+                msg,
+            })
+        };
+        let field = |name: &str, data_type| ipc::TraceEventFieldMetadata {
+            name: name.to_string(),
+            data_type,
+            unit: None,
+            kind: ipc::FieldKind::Gauge,
+            display: ipc::FieldDisplay::default(),
+        };
+        let schema = |version, fields| {
+            ipc::IpcMessage::TraceEventSchema(ipc::TraceEventSchema {
+                name: "pump".to_string(),
+                version,
+                fields,
+            })
+        };
+
+        update(schema(0, vec![field("state", DataType::UInt8)]))?;
+        update(
+            ipc::TraceEventFieldNamedValues {
+                event_name: "pump".to_string(),
+                field_name: "state".to_string(),
+                values: [(Value::UInt8(1), "running".to_string())].into(),
+            }
+            .into(),
+        )?;
+        // A field is added mid-segment
+        update(schema(
+            1,
+            vec![
+                field("state", DataType::UInt8),
+                field("pressure", DataType::Float32),
+            ],
+        ))?;
+        // Sending an earlier version again changes nothing
+        update(schema(0, vec![field("state", DataType::UInt8)]))?;
+        // A field cannot change type
+        assert!(update(schema(2, vec![field("state", DataType::String)])).is_err());
+        // Nor come back with another type after it was removed
+        update(schema(2, vec![field("pressure", DataType::Float32)]))?;
+        assert!(update(schema(3, vec![field("state", DataType::String)])).is_err());
+
+        let versions = |metadata: &TraceMetadata| {
+            metadata
+                .schema_history(&segment_id, "pump")
+                .into_iter()
+                .map(|schema| (schema.version, schema.fields.len()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(versions(&metadata), [(0, 1), (1, 2), (2, 1)]);
+
+        // Value tables carry over to the new version
+        let segment = metadata.get_segment(&segment_id);
+        let state = segment
+            .as_ref()
+            .and_then(|segment| segment.schema_versions("pump").get(1).copied())
+            .and_then(|schema| schema.get_field("state"));
+        assert_eq!(state.map(|state| state.values.len()), Some(1));
+
+        // Every version is replayed, so a late subscriber still rejects the incompatible one
+        let replayed = TraceMetadata::from(metadata.as_ipc())?;
+        assert_eq!(versions(&replayed), [(0, 1), (1, 2), (2, 1)]);
+        assert!(replayed
+            .update(&ipc::IpcMessageWithId {
+                segment_id,
+                source_name: "src".into(),
+                msg: schema(3, vec![field("state", DataType::String)]),
+            })
+            .is_err());
+        Ok(())
     }
 }
@@ -0,0 +1,178 @@
+use std::{cmp::Ordering, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use tokio::sync::watch;
+use zelos_trace_types::{
+    ipc::{FieldDisplay, FieldKind, TraceEventFieldMetadata},
+    DataType, Value,
+};
+
+use crate::{
+    source::{TraceSource, TraceSourceEvent},
+    time::now_time_ns,
+};
+
+/// Prefix of the event that publishes a parameter's value, followed by the parameter name
+pub const PARAMETER_EVENT_PREFIX: &str = "parameters/";
+
+/// Name of the field holding the value in a parameter's event
+pub const PARAMETER_VALUE_FIELD: &str = "value";
+
+/// Order two values of the same numeric or timestamp type, `None` for other types
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Int8(a), Value::Int8(b)) => a.partial_cmp(b),
+        (Value::Int16(a), Value::Int16(b)) => a.partial_cmp(b),
+        (Value::Int32(a), Value::Int32(b)) => a.partial_cmp(b),
+        (Value::Int64(a), Value::Int64(b)) => a.partial_cmp(b),
+        (Value::UInt8(a), Value::UInt8(b)) => a.partial_cmp(b),
+        (Value::UInt16(a), Value::UInt16(b)) => a.partial_cmp(b),
+        (Value::UInt32(a), Value::UInt32(b)) => a.partial_cmp(b),
+        (Value::UInt64(a), Value::UInt64(b)) => a.partial_cmp(b),
+        (Value::Float32(a), Value::Float32(b)) => a.partial_cmp(b),
+        (Value::Float64(a), Value::Float64(b)) => a.partial_cmp(b),
+        (Value::TimestampNs(a), Value::TimestampNs(b)) => a.partial_cmp(b),
+        _ => None,
+    }
+}
+
+/// A numeric bound as a display range for dashboards
+fn as_f64(value: &Value) -> Option<f64> {
+    value.as_number()?.as_f64()
+}
+
+/// Check that `value` is within `min..=max`
+fn check_range(name: &str, value: &Value, min: Option<&Value>, max: Option<&Value>) -> Result<()> {
+    if let Some(min) = min.filter(|min| {
+        !matches!(
+            compare(value, min),
+            Some(Ordering::Greater | Ordering::Equal)
+        )
+    }) {
+        return Err(anyhow!(
+            "Value {} of parameter '{}' is below its minimum {}",
+            value,
+            name,
+            min
+        ));
+    }
+    if let Some(max) =
+        max.filter(|max| !matches!(compare(value, max), Some(Ordering::Less | Ordering::Equal)))
+    {
+        return Err(anyhow!(
+            "Value {} of parameter '{}' is above its maximum {}",
+            value,
+            name,
+            max
+        ));
+    }
+    Ok(())
+}
+
+/// A typed, writable parameter of a [`crate::TraceSource`], such as a gain or threshold. Its value is published as
+/// the `value` field of the `parameters/<name>` event whenever it changes, and can be set through the router's
+/// controls, see [`crate::TraceRouter::set_parameter`].
+#[derive(Debug)]
+pub struct TraceSourceParameter {
+    pub name: Arc<str>,
+    pub default: Value,
+    pub min: Option<Value>,
+    pub max: Option<Value>,
+    value: watch::Sender<Value>,
+    event: Arc<TraceSourceEvent>,
+}
+
+impl TraceSourceParameter {
+    /// Check the parameter's range and add the event for its value to `source`
+    pub(crate) fn new(
+        source: &TraceSource,
+        name: &str,
+        default: Value,
+        min: Option<Value>,
+        max: Option<Value>,
+        unit: Option<String>,
+    ) -> Result<Self> {
+        for bound in min.iter().chain(max.iter()) {
+            if compare(bound, &default).is_none() {
+                return Err(anyhow!(
+                    "Bound {} of parameter '{}' is not an ordered value of type {:?}",
+                    bound,
+                    name,
+                    default.data_type()
+                ));
+            }
+        }
+        check_range(name, &default, min.as_ref(), max.as_ref())?;
+
+        let event = source.add_event(
+            &format!("{}{}", PARAMETER_EVENT_PREFIX, name),
+            std::iter::once(TraceEventFieldMetadata {
+                name: PARAMETER_VALUE_FIELD.to_string(),
+                data_type: default.data_type(),
+                unit,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay {
+                    min: min.as_ref().and_then(as_f64),
+                    max: max.as_ref().and_then(as_f64),
+                    ..Default::default()
+                },
+            }),
+        )?;
+        Ok(Self {
+            name: name.into(),
+            value: watch::Sender::new(default.clone()),
+            default,
+            min,
+            max,
+            event,
+        })
+    }
+
+    pub fn data_type(&self) -> DataType {
+        self.default.data_type()
+    }
+
+    /// The current value
+    pub fn get(&self) -> Value {
+        self.value.borrow().clone()
+    }
+
+    /// Receive the current value and every change to it
+    pub fn watch(&self) -> watch::Receiver<Value> {
+        self.value.subscribe()
+    }
+
+    /// Check that `value` has the parameter's type and is within its range
+    pub fn validate(&self, value: &Value) -> Result<()> {
+        if value.data_type() != self.data_type() {
+            return Err(anyhow!(
+                "Type mismatch for parameter '{}': expected {:?}, found {:?}",
+                self.name,
+                self.data_type(),
+                value.data_type()
+            ));
+        }
+        check_range(&self.name, value, self.min.as_ref(), self.max.as_ref())
+    }
+
+    /// Validate and set the value, publishing it and notifying watchers if it changed
+    pub fn set(&self, value: Value) -> Result<()> {
+        self.validate(&value)?;
+        if self.value.send_if_modified(|current| {
+            if *current == value {
+                return false;
+            }
+            *current = value.clone();
+            true
+        }) {
+            self.publish()?;
+        }
+        Ok(())
+    }
+
+    /// Emit the current value as an event
+    pub fn publish(&self) -> Result<()> {
+        self.event
+            .emit_values(now_time_ns(), vec![Some(self.get())])
+    }
+}
@@ -0,0 +1,518 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+use zelos_trace_types::{
+    ipc::{
+        FieldDisplay, FieldKind, IpcMessage, IpcMessageWithId, TraceEvent, TraceEventFieldMetadata,
+        TraceEventSchema,
+    },
+    DataType, Value,
+};
+
+use crate::TraceRouter;
+
+/// Prefix of the events derived from counter fields, e.g. `rates/link` for the counters of the `link` event
+pub const RATE_EVENT_PREFIX: &str = "rates/";
+
+/// Derived messages queued for the router before rates are dropped, see [`RateProcessor::run`]
+const DERIVED_CHANNEL_SIZE: usize = 4096;
+
+/// A counter value along with the range of its type, so wraparound can be told apart from a reset
+#[derive(Debug, Clone, Copy)]
+enum Count {
+    Integer { value: i128, min: i128, max: i128 },
+    Float(f64),
+}
+
+impl Count {
+    fn from_value(value: &Value) -> Option<Self> {
+        let integer = |value: i128, min: i128, max: i128| Some(Count::Integer { value, min, max });
+        match *value {
+            Value::Int8(v) => integer(v.into(), i8::MIN.into(), i8::MAX.into()),
+            Value::Int16(v) => integer(v.into(), i16::MIN.into(), i16::MAX.into()),
+            Value::Int32(v) => integer(v.into(), i32::MIN.into(), i32::MAX.into()),
+            Value::Int64(v) => integer(v.into(), i64::MIN.into(), i64::MAX.into()),
+            Value::UInt8(v) => integer(v.into(), 0, u8::MAX.into()),
+            Value::UInt16(v) => integer(v.into(), 0, u16::MAX.into()),
+            Value::UInt32(v) => integer(v.into(), 0, u32::MAX.into()),
+            Value::UInt64(v) => integer(v.into(), 0, u64::MAX.into()),
+            Value::Float32(v) => Some(Count::Float(v.into())),
+            Value::Float64(v) => Some(Count::Float(v)),
+            _ => None,
+        }
+    }
+
+    /// How much the counter advanced since `previous`. A counter that went from the top quarter of its range to the
+    /// bottom quarter wrapped around, any other decrease is a reset and counts from zero.
+    fn delta(self, previous: Count) -> f64 {
+        match (previous, self) {
+            (
+                Count::Integer {
+                    value: prev,
+                    min,
+                    max,
+                },
+                Count::Integer { value, .. },
+            ) => {
+                if value >= prev {
+                    return (value - prev) as f64;
+                }
+                let quarter = (max - min) / 4;
+                if prev > max - quarter && value < min + quarter {
+                    ((max - prev) + (value - min) + 1) as f64
+                } else {
+                    value.max(0) as f64
+                }
+            }
+            (Count::Float(prev), Count::Float(value)) if value >= prev => value - prev,
+            (_, Count::Float(value)) => value.max(0.0),
+            (_, Count::Integer { value, .. }) => value.max(0) as f64,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct CounterField {
+    name: String,
+    last: Option<(i64, Count)>,
+}
+
+/// The counters of an event and the name of the event their rates are emitted as
+#[derive(Debug)]
+struct EventCounters {
+    rate_event: Arc<str>,
+    fields: Vec<CounterField>,
+}
+
+/// Queues derived messages for the task that sends them to the router, without waiting for room. Rates that find the
+/// queue full are dropped and counted, schemas are held back until there is room since the rates after them need them.
+struct DerivedQueue {
+    sender: flume::Sender<IpcMessageWithId>,
+    schemas: VecDeque<IpcMessageWithId>,
+    dropped: u64,
+}
+
+impl DerivedQueue {
+    fn new(sender: flume::Sender<IpcMessageWithId>) -> Self {
+        Self {
+            sender,
+            schemas: VecDeque::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Queue `msg`, failing only if the task sending to the router has stopped
+    fn push(&mut self, msg: IpcMessageWithId) -> Result<()> {
+        // Schemas that were held back go first, rates queued ahead of them could not be read
+        while let Some(schema) = self.schemas.pop_front() {
+            if let Some(schema) = self.try_send(schema)? {
+                self.schemas.push_front(schema);
+                break;
+            }
+        }
+
+        let is_schema = matches!(msg.msg, IpcMessage::TraceEventSchema(_));
+        let full = if self.schemas.is_empty() {
+            self.try_send(msg)?
+        } else {
+            Some(msg)
+        };
+        match full {
+            Some(schema) if is_schema => self.schemas.push_back(schema),
+            Some(_) => self.drop_rate(),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Send `msg` if there is room, returning it if there is not
+    fn try_send(&self, msg: IpcMessageWithId) -> Result<Option<IpcMessageWithId>> {
+        match self.sender.try_send(msg) {
+            Ok(()) => Ok(None),
+            Err(flume::TrySendError::Full(msg)) => Ok(Some(msg)),
+            Err(flume::TrySendError::Disconnected(_)) => {
+                Err(anyhow!("Router stopped taking derived rates"))
+            }
+        }
+    }
+
+    fn drop_rate(&mut self) {
+        metrics::counter!("rates_dropped", "task" => "rates").increment(1);
+        self.dropped += 1;
+        if self.dropped.is_power_of_two() {
+            tracing::warn!(
+                "Dropped {} derived rates since the router is not keeping up",
+                self.dropped
+            );
+        }
+    }
+}
+
+/// Derives a rate signal for every counter field, emitted as a `rates/<event>` event alongside the counter's event in
+/// the same segment. The rate is the change per second between consecutive events, handling counters that wrap around
+/// at the limit of their type or reset to zero when a device restarts.
+#[derive(Debug, Default)]
+pub struct RateProcessor {
+    segments: HashMap<Uuid, HashMap<String, EventCounters>>,
+}
+
+impl RateProcessor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Process a routed message, returning the derived messages to route
+    pub fn process(&mut self, msg: &IpcMessageWithId) -> Vec<IpcMessageWithId> {
+        let derived = |derived_msg| IpcMessageWithId {
+            segment_id: msg.segment_id,
+            source_name: msg.source_name.clone(),
+            msg: derived_msg,
+        };
+        match &msg.msg {
+            IpcMessage::TraceEventSchema(schema) => self
+                .add_schema(msg.segment_id, schema)
+                .map(derived)
+                .into_iter()
+                .collect(),
+            IpcMessage::TraceEvent(event) => self
+                .rates(msg.segment_id, event)
+                .map(derived)
+                .into_iter()
+                .collect(),
+            IpcMessage::TraceSegmentEnd(_) => {
+                self.segments.remove(&msg.segment_id);
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Track the counters of `schema`, returning the schema of its rate event if it has any
+    fn add_schema(&mut self, segment_id: Uuid, schema: &TraceEventSchema) -> Option<IpcMessage> {
+        let events = self.segments.entry(segment_id).or_default();
+        let counters: Vec<_> = schema
+            .fields
+            .iter()
+            .filter(|field| field.kind == FieldKind::Counter)
+            .collect();
+        if counters.is_empty() {
+            events.remove(&schema.name);
+            return None;
+        }
+
+        let rate_event = format!("{}{}", RATE_EVENT_PREFIX, schema.name);
+        let fields = counters
+            .iter()
+            .map(|field| TraceEventFieldMetadata {
+                name: field.name.clone(),
+                data_type: DataType::Float64,
+                unit: Some(match &field.unit {
+                    Some(unit) => format!("{}/s", unit),
+                    None => "1/s".to_string(),
+                }),
+                kind: FieldKind::Gauge,
+                display: FieldDisplay {
+                    description: Some(format!("Rate of change of {}", field.name)),
+                    ..Default::default()
+                },
+            })
+            .collect();
+        events.insert(
+            schema.name.clone(),
+            EventCounters {
+                rate_event: rate_event.clone().into(),
+                fields: counters
+                    .iter()
+                    .map(|field| CounterField {
+                        name: field.name.clone(),
+                        last: None,
+                    })
+                    .collect(),
+            },
+        );
+        // Rates are versioned along with the event they are derived from
+        Some(IpcMessage::TraceEventSchema(TraceEventSchema {
+            name: rate_event,
+            version: schema.version,
+            fields,
+        }))
+    }
+
+    /// The rate event for `event`, if it has counters that were seen before
+    fn rates(&mut self, segment_id: Uuid, event: &TraceEvent) -> Option<IpcMessage> {
+        let counters = self.segments.get_mut(&segment_id)?.get_mut(&*event.name)?;
+        let mut rates = HashMap::new();
+        for field in counters.fields.iter_mut() {
+            let Some(count) = event.fields.get(&field.name).and_then(Count::from_value) else {
+                continue;
+            };
+            if let Some((last_time_ns, last_count)) = field.last {
+                let dt_ns = event.time_ns - last_time_ns;
+                if dt_ns <= 0 {
+                    continue;
+                }
+                let rate = count.delta(last_count) / (dt_ns as f64 / 1e9);
+                rates.insert(field.name.clone(), Value::Float64(rate));
+            }
+            field.last = Some((event.time_ns, count));
+        }
+        if rates.is_empty() {
+            return None;
+        }
+        Some(IpcMessage::TraceEvent(TraceEvent {
+            time_ns: event.time_ns,
+            name: counters.rate_event.clone(),
+            fields: rates.into(),
+        }))
+    }
+
+    /// Derive rates from every message routed through `router`, sending them back to it until `cancellation_token`
+    /// is cancelled. The processor must see every counter sample, so its subscription makes the router wait for it
+    /// rather than dropping it when it falls behind. Rates that cannot be sent back as fast as they are derived are
+    /// dropped. Fails if the router closes the subscription before cancellation.
+    pub async fn run(
+        router: Arc<TraceRouter>,
+        cancellation_token: CancellationToken,
+    ) -> Result<()> {
+        let (receiver, metadata) = router.subscribe_all_blocking().await?;
+
+        // The router waits for this subscription, so derived messages are queued for another task rather than waiting
+        // on a full router, which would be waiting on us
+        let (derived_sender, derived_receiver) = flume::bounded(DERIVED_CHANNEL_SIZE);
+        let mut queue = DerivedQueue::new(derived_sender);
+        let sender = router.sender();
+        tokio::spawn(async move {
+            while let Ok(derived) = derived_receiver.recv_async().await {
+                if sender.send_async(derived).await.is_err() {
+                    return;
+                }
+            }
+        });
+
+        let mut processor = Self::new();
+        for msg in metadata {
+            for derived in processor.process(&msg) {
+                queue.push(derived)?;
+            }
+        }
+
+        loop {
+            tokio::select! {
+                _ = cancellation_token.cancelled() => return Ok(()),
+                msg = receiver.recv_async() => {
+                    let Ok(msg) = msg else {
+                        if cancellation_token.is_cancelled() {
+                            return Ok(());
+                        }
+                        return Err(anyhow!("Router closed the rate processor's subscription"));
+                    };
+                    for derived in processor.process(&msg) {
+                        queue.push(derived)?;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn msg(segment_id: Uuid, msg: IpcMessage) -> IpcMessageWithId {
+        IpcMessageWithId {
+            segment_id,
+            source_name: "rig".into(),
+            msg,
+        }
+    }
+
+    fn event(segment_id: Uuid, time_ns: i64, packets: u8, temperature: f64) -> IpcMessageWithId {
+        msg(
+            segment_id,
+            IpcMessage::TraceEvent(TraceEvent {
+                time_ns,
+                name: "link".into(),
+                fields: HashMap::from([
+                    ("packets".to_string(), Value::UInt8(packets)),
+                    ("temperature".to_string(), Value::Float64(temperature)),
+                ])
+                .into(),
+            }),
+        )
+    }
+
+    fn rate(msgs: &[IpcMessageWithId]) -> Option<f64> {
+        match &msgs.first()?.msg {
+            IpcMessage::TraceEvent(event) => match event.fields.get("packets")? {
+                Value::Float64(rate) => Some(*rate),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn schema(segment_id: Uuid) -> IpcMessageWithId {
+        msg(
+            segment_id,
+            IpcMessage::TraceEventSchema(TraceEventSchema {
+                name: "link".to_string(),
+                version: 0,
+                fields: vec![
+                    TraceEventFieldMetadata {
+                        name: "packets".to_string(),
+                        data_type: DataType::UInt8,
+                        unit: Some("packets".to_string()),
+                        kind: FieldKind::Counter,
+                        display: FieldDisplay::default(),
+                    },
+                    TraceEventFieldMetadata {
+                        name: "temperature".to_string(),
+                        data_type: DataType::Float64,
+                        unit: Some("degC".to_string()),
+                        kind: FieldKind::Gauge,
+                        display: FieldDisplay::default(),
+                    },
+                ],
+            }),
+        )
+    }
+
+    #[test]
+    fn test_counter_rates() {
+        let segment_id = Uuid::now_v7();
+        let mut processor = RateProcessor::new();
+
+        let derived = processor.process(&schema(segment_id));
+        assert_eq!(derived.len(), 1);
+        assert_eq!(derived[0].segment_id, segment_id);
+        let IpcMessage::TraceEventSchema(schema) = &derived[0].msg else {
+            panic!("Expected a schema, got {:?}", derived[0].msg);
+        };
+        assert_eq!(schema.name, "rates/link");
+        assert_eq!(schema.fields.len(), 1);
+        assert_eq!(schema.fields[0].unit.as_deref(), Some("packets/s"));
+
+        // The first sample has nothing to compare against
+        assert!(processor
+            .process(&event(segment_id, 0, 10, 20.0))
+            .is_empty());
+
+        // 40 packets over half a second
+        let derived = processor.process(&event(segment_id, 500_000_000, 50, 21.0));
+        assert_eq!(rate(&derived), Some(80.0));
+
+        // Wrapping from 250 past 255 to 4 is 10 packets
+        processor.process(&event(segment_id, 1_000_000_000, 250, 21.0));
+        let derived = processor.process(&event(segment_id, 2_000_000_000, 4, 21.0));
+        assert_eq!(rate(&derived), Some(10.0));
+
+        // Dropping from the middle of the range is a reset, so every packet since is counted
+        processor.process(&event(segment_id, 3_000_000_000, 120, 21.0));
+        let derived = processor.process(&event(segment_id, 4_000_000_000, 3, 21.0));
+        assert_eq!(rate(&derived), Some(3.0));
+
+        // The state goes with the segment
+        processor.process(&msg(
+            segment_id,
+            IpcMessage::TraceSegmentEnd(zelos_trace_types::ipc::TraceSegmentEnd {
+                time_ns: 5_000_000_000,
+            }),
+        ));
+        assert!(processor
+            .process(&event(segment_id, 6_000_000_000, 9, 21.0))
+            .is_empty());
+    }
+
+    #[test]
+    fn test_derived_queue() -> Result<()> {
+        let segment_id = Uuid::now_v7();
+        let mut processor = RateProcessor::new();
+        let schema = processor.process(&schema(segment_id))[0].clone();
+        processor.process(&event(segment_id, 0, 10, 20.0));
+        let rate = processor.process(&event(segment_id, 1_000_000_000, 20, 20.0))[0].clone();
+
+        let (sender, receiver) = flume::bounded(1);
+        let mut queue = DerivedQueue::new(sender);
+        queue.push(schema.clone())?;
+
+        // Rates are dropped while the queue is full, schemas wait for room
+        queue.push(rate.clone())?;
+        queue.push(schema.clone())?;
+        queue.push(rate.clone())?;
+        assert_eq!(queue.dropped, 2);
+        assert_eq!(queue.schemas.len(), 1);
+        assert!(matches!(
+            receiver.try_recv()?.msg,
+            IpcMessage::TraceEventSchema(_)
+        ));
+
+        // The held back schema takes the room before the next rate
+        queue.push(rate.clone())?;
+        assert_eq!(queue.dropped, 3);
+        assert!(matches!(
+            receiver.try_recv()?.msg,
+            IpcMessage::TraceEventSchema(_)
+        ));
+        queue.push(rate)?;
+        assert!(matches!(
+            receiver.try_recv()?.msg,
+            IpcMessage::TraceEvent(_)
+        ));
+
+        drop(receiver);
+        assert!(queue.push(schema).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run() -> Result<()> {
+        let router_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(router_token.clone());
+        let router_task = tokio::spawn(run);
+        let processor_token = CancellationToken::new();
+        let processor = tokio::spawn(RateProcessor::run(router.clone(), processor_token.clone()));
+
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        sink.subscribe(crate::filter::Filter::parse("*/rig/rates/link")?)
+            .await;
+
+        // Samples sent before the processor subscribed are missed, so keep counting until rates come back
+        let segment_id = Uuid::now_v7();
+        let sender = router.sender();
+        sender.send_async(schema(segment_id)).await?;
+        let mut derived = None;
+        for second in 0..100u8 {
+            sender
+                .send_async(event(
+                    segment_id,
+                    second as i64 * 1_000_000_000,
+                    second,
+                    20.0,
+                ))
+                .await?;
+            let msg = tokio::time::timeout(Duration::from_millis(10), receiver.recv_async()).await;
+            if let Ok(msg) = msg {
+                derived = Some(msg?);
+                break;
+            }
+        }
+        let Some(derived) = derived else {
+            panic!("No rates were derived");
+        };
+        assert_eq!(rate(&[derived]), Some(1.0));
+
+        // The subscription ending without cancellation is an error
+        router_token.cancel();
+        router_task.await??;
+        assert!(processor.await?.is_err());
+        Ok(())
+    }
+}
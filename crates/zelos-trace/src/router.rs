@@ -1,24 +1,163 @@
-use std::{future::Future, sync::Arc};
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
 
 use anyhow::Result;
-use tokio::{sync::oneshot, time::Instant};
+use tokio::{
+    sync::{broadcast, oneshot, Notify, RwLock},
+    time::Instant,
+};
 use tokio_stream::{Stream, StreamExt};
 use tokio_util::sync::CancellationToken;
-use zelos_trace_types::ipc::{IpcMessageWithId, Receiver, Sender};
+use uuid::Uuid;
+use zelos_trace_types::{
+    ipc::{
+        IpcMessage, IpcMessageWithId, Receiver, Sender, TraceActionCall, TraceControl,
+        TraceControlCommand,
+    },
+    Value,
+};
 
 use crate::{
+    action::{ActionCallError, PendingCalls},
+    filter::{Filter, SegmentContext, SegmentContexts},
+    parameter::{PARAMETER_EVENT_PREFIX, PARAMETER_VALUE_FIELD},
+    segment::TraceSegment,
     sink::{TraceSinkHandle, TraceSinkHandleAllBlocking},
-    MetadataOnlyStore, Store, TraceSink,
+    MetadataOnlyStore, Store, TraceMetadata, TraceSink,
 };
 
 // TODO(tkeairns): Ground this constant into some relationship with # msgs/sec
 pub const DEFAULT_CHANNEL_SIZE: usize = 1024;
 
+/// Number of control commands kept for followers that fall behind
+const CONTROL_CHANNEL_SIZE: usize = 64;
+
+/// A suggested size for [`TraceRouterConfig::replay_buffer_bytes`]
+pub const DEFAULT_REPLAY_BUFFER_BYTES: usize = 64 * 1024 * 1024;
+
+/// Options for a [`TraceRouter`]
+#[derive(Debug, Clone, Default)]
+pub struct TraceRouterConfig {
+    /// Approximate number of bytes of recent events, logs and segment ends kept for subscribers that start from an
+    /// earlier time, such as clients resuming after a reconnect. With 0, the default, nothing is kept and such
+    /// subscribers only get the metadata.
+    pub replay_buffer_bytes: usize,
+}
+
 /// Subscription requests sent to the router's main task
-type SubscriptionRequest = (
-    Box<dyn TraceSinkHandle>,
-    oneshot::Sender<Result<Vec<IpcMessageWithId>>>,
-);
+pub(crate) enum SubscriptionRequest {
+    /// Add a sink, responding with the metadata to send it first
+    Subscribe(
+        Box<dyn TraceSinkHandle>,
+        oneshot::Sender<Result<Vec<IpcMessageWithId>>>,
+    ),
+    /// Add a filter to a sink, responding with the recent messages it matches
+    Replay(ReplayRequest),
+}
+
+/// Asks the router's main task to add `filter` to a sink and return the buffered messages it matches from
+/// `start_time_ns` onwards
+pub(crate) struct ReplayRequest {
+    pub filters: Arc<RwLock<Vec<Filter>>>,
+    pub filter: Filter,
+    pub start_time_ns: i64,
+    pub response: oneshot::Sender<Vec<IpcMessageWithId>>,
+}
+
+/// The most recent messages that are not part of the metadata replayed to every subscriber, along with the context
+/// of their segment when they were forwarded and their approximate size
+struct ReplayBuffer {
+    capacity_bytes: usize,
+    bytes: usize,
+    messages: VecDeque<(IpcMessageWithId, Arc<SegmentContext>, usize)>,
+}
+
+impl ReplayBuffer {
+    fn new(capacity_bytes: usize) -> Self {
+        Self {
+            capacity_bytes,
+            bytes: 0,
+            messages: VecDeque::new(),
+        }
+    }
+
+    fn push(&mut self, msg: &IpcMessageWithId, segment_contexts: &SegmentContexts) {
+        // Segment starts, schemas, sessions, annotations and open intervals are in the store's metadata
+        if self.capacity_bytes == 0
+            || !matches!(
+                msg.msg,
+                IpcMessage::TraceEvent(_) | IpcMessage::Log(_) | IpcMessage::TraceSegmentEnd(_)
+            )
+        {
+            return;
+        }
+        let size = approximate_size(msg);
+        if size > self.capacity_bytes {
+            return;
+        }
+        while self.bytes + size > self.capacity_bytes {
+            let Some((_, _, evicted)) = self.messages.pop_front() else {
+                break;
+            };
+            self.bytes -= evicted;
+        }
+        self.bytes += size;
+        self.messages.push_back((
+            msg.clone(),
+            segment_contexts.snapshot(&msg.segment_id),
+            size,
+        ));
+    }
+
+    /// The buffered messages matching `filter` from `start_time_ns` onwards
+    fn replay(&self, filter: &Filter, start_time_ns: i64) -> Vec<IpcMessageWithId> {
+        self.messages
+            .iter()
+            .filter(|(msg, segment, _)| {
+                msg.msg.time_ns().is_some_and(|t| t >= start_time_ns)
+                    && filter.matches(msg, segment)
+            })
+            .map(|(msg, _, _)| msg.clone())
+            .collect()
+    }
+}
+
+/// Roughly how much memory a buffered message holds, counting the values of events and the text of logs
+fn approximate_size(msg: &IpcMessageWithId) -> usize {
+    let value_size = |value: &Value| {
+        std::mem::size_of::<Value>()
+            + match value {
+                Value::Binary(bytes) => bytes.len(),
+                Value::String(string) => string.len(),
+                _ => 0,
+            }
+    };
+    let payload = match &msg.msg {
+        IpcMessage::TraceEvent(event) => event
+            .fields
+            .iter()
+            .map(|(name, value)| name.len() + value_size(value))
+            .sum(),
+        IpcMessage::Log(log) => {
+            log.target.len()
+                + log.message.len()
+                + log
+                    .fields
+                    .iter()
+                    .map(|(name, value)| name.len() + value_size(value))
+                    .sum::<usize>()
+        }
+        _ => 0,
+    };
+    std::mem::size_of::<IpcMessageWithId>() + payload
+}
 
 /// Pub-sub router for trace data
 pub struct TraceRouter {
@@ -27,6 +166,37 @@ pub struct TraceRouter {
 
     // Channel for subscription requests
     subscription_sender: flume::Sender<SubscriptionRequest>,
+
+    // Notified when a sink is dropped, so the main task can remove it
+    sink_closed: Arc<Notify>,
+
+    // Set while the main task is running
+    running: Arc<AtomicBool>,
+
+    // Control commands for the sources publishing to this router, see [`crate::TraceSource::follow_controls`]
+    controls: broadcast::Sender<TraceControl>,
+
+    // Action calls waiting for the result to come back through the router
+    pending_calls: PendingCalls,
+
+    // The store updated by the main task
+    store: Arc<dyn Store>,
+}
+
+/// Marks the router as running for as long as the main task holds it
+struct RunningGuard(Arc<AtomicBool>);
+
+impl RunningGuard {
+    fn new(running: Arc<AtomicBool>) -> Self {
+        running.store(true, Ordering::Relaxed);
+        Self(running)
+    }
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
 }
 
 impl TraceRouter {
@@ -42,6 +212,15 @@ impl TraceRouter {
     pub fn new_with_store(
         store: Arc<dyn Store>,
         cancellation_token: CancellationToken,
+    ) -> (Arc<Self>, impl Future<Output = Result<()>>) {
+        Self::new_with_config(store, TraceRouterConfig::default(), cancellation_token)
+    }
+
+    /// Create a new trace router with a specific store implementation and options
+    pub fn new_with_config(
+        store: Arc<dyn Store>,
+        config: TraceRouterConfig,
+        cancellation_token: CancellationToken,
     ) -> (Arc<Self>, impl Future<Output = Result<()>>) {
         // Initialize the channel for receiving trace streams.
         let (sender, receiver) = flume::bounded(DEFAULT_CHANNEL_SIZE);
@@ -49,34 +228,108 @@ impl TraceRouter {
         // Initialize the channel for subscription requests
         let (subscription_sender, subscription_receiver) = flume::bounded(1);
 
+        // Initialize the notification for closed sinks
+        let sink_closed = Arc::new(Notify::new());
+
+        let running = Arc::new(AtomicBool::new(false));
+
+        let (controls, _) = broadcast::channel(CONTROL_CHANNEL_SIZE);
+        let pending_calls = PendingCalls::default();
+
         let router = TraceRouter {
             sender,
             subscription_sender,
+            sink_closed: sink_closed.clone(),
+            running: running.clone(),
+            controls,
+            pending_calls: pending_calls.clone(),
+            store: store.clone(),
         };
 
         // Spawn the router's main task
-        let run = TraceRouter::run(receiver, subscription_receiver, store, cancellation_token);
+        let run = TraceRouter::run(
+            receiver,
+            subscription_receiver,
+            sink_closed,
+            store,
+            pending_calls,
+            running,
+            ReplayBuffer::new(config.replay_buffer_bytes),
+            cancellation_token,
+        );
 
         (Arc::new(router), run)
     }
 
     async fn forward_message(
         store: &Arc<dyn Store>,
+        pending_calls: &PendingCalls,
         sinks: &mut Vec<Box<dyn TraceSinkHandle>>,
+        segment_contexts: &mut SegmentContexts,
+        replay_buffer: &mut ReplayBuffer,
         msg: IpcMessageWithId,
     ) {
-        // Update the store
+        // Hand action results to their callers, they are still forwarded so subscribers can see them
+        if let IpcMessage::TraceActionResult(result) = &msg.msg {
+            pending_calls.resolve(result);
+        }
+
+        // Update the store, dropping messages it rejects so subscribers never see metadata the store does not have
         if let Err(e) = store.update(&msg) {
-            tracing::error!("Error while updating the store: {}", e);
+            tracing::error!(
+                "Dropping message from {} rejected by the store: {}",
+                msg.source_name,
+                e
+            );
+            return;
         }
 
+        // Keep the attributes and sessions of open segments, so sinks can filter on them
+        let before = segment_contexts.snapshot(&msg.segment_id);
+        segment_contexts.update(&msg);
+        replay_buffer.push(&msg, segment_contexts);
+
+        // A segment joining a session already sent its start and schemas, so sinks that only match it now get them
+        // from the store before the join
+        let joined = match &msg.msg {
+            IpcMessage::TraceSessionJoin(join) if !before.sessions.contains(&join.session_id) => {
+                store
+                    .shared_metadata()
+                    .and_then(|metadata| metadata.get_segment(&msg.segment_id))
+                    .map(|segment| {
+                        segment
+                            .as_ipc()
+                            .into_iter()
+                            .map(|replayed| IpcMessageWithId {
+                                segment_id: msg.segment_id,
+                                source_name: msg.source_name.clone(),
+                                msg: replayed,
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default()
+            }
+            _ => Vec::new(),
+        };
+
         // Forward this message to all subscribers
         let mut closed_sinks = Vec::new();
         {
+            let segment = segment_contexts.get(&msg.segment_id);
             metrics::gauge!("router_sinks", "task" => "router").set(sinks.len() as f64);
 
             for (idx, sink) in sinks.iter().enumerate() {
-                if let Err(e) = sink.send_async(&msg).await {
+                let mut result = Ok(());
+                for replayed in &joined {
+                    result = sink.send_newly_matched(replayed, &before, segment).await;
+                    if result.is_err() {
+                        break;
+                    }
+                }
+                if result.is_ok() {
+                    result = sink.send_async(&msg, segment).await;
+                }
+                if let Err(e) = result {
                     tracing::trace!("Error when sending on sink: {}", e);
                     // If we have an error here, this means that the sink is no longer
                     // available, so we add it to the list of sinks to remove
@@ -85,6 +338,8 @@ impl TraceRouter {
             }
         }
 
+        segment_contexts.remove_ended(&msg);
+
         // Remove all closed sinks
         if !closed_sinks.is_empty() {
             // Sort in reverse order so we can remove from highest index to lowest
@@ -111,29 +366,55 @@ impl TraceRouter {
         }
     }
 
+    async fn handle_replay(replay_buffer: &ReplayBuffer, request: ReplayRequest) {
+        // Add the filter before anything else is forwarded, so the live messages pick up where the replay ends
+        let messages = replay_buffer.replay(&request.filter, request.start_time_ns);
+        request.filters.write().await.push(request.filter);
+
+        if request.response.send(messages).is_err() {
+            tracing::debug!("Subscriber went away before its replay was sent");
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn run(
         receiver: Receiver,
         subscription_receiver: flume::Receiver<SubscriptionRequest>,
+        sink_closed: Arc<Notify>,
         store: Arc<dyn Store>,
+        pending_calls: PendingCalls,
+        running: Arc<AtomicBool>,
+        mut replay_buffer: ReplayBuffer,
         cancellation_token: CancellationToken,
     ) -> Result<()> {
         // Construct task-local state
+        let _running = RunningGuard::new(running);
         let mut sinks = Vec::new();
+        let mut segment_contexts = SegmentContexts::default();
 
         loop {
             tokio::select! {
                 // Handle subscription requests
                 sub_req = subscription_receiver.recv_async() => {
                     match sub_req {
-                        Ok((handle, sub_response_sender)) => {
+                        Ok(SubscriptionRequest::Subscribe(handle, sub_response_sender)) => {
                             Self::handle_subscribe(&store, &mut sinks, handle, sub_response_sender).await;
                         }
+                        Ok(SubscriptionRequest::Replay(request)) => {
+                            Self::handle_replay(&replay_buffer, request).await;
+                        }
                         Err(_) => {
                             break;
                         }
                     }
                 }
 
+                // Remove sinks as soon as their clients go away, rather than waiting for the next failed send
+                _ = sink_closed.notified() => {
+                    sinks.retain(|sink| !sink.is_closed());
+                    metrics::gauge!("router_sinks", "task" => "router").set(sinks.len() as f64);
+                }
+
                 msg = receiver.recv_async() => {
                     let msg = msg?;
 
@@ -143,7 +424,15 @@ impl TraceRouter {
 
                     // Update our state and forward
                     let start = Instant::now();
-                    TraceRouter::forward_message(&store, &mut sinks, msg).await;
+                    TraceRouter::forward_message(
+                        &store,
+                        &pending_calls,
+                        &mut sinks,
+                        &mut segment_contexts,
+                        &mut replay_buffer,
+                        msg,
+                    )
+                    .await;
                     let elapsed = start.elapsed();
 
                     metrics::histogram!("update_store_duration_ns", "task" => "router")
@@ -156,7 +445,15 @@ impl TraceRouter {
                     let start = Instant::now();
                     let mut count: usize = 0;
                     for msg in receiver.drain() {
-                        TraceRouter::forward_message(&store, &mut sinks, msg).await;
+                        TraceRouter::forward_message(
+                            &store,
+                            &pending_calls,
+                            &mut sinks,
+                            &mut segment_contexts,
+                            &mut replay_buffer,
+                            msg,
+                        )
+                        .await;
                         count += 1;
                     }
                     let elapsed = start.elapsed();
@@ -174,13 +471,166 @@ impl TraceRouter {
         self.sender.clone()
     }
 
+    /// Send a control command to the sources following this router's controls, and to any publishers connected to a
+    /// publish service that forwards them. Returns how many followers received it, commands are dropped if nothing is
+    /// following.
+    pub fn send_control(&self, control: TraceControl) -> usize {
+        tracing::debug!("Sending control {:?}", control);
+        self.controls.send(control).unwrap_or(0)
+    }
+
+    /// Receive the control commands sent to this router
+    pub fn controls(&self) -> broadcast::Receiver<TraceControl> {
+        self.controls.subscribe()
+    }
+
+    /// The sender for this router's control commands, for forwarding commands to it from elsewhere
+    pub fn control_sender(&self) -> broadcast::Sender<TraceControl> {
+        self.controls.clone()
+    }
+
+    /// Set a parameter of the source named `source_name`, which may be a local source following this router's
+    /// controls or one behind a connected publisher, and wait up to `timeout` for the source to answer. Like
+    /// [`TraceRouter::call_action`], only the segment `segment_id` or the only live segment of the source with the
+    /// parameter is set. Returns the value the source accepted, which is also published as the parameter's event, or
+    /// [`ActionCallError::Failed`] if the value has the wrong type or is out of range.
+    pub async fn set_parameter(
+        &self,
+        source_name: &str,
+        name: &str,
+        value: Value,
+        segment_id: Option<Uuid>,
+        timeout: Duration,
+    ) -> Result<Value, ActionCallError> {
+        let event_name = format!("{}{}", PARAMETER_EVENT_PREFIX, name);
+        let segment_id = self.target_segment(source_name, segment_id, |segment| {
+            segment.schemas.contains_key(&event_name)
+        })?;
+
+        let call_id = Uuid::now_v7();
+        let command = TraceControlCommand::SetParameter {
+            name: name.to_string(),
+            value,
+            call_id: Some(call_id),
+            segment_id: Some(segment_id),
+        };
+        let mut values = self.request(source_name, call_id, command, timeout).await?;
+        values
+            .remove(PARAMETER_VALUE_FIELD)
+            .ok_or_else(|| ActionCallError::Failed("Missing the accepted value".to_string()))
+    }
+
+    /// Call an action of the source named `source_name`, which may be a local source following this router's controls
+    /// or one behind a connected publisher, and wait up to `timeout` for its result. Only one instance of the source
+    /// runs the call: the segment `segment_id`, or if `None` the only live segment of the source that has the action
+    /// according to the router's metadata.
+    pub async fn call_action(
+        &self,
+        source_name: &str,
+        action_name: &str,
+        arguments: HashMap<String, Value>,
+        segment_id: Option<Uuid>,
+        timeout: Duration,
+    ) -> Result<HashMap<String, Value>, ActionCallError> {
+        let segment_id = self.target_segment(source_name, segment_id, |segment| {
+            segment.actions.contains_key(action_name)
+        })?;
+
+        let call_id = Uuid::now_v7();
+        let command = TraceControlCommand::CallAction(TraceActionCall {
+            call_id,
+            action_name: action_name.to_string(),
+            arguments,
+            segment_id: Some(segment_id),
+        });
+        self.request(source_name, call_id, command, timeout).await
+    }
+
+    /// The segment a request to `source_name` goes to: `segment_id` if given, or else the only live segment of the
+    /// source for which `has` is true
+    fn target_segment(
+        &self,
+        source_name: &str,
+        segment_id: Option<Uuid>,
+        has: impl Fn(&TraceSegment) -> bool,
+    ) -> Result<Uuid, ActionCallError> {
+        if self.controls.receiver_count() == 0 {
+            return Err(ActionCallError::NoSources);
+        }
+        if let Some(segment_id) = segment_id {
+            return Ok(segment_id);
+        }
+
+        let metadata = self.metadata().ok_or(ActionCallError::NotFound)?;
+        let mut segment_ids: Vec<_> = metadata
+            .segments_iter()
+            .filter(|segment| {
+                segment.source == source_name && segment.end_time.is_none() && has(segment)
+            })
+            .map(|segment| segment.id)
+            .collect();
+        match segment_ids.len() {
+            0 => Err(ActionCallError::NotFound),
+            1 => Ok(segment_ids.remove(0)),
+            _ => {
+                segment_ids.sort();
+                Err(ActionCallError::Ambiguous(segment_ids))
+            }
+        }
+    }
+
+    /// Send `command` to `source_name` and wait up to `timeout` for the result answering `call_id`
+    async fn request(
+        &self,
+        source_name: &str,
+        call_id: Uuid,
+        command: TraceControlCommand,
+        timeout: Duration,
+    ) -> Result<HashMap<String, Value>, ActionCallError> {
+        let result = self.pending_calls.register(call_id);
+        self.send_control(TraceControl {
+            source_name: Some(source_name.to_string()),
+            command,
+        });
+
+        match tokio::time::timeout(timeout, result).await {
+            Ok(Ok(result)) => result.result.map_err(ActionCallError::Failed),
+            _ => {
+                self.pending_calls.cancel(&call_id);
+                Err(ActionCallError::TimedOut(timeout))
+            }
+        }
+    }
+
+    /// The metadata of the segments and sessions seen by this router, if its store keeps it. It is updated as messages
+    /// are routed, so it trails messages still waiting in the router's channel.
+    pub fn metadata(&self) -> Option<TraceMetadata> {
+        self.store.shared_metadata()
+    }
+
+    /// Whether the router's main task is currently running
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// How full the router's input channel is, from 0.0 (empty) to 1.0 (publishers are being blocked)
+    pub fn saturation(&self) -> f64 {
+        match self.sender.capacity() {
+            Some(capacity) if capacity > 0 => self.sender.len() as f64 / capacity as f64,
+            _ => 0.0,
+        }
+    }
+
     /// Subscribe to all data, applying backpressure when needed
     pub async fn subscribe_all_blocking(&self) -> Result<(Receiver, Vec<IpcMessageWithId>)> {
         let (handle, receiver) = TraceSinkHandleAllBlocking::new();
         let (sub_response_sender, sub_response_receiver) = oneshot::channel();
 
         self.subscription_sender
-            .send_async((Box::new(handle), sub_response_sender))
+            .send_async(SubscriptionRequest::Subscribe(
+                Box::new(handle),
+                sub_response_sender,
+            ))
             .await
             .map_err(|_| anyhow::anyhow!("Router subscription channel closed"))?;
 
@@ -194,11 +644,15 @@ impl TraceRouter {
 
     /// Subscribe to trace streams
     pub async fn subscribe(&self) -> Result<(TraceSink, Receiver, Vec<IpcMessageWithId>)> {
-        let (sink, receiver, handle) = TraceSink::new();
+        let (sink, receiver, handle) =
+            TraceSink::new(self.sink_closed.clone(), self.subscription_sender.clone());
         let (sub_response_sender, sub_response_receiver) = oneshot::channel();
 
         self.subscription_sender
-            .send_async((Box::new(handle), sub_response_sender))
+            .send_async(SubscriptionRequest::Subscribe(
+                Box::new(handle),
+                sub_response_sender,
+            ))
             .await
             .map_err(|_| anyhow::anyhow!("Router subscription channel closed"))?;
 
@@ -225,3 +679,570 @@ impl TraceRouter {
         Ok((sink, stream))
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use zelos_trace_types::ipc::LogLevel;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_dropped_sink_is_removed() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        assert!(!receiver.is_disconnected());
+
+        // Dropping the sink should remove its handle without any further messages being routed
+        drop(sink);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !receiver.is_disconnected() {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_running_and_saturation() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        assert!(!router.is_running());
+
+        // Nothing drains the channel until the task is spawned
+        let sender = router.sender();
+        let msg = IpcMessageWithId {
+            segment_id: uuid::Uuid::now_v7(),
+            source_name: "test".into(),
+            msg: zelos_trace_types::ipc::TraceSegmentEnd { time_ns: 0 }.into(),
+        };
+        for _ in 0..DEFAULT_CHANNEL_SIZE / 2 {
+            sender.send_async(msg.clone()).await?;
+        }
+        assert_eq!(router.saturation(), 0.5);
+
+        let task = tokio::spawn(run);
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !router.is_running() || router.saturation() > 0.0 {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+
+        cancellation_token.cancel();
+        task.await??;
+        assert!(!router.is_running());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_call_action() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+        let timeout = Duration::from_secs(1);
+
+        let call =
+            |action_name: &'static str, arguments: Vec<(&str, Value)>, segment_id: Option<Uuid>| {
+                let arguments = arguments
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect();
+                router.call_action("rig", action_name, arguments, segment_id, timeout)
+            };
+        // Calls are routed by the router's metadata, which trails the sources' messages
+        let wait_for_segments = |count: usize| {
+            let router = router.clone();
+            async move {
+                let live_segments = || {
+                    router.metadata().map_or(0, |metadata| {
+                        metadata
+                            .segments_iter()
+                            .filter(|segment| {
+                                segment.end_time.is_none() && segment.actions.contains_key("add")
+                            })
+                            .count()
+                    })
+                };
+                tokio::time::timeout(timeout, async {
+                    while live_segments() < count {
+                        tokio::time::sleep(Duration::from_millis(1)).await;
+                    }
+                })
+                .await
+            }
+        };
+        assert_eq!(
+            call("add", vec![], None).await,
+            Err(ActionCallError::NoSources)
+        );
+
+        let (release, released) = flume::bounded::<()>(1);
+        let add_source = || -> Result<Arc<crate::TraceSource>> {
+            let source = Arc::new(crate::TraceSource::new("rig", router.sender()));
+            source
+                .build_action("add")
+                .add_parameter("a", zelos_trace_types::DataType::Int32, None)
+                .add_parameter("b", zelos_trace_types::DataType::Int32, None)
+                .add_return("sum", zelos_trace_types::DataType::Int32, None)
+                .build(|arguments| {
+                    let (Some(Value::Int32(a)), Some(Value::Int32(b))) =
+                        (arguments.get("a"), arguments.get("b"))
+                    else {
+                        anyhow::bail!("Missing arguments");
+                    };
+                    Ok([("sum".to_string(), Value::Int32(a + b))].into())
+                })?;
+            tokio::spawn(source.follow_controls(router.controls()));
+            Ok(source)
+        };
+        let source = add_source()?;
+        let released = released.clone();
+        source.build_action("wait").build(move |_| {
+            released.recv()?;
+            Ok(HashMap::new())
+        })?;
+        wait_for_segments(1).await?;
+
+        let values = call(
+            "add",
+            vec![("a", Value::Int32(1)), ("b", Value::Int32(2))],
+            None,
+        )
+        .await;
+        assert_eq!(values, Ok([("sum".to_string(), Value::Int32(3))].into()));
+
+        // A source cannot register an action twice
+        assert!(source
+            .build_action("add")
+            .build(|_| Ok(HashMap::new()))
+            .is_err());
+
+        // A blocked handler does not hold up other calls
+        let waiting = tokio::spawn({
+            let router = router.clone();
+            async move {
+                router
+                    .call_action("rig", "wait", HashMap::new(), None, timeout)
+                    .await
+            }
+        });
+        let values = call(
+            "add",
+            vec![("a", Value::Int32(2)), ("b", Value::Int32(2))],
+            None,
+        )
+        .await;
+        assert_eq!(values, Ok([("sum".to_string(), Value::Int32(4))].into()));
+        release.send(())?;
+        assert_eq!(waiting.await?, Ok(HashMap::new()));
+
+        // Bad calls are rejected by the source
+        let wrong_type = call(
+            "add",
+            vec![("a", Value::Int32(1)), ("b", Value::Int64(2))],
+            None,
+        )
+        .await;
+        assert!(matches!(wrong_type, Err(ActionCallError::Failed(_))));
+        let unknown = call("reset", vec![], Some(source.id)).await;
+        assert!(matches!(unknown, Err(ActionCallError::Failed(_))));
+
+        // Calls to actions no live segment has are not sent, and nobody answers for a segment that does not exist
+        assert_eq!(
+            call("reset", vec![], None).await,
+            Err(ActionCallError::NotFound)
+        );
+        let missing = router
+            .call_action(
+                "rig",
+                "add",
+                HashMap::new(),
+                Some(Uuid::now_v7()),
+                Duration::from_millis(50),
+            )
+            .await;
+        assert!(matches!(missing, Err(ActionCallError::TimedOut(_))));
+
+        // With a second instance of the source, calls have to name the segment that runs them
+        let other = add_source()?;
+        wait_for_segments(2).await?;
+        let Err(ActionCallError::Ambiguous(segment_ids)) = call("add", vec![], None).await else {
+            panic!("Expected an ambiguous call");
+        };
+        assert_eq!(segment_ids.len(), 2);
+        assert!(segment_ids.contains(&other.id));
+        let values = call(
+            "add",
+            vec![("a", Value::Int32(3)), ("b", Value::Int32(4))],
+            Some(other.id),
+        )
+        .await;
+        assert_eq!(values, Ok([("sum".to_string(), Value::Int32(7))].into()));
+        let for_other = TraceActionCall {
+            call_id: Uuid::now_v7(),
+            action_name: "add".to_string(),
+            arguments: HashMap::new(),
+            segment_id: Some(other.id),
+        };
+        assert!(!source.call_action(&for_other)?);
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_parameter() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+        let timeout = Duration::from_secs(1);
+
+        let source = Arc::new(crate::TraceSource::new("rig", router.sender()));
+        let gain = source.add_parameter(
+            "gain",
+            Value::Float64(0.5),
+            Some(Value::Float64(0.0)),
+            Some(Value::Float64(1.0)),
+            None,
+        )?;
+        tokio::spawn(source.follow_controls(router.controls()));
+        tokio::time::timeout(timeout, async {
+            while router
+                .metadata()
+                .and_then(|metadata| metadata.get_segment(&source.id))
+                .is_none_or(|segment| !segment.schemas.contains_key("parameters/gain"))
+            {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await?;
+
+        // The source answers with the value it accepted, or why it rejected it
+        let set = |name: &'static str, value: f64, segment_id: Option<Uuid>| {
+            router.set_parameter("rig", name, Value::Float64(value), segment_id, timeout)
+        };
+        assert_eq!(set("gain", 0.75, None).await, Ok(Value::Float64(0.75)));
+        assert_eq!(gain.get(), Value::Float64(0.75));
+        assert!(matches!(
+            set("gain", 2.0, None).await,
+            Err(ActionCallError::Failed(_))
+        ));
+        assert_eq!(gain.get(), Value::Float64(0.75));
+
+        // Unknown parameters are not sent, or rejected by the source if the segment is named
+        assert_eq!(
+            set("offset", 1.0, None).await,
+            Err(ActionCallError::NotFound)
+        );
+        assert!(matches!(
+            set("offset", 1.0, Some(source.id)).await,
+            Err(ActionCallError::Failed(_))
+        ));
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_attributes() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        sink.subscribe(crate::filter::Filter::parse("*/*/*?run=42")?)
+            .await;
+
+        let matching = crate::TraceSource::builder("rig")
+            .add_attribute("run", "42")
+            .app_version("1.2.3")
+            .build(router.sender());
+        let other = crate::TraceSource::builder("rig")
+            .add_attribute("run", "43")
+            .build(router.sender());
+        assert_eq!(
+            matching.attributes.get("pid"),
+            Some(&std::process::id().to_string())
+        );
+        assert_eq!(
+            matching.attributes.get("app_version").map(String::as_str),
+            Some("1.2.3")
+        );
+        let matching_id = matching.id;
+        drop(other);
+        drop(matching);
+
+        // Only the start and end of the segment with the matching attribute are received
+        let msgs: Vec<_> = tokio::time::timeout(Duration::from_secs(1), async {
+            receiver.stream().take(2).collect::<Vec<_>>().await
+        })
+        .await?;
+        assert!(msgs.iter().all(|msg| msg.segment_id == matching_id));
+        assert!(
+            matches!(&msgs[0].msg, IpcMessage::TraceSegmentStart(start) if start.attributes["run"] == "42")
+        );
+        assert!(receiver.is_empty());
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_logs() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        sink.subscribe(crate::filter::Filter::parse(
+            "*/app/motor::can?$level=warn",
+        )?)
+        .await;
+
+        // Options do not hide attributes with the same name
+        let filter = crate::filter::Filter::parse("*/*/*?level=3&$level=warn")?;
+        assert_eq!(
+            filter.attributes.get("level").map(String::as_str),
+            Some("3")
+        );
+        assert_eq!(filter.log_level, Some(LogLevel::Warn));
+        assert!(crate::filter::Filter::parse("*/*/*?$sesion=1").is_err());
+
+        // Logs match the event name by their target
+        let source = crate::TraceSource::new("app", router.sender());
+        source.log(LogLevel::Info, "motor::can", "Bus load 40%", [])?;
+        source.log(LogLevel::Warn, "motor::can", "Bus off", [])?;
+        source.log(LogLevel::Error, "motor::pwm", "Overcurrent", [])?;
+        drop(source);
+
+        let msg = tokio::time::timeout(Duration::from_secs(1), receiver.recv_async()).await??;
+        assert!(
+            matches!(&msg.msg, IpcMessage::Log(log) if log.level == LogLevel::Warn && log.message == "Bus off")
+        );
+        assert!(receiver.is_empty());
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_open_intervals() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let (early, _metadata) = router.subscribe_all_blocking().await?;
+        let source = crate::TraceSource::new("state-machine", router.sender());
+        let running = source.begin_interval("RUNNING", [])?;
+        let homing = running.begin_child("HOMING", [("axis".to_string(), Value::UInt8(1))])?;
+        let init = source.begin_interval("INIT", [])?;
+        init.end([("ok".to_string(), Value::Boolean(true))])?;
+
+        // Wait until the router forwarded every message
+        tokio::time::timeout(Duration::from_secs(1), async {
+            early.stream().take(5).collect::<Vec<_>>().await
+        })
+        .await?;
+
+        // A late subscriber learns about the intervals that are still open, parents first
+        let (_receiver, metadata) = router.subscribe_all_blocking().await?;
+        let begins: Vec<_> = metadata
+            .iter()
+            .filter_map(|msg| match &msg.msg {
+                IpcMessage::TraceIntervalBegin(begin) => {
+                    Some((begin.name.as_str(), begin.parent_id))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(begins, [("RUNNING", None), ("HOMING", Some(running.id))]);
+        drop(homing);
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[test]
+    fn test_replay_buffer_bytes() {
+        let segment_contexts = SegmentContexts::default();
+        let log = |time_ns, message: &str| IpcMessageWithId {
+            segment_id: uuid::Uuid::nil(),
+            source_name: "test".into(),
+            msg: zelos_trace_types::ipc::TraceLog {
+                time_ns,
+                level: LogLevel::Info,
+                target: "test".into(),
+                message: message.into(),
+                fields: Default::default(),
+            }
+            .into(),
+        };
+        let replayed_times = |buffer: &ReplayBuffer| -> Vec<i64> {
+            buffer
+                .replay(&crate::filter::Filter::any(), 0)
+                .iter()
+                .filter_map(|msg| msg.msg.time_ns())
+                .collect()
+        };
+
+        // Nothing is kept by default
+        let mut disabled = ReplayBuffer::new(TraceRouterConfig::default().replay_buffer_bytes);
+        disabled.push(&log(1, "a"), &segment_contexts);
+        assert!(replayed_times(&disabled).is_empty());
+
+        // Room for three small logs, so older ones are evicted and an oversized one is skipped
+        let small = approximate_size(&log(0, "a"));
+        let mut buffer = ReplayBuffer::new(small * 3);
+        for time_ns in 1..=4 {
+            buffer.push(&log(time_ns, "a"), &segment_contexts);
+        }
+        assert_eq!(replayed_times(&buffer), vec![2, 3, 4]);
+        buffer.push(&log(5, &"a".repeat(small * 3)), &segment_contexts);
+        assert_eq!(replayed_times(&buffer), vec![2, 3, 4]);
+
+        // A larger log evicts as many older ones as it needs
+        buffer.push(&log(6, &"a".repeat(small)), &segment_contexts);
+        assert_eq!(replayed_times(&buffer), vec![4, 6]);
+        assert!(buffer.bytes <= small * 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_schema_is_dropped() -> Result<()> {
+        use zelos_trace_types::{
+            ipc::{
+                FieldDisplay, FieldKind, TraceEventFieldMetadata, TraceEventSchema,
+                TraceSegmentEnd, TraceSegmentStart,
+            },
+            DataType,
+        };
+
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        sink.subscribe(crate::filter::Filter::any()).await;
+
+        let segment_id = uuid::Uuid::now_v7();
+        let schema = |version, data_type| TraceEventSchema {
+            name: "motor".to_string(),
+            version,
+            fields: vec![TraceEventFieldMetadata {
+                name: "rpm".to_string(),
+                data_type,
+                unit: None,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay::default(),
+            }],
+        };
+        let msgs: [IpcMessage; 4] = [
+            TraceSegmentStart {
+                time_ns: 0,
+                source_name: "rig".to_string(),
+                attributes: Default::default(),
+            }
+            .into(),
+            schema(0, DataType::Float64).into(),
+            // A later version cannot change the type of a field, so the store rejects it
+            schema(1, DataType::String).into(),
+            TraceSegmentEnd { time_ns: 1 }.into(),
+        ];
+        for msg in msgs {
+            router
+                .sender()
+                .send_async(IpcMessageWithId {
+                    segment_id,
+                    source_name: "rig".into(),
+                    msg,
+                })
+                .await?;
+        }
+
+        let msgs: Vec<_> = tokio::time::timeout(Duration::from_secs(1), async {
+            receiver.stream().take(3).collect::<Vec<_>>().await
+        })
+        .await?;
+        assert!(matches!(
+            &msgs[1].msg,
+            IpcMessage::TraceEventSchema(schema) if schema.version == 0
+        ));
+        assert!(matches!(msgs[2].msg, IpcMessage::TraceSegmentEnd(_)));
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_session() -> Result<()> {
+        let cancellation_token = CancellationToken::new();
+        let (router, run) = TraceRouter::new(cancellation_token.clone());
+        let task = tokio::spawn(run);
+
+        let session = crate::session::TraceSessionHandle::builder("hil")
+            .add_join_attribute("run", "42")
+            .start(router.sender())?;
+        let (sink, receiver, _metadata) = router.subscribe().await?;
+        sink.subscribe(crate::filter::Filter::any().with_session(session.id))
+            .await;
+
+        // Sources join by the session's rule or explicitly
+        let by_rule = crate::TraceSource::builder("rig")
+            .add_attribute("run", "42")
+            .build(router.sender());
+        let explicit = crate::TraceSource::new("dut", router.sender());
+        explicit
+            .build_event("status")
+            .add_i32_field("code", None)
+            .build()?;
+        explicit.join_session(session.id)?;
+        let other = crate::TraceSource::new("other", router.sender());
+        let ids = [by_rule.id, explicit.id];
+        let explicit_id = explicit.id;
+        drop((by_rule, explicit, other));
+        session.end()?;
+
+        let msgs: Vec<_> = tokio::time::timeout(Duration::from_secs(1), async {
+            receiver.stream().take(7).collect::<Vec<_>>().await
+        })
+        .await?;
+        assert!(msgs[..6].iter().all(|msg| ids.contains(&msg.segment_id)));
+
+        // The explicit joiner's start and schemas were sent before it joined, so they are replayed ahead of the join
+        let explicit_msgs: Vec<_> = msgs
+            .iter()
+            .filter(|msg| msg.segment_id == explicit_id)
+            .map(|msg| &msg.msg)
+            .collect();
+        assert!(matches!(
+            explicit_msgs[..],
+            [
+                IpcMessage::TraceSegmentStart(_),
+                IpcMessage::TraceEventSchema(_),
+                IpcMessage::TraceSessionJoin(_),
+                IpcMessage::TraceSegmentEnd(_),
+            ]
+        ));
+        assert!(
+            matches!(&msgs[6].msg, IpcMessage::TraceSessionEnd(end) if end.session_id == session.id)
+        );
+        assert!(receiver.is_empty());
+
+        cancellation_token.cancel();
+        task.await??;
+        Ok(())
+    }
+}
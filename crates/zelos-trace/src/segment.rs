@@ -36,6 +36,7 @@ impl TraceEventFieldRef<'_> {
             signal: field.metadata.name.clone(),
             data_type: field.metadata.data_type.clone(),
             unit: field.metadata.unit.clone(),
+            display: (!field.metadata.display.is_empty()).then(|| field.metadata.display.clone()),
             value_table: if field.values.is_empty() {
                 None
             } else {
@@ -78,6 +79,7 @@ impl TraceEventField {
 #[derive(Clone, Debug)]
 pub struct TraceEventSchema {
     pub name: String,
+    pub version: u32,
     pub fields: Vec<TraceEventField>,
 }
 
@@ -85,6 +87,7 @@ impl TraceEventSchema {
     pub fn from_ipc(msg: ipc::TraceEventSchema) -> Self {
         Self {
             name: msg.name,
+            version: msg.version,
             fields: msg
                 .fields
                 .into_iter()
@@ -108,6 +111,30 @@ impl TraceEventSchema {
     pub fn metadata(&self) -> impl Iterator<Item = &TraceEventFieldMetadata> {
         self.fields.iter().map(|field| &field.metadata)
     }
+
+    /// The schema message for this version, without its value tables
+    pub fn as_ipc(&self) -> ipc::TraceEventSchema {
+        ipc::TraceEventSchema {
+            name: self.name.clone(),
+            version: self.version,
+            fields: self.metadata().cloned().collect(),
+        }
+    }
+
+    /// The next version of this schema, keeping the value tables of the fields it keeps. Fails if the fields changed
+    /// in an incompatible way from this or any of the `earlier` versions, see
+    /// [`ipc::TraceEventSchema::check_evolution_from`].
+    pub fn evolve(&self, earlier: &[Self], msg: &ipc::TraceEventSchema) -> anyhow::Result<Self> {
+        let versions: Vec<_> = earlier.iter().chain([self]).map(Self::as_ipc).collect();
+        ipc::TraceEventSchema::check_evolution_from(&versions, msg)?;
+        let mut next = Self::from_ipc(msg.clone());
+        for field in next.fields.iter_mut() {
+            if let Some(previous) = self.get_field(&field.metadata.name) {
+                field.values = previous.values.clone();
+            }
+        }
+        Ok(next)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -116,7 +143,16 @@ pub struct TraceSegment {
     pub source: String,
     pub start_time: Option<DateTime<Utc>>,
     pub end_time: Option<DateTime<Utc>>,
+    /// Attributes of the segment's start message, such as host, pid and user tags
+    pub attributes: HashMap<String, String>,
     pub schemas: HashTrieMapSync<String, TraceEventSchema>,
+    /// Earlier versions of the schemas whose fields changed during the segment, by event name, oldest first
+    pub schema_history: HashTrieMapSync<String, Vec<TraceEventSchema>>,
+    pub actions: HashTrieMapSync<String, ipc::TraceActionSchema>,
+    /// Annotations added on the segment's timeline, by id
+    pub annotations: HashTrieMapSync<Uuid, ipc::TraceAnnotation>,
+    /// Intervals that began and have not ended yet, by id, so late subscribers learn about them
+    pub open_intervals: HashTrieMapSync<Uuid, ipc::TraceIntervalBegin>,
 }
 
 impl TraceSegment {
@@ -127,7 +163,12 @@ impl TraceSegment {
             source: source_name,
             start_time: None,
             end_time: None,
+            attributes: HashMap::new(),
             schemas: HashTrieMapSync::new_sync(),
+            schema_history: HashTrieMapSync::new_sync(),
+            actions: HashTrieMapSync::new_sync(),
+            annotations: HashTrieMapSync::new_sync(),
+            open_intervals: HashTrieMapSync::new_sync(),
         }
     }
 
@@ -138,14 +179,22 @@ impl TraceSegment {
             source: start.source_name.clone(),
             start_time: Some(DateTime::from_timestamp_nanos(start.time_ns)),
             end_time: None,
+            attributes: start.attributes.clone(),
             schemas: HashTrieMapSync::new_sync(),
+            schema_history: HashTrieMapSync::new_sync(),
+            actions: HashTrieMapSync::new_sync(),
+            annotations: HashTrieMapSync::new_sync(),
+            open_intervals: HashTrieMapSync::new_sync(),
         }
     }
 
-    pub fn update_mut(&mut self, msg: &ipc::IpcMessage) {
+    /// Apply a message to this segment. Fails, leaving the segment unchanged, if the message is a schema version whose
+    /// fields are incompatible with an earlier version.
+    pub fn update_mut(&mut self, msg: &ipc::IpcMessage) -> anyhow::Result<()> {
         match msg {
             ipc::IpcMessage::TraceSegmentStart(m) => {
                 self.source = m.source_name.clone();
+                self.attributes.extend(m.attributes.clone());
 
                 // Update the start time if it's earlier than the existing one
                 let start_time = DateTime::from_timestamp_nanos(m.time_ns);
@@ -159,13 +208,30 @@ impl TraceSegment {
             }
             ipc::IpcMessage::TraceSegmentEnd(m) => {
                 self.end_time = Some(DateTime::from_timestamp_nanos(m.time_ns));
+                // Intervals end with their segment
+                self.open_intervals = HashTrieMapSync::new_sync();
             }
-            ipc::IpcMessage::TraceEventSchema(m) => {
-                if !self.schemas.contains_key(&m.name) {
+            ipc::IpcMessage::TraceEventSchema(m) => match self.schemas.get(&m.name) {
+                None => {
                     self.schemas
                         .insert_mut(m.name.clone(), TraceEventSchema::from_ipc(m.clone()));
                 }
-            }
+                // Schemas are sent again on reconnect, only a later version replaces the current one
+                Some(current) if m.version > current.version => {
+                    let mut history = self
+                        .schema_history
+                        .get(&m.name)
+                        .cloned()
+                        .unwrap_or_default();
+                    let next = current.evolve(&history, m).map_err(|e| {
+                        anyhow::anyhow!("Rejected schema from source {}: {}", self.source, e)
+                    })?;
+                    history.push(current.clone());
+                    self.schema_history.insert_mut(m.name.clone(), history);
+                    self.schemas.insert_mut(m.name.clone(), next);
+                }
+                Some(_) => {}
+            },
             ipc::IpcMessage::TraceEventFieldNamedValues(m) => {
                 // Update our event schema in place
                 if let Some(mut event_schema) = self.schemas.get(&m.event_name).cloned() {
@@ -175,16 +241,44 @@ impl TraceSegment {
                     self.schemas.insert_mut(m.event_name.clone(), event_schema);
                 }
             }
-            ipc::IpcMessage::TraceEvent(_m) => {
-                // Do nothing
+            ipc::IpcMessage::TraceActionSchema(m) => {
+                self.actions.insert_mut(m.name.clone(), m.clone());
+            }
+            ipc::IpcMessage::Annotation(m) => {
+                self.annotations.insert_mut(m.annotation_id, m.clone());
+            }
+            ipc::IpcMessage::TraceIntervalBegin(m) => {
+                self.open_intervals.insert_mut(m.interval_id, m.clone());
+            }
+            ipc::IpcMessage::TraceIntervalEnd(m) => {
+                self.open_intervals.remove_mut(&m.interval_id);
+            }
+            ipc::IpcMessage::TraceEvent(_)
+            | ipc::IpcMessage::TraceActionResult(_)
+            | ipc::IpcMessage::Log(_)
+            | ipc::IpcMessage::TraceSessionStart(_)
+            | ipc::IpcMessage::TraceSessionEnd(_)
+            | ipc::IpcMessage::TraceSessionJoin(_) => {
+                // Sessions are tracked by TraceMetadata
             }
         }
+        Ok(())
     }
 
-    pub fn update(&self, msg: &ipc::IpcMessage) -> Self {
+    pub fn update(&self, msg: &ipc::IpcMessage) -> anyhow::Result<Self> {
         let mut new = self.clone();
-        new.update_mut(msg);
-        new
+        new.update_mut(msg)?;
+        Ok(new)
+    }
+
+    /// Every version of an event's schema seen in this segment, oldest first and ending with the current one
+    pub fn schema_versions(&self, event_name: &str) -> Vec<&TraceEventSchema> {
+        self.schema_history
+            .get(event_name)
+            .into_iter()
+            .flatten()
+            .chain(self.schemas.get(event_name))
+            .collect()
     }
 
     pub fn maybe_event<'a>(&'a self, event_name: &str) -> Option<TraceEventSchemaRef<'a>> {
@@ -261,18 +355,18 @@ impl TraceSegment {
             let start = ipc::TraceSegmentStart {
                 time_ns: start_time_ns,
                 source_name: self.source.clone(),
+                attributes: self.attributes.clone(),
             };
             msgs.push(start.into());
         }
 
         // Iterate over all schemas and send messages as required
         for (event_name, schema) in &self.schemas {
-            // Send the schema
-            let event_schema = ipc::TraceEventSchema {
-                name: schema.name.clone(),
-                fields: schema.fields.iter().map(|f| f.metadata.clone()).collect(),
-            };
-            msgs.push(event_schema.into());
+            // Send every version of the schema oldest first, so later versions are checked against all of them and
+            // events are read with the current one
+            for version in self.schema_versions(event_name) {
+                msgs.push(version.as_ipc().into());
+            }
 
             // For each field with values, send the hashmap
             for (field_name, values) in schema
@@ -290,6 +384,19 @@ impl TraceSegment {
             }
         }
 
+        for action in self.actions.values() {
+            msgs.push(action.clone().into());
+        }
+
+        for annotation in self.annotations.values() {
+            msgs.push(annotation.clone().into());
+        }
+
+        // Begin open intervals in order, so parents begin before the intervals nested in them
+        let mut open_intervals: Vec<_> = self.open_intervals.values().cloned().collect();
+        open_intervals.sort_by_key(|begin| (begin.time_ns, begin.parent_id.is_some()));
+        msgs.extend(open_intervals.into_iter().map(Into::into));
+
         // Send end, if we have an end timestamp
         if let Some(end_time_ns) = self.end_time.and_then(|t| t.timestamp_nanos_opt()) {
             let end = ipc::TraceSegmentEnd {
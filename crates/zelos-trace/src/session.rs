@@ -0,0 +1,202 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rpds::{HashTrieMapSync, HashTrieSetSync};
+use uuid::Uuid;
+use zelos_trace_types::ipc::{
+    self, IpcMessage, IpcMessageWithId, Sender, TraceSessionEnd, TraceSessionStart,
+};
+
+use crate::{segment::TraceSegment, time::now_time_ns};
+
+/// A session grouping the segments of e.g. a test run across sources, as tracked by [`crate::TraceMetadata`]
+#[derive(Clone, Debug)]
+pub struct TraceSession {
+    pub id: Uuid,
+    pub name: String,
+    pub attributes: HashMap<String, String>,
+    /// Segments that start with attributes including all of these join while the session is open
+    pub join_attributes: HashMap<String, String>,
+    pub start_time: DateTime<Utc>,
+    pub end_time: Option<DateTime<Utc>>,
+    /// Ids of the segments that joined the session
+    pub segments: HashTrieSetSync<Uuid>,
+}
+
+impl TraceSession {
+    /// Create a session from a start message
+    pub fn from_ipc(start: &ipc::TraceSessionStart) -> Self {
+        Self {
+            id: start.session_id,
+            name: start.name.clone(),
+            attributes: start.attributes.clone(),
+            join_attributes: start.join_attributes.clone(),
+            start_time: DateTime::from_timestamp_nanos(start.time_ns),
+            end_time: None,
+            segments: HashTrieSetSync::new_sync(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.end_time.is_none()
+    }
+
+    /// Whether a segment with `attributes` starting now joins this session by its rule
+    pub fn admits(&self, attributes: &HashMap<String, String>) -> bool {
+        self.is_open()
+            && !self.join_attributes.is_empty()
+            && self
+                .join_attributes
+                .iter()
+                .all(|(key, value)| attributes.get(key) == Some(value))
+    }
+
+    fn as_start(&self) -> ipc::TraceSessionStart {
+        ipc::TraceSessionStart {
+            session_id: self.id,
+            time_ns: self.start_time.timestamp_nanos_opt().unwrap_or_default(),
+            name: self.name.clone(),
+            attributes: self.attributes.clone(),
+            join_attributes: self.join_attributes.clone(),
+        }
+    }
+
+    /// Represent this session as ipc messages: its start, a join for each of its `segments` and its end
+    pub fn as_ipc(
+        &self,
+        segments: &HashTrieMapSync<Uuid, TraceSegment>,
+    ) -> Vec<ipc::IpcMessageWithId> {
+        let session_msg = |msg: IpcMessage| IpcMessageWithId {
+            segment_id: self.id,
+            source_name: self.name.as_str().into(),
+            msg,
+        };
+
+        let mut msgs = vec![session_msg(self.as_start().into())];
+        msgs.extend(self.segments.iter().filter_map(|segment_id| {
+            segments.get(segment_id).map(|segment| IpcMessageWithId {
+                segment_id: *segment_id,
+                source_name: segment.source.as_str().into(),
+                msg: ipc::TraceSessionJoin {
+                    session_id: self.id,
+                }
+                .into(),
+            })
+        }));
+        if let Some(end_time_ns) = self.end_time.and_then(|t| t.timestamp_nanos_opt()) {
+            msgs.push(session_msg(
+                ipc::TraceSessionEnd {
+                    session_id: self.id,
+                    time_ns: end_time_ns,
+                }
+                .into(),
+            ));
+        }
+        msgs
+    }
+}
+
+/// An open session, which ends when this is dropped. Sources join it with [`crate::TraceSource::join_session`], or
+/// by having the attributes added with [`builder::TraceSessionBuilder::add_join_attribute`].
+#[derive(Debug)]
+pub struct TraceSessionHandle {
+    pub id: Uuid,
+    pub name: Arc<str>,
+    sender: Sender,
+    ended: AtomicBool,
+}
+
+impl TraceSessionHandle {
+    pub fn builder(name: &str) -> builder::TraceSessionBuilder<'_> {
+        builder::TraceSessionBuilder::new(name)
+    }
+
+    /// End the session, later calls do nothing
+    pub fn end(&self) -> Result<()> {
+        if self.ended.swap(true, Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.sender.send(IpcMessageWithId {
+            segment_id: self.id,
+            source_name: self.name.clone(),
+            msg: TraceSessionEnd {
+                session_id: self.id,
+                time_ns: now_time_ns(),
+            }
+            .into(),
+        })?;
+        Ok(())
+    }
+}
+
+impl Drop for TraceSessionHandle {
+    fn drop(&mut self) {
+        if let Err(e) = self.end() {
+            tracing::debug!("Error emitting trace session end: {}", e);
+        }
+    }
+}
+
+pub mod builder {
+    use super::*;
+
+    #[must_use]
+    pub struct TraceSessionBuilder<'a> {
+        name: &'a str,
+        attributes: HashMap<String, String>,
+        join_attributes: HashMap<String, String>,
+    }
+
+    impl<'a> TraceSessionBuilder<'a> {
+        pub(crate) fn new(name: &'a str) -> Self {
+            TraceSessionBuilder {
+                name,
+                attributes: HashMap::new(),
+                join_attributes: HashMap::new(),
+            }
+        }
+
+        /// Start the session, emitting its start on `sender`.
+        pub fn start(self, sender: Sender) -> Result<TraceSessionHandle> {
+            let session = TraceSessionHandle {
+                id: Uuid::now_v7(),
+                name: self.name.into(),
+                sender,
+                ended: AtomicBool::new(false),
+            };
+            session.sender.send(IpcMessageWithId {
+                segment_id: session.id,
+                source_name: session.name.clone(),
+                msg: TraceSessionStart {
+                    session_id: session.id,
+                    time_ns: now_time_ns(),
+                    name: self.name.to_string(),
+                    attributes: self.attributes,
+                    join_attributes: self.join_attributes,
+                }
+                .into(),
+            })?;
+            Ok(session)
+        }
+
+        /// Add an attribute describing the session, such as a test run id
+        pub fn add_attribute(mut self, key: &str, value: &str) -> Self {
+            self.attributes.insert(key.to_string(), value.to_string());
+            self
+        }
+
+        /// Have segments that start with the attribute `key` set to `value` join the session while it is open
+        pub fn add_join_attribute(mut self, key: &str, value: &str) -> Self {
+            self.join_attributes
+                .insert(key.to_string(), value.to_string());
+            self
+        }
+    }
+}
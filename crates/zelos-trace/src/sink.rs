@@ -1,34 +1,75 @@
-use std::sync::Arc;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
 
 use anyhow::Result;
 use async_trait::async_trait;
-use tokio::sync::RwLock;
+use tokio::sync::{Notify, RwLock};
 use zelos_trace_types::ipc::{IpcMessageWithId, Receiver, Sender};
 
-use crate::{filter::Filter, router::DEFAULT_CHANNEL_SIZE};
+use crate::{
+    filter::{Filter, SegmentContext},
+    router::{ReplayRequest, SubscriptionRequest, DEFAULT_CHANNEL_SIZE},
+};
 
 #[async_trait]
 pub(crate) trait TraceSinkHandle: Send + Sync {
-    async fn send_async(&self, msg: &IpcMessageWithId) -> Result<()>;
+    /// Send `msg` if the sink wants it, given the context of its segment
+    async fn send_async(&self, msg: &IpcMessageWithId, segment: &SegmentContext) -> Result<()>;
+
+    /// Send `msg` if the sink wants it now that its segment's context changed from `before` to `after`, but did not
+    /// want it before, e.g. the start of a segment that joined a session the sink subscribed to
+    async fn send_newly_matched(
+        &self,
+        _msg: &IpcMessageWithId,
+        _before: &SegmentContext,
+        _after: &SegmentContext,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    /// Returns true once the client side of this sink has gone away and the handle can be removed
+    fn is_closed(&self) -> bool;
 }
 
 /// The handle for a trace sink that has filters
 pub(crate) struct TraceSinkHandleFiltered {
     pub sender: Sender,
     pub filters: Arc<RwLock<Vec<Filter>>>,
+    pub closed: Arc<AtomicBool>,
 }
 
 #[async_trait]
 impl TraceSinkHandle for TraceSinkHandleFiltered {
-    async fn send_async(&self, msg: &IpcMessageWithId) -> Result<()> {
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::Acquire) || self.sender.is_disconnected()
+    }
+
+    async fn send_async(&self, msg: &IpcMessageWithId, segment: &SegmentContext) -> Result<()> {
         for filter in self.filters.read().await.iter() {
-            if filter.matches(msg) {
+            if filter.matches(msg, segment) {
                 self.sender.try_send(msg.clone())?;
                 continue;
             }
         }
         Ok(())
     }
+
+    async fn send_newly_matched(
+        &self,
+        msg: &IpcMessageWithId,
+        before: &SegmentContext,
+        after: &SegmentContext,
+    ) -> Result<()> {
+        let filters = self.filters.read().await;
+        if filters.iter().any(|filter| filter.matches(msg, after))
+            && !filters.iter().any(|filter| filter.matches(msg, before))
+        {
+            self.sender.try_send(msg.clone())?;
+        }
+        Ok(())
+    }
 }
 
 pub(crate) struct TraceSinkHandleAllBlocking {
@@ -44,31 +85,77 @@ impl TraceSinkHandleAllBlocking {
 
 #[async_trait]
 impl TraceSinkHandle for TraceSinkHandleAllBlocking {
-    async fn send_async(&self, msg: &IpcMessageWithId) -> Result<()> {
+    fn is_closed(&self) -> bool {
+        self.sender.is_disconnected()
+    }
+    async fn send_async(&self, msg: &IpcMessageWithId, _segment: &SegmentContext) -> Result<()> {
         self.sender.send_async(msg.clone()).await?;
         Ok(())
     }
 }
 
+/// A command from a subscriber, as sent over the gRPC subscribe service or the HTTP gateway. A missing filter matches
+/// everything, see [`Filter::any`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SinkCommand {
+    /// Add a filter, replaying the recent messages it matches from `start_time_ns` onwards if given
+    Subscribe {
+        filter: Option<String>,
+        start_time_ns: Option<i64>,
+    },
+    /// Remove a filter added earlier
+    Unsubscribe { filter: Option<String> },
+}
+
+impl SinkCommand {
+    pub fn filter(&self) -> Option<&String> {
+        match self {
+            SinkCommand::Subscribe { filter, .. } | SinkCommand::Unsubscribe { filter } => {
+                filter.as_ref()
+            }
+        }
+    }
+}
+
 /// A trace sink is a client connection for the trace router. It hold state about what data the client has seen and is
-/// subscribed to.
+/// subscribed to. Dropping the sink removes it from the router.
 #[derive(Debug)]
 pub struct TraceSink {
     /// The list of filters for this sink
     filters: Arc<RwLock<Vec<Filter>>>,
+
+    /// Set when this sink is dropped
+    closed: Arc<AtomicBool>,
+
+    /// Wakes the router to remove closed sinks
+    sink_closed: Arc<Notify>,
+
+    /// Asks the router to replay recent messages when subscribing from a start time
+    subscription_sender: flume::Sender<SubscriptionRequest>,
 }
 
 impl TraceSink {
     /// Create a new TraceSink and TraceSinkHandle pair that share a set of filters
-    pub(crate) fn new() -> (Self, Receiver, TraceSinkHandleFiltered) {
+    pub(crate) fn new(
+        sink_closed: Arc<Notify>,
+        subscription_sender: flume::Sender<SubscriptionRequest>,
+    ) -> (Self, Receiver, TraceSinkHandleFiltered) {
         let (sender, receiver) = flume::bounded::<IpcMessageWithId>(1024);
         let filters = Arc::new(RwLock::new(Vec::new()));
+        let closed = Arc::new(AtomicBool::new(false));
         (
             Self {
                 filters: filters.clone(),
+                closed: closed.clone(),
+                sink_closed,
+                subscription_sender,
             },
             receiver,
-            TraceSinkHandleFiltered { sender, filters },
+            TraceSinkHandleFiltered {
+                sender,
+                filters,
+                closed,
+            },
         )
     }
 
@@ -78,9 +165,65 @@ impl TraceSink {
         filters.push(filter);
     }
 
+    /// Add `filter` to the list of filters for this sink, returning the messages it matches from `start_time_ns`
+    /// onwards that the router still holds, see [`crate::router::TraceRouterConfig::replay_buffer_bytes`]. The filter
+    /// is added by the router in between forwarding messages, so every message after the replayed ones reaches the
+    /// sink.
+    pub async fn subscribe_from(
+        &self,
+        filter: Filter,
+        start_time_ns: i64,
+    ) -> Result<Vec<IpcMessageWithId>> {
+        let (replay_sender, replay_receiver) = tokio::sync::oneshot::channel();
+        self.subscription_sender
+            .send_async(SubscriptionRequest::Replay(ReplayRequest {
+                filters: self.filters.clone(),
+                filter,
+                start_time_ns,
+                response: replay_sender,
+            }))
+            .await
+            .map_err(|_| anyhow::anyhow!("Router subscription channel closed"))?;
+        replay_receiver
+            .await
+            .map_err(|_| anyhow::anyhow!("Response channel closed"))
+    }
+
     /// Remove `filter` from the list of filters for this sink
     pub async fn unsubscribe(&self, filter: Filter) {
         let mut filters = self.filters.write().await;
         filters.retain(|f| f != &filter);
     }
+
+    /// Apply a subscriber's command, returning the messages replayed for a subscription with a start time. Fails if
+    /// the filter does not parse or the router is gone.
+    pub async fn apply(&self, command: SinkCommand) -> Result<Vec<IpcMessageWithId>> {
+        let parsed = match command.filter() {
+            Some(filter) => Filter::parse(filter)
+                .map_err(|e| anyhow::anyhow!("Failed to parse filter: {}", e))?,
+            None => Filter::any(),
+        };
+
+        match command {
+            SinkCommand::Subscribe {
+                start_time_ns: Some(start_time_ns),
+                ..
+            } => self.subscribe_from(parsed, start_time_ns).await,
+            SinkCommand::Subscribe { .. } => {
+                self.subscribe(parsed).await;
+                Ok(Vec::new())
+            }
+            SinkCommand::Unsubscribe { .. } => {
+                self.unsubscribe(parsed).await;
+                Ok(Vec::new())
+            }
+        }
+    }
+}
+
+impl Drop for TraceSink {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::Release);
+        self.sink_closed.notify_one();
+    }
 }
@@ -1,42 +1,196 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    sync::{
+        atomic::{AtomicBool, AtomicI64, AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+};
 
 use anyhow::{anyhow, Result};
 use parking_lot::RwLock;
+use tokio::sync::broadcast;
 use uuid::Uuid;
 use zelos_trace_types::{
     ipc::{
-        IpcMessage, IpcMessageWithId, Sender, TraceEvent, TraceEventFieldMetadata,
-        TraceEventFieldNamedValues, TraceEventSchema, TraceSegmentEnd, TraceSegmentStart,
+        FieldDisplay, FieldKind, IpcMessage, IpcMessageWithId, LogLevel, Sender, TraceActionCall,
+        TraceActionResult, TraceActionSchema, TraceAnnotation, TraceControl, TraceControlCommand,
+        TraceEvent, TraceEventFieldMetadata, TraceEventFieldNamedValues, TraceEventFields,
+        TraceEventSchema, TraceLog, TraceSegmentEnd, TraceSegmentStart, TraceSessionJoin,
     },
     Value,
 };
 
-use crate::time::now_time_ns;
+use crate::{
+    action::TraceSourceAction,
+    interval::TraceInterval,
+    parameter::{TraceSourceParameter, PARAMETER_EVENT_PREFIX, PARAMETER_VALUE_FIELD},
+    time::now_time_ns,
+};
+
+/// Whether and how often an event is emitted, changed at runtime by control commands
+#[derive(Debug)]
+struct EventControls {
+    enabled: AtomicBool,
+    /// Minimum time between emitted events, 0 for no limit
+    min_interval_ns: AtomicI64,
+    /// Time of the last event emitted while rate limited
+    last_time_ns: AtomicI64,
+    /// Latest version of the event's schema, handles to earlier versions cannot emit
+    version: AtomicU32,
+}
+
+impl Default for EventControls {
+    fn default() -> Self {
+        Self {
+            enabled: AtomicBool::new(true),
+            min_interval_ns: AtomicI64::new(0),
+            last_time_ns: AtomicI64::new(i64::MIN),
+            version: AtomicU32::new(0),
+        }
+    }
+}
+
+/// Counts an emit in [`TraceSourceEvent::emitting`] until it is dropped
+struct Emitting<'a>(&'a AtomicUsize);
+
+impl Drop for Emitting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+impl EventControls {
+    /// Whether an event at `time_ns` should be emitted, recording it if so
+    fn admit(&self, time_ns: i64) -> bool {
+        if !self.enabled.load(Ordering::Relaxed) {
+            return false;
+        }
+        let min_interval_ns = self.min_interval_ns.load(Ordering::Relaxed);
+        if min_interval_ns == 0 {
+            return true;
+        }
+        // Emitters race to record their time, only the one that moves last_time_ns forward is admitted
+        let mut last_time_ns = self.last_time_ns.load(Ordering::Relaxed);
+        loop {
+            if last_time_ns != i64::MIN && time_ns.saturating_sub(last_time_ns) < min_interval_ns {
+                return false;
+            }
+            match self.last_time_ns.compare_exchange_weak(
+                last_time_ns,
+                time_ns,
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => return true,
+                Err(current) => last_time_ns = current,
+            }
+        }
+    }
+}
 
 /// TraceSourceEvent is a child of a TraceSource, which contains the schema for the event, as well as helpers for building and emitting new events for that schema.
 #[derive(Debug)]
 pub struct TraceSourceEvent {
     id: Uuid,
-    source_name: String,
+    source_name: Arc<str>,
     sender: Sender,
-    pub name: String,
-    pub schema: Vec<TraceEventFieldMetadata>,
+    pub name: Arc<str>,
+    /// Version of the schema, increased by [`TraceSource::update_event`]
+    pub version: u32,
+    pub schema: Arc<[TraceEventFieldMetadata]>,
+    /// Shared by every version of the event
+    controls: Arc<EventControls>,
+    /// Emits from this version that passed the version check and may not have sent their event yet
+    emitting: AtomicUsize,
 }
 
 impl TraceSourceEvent {
-    pub fn emit(&self, time_ns: i64, fields: impl Iterator<Item = (String, Value)>) -> Result<()> {
-        let evt = TraceEvent {
-            time_ns,
-            name: self.name.clone(),
-            fields: fields.collect(),
+    /// Whether events are currently emitted, emitting a disabled event does nothing
+    pub fn is_enabled(&self) -> bool {
+        self.controls.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.controls.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Emit at most `max_rate_hz` events per second by event time, dropping the rest, or all events if `None`
+    pub fn set_max_rate(&self, max_rate_hz: Option<f64>) {
+        let min_interval_ns = match max_rate_hz {
+            // Rates too low for the interval to fit in an i64 are clamped rather than wrapping to no limit
+            Some(hz) if hz > 0.0 => (1e9 / hz).min(i64::MAX as f64) as i64,
+            _ => 0,
         };
+        self.controls
+            .min_interval_ns
+            .store(min_interval_ns, Ordering::Relaxed);
+        self.controls
+            .last_time_ns
+            .store(i64::MIN, Ordering::Relaxed);
+    }
 
-        self.sender.send(IpcMessageWithId {
+    fn ipc_schema(&self) -> TraceEventSchema {
+        TraceEventSchema {
+            name: self.name.to_string(),
+            version: self.version,
+            fields: self.schema.to_vec(),
+        }
+    }
+
+    /// Fail if the event was updated since this handle was made, its events would be read with the newer fields.
+    /// The update waits for the returned guard to be dropped before sending the new schema, so hold it until the
+    /// event is sent.
+    fn check_current(&self) -> Result<Emitting<'_>> {
+        // Counted before the check, so an update that replaces the version after it either fails this check or
+        // sees the count
+        self.emitting.fetch_add(1, Ordering::SeqCst);
+        let emitting = Emitting(&self.emitting);
+        let current = self.controls.version.load(Ordering::SeqCst);
+        if current != self.version {
+            return Err(anyhow!(
+                "Event '{}' version {} was replaced by version {}, emit from the handle returned by update_event",
+                self.name,
+                self.version,
+                current
+            ));
+        }
+        Ok(emitting)
+    }
+
+    fn message(&self, time_ns: i64, fields: TraceEventFields) -> IpcMessageWithId {
+        IpcMessageWithId {
             segment_id: self.id,
             source_name: self.source_name.clone(),
-            msg: IpcMessage::TraceEvent(evt),
-        })?;
+            msg: IpcMessage::TraceEvent(TraceEvent {
+                time_ns,
+                name: self.name.clone(),
+                fields,
+            }),
+        }
+    }
 
+    /// Build indexed fields from values in schema order, checking there is one per field
+    fn indexed(&self, values: Vec<Option<Value>>) -> Result<TraceEventFields> {
+        if values.len() != self.schema.len() {
+            return Err(anyhow!(
+                "Expected {} values for event '{}', found {}",
+                self.schema.len(),
+                self.name,
+                values.len()
+            ));
+        }
+        Ok(TraceEventFields::Indexed {
+            schema: self.schema.clone(),
+            values,
+        })
+    }
+
+    pub fn emit(&self, time_ns: i64, fields: impl Iterator<Item = (String, Value)>) -> Result<()> {
+        let _emitting = self.check_current()?;
+        if !self.controls.admit(time_ns) {
+            return Ok(());
+        }
+        self.sender.send(self.message(time_ns, fields.collect()))?;
         Ok(())
     }
 
@@ -45,20 +199,37 @@ impl TraceSourceEvent {
         time_ns: i64,
         fields: impl Iterator<Item = (String, Value)>,
     ) -> Result<()> {
-        let evt = TraceEvent {
-            time_ns,
-            name: self.name.clone(),
-            fields: fields.collect(),
-        };
-
+        let _emitting = self.check_current()?;
+        if !self.controls.admit(time_ns) {
+            return Ok(());
+        }
         self.sender
-            .send_async(IpcMessageWithId {
-                segment_id: self.id,
-                source_name: self.source_name.clone(),
-                msg: IpcMessage::TraceEvent(evt),
-            })
+            .send_async(self.message(time_ns, fields.collect()))
             .await?;
+        Ok(())
+    }
+
+    /// Emit values in the order of the schema's fields, `None` for fields without a value. Unlike [`Self::emit`], this
+    /// allocates no field names. Values are not checked against the field types, use [`Self::build`] for that.
+    pub fn emit_values(&self, time_ns: i64, values: Vec<Option<Value>>) -> Result<()> {
+        let _emitting = self.check_current()?;
+        let fields = self.indexed(values)?;
+        if !self.controls.admit(time_ns) {
+            return Ok(());
+        }
+        self.sender.send(self.message(time_ns, fields))?;
+        Ok(())
+    }
 
+    pub async fn emit_values_async(&self, time_ns: i64, values: Vec<Option<Value>>) -> Result<()> {
+        let _emitting = self.check_current()?;
+        let fields = self.indexed(values)?;
+        if !self.controls.admit(time_ns) {
+            return Ok(());
+        }
+        self.sender
+            .send_async(self.message(time_ns, fields))
+            .await?;
         Ok(())
     }
 
@@ -72,19 +243,69 @@ impl TraceSourceEvent {
 #[derive(Debug)]
 pub struct TraceSource {
     pub id: Uuid,
-    pub source_name: String,
+    pub source_name: Arc<str>,
     sender: Sender,
     events: RwLock<HashMap<String, Arc<TraceSourceEvent>>>,
+    /// Earlier versions of the events whose fields changed, by event name, oldest first
+    event_history: RwLock<HashMap<String, Vec<TraceEventSchema>>>,
+    /// Value tables added to this source, kept to resend them on request
+    value_tables: RwLock<Vec<TraceEventFieldNamedValues>>,
+    actions: RwLock<HashMap<String, Arc<TraceSourceAction>>>,
+    parameters: RwLock<HashMap<String, Arc<TraceSourceParameter>>>,
+    /// Attributes sent with the segment start, see [`default_attributes`]
+    pub attributes: HashMap<String, String>,
+}
+
+/// Environment variable holding the version or git SHA of the application, sent as the `app_version` attribute
+pub const APP_VERSION_ENV: &str = "ZELOS_APP_VERSION";
+
+/// Attributes describing where a segment was produced: the `host` name, the `pid` of the process and the
+/// `zelos_version` of the zelos-trace library. The application's own version is only known if it is set in
+/// [`APP_VERSION_ENV`] or with [`builder::TraceSourceBuilder::app_version`].
+pub fn default_attributes() -> HashMap<String, String> {
+    let mut attributes = HashMap::from([
+        (
+            "host".to_string(),
+            gethostname::gethostname().to_string_lossy().into_owned(),
+        ),
+        ("pid".to_string(), std::process::id().to_string()),
+        (
+            "zelos_version".to_string(),
+            env!("CARGO_PKG_VERSION").to_string(),
+        ),
+    ]);
+    if let Ok(app_version) = std::env::var(APP_VERSION_ENV) {
+        attributes.insert("app_version".to_string(), app_version);
+    }
+    attributes
 }
 
 impl TraceSource {
+    /// Create a source with the [`default_attributes`], use [`TraceSource::builder`] to add tags
     pub fn new(source_name: &str, sender: Sender) -> Self {
+        Self::with_attributes(source_name, sender, default_attributes())
+    }
+
+    pub fn builder(source_name: &str) -> builder::TraceSourceBuilder<'_> {
+        builder::TraceSourceBuilder::new(source_name)
+    }
+
+    fn with_attributes(
+        source_name: &str,
+        sender: Sender,
+        attributes: HashMap<String, String>,
+    ) -> Self {
         let id = Uuid::now_v7();
         let src = TraceSource {
             id,
-            source_name: source_name.to_string(),
+            source_name: source_name.into(),
             sender,
             events: RwLock::new(HashMap::new()),
+            event_history: RwLock::new(HashMap::new()),
+            value_tables: RwLock::new(Vec::new()),
+            actions: RwLock::new(HashMap::new()),
+            parameters: RwLock::new(HashMap::new()),
+            attributes,
         };
 
         tracing::debug!(?id, ?source_name, "TraceSource::new");
@@ -106,6 +327,17 @@ impl TraceSource {
         Ok(())
     }
 
+    /// Emit `msg` unless the channel is full, rather than waiting for room
+    fn try_emit(&self, msg: IpcMessage) -> Result<()> {
+        self.sender.try_send(IpcMessageWithId {
+            segment_id: self.id,
+            source_name: self.source_name.clone(),
+            msg,
+        })?;
+
+        Ok(())
+    }
+
     async fn emit_async(&self, msg: IpcMessage) -> Result<()> {
         self.sender
             .send_async(IpcMessageWithId {
@@ -121,7 +353,8 @@ impl TraceSource {
     pub fn emit_start(&self) -> Result<()> {
         self.emit(IpcMessage::TraceSegmentStart(TraceSegmentStart {
             time_ns: now_time_ns(),
-            source_name: self.source_name.clone(),
+            source_name: self.source_name.to_string(),
+            attributes: self.attributes.clone(),
         }))
     }
 
@@ -131,19 +364,109 @@ impl TraceSource {
         }))
     }
 
+    /// Add this source's segment to a session, see [`crate::session::TraceSessionHandle`]
+    pub fn join_session(&self, session_id: Uuid) -> Result<()> {
+        self.emit(IpcMessage::TraceSessionJoin(TraceSessionJoin {
+            session_id,
+        }))
+    }
+
+    /// Begin a named interval with `fields`, which ends with `fields` passed to [`TraceInterval::end`] or when the
+    /// returned interval is dropped
+    pub fn begin_interval(
+        &self,
+        name: &str,
+        fields: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<TraceInterval> {
+        TraceInterval::begin(
+            self.id,
+            self.source_name.clone(),
+            self.sender.clone(),
+            None,
+            name,
+            fields,
+        )
+    }
+
+    /// Emit a structured log line, timestamped now, with `fields` as key-value context
+    pub fn log(
+        &self,
+        level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<()> {
+        self.emit(IpcMessage::Log(TraceLog {
+            time_ns: now_time_ns(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+            fields: fields.into_iter().collect(),
+        }))
+    }
+
+    /// Like [`TraceSource::log`], but fails instead of waiting if the channel is full, for callers such as loggers
+    /// that must not block
+    pub fn try_log(
+        &self,
+        level: LogLevel,
+        target: &str,
+        message: &str,
+        fields: impl IntoIterator<Item = (String, Value)>,
+    ) -> Result<()> {
+        self.try_emit(IpcMessage::Log(TraceLog {
+            time_ns: now_time_ns(),
+            level,
+            target: target.to_string(),
+            message: message.to_string(),
+            fields: fields.into_iter().collect(),
+        }))
+    }
+
+    /// Add an annotation such as "brake test started" to this source's timeline, covering `start_time_ns..=end_time_ns`.
+    /// Returns the id of the annotation.
+    pub fn annotate(
+        &self,
+        start_time_ns: i64,
+        end_time_ns: i64,
+        text: &str,
+        tags: &[&str],
+        author: Option<&str>,
+    ) -> Result<Uuid> {
+        if end_time_ns < start_time_ns {
+            return Err(anyhow!(
+                "Annotation '{}' ends at {} before it starts at {}",
+                text,
+                end_time_ns,
+                start_time_ns
+            ));
+        }
+        let annotation_id = Uuid::now_v7();
+        self.emit(IpcMessage::Annotation(TraceAnnotation {
+            annotation_id,
+            start_time_ns,
+            end_time_ns,
+            text: text.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            author: author.map(str::to_string),
+        }))?;
+        Ok(annotation_id)
+    }
+
     pub fn add_value_table(
         &self,
         name: &str,
         field_name: &str,
         values: impl Iterator<Item = (Value, String)>,
     ) -> Result<()> {
-        self.emit(IpcMessage::TraceEventFieldNamedValues(
-            TraceEventFieldNamedValues {
-                event_name: name.to_string(),
-                field_name: field_name.to_string(),
-                values: values.collect(),
-            },
-        ))
+        let value_table = TraceEventFieldNamedValues {
+            event_name: name.to_string(),
+            field_name: field_name.to_string(),
+            values: values.collect(),
+        };
+        self.emit(IpcMessage::TraceEventFieldNamedValues(value_table.clone()))?;
+        self.value_tables.write().push(value_table);
+        Ok(())
     }
 
     pub fn add_event(
@@ -159,15 +482,15 @@ impl TraceSource {
             id: self.id,
             source_name: self.source_name.clone(),
             sender: self.sender.clone(),
-            name: name.to_string(),
+            name: name.into(),
+            version: 0,
             schema: schema.collect(),
+            controls: Arc::new(EventControls::default()),
+            emitting: AtomicUsize::new(0),
         });
 
         // Emit the event to the router
-        self.emit(IpcMessage::TraceEventSchema(TraceEventSchema {
-            name: name.to_string(),
-            fields: msg.schema.clone(),
-        }))?;
+        self.emit(msg.ipc_schema().into())?;
 
         // Insert the event into our metadata store
         self.events.write().insert(name.to_string(), msg.clone());
@@ -188,16 +511,15 @@ impl TraceSource {
             id: self.id,
             source_name: self.source_name.clone(),
             sender: self.sender.clone(),
-            name: name.to_string(),
+            name: name.into(),
+            version: 0,
             schema: schema.collect(),
+            controls: Arc::new(EventControls::default()),
+            emitting: AtomicUsize::new(0),
         });
 
         // Emit the event to the router
-        self.emit_async(IpcMessage::TraceEventSchema(TraceEventSchema {
-            name: name.to_string(),
-            fields: msg.schema.clone(),
-        }))
-        .await?;
+        self.emit_async(msg.ipc_schema().into()).await?;
 
         // Insert the event into our metadata store
         self.events.write().insert(name.to_string(), msg.clone());
@@ -205,6 +527,73 @@ impl TraceSource {
         Ok(msg)
     }
 
+    /// Replace an existing event with the next version of its fields, checked against every earlier version. Handles
+    /// to the replaced version can no longer emit. Returns the replaced version along with the next one.
+    fn next_version(
+        &self,
+        name: &str,
+        schema: impl Iterator<Item = TraceEventFieldMetadata>,
+    ) -> Result<(Arc<TraceSourceEvent>, Arc<TraceSourceEvent>)> {
+        // Checked and replaced under one lock, so concurrent updates cannot both take the same version
+        let mut events = self.events.write();
+        let current = events
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Event={} does not exist", name))?;
+        let next = Arc::new(TraceSourceEvent {
+            id: self.id,
+            source_name: self.source_name.clone(),
+            sender: self.sender.clone(),
+            name: current.name.clone(),
+            version: current.version + 1,
+            schema: schema.collect(),
+            controls: current.controls.clone(),
+            emitting: AtomicUsize::new(0),
+        });
+        let current_schema = current.ipc_schema();
+        let mut history = self.event_history.write();
+        let earlier = history.entry(name.to_string()).or_default();
+        TraceEventSchema::check_evolution_from(
+            earlier.iter().chain([&current_schema]),
+            &next.ipc_schema(),
+        )?;
+        earlier.push(current_schema);
+        next.controls.version.store(next.version, Ordering::SeqCst);
+        events.insert(name.to_string(), next.clone());
+        Ok((current, next))
+    }
+
+    /// Change the fields of an existing event, sending its schema as a new version. Fields may be added and removed,
+    /// but a field cannot change the data type it had in any earlier version. Events emitted afterwards are read with
+    /// the new version, so emitting from a handle to an earlier version fails; use the returned one instead. Emits
+    /// from the replaced version that already passed that check are sent before the new schema.
+    pub fn update_event(
+        &self,
+        name: &str,
+        schema: impl Iterator<Item = TraceEventFieldMetadata>,
+    ) -> Result<Arc<TraceSourceEvent>> {
+        let (replaced, msg) = self.next_version(name, schema)?;
+        // Events from the replaced version that passed their check are sent ahead of the new schema
+        while replaced.emitting.load(Ordering::SeqCst) != 0 {
+            std::thread::yield_now();
+        }
+        self.emit(msg.ipc_schema().into())?;
+        Ok(msg)
+    }
+
+    pub async fn update_event_async(
+        &self,
+        name: &str,
+        schema: impl Iterator<Item = TraceEventFieldMetadata>,
+    ) -> Result<Arc<TraceSourceEvent>> {
+        let (replaced, msg) = self.next_version(name, schema)?;
+        while replaced.emitting.load(Ordering::SeqCst) != 0 {
+            tokio::task::yield_now().await;
+        }
+        self.emit_async(msg.ipc_schema().into()).await?;
+        Ok(msg)
+    }
+
     pub fn get_event(&self, name: &str) -> Result<Arc<TraceSourceEvent>> {
         self.events
             .read()
@@ -216,6 +605,241 @@ impl TraceSource {
     pub fn build_event<'a>(&'a self, name: &'a str) -> builder::TraceSourceEventBuilder<'a> {
         builder::TraceSourceEventBuilder::new(self, name)
     }
+
+    /// Register an action that subscribers can call through the router, see [`crate::TraceRouter::call_action`].
+    /// Calls are checked against `parameters` and `returns` before and after running `handler`.
+    pub fn add_action(
+        &self,
+        name: &str,
+        parameters: impl Iterator<Item = TraceEventFieldMetadata>,
+        returns: impl Iterator<Item = TraceEventFieldMetadata>,
+        handler: impl Fn(HashMap<String, Value>) -> Result<HashMap<String, Value>>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Result<()> {
+        let schema = TraceActionSchema {
+            name: name.to_string(),
+            parameters: parameters.collect(),
+            returns: returns.collect(),
+        };
+        // Checked and inserted under one lock, so concurrent registrations of a name cannot both succeed
+        match self.actions.write().entry(name.to_string()) {
+            Entry::Occupied(_) => return Err(anyhow!("Action={} already exists", name)),
+            Entry::Vacant(entry) => {
+                entry.insert(Arc::new(TraceSourceAction::new(
+                    schema.clone(),
+                    Box::new(handler),
+                )));
+            }
+        }
+        self.emit(IpcMessage::TraceActionSchema(schema))?;
+
+        Ok(())
+    }
+
+    pub fn build_action<'a>(&'a self, name: &'a str) -> builder::TraceSourceActionBuilder<'a> {
+        builder::TraceSourceActionBuilder::new(self, name)
+    }
+
+    /// Declare a parameter with the type of `default`, optionally limited to `min..=max`. Its value is published as an
+    /// event and can be set by the application or through the router's controls; use
+    /// [`TraceSourceParameter::watch`] to be notified of changes.
+    pub fn add_parameter(
+        &self,
+        name: &str,
+        default: Value,
+        min: Option<Value>,
+        max: Option<Value>,
+        unit: Option<String>,
+    ) -> Result<Arc<TraceSourceParameter>> {
+        if self.parameters.read().contains_key(name) {
+            return Err(anyhow!("Parameter={} already exists", name));
+        }
+
+        let parameter = Arc::new(TraceSourceParameter::new(
+            self, name, default, min, max, unit,
+        )?);
+        parameter.publish()?;
+        self.parameters
+            .write()
+            .insert(name.to_string(), parameter.clone());
+
+        Ok(parameter)
+    }
+
+    pub fn get_parameter(&self, name: &str) -> Result<Arc<TraceSourceParameter>> {
+        self.parameters
+            .read()
+            .get(name)
+            .cloned()
+            .ok_or_else(|| anyhow!("Parameter not found"))
+    }
+
+    /// Emit the schemas of all events and actions and all value tables again, e.g. for a consumer that missed them.
+    /// Every version of an event's schema is sent, oldest first.
+    pub fn resend_metadata(&self) -> Result<()> {
+        let mut schemas: Vec<_> = self
+            .event_history
+            .read()
+            .values()
+            .flatten()
+            .cloned()
+            .collect();
+        schemas.extend(self.events.read().values().map(|event| event.ipc_schema()));
+        for schema in schemas {
+            self.emit(IpcMessage::TraceEventSchema(schema))?;
+        }
+        let value_tables = self.value_tables.read().clone();
+        for value_table in value_tables {
+            self.emit(IpcMessage::TraceEventFieldNamedValues(value_table))?;
+        }
+        let actions: Vec<_> = self
+            .actions
+            .read()
+            .values()
+            .map(|action| action.schema.clone())
+            .collect();
+        for action in actions {
+            self.emit(IpcMessage::TraceActionSchema(action))?;
+        }
+        Ok(())
+    }
+
+    /// Apply a control command if it targets this source, returning whether it did. Commands for events or
+    /// parameters this source does not have are ignored, unless the command asks for an answer. Invalid parameter
+    /// values are returned as errors, or answered if the command has a `call_id`.
+    pub fn apply_control(&self, control: &TraceControl) -> Result<bool> {
+        if !control.targets(&self.source_name) {
+            return Ok(false);
+        }
+        tracing::debug!(source_name = ?self.source_name, "Applying control {:?}", control.command);
+
+        let matching = |event_name: &Option<String>| -> Vec<Arc<TraceSourceEvent>> {
+            let events = self.events.read();
+            match event_name {
+                Some(name) => events.get(name).cloned().into_iter().collect(),
+                // Parameter events only change when their value does, so they are left out of commands for all events
+                None => events
+                    .iter()
+                    .filter(|(name, _)| !name.starts_with(PARAMETER_EVENT_PREFIX))
+                    .map(|(_, event)| event.clone())
+                    .collect(),
+            }
+        };
+        match &control.command {
+            TraceControlCommand::SetEventEnabled {
+                event_name,
+                enabled,
+            } => {
+                for event in matching(event_name) {
+                    event.set_enabled(*enabled);
+                }
+            }
+            TraceControlCommand::SetSampleRate {
+                event_name,
+                max_rate_hz,
+            } => {
+                for event in matching(event_name) {
+                    event.set_max_rate(*max_rate_hz);
+                }
+            }
+            TraceControlCommand::ResendMetadata => self.resend_metadata()?,
+            TraceControlCommand::CallAction(call) => return self.call_action(call),
+            TraceControlCommand::SetParameter {
+                name,
+                value,
+                call_id,
+                segment_id,
+            } => {
+                if segment_id.is_some_and(|segment_id| segment_id != self.id) {
+                    return Ok(false);
+                }
+                let parameter = self.parameters.read().get(name).cloned();
+                let Some(call_id) = call_id else {
+                    if let Some(parameter) = parameter {
+                        parameter.set(value.clone())?;
+                    }
+                    return Ok(true);
+                };
+                let result = match parameter {
+                    Some(parameter) => parameter
+                        .set(value.clone())
+                        .map(|()| [(PARAMETER_VALUE_FIELD.to_string(), parameter.get())].into())
+                        .map_err(|e| e.to_string()),
+                    None => Err(format!("Unknown parameter '{}'", name)),
+                };
+                self.emit(IpcMessage::TraceActionResult(TraceActionResult {
+                    call_id: *call_id,
+                    action_name: format!("{}{}", PARAMETER_EVENT_PREFIX, name),
+                    result,
+                }))?;
+            }
+        }
+        Ok(true)
+    }
+
+    /// Run the action of `call` and emit its result, if the call targets this source's segment, returning whether it
+    /// did. Unknown actions are answered with an error result.
+    pub fn call_action(&self, call: &TraceActionCall) -> Result<bool> {
+        if call
+            .segment_id
+            .is_some_and(|segment_id| segment_id != self.id)
+        {
+            return Ok(false);
+        }
+        let action = self.actions.read().get(&call.action_name).cloned();
+        let result = match action {
+            Some(action) => action.call(call.clone()),
+            None => TraceActionResult {
+                call_id: call.call_id,
+                action_name: call.action_name.clone(),
+                result: Err(format!("Unknown action '{}'", call.action_name)),
+            },
+        };
+        self.emit(IpcMessage::TraceActionResult(result))?;
+        Ok(true)
+    }
+
+    /// Apply control commands from `controls`, e.g. [`crate::TraceRouter::controls`], until the channel closes or the
+    /// source is dropped. Commands for other sources are ignored. The task only holds a weak reference, so it does not
+    /// keep the source's segment open. Actions run on the blocking thread pool, so a slow handler does not hold up
+    /// other commands.
+    pub fn follow_controls(
+        self: &Arc<Self>,
+        mut controls: broadcast::Receiver<TraceControl>,
+    ) -> impl Future<Output = ()> + Send + 'static {
+        let source = Arc::downgrade(self);
+        async move {
+            loop {
+                let control = match controls.recv().await {
+                    Ok(control) => control,
+                    Err(broadcast::error::RecvError::Lagged(count)) => {
+                        tracing::warn!("Missed {} control commands", count);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return,
+                };
+                let Some(source) = source.upgrade() else {
+                    return;
+                };
+                if let TraceControlCommand::CallAction(call) = &control.command {
+                    if control.targets(&source.source_name) {
+                        let call = call.clone();
+                        tokio::task::spawn_blocking(move || {
+                            if let Err(e) = source.call_action(&call) {
+                                tracing::error!("Error calling action: {}", e);
+                            }
+                        });
+                    }
+                    continue;
+                }
+                if let Err(e) = source.apply_control(&control) {
+                    tracing::error!("Error applying control: {}", e);
+                }
+            }
+        }
+    }
 }
 
 impl Drop for TraceSource {
@@ -234,47 +858,48 @@ pub mod builder {
     #[must_use]
     pub struct EventBuilder<'a> {
         parent: &'a TraceSourceEvent,
-        data: HashMap<String, Value>,
+        data: Vec<Option<Value>>,
     }
 
     impl<'a> EventBuilder<'a> {
         pub(crate) fn new(parent: &'a TraceSourceEvent) -> Self {
             EventBuilder {
                 parent,
-                data: HashMap::new(),
+                data: vec![None; parent.schema.len()],
             }
         }
 
         /// Emit the event at the current time.
-        pub fn emit(mut self) -> Result<()> {
-            self.parent.emit(now_time_ns(), self.data.drain())
+        pub fn emit(self) -> Result<()> {
+            self.parent.emit_values(now_time_ns(), self.data)
         }
 
         /// Emit the event at a specific time.
-        pub fn emit_at(mut self, time_ns: i64) -> Result<()> {
-            self.parent.emit(time_ns, self.data.drain())
+        pub fn emit_at(self, time_ns: i64) -> Result<()> {
+            self.parent.emit_values(time_ns, self.data)
         }
 
         /// Emit the event at the current time via async
-        pub async fn emit_async(mut self) -> Result<()> {
+        pub async fn emit_async(self) -> Result<()> {
             self.parent
-                .emit_async(now_time_ns(), self.data.drain())
+                .emit_values_async(now_time_ns(), self.data)
                 .await
         }
 
         /// Emit the event at a specific time via async
-        pub async fn emit_at_async(mut self, time_ns: i64) -> Result<()> {
-            self.parent.emit_async(time_ns, self.data.drain()).await
+        pub async fn emit_at_async(self, time_ns: i64) -> Result<()> {
+            self.parent.emit_values_async(time_ns, self.data).await
         }
 
         /// Attempt to insert a value into the event, returning an error if the field is not found or the type does not match.
         pub fn try_insert(&mut self, name: &str, value: Value) -> Result<()> {
             // Find the field in the schema
-            let field = self
+            let (index, field) = self
                 .parent
                 .schema
                 .iter()
-                .find(|field| field.name == name)
+                .enumerate()
+                .find(|(_, field)| field.name == name)
                 .ok_or_else(|| anyhow!("Field '{}' not found in schema", name))?;
 
             // Check if our value matches the field type
@@ -288,7 +913,7 @@ pub mod builder {
             }
 
             // Insert the value into our event
-            self.data.insert(name.to_string(), value);
+            self.data[index] = Some(value);
             Ok(())
         }
 
@@ -363,11 +988,107 @@ pub mod builder {
         }
     }
 
+    #[must_use]
+    pub struct TraceSourceBuilder<'a> {
+        source_name: &'a str,
+        attributes: HashMap<String, String>,
+    }
+
+    impl<'a> TraceSourceBuilder<'a> {
+        pub(crate) fn new(source_name: &'a str) -> Self {
+            TraceSourceBuilder {
+                source_name,
+                attributes: default_attributes(),
+            }
+        }
+
+        /// Build the source, emitting its segment start with the attributes on `sender`.
+        pub fn build(self, sender: Sender) -> TraceSource {
+            TraceSource::with_attributes(self.source_name, sender, self.attributes)
+        }
+
+        /// Add an attribute such as a git SHA or test run id, replacing a default attribute with the same key
+        pub fn add_attribute(mut self, key: &str, value: &str) -> Self {
+            self.attributes.insert(key.to_string(), value.to_string());
+            self
+        }
+
+        /// Set the `app_version` attribute to the version or git SHA of the application, e.g.
+        /// `env!("CARGO_PKG_VERSION")` of the binary, replacing one from [`APP_VERSION_ENV`]
+        pub fn app_version(self, version: &str) -> Self {
+            self.add_attribute("app_version", version)
+        }
+    }
+
+    #[must_use]
+    pub struct TraceSourceActionBuilder<'a> {
+        source: &'a TraceSource,
+        name: &'a str,
+        parameters: Vec<TraceEventFieldMetadata>,
+        returns: Vec<TraceEventFieldMetadata>,
+    }
+
+    impl<'a> TraceSourceActionBuilder<'a> {
+        pub(crate) fn new(source: &'a TraceSource, name: &'a str) -> Self {
+            TraceSourceActionBuilder {
+                source,
+                name,
+                parameters: Vec::new(),
+                returns: Vec::new(),
+            }
+        }
+
+        /// Build the action with `handler` and add it to the source.
+        pub fn build(
+            self,
+            handler: impl Fn(HashMap<String, Value>) -> Result<HashMap<String, Value>>
+                + Send
+                + Sync
+                + 'static,
+        ) -> Result<()> {
+            self.source.add_action(
+                self.name,
+                self.parameters.into_iter(),
+                self.returns.into_iter(),
+                handler,
+            )
+        }
+
+        pub fn add_parameter(
+            mut self,
+            name: &str,
+            data_type: DataType,
+            unit: Option<String>,
+        ) -> Self {
+            self.parameters.push(TraceEventFieldMetadata {
+                name: name.to_string(),
+                data_type,
+                unit,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay::default(),
+            });
+            self
+        }
+
+        pub fn add_return(mut self, name: &str, data_type: DataType, unit: Option<String>) -> Self {
+            self.returns.push(TraceEventFieldMetadata {
+                name: name.to_string(),
+                data_type,
+                unit,
+                kind: FieldKind::Gauge,
+                display: FieldDisplay::default(),
+            });
+            self
+        }
+    }
+
     #[must_use]
     pub struct TraceSourceEventBuilder<'a> {
         source: &'a TraceSource,
         name: &'a str,
-        schema: HashMap<String, TraceEventFieldMetadata>,
+        /// Fields in the order they were added, which is the order of values for [`TraceSourceEvent::emit_values`]
+        schema: Vec<TraceEventFieldMetadata>,
+        displays: HashMap<String, FieldDisplay>,
     }
 
     impl<'a> TraceSourceEventBuilder<'a> {
@@ -375,31 +1096,92 @@ pub mod builder {
             TraceSourceEventBuilder {
                 source,
                 name,
-                schema: HashMap::new(),
+                schema: Vec::new(),
+                displays: HashMap::new(),
             }
         }
 
         /// Build the event and add it to the source.
         pub fn build(self) -> Result<Arc<TraceSourceEvent>> {
-            self.source.add_event(self.name, self.schema.into_values())
+            let (source, name, fields) = self.into_fields()?;
+            source.add_event(name, fields.into_iter())
         }
 
         /// Build the event and add it to the source via async.
         pub async fn build_async(self) -> Result<Arc<TraceSourceEvent>> {
-            self.source
-                .add_event_async(self.name, self.schema.into_values())
-                .await
+            let (source, name, fields) = self.into_fields()?;
+            source.add_event_async(name, fields.into_iter()).await
+        }
+
+        /// Build a new version of an event the source already has, replacing its fields, see
+        /// [`TraceSource::update_event`].
+        pub fn update(self) -> Result<Arc<TraceSourceEvent>> {
+            let (source, name, fields) = self.into_fields()?;
+            source.update_event(name, fields.into_iter())
+        }
+
+        /// Build a new version of an event the source already has via async.
+        pub async fn update_async(self) -> Result<Arc<TraceSourceEvent>> {
+            let (source, name, fields) = self.into_fields()?;
+            source.update_event_async(name, fields.into_iter()).await
+        }
+
+        /// The fields of the event in the order they were added with their displays applied, which fails if a field
+        /// was added twice or a display was given for a field that was never added
+        fn into_fields(
+            mut self,
+        ) -> Result<(&'a TraceSource, &'a str, Vec<TraceEventFieldMetadata>)> {
+            for (i, field) in self.schema.iter().enumerate() {
+                if self.schema[..i].iter().any(|f| f.name == field.name) {
+                    return Err(anyhow!(
+                        "Event {} has field {} more than once",
+                        self.name,
+                        field.name
+                    ));
+                }
+            }
+            for (field_name, display) in self.displays {
+                let field = self
+                    .schema
+                    .iter_mut()
+                    .find(|field| field.name == field_name)
+                    .ok_or_else(|| {
+                        anyhow!(
+                            "Event {} has no field {} to describe",
+                            self.name,
+                            field_name
+                        )
+                    })?;
+                field.display = display;
+            }
+            Ok((self.source, self.name, self.schema))
+        }
+
+        /// Describe how a field is displayed, e.g. its description, expected range and precision
+        pub fn describe_field(mut self, name: &str, display: FieldDisplay) -> Self {
+            self.displays.insert(name.to_string(), display);
+            self
         }
 
-        pub fn add_field(mut self, name: &str, data_type: DataType, unit: Option<String>) -> Self {
-            self.schema.insert(
-                name.to_string(),
-                TraceEventFieldMetadata {
-                    name: name.to_string(),
-                    data_type,
-                    unit,
-                },
-            );
+        pub fn add_field(self, name: &str, data_type: DataType, unit: Option<String>) -> Self {
+            self.add_field_of_kind(name, data_type, unit, FieldKind::Gauge)
+        }
+
+        /// Add a field whose values are of `kind`, such as a counter, instead of a gauge
+        pub fn add_field_of_kind(
+            mut self,
+            name: &str,
+            data_type: DataType,
+            unit: Option<String>,
+            kind: FieldKind,
+        ) -> Self {
+            self.schema.push(TraceEventFieldMetadata {
+                name: name.to_string(),
+                data_type,
+                unit,
+                kind,
+                display: FieldDisplay::default(),
+            });
             self
         }
 
@@ -510,7 +1292,7 @@ mod test {
             let m = receiver.recv()?;
             assert_eq!(m.segment_id, id);
             if let IpcMessage::TraceEvent(event) = &m.msg {
-                assert_eq!(event.name, "hello");
+                assert_eq!(&*event.name, "hello");
                 assert_eq!(event.fields.len(), 1);
                 let data = event.fields.iter().collect::<Vec<_>>();
                 assert_eq!(data[0].0, "sig");
@@ -531,7 +1313,7 @@ mod test {
             let m = receiver.recv()?;
             assert_eq!(m.segment_id, id);
             if let IpcMessage::TraceEvent(event) = &m.msg {
-                assert_eq!(event.name, "hello");
+                assert_eq!(&*event.name, "hello");
                 assert_eq!(event.fields.len(), 1);
                 let data = event.fields.iter().collect::<Vec<_>>();
                 assert_eq!(data[0].0, "sig");
@@ -553,4 +1335,366 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_emit_values() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        let evt = src
+            .build_event("pump")
+            .add_u8_field("state", None)
+            .add_f32_field("pressure", Some("bar".to_string()))
+            .build()?;
+        receiver.drain();
+
+        // One value per field, in schema order
+        let err = evt
+            .emit_values(1, vec![Some(Value::UInt8(1))])
+            .err()
+            .ok_or_else(|| anyhow!("Expected a short value list to fail"))?;
+        assert!(err.to_string().contains("Expected 2 values"));
+        assert!(receiver.is_empty());
+
+        // In the order the fields were added
+        evt.emit_values(2, vec![None, Some(Value::Float32(1.5))])?;
+        let IpcMessage::TraceEvent(event) = receiver.recv()?.msg else {
+            panic!("Expected TraceEvent");
+        };
+        assert!(matches!(event.fields, TraceEventFields::Indexed { .. }));
+        assert_eq!(event.fields.len(), 1);
+        assert_eq!(event.fields.get("pressure"), Some(&Value::Float32(1.5)));
+
+        // A field can only be added once
+        assert!(src
+            .build_event("fan")
+            .add_u16_field("rpm", None)
+            .add_u16_field("rpm", None)
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_describe_field() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        receiver.drain();
+
+        let display = FieldDisplay::default()
+            .with_description("Coolant temperature at the pump outlet")
+            .with_range(-40.0, 125.0)
+            .with_precision(1);
+        src.build_event("coolant")
+            .describe_field("temperature", display.clone())
+            .add_f32_field("temperature", Some("degC".to_string()))
+            .add_bool_field("pump_on", None)
+            .build()?;
+        let IpcMessage::TraceEventSchema(schema) = receiver.recv()?.msg else {
+            panic!("Expected TraceEventSchema");
+        };
+        for field in schema.fields {
+            match field.name.as_str() {
+                "temperature" => assert_eq!(field.display, display),
+                _ => assert!(field.display.is_empty()),
+            }
+        }
+
+        // Describing a field that was never added is a mistake
+        assert!(src
+            .build_event("fan")
+            .add_u16_field("rpm", None)
+            .describe_field("speed", display)
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_event() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        let first = src
+            .build_event("pump")
+            .add_u8_field("state", None)
+            .build()?;
+        receiver.drain();
+
+        // Add a field mid-segment
+        let evt = src
+            .build_event("pump")
+            .add_u8_field("state", None)
+            .add_f32_field("pressure", Some("bar".to_string()))
+            .update()?;
+        assert_eq!(evt.version, 1);
+        let IpcMessage::TraceEventSchema(schema) = receiver.recv()?.msg else {
+            panic!("Expected TraceEventSchema");
+        };
+        assert_eq!(schema.version, 1);
+        assert_eq!(schema.fields.len(), 2);
+        assert_eq!(src.get_event("pump")?.version, 1);
+
+        // A kept field cannot change type, and the current version stays in place
+        let err = src
+            .build_event("pump")
+            .add_string_field("state", None)
+            .update()
+            .err()
+            .ok_or_else(|| anyhow!("Expected a type change to fail"))?;
+        assert!(err.to_string().contains("cannot change type"));
+        assert_eq!(src.get_event("pump")?.version, 1);
+        assert!(receiver.is_empty());
+
+        // Handles to the replaced version cannot emit, its events would be read with the new fields
+        let err = first
+            .build()
+            .try_insert_u8("state", 1)?
+            .emit()
+            .err()
+            .ok_or_else(|| anyhow!("Expected a stale handle to fail"))?;
+        assert!(err.to_string().contains("was replaced by version 1"));
+        evt.build().try_insert_u8("state", 1)?.emit()?;
+        assert!(matches!(receiver.recv()?.msg, IpcMessage::TraceEvent(_)));
+
+        // A removed field cannot come back with another type either
+        src.build_event("pump")
+            .add_f32_field("pressure", Some("bar".to_string()))
+            .update()?;
+        assert!(src
+            .build_event("pump")
+            .add_string_field("state", None)
+            .update()
+            .is_err());
+        receiver.drain();
+
+        // Every version is sent again, oldest first
+        src.resend_metadata()?;
+        let versions: Vec<_> = receiver
+            .drain()
+            .filter_map(|msg| match msg.msg {
+                IpcMessage::TraceEventSchema(schema) => Some(schema.version),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(versions, [0, 1, 2]);
+
+        // Only existing events can be updated
+        assert!(src
+            .build_event("fan")
+            .add_u16_field("rpm", None)
+            .update()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_event_while_emitting() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        let first = src
+            .build_event("pump")
+            .add_u8_field("state", None)
+            .build()?;
+        receiver.drain();
+
+        // Emit from the first version on another thread until it is replaced
+        let emitter = std::thread::spawn(move || {
+            let mut emitted = 0;
+            while first.emit_values(0, vec![Some(Value::UInt8(1))]).is_ok() {
+                emitted += 1;
+            }
+            emitted
+        });
+        while receiver.is_empty() {
+            std::thread::yield_now();
+        }
+        src.build_event("pump")
+            .add_f32_field("pressure", None)
+            .update()?;
+        let Ok(emitted) = emitter.join() else {
+            panic!("Emitter panicked");
+        };
+
+        // Every event of the first version is sent ahead of the schema that replaces it
+        let msgs: Vec<_> = receiver.drain().collect();
+        let schema = msgs
+            .iter()
+            .position(|msg| matches!(msg.msg, IpcMessage::TraceEventSchema(_)))
+            .ok_or_else(|| anyhow!("Expected the new schema"))?;
+        assert_eq!(schema, emitted);
+        assert!(msgs[..schema]
+            .iter()
+            .all(|msg| matches!(msg.msg, IpcMessage::TraceEvent(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_control() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        let evt = src
+            .build_event("hello")
+            .add_i32_field("sig", None)
+            .build()?;
+        src.add_value_table(
+            "hello",
+            "sig",
+            [(Value::Int32(1), "one".to_string())].into_iter(),
+        )?;
+        let _ = receiver.drain();
+        let emit = |time_ns| evt.build().try_insert_i32("sig", 1)?.emit_at(time_ns);
+        let control = |source_name: Option<&str>, command| TraceControl {
+            source_name: source_name.map(|name| name.to_string()),
+            command,
+        };
+
+        // Commands for other sources are ignored
+        let disable = TraceControlCommand::SetEventEnabled {
+            event_name: Some("hello".to_string()),
+            enabled: false,
+        };
+        assert!(!src.apply_control(&control(Some("other"), disable.clone()))?);
+        assert!(evt.is_enabled());
+
+        // Disabled events are dropped
+        assert!(src.apply_control(&control(Some("src"), disable))?);
+        emit(0)?;
+        assert!(receiver.is_empty());
+
+        // Rate limited events are dropped until the interval has passed
+        let enable = TraceControlCommand::SetEventEnabled {
+            event_name: None,
+            enabled: true,
+        };
+        src.apply_control(&control(None, enable))?;
+        src.apply_control(&control(
+            None,
+            TraceControlCommand::SetSampleRate {
+                event_name: Some("hello".to_string()),
+                max_rate_hz: Some(10.0),
+            },
+        ))?;
+        for time_ns in (0..300_000_000).step_by(50_000_000) {
+            emit(time_ns)?;
+        }
+        let times: Vec<_> = receiver.drain().filter_map(|m| m.msg.time_ns()).collect();
+        assert_eq!(times, [0, 100_000_000, 200_000_000]);
+
+        // A rate so low its interval overflows still limits rather than disabling the limit
+        evt.set_max_rate(Some(1e-12));
+        emit(0)?;
+        emit(1_000_000_000_000_000_000)?;
+        assert_eq!(receiver.drain().count(), 1);
+        evt.set_max_rate(None);
+
+        // Metadata is emitted again on request
+        src.apply_control(&control(None, TraceControlCommand::ResendMetadata))?;
+        let metadata: Vec<_> = receiver.drain().map(|m| m.msg).collect();
+        assert!(matches!(&metadata[..], [
+            IpcMessage::TraceEventSchema(schema),
+            IpcMessage::TraceEventFieldNamedValues(table),
+        ] if schema.name == "hello" && table.field_name == "sig"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_concurrent_rate_limit() -> Result<()> {
+        let controls = EventControls::default();
+        controls.min_interval_ns.store(1_000, Ordering::Relaxed);
+
+        // Of many emitters racing with the same time, only one is admitted
+        let admitted: usize = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| scope.spawn(|| (0..100).filter(|_| controls.admit(500)).count()))
+                .collect();
+            handles.into_iter().filter_map(|h| h.join().ok()).sum()
+        });
+        assert_eq!(admitted, 1);
+        assert!(controls.admit(1_500));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parameters() -> Result<()> {
+        let (sender, receiver) = flume::unbounded::<IpcMessageWithId>();
+        let src = TraceSource::new("src", sender);
+        let _ = receiver.drain();
+
+        // Defaults must be within the range, and bounds must have the parameter's type
+        assert!(src
+            .add_parameter(
+                "gain",
+                Value::Float64(5.0),
+                None,
+                Some(Value::Float64(1.0)),
+                None
+            )
+            .is_err());
+        assert!(src
+            .add_parameter(
+                "gain",
+                Value::Float64(0.5),
+                Some(Value::Int32(0)),
+                None,
+                None
+            )
+            .is_err());
+
+        let gain = src.add_parameter(
+            "gain",
+            Value::Float64(0.5),
+            Some(Value::Float64(0.0)),
+            Some(Value::Float64(1.0)),
+            None,
+        )?;
+        let mut watch = gain.watch();
+        let published = |receiver: &flume::Receiver<IpcMessageWithId>| -> Vec<Value> {
+            receiver
+                .drain()
+                .filter_map(|m| match m.msg {
+                    IpcMessage::TraceEvent(event) if &*event.name == "parameters/gain" => {
+                        event.fields.get("value").cloned()
+                    }
+                    _ => None,
+                })
+                .collect()
+        };
+        assert_eq!(published(&receiver), [Value::Float64(0.5)]);
+
+        // Valid values are applied, published and watched
+        let set = |value| TraceControl {
+            source_name: Some("src".to_string()),
+            command: TraceControlCommand::SetParameter {
+                name: "gain".to_string(),
+                value,
+                call_id: None,
+                segment_id: None,
+            },
+        };
+        src.apply_control(&set(Value::Float64(0.75)))?;
+        assert!(watch.has_changed()?);
+        assert_eq!(*watch.borrow_and_update(), Value::Float64(0.75));
+        assert_eq!(published(&receiver), [Value::Float64(0.75)]);
+
+        // Invalid values are rejected and leave the parameter unchanged
+        assert!(src.apply_control(&set(Value::Float64(2.0))).is_err());
+        assert!(src.apply_control(&set(Value::Int32(0))).is_err());
+        assert!(!watch.has_changed()?);
+        assert_eq!(gain.get(), Value::Float64(0.75));
+        assert!(published(&receiver).is_empty());
+
+        // Disabling all events leaves the parameter events alone
+        src.apply_control(&TraceControl {
+            source_name: None,
+            command: TraceControlCommand::SetEventEnabled {
+                event_name: None,
+                enabled: false,
+            },
+        })?;
+        src.apply_control(&set(Value::Float64(0.25)))?;
+        assert_eq!(published(&receiver), [Value::Float64(0.25)]);
+        Ok(())
+    }
 }
@@ -9,6 +9,11 @@ pub trait Store: Send + Sync {
 
     /// Updates this store with an ipc message
     fn update(&self, msg: &ipc::IpcMessageWithId) -> Result<()>;
+
+    /// The live metadata this store keeps, shared with anything that needs to look up segments, if it keeps any
+    fn shared_metadata(&self) -> Option<TraceMetadata> {
+        None
+    }
 }
 
 pub struct MetadataOnlyStore {
@@ -21,6 +26,11 @@ impl MetadataOnlyStore {
             metadata: TraceMetadata::new(),
         }
     }
+
+    /// The metadata tracked by this store, shared with any clones
+    pub fn metadata(&self) -> &TraceMetadata {
+        &self.metadata
+    }
 }
 
 impl Store for MetadataOnlyStore {
@@ -29,8 +39,11 @@ impl Store for MetadataOnlyStore {
     }
 
     fn update(&self, msg: &ipc::IpcMessageWithId) -> Result<()> {
-        self.metadata.update(msg);
-        Ok(())
+        self.metadata.update(msg)
+    }
+
+    fn shared_metadata(&self) -> Option<TraceMetadata> {
+        Some(self.metadata.clone())
     }
 }
 
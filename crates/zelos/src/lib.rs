@@ -5,7 +5,11 @@
 
 pub use zelos_proto as proto;
 pub use zelos_trace as trace;
+pub use zelos_trace_datagram as trace_datagram;
 pub use zelos_trace_grpc as trace_grpc;
+pub use zelos_trace_http as trace_http;
+pub use zelos_trace_shm as trace_shm;
+pub use zelos_trace_udp as trace_udp;
 
 // Re-export commonly used types
 pub use zelos_trace::{Store, TraceRouter, TraceSink, TraceSource};
@@ -2,7 +2,10 @@ use std::time::Duration;
 
 use tokio_util::sync::CancellationToken;
 use zelos_trace::{TraceRouter, TraceSource};
-use zelos_trace_grpc::publish::{TracePublishClient, TracePublishClientConfig};
+use zelos_trace_grpc::{
+    publish::{TracePublishClient, TracePublishClientConfig},
+    BackoffConfig, UrlSelection,
+};
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
@@ -17,16 +20,29 @@ async fn main() -> anyhow::Result<()> {
     // Custom client configuration
     let config = TracePublishClientConfig {
         url: url.clone(),
+        fallback_urls: vec!["grpc://127.0.0.1:2301".to_string()],
+        url_selection: UrlSelection::Failover,
         batch_size: 256,
         batch_timeout: Duration::from_millis(50),
-        reconnect_delay: Duration::from_millis(500),
+        columnar: true,
+        reconnect: BackoffConfig {
+            initial_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.5,
+            max_attempts: Some(10),
+            stable_after: Duration::from_secs(30),
+        },
     };
 
     let (client, client_task) = TracePublishClient::new(router.clone(), config);
     tokio::spawn(client_task);
 
     client.wait_until_connected(Duration::from_secs(5)).await?;
-    println!("Connected to agent at {url}");
+    println!(
+        "Connected to agent after {} attempt(s)",
+        client.connection_stats().attempts
+    );
 
     // Emit a short burst of events
     let source = TraceSource::new("publish-config-demo", router.sender());
@@ -0,0 +1,46 @@
+use std::time::{Duration, Instant};
+use tokio::time::{interval, MissedTickBehavior};
+use tokio_util::sync::CancellationToken;
+use zelos_trace::{TraceRouter, TraceSource};
+use zelos_trace_shm::{ShmPublisher, ShmPublisherConfig};
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    tracing_subscriber::fmt::init();
+
+    // The agent must be started with a ring at the same path, e.g. `just agent --shm-ring /dev/shm/zelos-high-freq`
+    let path = std::env::var("ZELOS_SHM_PATH")
+        .map(Into::into)
+        .unwrap_or_else(|_| zelos_trace_shm::default_path("high-freq"));
+
+    let cancellation_token = CancellationToken::new();
+    let (router, router_task) = TraceRouter::new(cancellation_token.clone());
+    tokio::spawn(router_task);
+
+    let publisher = ShmPublisher::new(router.clone(), path, ShmPublisherConfig::default());
+    tokio::spawn(publisher.run(cancellation_token.clone()));
+
+    let source = TraceSource::new("high_freq_shm", router.sender());
+    let data_event = source
+        .build_event("data")
+        .add_f64_field("value", Some("V".to_string()))
+        .build()?;
+
+    // 10 kHz = 100us period, in bursts of 10 samples per millisecond
+    let mut tick = interval(Duration::from_millis(1));
+    tick.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let start = Instant::now();
+    loop {
+        tick.tick().await;
+        let now_ns = zelos_trace::time::now_time_ns();
+        for i in 0..10 {
+            let elapsed = start.elapsed().as_secs_f64() + i as f64 * 100e-6;
+            let value = (2.0 * std::f64::consts::PI * 100.0 * elapsed).sin();
+            data_event
+                .build()
+                .try_insert_f64("value", value)?
+                .emit_at(now_ns + i * 100_000)?;
+        }
+    }
+}
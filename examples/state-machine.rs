@@ -56,14 +56,21 @@ async fn main() -> anyhow::Result<()> {
         .into_iter(),
     )?;
 
-    // Emit some sample transitions
+    // Emit some sample transitions, each state also being an interval that ends at the next transition
+    let names = ["IDLE", "INIT", "RUNNING", "ERROR"];
     let transitions = [
         (0u8, 1u8, 12.3),
         (1u8, 2u8, 5.4),
         (2u8, 3u8, 1.1),
         (3u8, 0u8, 20.7),
     ];
+    let mut state = source.begin_interval(names[0], [])?;
     for (previous, current, ms) in transitions {
+        state.end([("next".to_string(), Value::UInt8(current))])?;
+        state = source.begin_interval(
+            names[current as usize],
+            [("previous".to_string(), Value::UInt8(previous))],
+        )?;
         state_event
             .build()
             .try_insert_u8("current", current)?
@@ -0,0 +1,67 @@
+use std::{
+    net::UdpSocket,
+    time::{Duration, Instant},
+};
+
+use zelos_trace_datagram::{
+    decode, Encoder, FieldDef, FieldType, FieldValue, Packet, MAX_DATAGRAM_LEN, RECEIVE_TIME,
+};
+
+const SCHEMA_ID: u16 = 1;
+
+/// Publishes like a microcontroller would: a fixed buffer, no clock and plain UDP
+fn main() -> anyhow::Result<()> {
+    // The agent must be started with a UDP listener, e.g. `just agent --udp-listen 127.0.0.1:2302`
+    let addr = std::env::var("ZELOS_UDP_ADDR").unwrap_or_else(|_| "127.0.0.1:2302".to_string());
+    let socket = UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(&addr)?;
+    socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+
+    // A new segment id for every boot
+    let mut encoder = Encoder::new(rand::random());
+    let mut buf = [0; MAX_DATAGRAM_LEN];
+    let fields = [
+        FieldDef {
+            name: "temperature",
+            field_type: FieldType::Float32,
+            unit: Some("°C"),
+        },
+        FieldDef {
+            name: "heater_on",
+            field_type: FieldType::Boolean,
+            unit: None,
+        },
+    ];
+
+    // Register the schema until the agent acks it
+    loop {
+        socket.send(encoder.register(&mut buf, SCHEMA_ID, "udp_device", "thermal", &fields)?)?;
+        let mut ack = [0; MAX_DATAGRAM_LEN];
+        let Ok(len) = socket.recv(&mut ack) else {
+            println!("No ack from {}, retrying", addr);
+            continue;
+        };
+        if let Ok((
+            _,
+            Packet::RegisterAck {
+                schema_id: SCHEMA_ID,
+            },
+        )) = decode(&ack[..len])
+        {
+            break;
+        }
+    }
+    println!("Registered with {}, sending at 100 Hz", addr);
+
+    let start = Instant::now();
+    loop {
+        let t = start.elapsed().as_secs_f32();
+        let temperature = 40.0 + 5.0 * (t / 10.0).sin();
+        let values = [
+            FieldValue::Float32(temperature),
+            FieldValue::Boolean(temperature < 40.0),
+        ];
+        socket.send(encoder.event(&mut buf, SCHEMA_ID, RECEIVE_TIME, &values)?)?;
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,222 @@
+// Code generated by protoc-gen-go. DO NOT EDIT.
+// versions:
+// 	protoc-gen-go v1.36.7
+// 	protoc        v6.31.1
+// source: zeloscloud/trace/action.proto
+
+package trace
+
+import (
+	protoreflect "google.golang.org/protobuf/reflect/protoreflect"
+	protoimpl "google.golang.org/protobuf/runtime/protoimpl"
+	reflect "reflect"
+	sync "sync"
+	unsafe "unsafe"
+)
+
+const (
+	// Verify that this generated code is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(20 - protoimpl.MinVersion)
+	// Verify that runtime/protoimpl is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(protoimpl.MaxVersion - 20)
+)
+
+type CallActionRequest struct {
+	state      protoimpl.MessageState  `protogen:"open.v1"`
+	SourceName string                  `protobuf:"bytes,1,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	ActionName string                  `protobuf:"bytes,2,opt,name=action_name,json=actionName,proto3" json:"action_name,omitempty"`
+	Arguments  []*TraceEventFieldEntry `protobuf:"bytes,3,rep,name=arguments,proto3" json:"arguments,omitempty"`
+	// How long to wait for the result, the server's default if unset
+	TimeoutMs *uint64 `protobuf:"varint,4,opt,name=timeout_ms,json=timeoutMs,proto3,oneof" json:"timeout_ms,omitempty"`
+	// The segment of the source instance to call, needed if several live segments of the source have the action
+	SegmentId     []byte `protobuf:"bytes,5,opt,name=segment_id,json=segmentId,proto3,oneof" json:"segment_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *CallActionRequest) Reset() {
+	*x = CallActionRequest{}
+	mi := &file_zeloscloud_trace_action_proto_msgTypes[0]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *CallActionRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*CallActionRequest) ProtoMessage() {}
+
+func (x *CallActionRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_action_proto_msgTypes[0]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use CallActionRequest.ProtoReflect.Descriptor instead.
+func (*CallActionRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_action_proto_rawDescGZIP(), []int{0}
+}
+
+func (x *CallActionRequest) GetSourceName() string {
+	if x != nil {
+		return x.SourceName
+	}
+	return ""
+}
+
+func (x *CallActionRequest) GetActionName() string {
+	if x != nil {
+		return x.ActionName
+	}
+	return ""
+}
+
+func (x *CallActionRequest) GetArguments() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Arguments
+	}
+	return nil
+}
+
+func (x *CallActionRequest) GetTimeoutMs() uint64 {
+	if x != nil && x.TimeoutMs != nil {
+		return *x.TimeoutMs
+	}
+	return 0
+}
+
+func (x *CallActionRequest) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+type CallActionResponse struct {
+	state         protoimpl.MessageState  `protogen:"open.v1"`
+	Values        []*TraceEventFieldEntry `protobuf:"bytes,1,rep,name=values,proto3" json:"values,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *CallActionResponse) Reset() {
+	*x = CallActionResponse{}
+	mi := &file_zeloscloud_trace_action_proto_msgTypes[1]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *CallActionResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*CallActionResponse) ProtoMessage() {}
+
+func (x *CallActionResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_action_proto_msgTypes[1]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use CallActionResponse.ProtoReflect.Descriptor instead.
+func (*CallActionResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_action_proto_rawDescGZIP(), []int{1}
+}
+
+func (x *CallActionResponse) GetValues() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Values
+	}
+	return nil
+}
+
+var File_zeloscloud_trace_action_proto protoreflect.FileDescriptor
+
+const file_zeloscloud_trace_action_proto_rawDesc = "" +
+	"\n" +
+	"\x1dzeloscloud/trace/action.proto\x12\x10zeloscloud.trace\x1a\x1czeloscloud/trace/trace.proto\"\x81\x02\n" +
+	"\x11CallActionRequest\x12\x1f\n" +
+	"\vsource_name\x18\x01 \x01(\tR\n" +
+	"sourceName\x12\x1f\n" +
+	"\vaction_name\x18\x02 \x01(\tR\n" +
+	"actionName\x12D\n" +
+	"\targuments\x18\x03 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\targuments\x12\"\n" +
+	"\n" +
+	"timeout_ms\x18\x04 \x01(\x04H\x00R\ttimeoutMs\x88\x01\x01\x12\"\n" +
+	"\n" +
+	"segment_id\x18\x05 \x01(\fH\x01R\tsegmentId\x88\x01\x01B\r\n" +
+	"\v_timeout_msB\r\n" +
+	"\v_segment_id\"T\n" +
+	"\x12CallActionResponse\x12>\n" +
+	"\x06values\x18\x01 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06values2`\n" +
+	"\vTraceAction\x12Q\n" +
+	"\x04Call\x12#.zeloscloud.trace.CallActionRequest\x1a$.zeloscloud.trace.CallActionResponseb\x06proto3"
+
+var (
+	file_zeloscloud_trace_action_proto_rawDescOnce sync.Once
+	file_zeloscloud_trace_action_proto_rawDescData []byte
+)
+
+func file_zeloscloud_trace_action_proto_rawDescGZIP() []byte {
+	file_zeloscloud_trace_action_proto_rawDescOnce.Do(func() {
+		file_zeloscloud_trace_action_proto_rawDescData = protoimpl.X.CompressGZIP(unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_action_proto_rawDesc), len(file_zeloscloud_trace_action_proto_rawDesc)))
+	})
+	return file_zeloscloud_trace_action_proto_rawDescData
+}
+
+var file_zeloscloud_trace_action_proto_msgTypes = make([]protoimpl.MessageInfo, 2)
+var file_zeloscloud_trace_action_proto_goTypes = []any{
+	(*CallActionRequest)(nil),    // 0: zeloscloud.trace.CallActionRequest
+	(*CallActionResponse)(nil),   // 1: zeloscloud.trace.CallActionResponse
+	(*TraceEventFieldEntry)(nil), // 2: zeloscloud.trace.TraceEventFieldEntry
+}
+var file_zeloscloud_trace_action_proto_depIdxs = []int32{
+	2, // 0: zeloscloud.trace.CallActionRequest.arguments:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	2, // 1: zeloscloud.trace.CallActionResponse.values:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	0, // 2: zeloscloud.trace.TraceAction.Call:input_type -> zeloscloud.trace.CallActionRequest
+	1, // 3: zeloscloud.trace.TraceAction.Call:output_type -> zeloscloud.trace.CallActionResponse
+	3, // [3:4] is the sub-list for method output_type
+	2, // [2:3] is the sub-list for method input_type
+	2, // [2:2] is the sub-list for extension type_name
+	2, // [2:2] is the sub-list for extension extendee
+	0, // [0:2] is the sub-list for field type_name
+}
+
+func init() { file_zeloscloud_trace_action_proto_init() }
+func file_zeloscloud_trace_action_proto_init() {
+	if File_zeloscloud_trace_action_proto != nil {
+		return
+	}
+	file_zeloscloud_trace_trace_proto_init()
+	file_zeloscloud_trace_action_proto_msgTypes[0].OneofWrappers = []any{}
+	type x struct{}
+	out := protoimpl.TypeBuilder{
+		File: protoimpl.DescBuilder{
+			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
+			RawDescriptor: unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_action_proto_rawDesc), len(file_zeloscloud_trace_action_proto_rawDesc)),
+			NumEnums:      0,
+			NumMessages:   2,
+			NumExtensions: 0,
+			NumServices:   1,
+		},
+		GoTypes:           file_zeloscloud_trace_action_proto_goTypes,
+		DependencyIndexes: file_zeloscloud_trace_action_proto_depIdxs,
+		MessageInfos:      file_zeloscloud_trace_action_proto_msgTypes,
+	}.Build()
+	File_zeloscloud_trace_action_proto = out.File
+	file_zeloscloud_trace_action_proto_goTypes = nil
+	file_zeloscloud_trace_action_proto_depIdxs = nil
+}
@@ -0,0 +1,127 @@
+// Code generated by protoc-gen-go-grpc. DO NOT EDIT.
+// versions:
+// - protoc-gen-go-grpc v1.5.1
+// - protoc             v6.31.1
+// source: zeloscloud/trace/action.proto
+
+package trace
+
+import (
+	context "context"
+	grpc "google.golang.org/grpc"
+	codes "google.golang.org/grpc/codes"
+	status "google.golang.org/grpc/status"
+)
+
+// This is a compile-time assertion to ensure that this generated file
+// is compatible with the grpc package it is being compiled against.
+// Requires gRPC-Go v1.64.0 or later.
+const _ = grpc.SupportPackageIsVersion9
+
+const (
+	TraceAction_Call_FullMethodName = "/zeloscloud.trace.TraceAction/Call"
+)
+
+// TraceActionClient is the client API for TraceAction service.
+//
+// For semantics around ctx use and closing/ending streaming RPCs, please refer to https://pkg.go.dev/google.golang.org/grpc/?tab=doc#ClientConn.NewStream.
+//
+// Call actions registered by sources. Calls are routed to the publisher of one instance of the source and its result is
+// returned.
+type TraceActionClient interface {
+	Call(ctx context.Context, in *CallActionRequest, opts ...grpc.CallOption) (*CallActionResponse, error)
+}
+
+type traceActionClient struct {
+	cc grpc.ClientConnInterface
+}
+
+func NewTraceActionClient(cc grpc.ClientConnInterface) TraceActionClient {
+	return &traceActionClient{cc}
+}
+
+func (c *traceActionClient) Call(ctx context.Context, in *CallActionRequest, opts ...grpc.CallOption) (*CallActionResponse, error) {
+	cOpts := append([]grpc.CallOption{grpc.StaticMethod()}, opts...)
+	out := new(CallActionResponse)
+	err := c.cc.Invoke(ctx, TraceAction_Call_FullMethodName, in, out, cOpts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+// TraceActionServer is the server API for TraceAction service.
+// All implementations must embed UnimplementedTraceActionServer
+// for forward compatibility.
+//
+// Call actions registered by sources. Calls are routed to the publisher of one instance of the source and its result is
+// returned.
+type TraceActionServer interface {
+	Call(context.Context, *CallActionRequest) (*CallActionResponse, error)
+	mustEmbedUnimplementedTraceActionServer()
+}
+
+// UnimplementedTraceActionServer must be embedded to have
+// forward compatible implementations.
+//
+// NOTE: this should be embedded by value instead of pointer to avoid a nil
+// pointer dereference when methods are called.
+type UnimplementedTraceActionServer struct{}
+
+func (UnimplementedTraceActionServer) Call(context.Context, *CallActionRequest) (*CallActionResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Call not implemented")
+}
+func (UnimplementedTraceActionServer) mustEmbedUnimplementedTraceActionServer() {}
+func (UnimplementedTraceActionServer) testEmbeddedByValue()                     {}
+
+// UnsafeTraceActionServer may be embedded to opt out of forward compatibility for this service.
+// Use of this interface is not recommended, as added methods to TraceActionServer will
+// result in compilation errors.
+type UnsafeTraceActionServer interface {
+	mustEmbedUnimplementedTraceActionServer()
+}
+
+func RegisterTraceActionServer(s grpc.ServiceRegistrar, srv TraceActionServer) {
+	// If the following call pancis, it indicates UnimplementedTraceActionServer was
+	// embedded by pointer and is nil.  This will cause panics if an
+	// unimplemented method is ever invoked, so we test this at initialization
+	// time to prevent it from happening at runtime later due to I/O.
+	if t, ok := srv.(interface{ testEmbeddedByValue() }); ok {
+		t.testEmbeddedByValue()
+	}
+	s.RegisterService(&TraceAction_ServiceDesc, srv)
+}
+
+func _TraceAction_Call_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(CallActionRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(TraceActionServer).Call(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: TraceAction_Call_FullMethodName,
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(TraceActionServer).Call(ctx, req.(*CallActionRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+// TraceAction_ServiceDesc is the grpc.ServiceDesc for TraceAction service.
+// It's only intended for direct use with grpc.RegisterService,
+// and not to be introspected or modified (even as a copy)
+var TraceAction_ServiceDesc = grpc.ServiceDesc{
+	ServiceName: "zeloscloud.trace.TraceAction",
+	HandlerType: (*TraceActionServer)(nil),
+	Methods: []grpc.MethodDesc{
+		{
+			MethodName: "Call",
+			Handler:    _TraceAction_Call_Handler,
+		},
+	},
+	Streams:  []grpc.StreamDesc{},
+	Metadata: "zeloscloud/trace/action.proto",
+}
@@ -0,0 +1,848 @@
+// Code generated by protoc-gen-go. DO NOT EDIT.
+// versions:
+// 	protoc-gen-go v1.36.7
+// 	protoc        v6.31.1
+// source: zeloscloud/trace/catalog.proto
+
+package trace
+
+import (
+	protoreflect "google.golang.org/protobuf/reflect/protoreflect"
+	protoimpl "google.golang.org/protobuf/runtime/protoimpl"
+	reflect "reflect"
+	sync "sync"
+	unsafe "unsafe"
+)
+
+const (
+	// Verify that this generated code is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(20 - protoimpl.MinVersion)
+	// Verify that runtime/protoimpl is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(protoimpl.MaxVersion - 20)
+)
+
+type SegmentInfo struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the segment
+	SegmentId     []byte            `protobuf:"bytes,1,opt,name=segment_id,json=segmentId,proto3" json:"segment_id,omitempty"`
+	SourceName    string            `protobuf:"bytes,2,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	StartTimeNs   *int64            `protobuf:"fixed64,3,opt,name=start_time_ns,json=startTimeNs,proto3,oneof" json:"start_time_ns,omitempty"`
+	EndTimeNs     *int64            `protobuf:"fixed64,4,opt,name=end_time_ns,json=endTimeNs,proto3,oneof" json:"end_time_ns,omitempty"`
+	Attributes    map[string]string `protobuf:"bytes,5,rep,name=attributes,proto3" json:"attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SegmentInfo) Reset() {
+	*x = SegmentInfo{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[0]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SegmentInfo) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SegmentInfo) ProtoMessage() {}
+
+func (x *SegmentInfo) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[0]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SegmentInfo.ProtoReflect.Descriptor instead.
+func (*SegmentInfo) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{0}
+}
+
+func (x *SegmentInfo) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+func (x *SegmentInfo) GetSourceName() string {
+	if x != nil {
+		return x.SourceName
+	}
+	return ""
+}
+
+func (x *SegmentInfo) GetStartTimeNs() int64 {
+	if x != nil && x.StartTimeNs != nil {
+		return *x.StartTimeNs
+	}
+	return 0
+}
+
+func (x *SegmentInfo) GetEndTimeNs() int64 {
+	if x != nil && x.EndTimeNs != nil {
+		return *x.EndTimeNs
+	}
+	return 0
+}
+
+func (x *SegmentInfo) GetAttributes() map[string]string {
+	if x != nil {
+		return x.Attributes
+	}
+	return nil
+}
+
+type ListSegmentsRequest struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// Only return segments produced by this source
+	SourceName *string `protobuf:"bytes,1,opt,name=source_name,json=sourceName,proto3,oneof" json:"source_name,omitempty"`
+	// Only return segments that joined this session
+	SessionId     []byte `protobuf:"bytes,2,opt,name=session_id,json=sessionId,proto3,oneof" json:"session_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSegmentsRequest) Reset() {
+	*x = ListSegmentsRequest{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[1]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSegmentsRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSegmentsRequest) ProtoMessage() {}
+
+func (x *ListSegmentsRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[1]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSegmentsRequest.ProtoReflect.Descriptor instead.
+func (*ListSegmentsRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{1}
+}
+
+func (x *ListSegmentsRequest) GetSourceName() string {
+	if x != nil && x.SourceName != nil {
+		return *x.SourceName
+	}
+	return ""
+}
+
+func (x *ListSegmentsRequest) GetSessionId() []byte {
+	if x != nil {
+		return x.SessionId
+	}
+	return nil
+}
+
+type ListSegmentsResponse struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Segments      []*SegmentInfo         `protobuf:"bytes,1,rep,name=segments,proto3" json:"segments,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSegmentsResponse) Reset() {
+	*x = ListSegmentsResponse{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[2]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSegmentsResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSegmentsResponse) ProtoMessage() {}
+
+func (x *ListSegmentsResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[2]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSegmentsResponse.ProtoReflect.Descriptor instead.
+func (*ListSegmentsResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{2}
+}
+
+func (x *ListSegmentsResponse) GetSegments() []*SegmentInfo {
+	if x != nil {
+		return x.Segments
+	}
+	return nil
+}
+
+type GetSegmentRequest struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	SegmentId     []byte                 `protobuf:"bytes,1,opt,name=segment_id,json=segmentId,proto3" json:"segment_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *GetSegmentRequest) Reset() {
+	*x = GetSegmentRequest{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[3]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *GetSegmentRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*GetSegmentRequest) ProtoMessage() {}
+
+func (x *GetSegmentRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[3]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use GetSegmentRequest.ProtoReflect.Descriptor instead.
+func (*GetSegmentRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{3}
+}
+
+func (x *GetSegmentRequest) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+type GetSegmentResponse struct {
+	state         protoimpl.MessageState        `protogen:"open.v1"`
+	Segment       *SegmentInfo                  `protobuf:"bytes,1,opt,name=segment,proto3" json:"segment,omitempty"`
+	Schemas       []*TraceEventSchema           `protobuf:"bytes,2,rep,name=schemas,proto3" json:"schemas,omitempty"`
+	ValueTables   []*TraceEventFieldNamedValues `protobuf:"bytes,3,rep,name=value_tables,json=valueTables,proto3" json:"value_tables,omitempty"`
+	Actions       []*TraceActionSchema          `protobuf:"bytes,4,rep,name=actions,proto3" json:"actions,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *GetSegmentResponse) Reset() {
+	*x = GetSegmentResponse{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[4]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *GetSegmentResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*GetSegmentResponse) ProtoMessage() {}
+
+func (x *GetSegmentResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[4]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use GetSegmentResponse.ProtoReflect.Descriptor instead.
+func (*GetSegmentResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{4}
+}
+
+func (x *GetSegmentResponse) GetSegment() *SegmentInfo {
+	if x != nil {
+		return x.Segment
+	}
+	return nil
+}
+
+func (x *GetSegmentResponse) GetSchemas() []*TraceEventSchema {
+	if x != nil {
+		return x.Schemas
+	}
+	return nil
+}
+
+func (x *GetSegmentResponse) GetValueTables() []*TraceEventFieldNamedValues {
+	if x != nil {
+		return x.ValueTables
+	}
+	return nil
+}
+
+func (x *GetSegmentResponse) GetActions() []*TraceActionSchema {
+	if x != nil {
+		return x.Actions
+	}
+	return nil
+}
+
+type Signal struct {
+	state         protoimpl.MessageState             `protogen:"open.v1"`
+	DataSegmentId []byte                             `protobuf:"bytes,1,opt,name=data_segment_id,json=dataSegmentId,proto3" json:"data_segment_id,omitempty"`
+	Source        string                             `protobuf:"bytes,2,opt,name=source,proto3" json:"source,omitempty"`
+	Message       string                             `protobuf:"bytes,3,opt,name=message,proto3" json:"message,omitempty"`
+	Signal        string                             `protobuf:"bytes,4,opt,name=signal,proto3" json:"signal,omitempty"`
+	DataType      DataType                           `protobuf:"varint,5,opt,name=data_type,json=dataType,proto3,enum=zeloscloud.trace.DataType" json:"data_type,omitempty"`
+	Unit          *string                            `protobuf:"bytes,6,opt,name=unit,proto3,oneof" json:"unit,omitempty"`
+	ValueTable    []*TraceEventFieldNamedValuesEntry `protobuf:"bytes,7,rep,name=value_table,json=valueTable,proto3" json:"value_table,omitempty"`
+	Display       *FieldDisplay                      `protobuf:"bytes,8,opt,name=display,proto3" json:"display,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *Signal) Reset() {
+	*x = Signal{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[5]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *Signal) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*Signal) ProtoMessage() {}
+
+func (x *Signal) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[5]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use Signal.ProtoReflect.Descriptor instead.
+func (*Signal) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{5}
+}
+
+func (x *Signal) GetDataSegmentId() []byte {
+	if x != nil {
+		return x.DataSegmentId
+	}
+	return nil
+}
+
+func (x *Signal) GetSource() string {
+	if x != nil {
+		return x.Source
+	}
+	return ""
+}
+
+func (x *Signal) GetMessage() string {
+	if x != nil {
+		return x.Message
+	}
+	return ""
+}
+
+func (x *Signal) GetSignal() string {
+	if x != nil {
+		return x.Signal
+	}
+	return ""
+}
+
+func (x *Signal) GetDataType() DataType {
+	if x != nil {
+		return x.DataType
+	}
+	return DataType_DATA_TYPE_UNSPECIFIED
+}
+
+func (x *Signal) GetUnit() string {
+	if x != nil && x.Unit != nil {
+		return *x.Unit
+	}
+	return ""
+}
+
+func (x *Signal) GetValueTable() []*TraceEventFieldNamedValuesEntry {
+	if x != nil {
+		return x.ValueTable
+	}
+	return nil
+}
+
+func (x *Signal) GetDisplay() *FieldDisplay {
+	if x != nil {
+		return x.Display
+	}
+	return nil
+}
+
+type ListSignalsRequest struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// Signal keys to match, formatted as `<segment id or *>/<source>/<message>.<signal>`. All signals are returned when
+	// no keys are given.
+	SignalKeys    []string `protobuf:"bytes,1,rep,name=signal_keys,json=signalKeys,proto3" json:"signal_keys,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSignalsRequest) Reset() {
+	*x = ListSignalsRequest{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[6]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSignalsRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSignalsRequest) ProtoMessage() {}
+
+func (x *ListSignalsRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[6]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSignalsRequest.ProtoReflect.Descriptor instead.
+func (*ListSignalsRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{6}
+}
+
+func (x *ListSignalsRequest) GetSignalKeys() []string {
+	if x != nil {
+		return x.SignalKeys
+	}
+	return nil
+}
+
+type ListSignalsResponse struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Signals       []*Signal              `protobuf:"bytes,1,rep,name=signals,proto3" json:"signals,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSignalsResponse) Reset() {
+	*x = ListSignalsResponse{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[7]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSignalsResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSignalsResponse) ProtoMessage() {}
+
+func (x *ListSignalsResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[7]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSignalsResponse.ProtoReflect.Descriptor instead.
+func (*ListSignalsResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{7}
+}
+
+func (x *ListSignalsResponse) GetSignals() []*Signal {
+	if x != nil {
+		return x.Signals
+	}
+	return nil
+}
+
+type SessionInfo struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the session
+	SessionId      []byte            `protobuf:"bytes,1,opt,name=session_id,json=sessionId,proto3" json:"session_id,omitempty"`
+	Name           string            `protobuf:"bytes,2,opt,name=name,proto3" json:"name,omitempty"`
+	Attributes     map[string]string `protobuf:"bytes,3,rep,name=attributes,proto3" json:"attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
+	JoinAttributes map[string]string `protobuf:"bytes,4,rep,name=join_attributes,json=joinAttributes,proto3" json:"join_attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
+	StartTimeNs    int64             `protobuf:"fixed64,5,opt,name=start_time_ns,json=startTimeNs,proto3" json:"start_time_ns,omitempty"`
+	EndTimeNs      *int64            `protobuf:"fixed64,6,opt,name=end_time_ns,json=endTimeNs,proto3,oneof" json:"end_time_ns,omitempty"`
+	// Ids of the segments that joined the session
+	SegmentIds    [][]byte `protobuf:"bytes,7,rep,name=segment_ids,json=segmentIds,proto3" json:"segment_ids,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SessionInfo) Reset() {
+	*x = SessionInfo{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[8]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SessionInfo) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SessionInfo) ProtoMessage() {}
+
+func (x *SessionInfo) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[8]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SessionInfo.ProtoReflect.Descriptor instead.
+func (*SessionInfo) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{8}
+}
+
+func (x *SessionInfo) GetSessionId() []byte {
+	if x != nil {
+		return x.SessionId
+	}
+	return nil
+}
+
+func (x *SessionInfo) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *SessionInfo) GetAttributes() map[string]string {
+	if x != nil {
+		return x.Attributes
+	}
+	return nil
+}
+
+func (x *SessionInfo) GetJoinAttributes() map[string]string {
+	if x != nil {
+		return x.JoinAttributes
+	}
+	return nil
+}
+
+func (x *SessionInfo) GetStartTimeNs() int64 {
+	if x != nil {
+		return x.StartTimeNs
+	}
+	return 0
+}
+
+func (x *SessionInfo) GetEndTimeNs() int64 {
+	if x != nil && x.EndTimeNs != nil {
+		return *x.EndTimeNs
+	}
+	return 0
+}
+
+func (x *SessionInfo) GetSegmentIds() [][]byte {
+	if x != nil {
+		return x.SegmentIds
+	}
+	return nil
+}
+
+type ListSessionsRequest struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSessionsRequest) Reset() {
+	*x = ListSessionsRequest{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[9]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSessionsRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSessionsRequest) ProtoMessage() {}
+
+func (x *ListSessionsRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[9]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSessionsRequest.ProtoReflect.Descriptor instead.
+func (*ListSessionsRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{9}
+}
+
+type ListSessionsResponse struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Sessions      []*SessionInfo         `protobuf:"bytes,1,rep,name=sessions,proto3" json:"sessions,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ListSessionsResponse) Reset() {
+	*x = ListSessionsResponse{}
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[10]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ListSessionsResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ListSessionsResponse) ProtoMessage() {}
+
+func (x *ListSessionsResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_catalog_proto_msgTypes[10]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ListSessionsResponse.ProtoReflect.Descriptor instead.
+func (*ListSessionsResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_catalog_proto_rawDescGZIP(), []int{10}
+}
+
+func (x *ListSessionsResponse) GetSessions() []*SessionInfo {
+	if x != nil {
+		return x.Sessions
+	}
+	return nil
+}
+
+var File_zeloscloud_trace_catalog_proto protoreflect.FileDescriptor
+
+const file_zeloscloud_trace_catalog_proto_rawDesc = "" +
+	"\n" +
+	"\x1ezeloscloud/trace/catalog.proto\x12\x10zeloscloud.trace\x1a\x1czeloscloud/trace/trace.proto\"\xcb\x02\n" +
+	"\vSegmentInfo\x12\x1d\n" +
+	"\n" +
+	"segment_id\x18\x01 \x01(\fR\tsegmentId\x12\x1f\n" +
+	"\vsource_name\x18\x02 \x01(\tR\n" +
+	"sourceName\x12'\n" +
+	"\rstart_time_ns\x18\x03 \x01(\x10H\x00R\vstartTimeNs\x88\x01\x01\x12#\n" +
+	"\vend_time_ns\x18\x04 \x01(\x10H\x01R\tendTimeNs\x88\x01\x01\x12M\n" +
+	"\n" +
+	"attributes\x18\x05 \x03(\v2-.zeloscloud.trace.SegmentInfo.AttributesEntryR\n" +
+	"attributes\x1a=\n" +
+	"\x0fAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01B\x10\n" +
+	"\x0e_start_time_nsB\x0e\n" +
+	"\f_end_time_ns\"~\n" +
+	"\x13ListSegmentsRequest\x12$\n" +
+	"\vsource_name\x18\x01 \x01(\tH\x00R\n" +
+	"sourceName\x88\x01\x01\x12\"\n" +
+	"\n" +
+	"session_id\x18\x02 \x01(\fH\x01R\tsessionId\x88\x01\x01B\x0e\n" +
+	"\f_source_nameB\r\n" +
+	"\v_session_id\"Q\n" +
+	"\x14ListSegmentsResponse\x129\n" +
+	"\bsegments\x18\x01 \x03(\v2\x1d.zeloscloud.trace.SegmentInfoR\bsegments\"2\n" +
+	"\x11GetSegmentRequest\x12\x1d\n" +
+	"\n" +
+	"segment_id\x18\x01 \x01(\fR\tsegmentId\"\x9b\x02\n" +
+	"\x12GetSegmentResponse\x127\n" +
+	"\asegment\x18\x01 \x01(\v2\x1d.zeloscloud.trace.SegmentInfoR\asegment\x12<\n" +
+	"\aschemas\x18\x02 \x03(\v2\".zeloscloud.trace.TraceEventSchemaR\aschemas\x12O\n" +
+	"\fvalue_tables\x18\x03 \x03(\v2,.zeloscloud.trace.TraceEventFieldNamedValuesR\vvalueTables\x12=\n" +
+	"\aactions\x18\x04 \x03(\v2#.zeloscloud.trace.TraceActionSchemaR\aactions\"\xe3\x02\n" +
+	"\x06Signal\x12&\n" +
+	"\x0fdata_segment_id\x18\x01 \x01(\fR\rdataSegmentId\x12\x16\n" +
+	"\x06source\x18\x02 \x01(\tR\x06source\x12\x18\n" +
+	"\amessage\x18\x03 \x01(\tR\amessage\x12\x16\n" +
+	"\x06signal\x18\x04 \x01(\tR\x06signal\x127\n" +
+	"\tdata_type\x18\x05 \x01(\x0e2\x1a.zeloscloud.trace.DataTypeR\bdataType\x12\x17\n" +
+	"\x04unit\x18\x06 \x01(\tH\x00R\x04unit\x88\x01\x01\x12R\n" +
+	"\vvalue_table\x18\a \x03(\v21.zeloscloud.trace.TraceEventFieldNamedValuesEntryR\n" +
+	"valueTable\x128\n" +
+	"\adisplay\x18\b \x01(\v2\x1e.zeloscloud.trace.FieldDisplayR\adisplayB\a\n" +
+	"\x05_unit\"5\n" +
+	"\x12ListSignalsRequest\x12\x1f\n" +
+	"\vsignal_keys\x18\x01 \x03(\tR\n" +
+	"signalKeys\"I\n" +
+	"\x13ListSignalsResponse\x122\n" +
+	"\asignals\x18\x01 \x03(\v2\x18.zeloscloud.trace.SignalR\asignals\"\xe7\x03\n" +
+	"\vSessionInfo\x12\x1d\n" +
+	"\n" +
+	"session_id\x18\x01 \x01(\fR\tsessionId\x12\x12\n" +
+	"\x04name\x18\x02 \x01(\tR\x04name\x12M\n" +
+	"\n" +
+	"attributes\x18\x03 \x03(\v2-.zeloscloud.trace.SessionInfo.AttributesEntryR\n" +
+	"attributes\x12Z\n" +
+	"\x0fjoin_attributes\x18\x04 \x03(\v21.zeloscloud.trace.SessionInfo.JoinAttributesEntryR\x0ejoinAttributes\x12\"\n" +
+	"\rstart_time_ns\x18\x05 \x01(\x10R\vstartTimeNs\x12#\n" +
+	"\vend_time_ns\x18\x06 \x01(\x10H\x00R\tendTimeNs\x88\x01\x01\x12\x1f\n" +
+	"\vsegment_ids\x18\a \x03(\fR\n" +
+	"segmentIds\x1a=\n" +
+	"\x0fAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\x1aA\n" +
+	"\x13JoinAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01B\x0e\n" +
+	"\f_end_time_ns\"\x15\n" +
+	"\x13ListSessionsRequest\"Q\n" +
+	"\x14ListSessionsResponse\x129\n" +
+	"\bsessions\x18\x01 \x03(\v2\x1d.zeloscloud.trace.SessionInfoR\bsessions2\x81\x03\n" +
+	"\fTraceCatalog\x12]\n" +
+	"\fListSegments\x12%.zeloscloud.trace.ListSegmentsRequest\x1a&.zeloscloud.trace.ListSegmentsResponse\x12W\n" +
+	"\n" +
+	"GetSegment\x12#.zeloscloud.trace.GetSegmentRequest\x1a$.zeloscloud.trace.GetSegmentResponse\x12Z\n" +
+	"\vListSignals\x12$.zeloscloud.trace.ListSignalsRequest\x1a%.zeloscloud.trace.ListSignalsResponse\x12]\n" +
+	"\fListSessions\x12%.zeloscloud.trace.ListSessionsRequest\x1a&.zeloscloud.trace.ListSessionsResponseb\x06proto3"
+
+var (
+	file_zeloscloud_trace_catalog_proto_rawDescOnce sync.Once
+	file_zeloscloud_trace_catalog_proto_rawDescData []byte
+)
+
+func file_zeloscloud_trace_catalog_proto_rawDescGZIP() []byte {
+	file_zeloscloud_trace_catalog_proto_rawDescOnce.Do(func() {
+		file_zeloscloud_trace_catalog_proto_rawDescData = protoimpl.X.CompressGZIP(unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_catalog_proto_rawDesc), len(file_zeloscloud_trace_catalog_proto_rawDesc)))
+	})
+	return file_zeloscloud_trace_catalog_proto_rawDescData
+}
+
+var file_zeloscloud_trace_catalog_proto_msgTypes = make([]protoimpl.MessageInfo, 14)
+var file_zeloscloud_trace_catalog_proto_goTypes = []any{
+	(*SegmentInfo)(nil),                     // 0: zeloscloud.trace.SegmentInfo
+	(*ListSegmentsRequest)(nil),             // 1: zeloscloud.trace.ListSegmentsRequest
+	(*ListSegmentsResponse)(nil),            // 2: zeloscloud.trace.ListSegmentsResponse
+	(*GetSegmentRequest)(nil),               // 3: zeloscloud.trace.GetSegmentRequest
+	(*GetSegmentResponse)(nil),              // 4: zeloscloud.trace.GetSegmentResponse
+	(*Signal)(nil),                          // 5: zeloscloud.trace.Signal
+	(*ListSignalsRequest)(nil),              // 6: zeloscloud.trace.ListSignalsRequest
+	(*ListSignalsResponse)(nil),             // 7: zeloscloud.trace.ListSignalsResponse
+	(*SessionInfo)(nil),                     // 8: zeloscloud.trace.SessionInfo
+	(*ListSessionsRequest)(nil),             // 9: zeloscloud.trace.ListSessionsRequest
+	(*ListSessionsResponse)(nil),            // 10: zeloscloud.trace.ListSessionsResponse
+	nil,                                     // 11: zeloscloud.trace.SegmentInfo.AttributesEntry
+	nil,                                     // 12: zeloscloud.trace.SessionInfo.AttributesEntry
+	nil,                                     // 13: zeloscloud.trace.SessionInfo.JoinAttributesEntry
+	(*TraceEventSchema)(nil),                // 14: zeloscloud.trace.TraceEventSchema
+	(*TraceEventFieldNamedValues)(nil),      // 15: zeloscloud.trace.TraceEventFieldNamedValues
+	(*TraceActionSchema)(nil),               // 16: zeloscloud.trace.TraceActionSchema
+	(DataType)(0),                           // 17: zeloscloud.trace.DataType
+	(*TraceEventFieldNamedValuesEntry)(nil), // 18: zeloscloud.trace.TraceEventFieldNamedValuesEntry
+	(*FieldDisplay)(nil),                    // 19: zeloscloud.trace.FieldDisplay
+}
+var file_zeloscloud_trace_catalog_proto_depIdxs = []int32{
+	11, // 0: zeloscloud.trace.SegmentInfo.attributes:type_name -> zeloscloud.trace.SegmentInfo.AttributesEntry
+	0,  // 1: zeloscloud.trace.ListSegmentsResponse.segments:type_name -> zeloscloud.trace.SegmentInfo
+	0,  // 2: zeloscloud.trace.GetSegmentResponse.segment:type_name -> zeloscloud.trace.SegmentInfo
+	14, // 3: zeloscloud.trace.GetSegmentResponse.schemas:type_name -> zeloscloud.trace.TraceEventSchema
+	15, // 4: zeloscloud.trace.GetSegmentResponse.value_tables:type_name -> zeloscloud.trace.TraceEventFieldNamedValues
+	16, // 5: zeloscloud.trace.GetSegmentResponse.actions:type_name -> zeloscloud.trace.TraceActionSchema
+	17, // 6: zeloscloud.trace.Signal.data_type:type_name -> zeloscloud.trace.DataType
+	18, // 7: zeloscloud.trace.Signal.value_table:type_name -> zeloscloud.trace.TraceEventFieldNamedValuesEntry
+	19, // 8: zeloscloud.trace.Signal.display:type_name -> zeloscloud.trace.FieldDisplay
+	5,  // 9: zeloscloud.trace.ListSignalsResponse.signals:type_name -> zeloscloud.trace.Signal
+	12, // 10: zeloscloud.trace.SessionInfo.attributes:type_name -> zeloscloud.trace.SessionInfo.AttributesEntry
+	13, // 11: zeloscloud.trace.SessionInfo.join_attributes:type_name -> zeloscloud.trace.SessionInfo.JoinAttributesEntry
+	8,  // 12: zeloscloud.trace.ListSessionsResponse.sessions:type_name -> zeloscloud.trace.SessionInfo
+	1,  // 13: zeloscloud.trace.TraceCatalog.ListSegments:input_type -> zeloscloud.trace.ListSegmentsRequest
+	3,  // 14: zeloscloud.trace.TraceCatalog.GetSegment:input_type -> zeloscloud.trace.GetSegmentRequest
+	6,  // 15: zeloscloud.trace.TraceCatalog.ListSignals:input_type -> zeloscloud.trace.ListSignalsRequest
+	9,  // 16: zeloscloud.trace.TraceCatalog.ListSessions:input_type -> zeloscloud.trace.ListSessionsRequest
+	2,  // 17: zeloscloud.trace.TraceCatalog.ListSegments:output_type -> zeloscloud.trace.ListSegmentsResponse
+	4,  // 18: zeloscloud.trace.TraceCatalog.GetSegment:output_type -> zeloscloud.trace.GetSegmentResponse
+	7,  // 19: zeloscloud.trace.TraceCatalog.ListSignals:output_type -> zeloscloud.trace.ListSignalsResponse
+	10, // 20: zeloscloud.trace.TraceCatalog.ListSessions:output_type -> zeloscloud.trace.ListSessionsResponse
+	17, // [17:21] is the sub-list for method output_type
+	13, // [13:17] is the sub-list for method input_type
+	13, // [13:13] is the sub-list for extension type_name
+	13, // [13:13] is the sub-list for extension extendee
+	0,  // [0:13] is the sub-list for field type_name
+}
+
+func init() { file_zeloscloud_trace_catalog_proto_init() }
+func file_zeloscloud_trace_catalog_proto_init() {
+	if File_zeloscloud_trace_catalog_proto != nil {
+		return
+	}
+	file_zeloscloud_trace_trace_proto_init()
+	file_zeloscloud_trace_catalog_proto_msgTypes[0].OneofWrappers = []any{}
+	file_zeloscloud_trace_catalog_proto_msgTypes[1].OneofWrappers = []any{}
+	file_zeloscloud_trace_catalog_proto_msgTypes[5].OneofWrappers = []any{}
+	file_zeloscloud_trace_catalog_proto_msgTypes[8].OneofWrappers = []any{}
+	type x struct{}
+	out := protoimpl.TypeBuilder{
+		File: protoimpl.DescBuilder{
+			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
+			RawDescriptor: unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_catalog_proto_rawDesc), len(file_zeloscloud_trace_catalog_proto_rawDesc)),
+			NumEnums:      0,
+			NumMessages:   14,
+			NumExtensions: 0,
+			NumServices:   1,
+		},
+		GoTypes:           file_zeloscloud_trace_catalog_proto_goTypes,
+		DependencyIndexes: file_zeloscloud_trace_catalog_proto_depIdxs,
+		MessageInfos:      file_zeloscloud_trace_catalog_proto_msgTypes,
+	}.Build()
+	File_zeloscloud_trace_catalog_proto = out.File
+	file_zeloscloud_trace_catalog_proto_goTypes = nil
+	file_zeloscloud_trace_catalog_proto_depIdxs = nil
+}
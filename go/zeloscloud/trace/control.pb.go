@@ -0,0 +1,321 @@
+// Code generated by protoc-gen-go. DO NOT EDIT.
+// versions:
+// 	protoc-gen-go v1.36.7
+// 	protoc        v6.31.1
+// source: zeloscloud/trace/control.proto
+
+package trace
+
+import (
+	protoreflect "google.golang.org/protobuf/reflect/protoreflect"
+	protoimpl "google.golang.org/protobuf/runtime/protoimpl"
+	reflect "reflect"
+	sync "sync"
+	unsafe "unsafe"
+)
+
+const (
+	// Verify that this generated code is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(20 - protoimpl.MinVersion)
+	// Verify that runtime/protoimpl is sufficiently up-to-date.
+	_ = protoimpl.EnforceVersion(protoimpl.MaxVersion - 20)
+)
+
+type SendControlRequest struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Control       *TraceControl          `protobuf:"bytes,1,opt,name=control,proto3" json:"control,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SendControlRequest) Reset() {
+	*x = SendControlRequest{}
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[0]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SendControlRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SendControlRequest) ProtoMessage() {}
+
+func (x *SendControlRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[0]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SendControlRequest.ProtoReflect.Descriptor instead.
+func (*SendControlRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_control_proto_rawDescGZIP(), []int{0}
+}
+
+func (x *SendControlRequest) GetControl() *TraceControl {
+	if x != nil {
+		return x.Control
+	}
+	return nil
+}
+
+type SendControlResponse struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// Number of publishers and local sources the command was delivered to
+	Receivers     uint32 `protobuf:"varint,1,opt,name=receivers,proto3" json:"receivers,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SendControlResponse) Reset() {
+	*x = SendControlResponse{}
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[1]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SendControlResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SendControlResponse) ProtoMessage() {}
+
+func (x *SendControlResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[1]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SendControlResponse.ProtoReflect.Descriptor instead.
+func (*SendControlResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_control_proto_rawDescGZIP(), []int{1}
+}
+
+func (x *SendControlResponse) GetReceivers() uint32 {
+	if x != nil {
+		return x.Receivers
+	}
+	return 0
+}
+
+type SetParameterRequest struct {
+	state      protoimpl.MessageState `protogen:"open.v1"`
+	SourceName string                 `protobuf:"bytes,1,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	Name       string                 `protobuf:"bytes,2,opt,name=name,proto3" json:"name,omitempty"`
+	Value      *Value                 `protobuf:"bytes,3,opt,name=value,proto3" json:"value,omitempty"`
+	// The segment of the source instance to set it on, needed if several live segments of the source have the parameter
+	SegmentId []byte `protobuf:"bytes,4,opt,name=segment_id,json=segmentId,proto3,oneof" json:"segment_id,omitempty"`
+	// How long to wait for the source to answer, the server's default if unset
+	TimeoutMs     *uint64 `protobuf:"varint,5,opt,name=timeout_ms,json=timeoutMs,proto3,oneof" json:"timeout_ms,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SetParameterRequest) Reset() {
+	*x = SetParameterRequest{}
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[2]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SetParameterRequest) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SetParameterRequest) ProtoMessage() {}
+
+func (x *SetParameterRequest) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[2]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SetParameterRequest.ProtoReflect.Descriptor instead.
+func (*SetParameterRequest) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_control_proto_rawDescGZIP(), []int{2}
+}
+
+func (x *SetParameterRequest) GetSourceName() string {
+	if x != nil {
+		return x.SourceName
+	}
+	return ""
+}
+
+func (x *SetParameterRequest) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *SetParameterRequest) GetValue() *Value {
+	if x != nil {
+		return x.Value
+	}
+	return nil
+}
+
+func (x *SetParameterRequest) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+func (x *SetParameterRequest) GetTimeoutMs() uint64 {
+	if x != nil && x.TimeoutMs != nil {
+		return *x.TimeoutMs
+	}
+	return 0
+}
+
+type SetParameterResponse struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Value         *Value                 `protobuf:"bytes,1,opt,name=value,proto3" json:"value,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SetParameterResponse) Reset() {
+	*x = SetParameterResponse{}
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[3]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SetParameterResponse) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SetParameterResponse) ProtoMessage() {}
+
+func (x *SetParameterResponse) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_control_proto_msgTypes[3]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SetParameterResponse.ProtoReflect.Descriptor instead.
+func (*SetParameterResponse) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_control_proto_rawDescGZIP(), []int{3}
+}
+
+func (x *SetParameterResponse) GetValue() *Value {
+	if x != nil {
+		return x.Value
+	}
+	return nil
+}
+
+var File_zeloscloud_trace_control_proto protoreflect.FileDescriptor
+
+const file_zeloscloud_trace_control_proto_rawDesc = "" +
+	"\n" +
+	"\x1ezeloscloud/trace/control.proto\x12\x10zeloscloud.trace\x1a\x1czeloscloud/trace/trace.proto\"N\n" +
+	"\x12SendControlRequest\x128\n" +
+	"\acontrol\x18\x01 \x01(\v2\x1e.zeloscloud.trace.TraceControlR\acontrol\"3\n" +
+	"\x13SendControlResponse\x12\x1c\n" +
+	"\treceivers\x18\x01 \x01(\rR\treceivers\"\xdf\x01\n" +
+	"\x13SetParameterRequest\x12\x1f\n" +
+	"\vsource_name\x18\x01 \x01(\tR\n" +
+	"sourceName\x12\x12\n" +
+	"\x04name\x18\x02 \x01(\tR\x04name\x12-\n" +
+	"\x05value\x18\x03 \x01(\v2\x17.zeloscloud.trace.ValueR\x05value\x12\"\n" +
+	"\n" +
+	"segment_id\x18\x04 \x01(\fH\x00R\tsegmentId\x88\x01\x01\x12\"\n" +
+	"\n" +
+	"timeout_ms\x18\x05 \x01(\x04H\x01R\ttimeoutMs\x88\x01\x01B\r\n" +
+	"\v_segment_idB\r\n" +
+	"\v_timeout_ms\"E\n" +
+	"\x14SetParameterResponse\x12-\n" +
+	"\x05value\x18\x01 \x01(\v2\x17.zeloscloud.trace.ValueR\x05value2\xc8\x01\n" +
+	"\x12TraceSourceControl\x12S\n" +
+	"\x04Send\x12$.zeloscloud.trace.SendControlRequest\x1a%.zeloscloud.trace.SendControlResponse\x12]\n" +
+	"\fSetParameter\x12%.zeloscloud.trace.SetParameterRequest\x1a&.zeloscloud.trace.SetParameterResponseb\x06proto3"
+
+var (
+	file_zeloscloud_trace_control_proto_rawDescOnce sync.Once
+	file_zeloscloud_trace_control_proto_rawDescData []byte
+)
+
+func file_zeloscloud_trace_control_proto_rawDescGZIP() []byte {
+	file_zeloscloud_trace_control_proto_rawDescOnce.Do(func() {
+		file_zeloscloud_trace_control_proto_rawDescData = protoimpl.X.CompressGZIP(unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_control_proto_rawDesc), len(file_zeloscloud_trace_control_proto_rawDesc)))
+	})
+	return file_zeloscloud_trace_control_proto_rawDescData
+}
+
+var file_zeloscloud_trace_control_proto_msgTypes = make([]protoimpl.MessageInfo, 4)
+var file_zeloscloud_trace_control_proto_goTypes = []any{
+	(*SendControlRequest)(nil),   // 0: zeloscloud.trace.SendControlRequest
+	(*SendControlResponse)(nil),  // 1: zeloscloud.trace.SendControlResponse
+	(*SetParameterRequest)(nil),  // 2: zeloscloud.trace.SetParameterRequest
+	(*SetParameterResponse)(nil), // 3: zeloscloud.trace.SetParameterResponse
+	(*TraceControl)(nil),         // 4: zeloscloud.trace.TraceControl
+	(*Value)(nil),                // 5: zeloscloud.trace.Value
+}
+var file_zeloscloud_trace_control_proto_depIdxs = []int32{
+	4, // 0: zeloscloud.trace.SendControlRequest.control:type_name -> zeloscloud.trace.TraceControl
+	5, // 1: zeloscloud.trace.SetParameterRequest.value:type_name -> zeloscloud.trace.Value
+	5, // 2: zeloscloud.trace.SetParameterResponse.value:type_name -> zeloscloud.trace.Value
+	0, // 3: zeloscloud.trace.TraceSourceControl.Send:input_type -> zeloscloud.trace.SendControlRequest
+	2, // 4: zeloscloud.trace.TraceSourceControl.SetParameter:input_type -> zeloscloud.trace.SetParameterRequest
+	1, // 5: zeloscloud.trace.TraceSourceControl.Send:output_type -> zeloscloud.trace.SendControlResponse
+	3, // 6: zeloscloud.trace.TraceSourceControl.SetParameter:output_type -> zeloscloud.trace.SetParameterResponse
+	5, // [5:7] is the sub-list for method output_type
+	3, // [3:5] is the sub-list for method input_type
+	3, // [3:3] is the sub-list for extension type_name
+	3, // [3:3] is the sub-list for extension extendee
+	0, // [0:3] is the sub-list for field type_name
+}
+
+func init() { file_zeloscloud_trace_control_proto_init() }
+func file_zeloscloud_trace_control_proto_init() {
+	if File_zeloscloud_trace_control_proto != nil {
+		return
+	}
+	file_zeloscloud_trace_trace_proto_init()
+	file_zeloscloud_trace_control_proto_msgTypes[2].OneofWrappers = []any{}
+	type x struct{}
+	out := protoimpl.TypeBuilder{
+		File: protoimpl.DescBuilder{
+			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
+			RawDescriptor: unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_control_proto_rawDesc), len(file_zeloscloud_trace_control_proto_rawDesc)),
+			NumEnums:      0,
+			NumMessages:   4,
+			NumExtensions: 0,
+			NumServices:   1,
+		},
+		GoTypes:           file_zeloscloud_trace_control_proto_goTypes,
+		DependencyIndexes: file_zeloscloud_trace_control_proto_depIdxs,
+		MessageInfos:      file_zeloscloud_trace_control_proto_msgTypes,
+	}.Build()
+	File_zeloscloud_trace_control_proto = out.File
+	file_zeloscloud_trace_control_proto_goTypes = nil
+	file_zeloscloud_trace_control_proto_depIdxs = nil
+}
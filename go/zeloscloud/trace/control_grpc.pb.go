@@ -0,0 +1,165 @@
+// Code generated by protoc-gen-go-grpc. DO NOT EDIT.
+// versions:
+// - protoc-gen-go-grpc v1.5.1
+// - protoc             v6.31.1
+// source: zeloscloud/trace/control.proto
+
+package trace
+
+import (
+	context "context"
+	grpc "google.golang.org/grpc"
+	codes "google.golang.org/grpc/codes"
+	status "google.golang.org/grpc/status"
+)
+
+// This is a compile-time assertion to ensure that this generated file
+// is compatible with the grpc package it is being compiled against.
+// Requires gRPC-Go v1.64.0 or later.
+const _ = grpc.SupportPackageIsVersion9
+
+const (
+	TraceSourceControl_Send_FullMethodName         = "/zeloscloud.trace.TraceSourceControl/Send"
+	TraceSourceControl_SetParameter_FullMethodName = "/zeloscloud.trace.TraceSourceControl/SetParameter"
+)
+
+// TraceSourceControlClient is the client API for TraceSourceControl service.
+//
+// For semantics around ctx use and closing/ending streaming RPCs, please refer to https://pkg.go.dev/google.golang.org/grpc/?tab=doc#ClientConn.NewStream.
+//
+// Send control commands to the sources publishing to a server, such as enabling events or limiting their rate
+type TraceSourceControlClient interface {
+	Send(ctx context.Context, in *SendControlRequest, opts ...grpc.CallOption) (*SendControlResponse, error)
+	// Set a parameter of one instance of a source and return the value it accepted
+	SetParameter(ctx context.Context, in *SetParameterRequest, opts ...grpc.CallOption) (*SetParameterResponse, error)
+}
+
+type traceSourceControlClient struct {
+	cc grpc.ClientConnInterface
+}
+
+func NewTraceSourceControlClient(cc grpc.ClientConnInterface) TraceSourceControlClient {
+	return &traceSourceControlClient{cc}
+}
+
+func (c *traceSourceControlClient) Send(ctx context.Context, in *SendControlRequest, opts ...grpc.CallOption) (*SendControlResponse, error) {
+	cOpts := append([]grpc.CallOption{grpc.StaticMethod()}, opts...)
+	out := new(SendControlResponse)
+	err := c.cc.Invoke(ctx, TraceSourceControl_Send_FullMethodName, in, out, cOpts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+func (c *traceSourceControlClient) SetParameter(ctx context.Context, in *SetParameterRequest, opts ...grpc.CallOption) (*SetParameterResponse, error) {
+	cOpts := append([]grpc.CallOption{grpc.StaticMethod()}, opts...)
+	out := new(SetParameterResponse)
+	err := c.cc.Invoke(ctx, TraceSourceControl_SetParameter_FullMethodName, in, out, cOpts...)
+	if err != nil {
+		return nil, err
+	}
+	return out, nil
+}
+
+// TraceSourceControlServer is the server API for TraceSourceControl service.
+// All implementations must embed UnimplementedTraceSourceControlServer
+// for forward compatibility.
+//
+// Send control commands to the sources publishing to a server, such as enabling events or limiting their rate
+type TraceSourceControlServer interface {
+	Send(context.Context, *SendControlRequest) (*SendControlResponse, error)
+	// Set a parameter of one instance of a source and return the value it accepted
+	SetParameter(context.Context, *SetParameterRequest) (*SetParameterResponse, error)
+	mustEmbedUnimplementedTraceSourceControlServer()
+}
+
+// UnimplementedTraceSourceControlServer must be embedded to have
+// forward compatible implementations.
+//
+// NOTE: this should be embedded by value instead of pointer to avoid a nil
+// pointer dereference when methods are called.
+type UnimplementedTraceSourceControlServer struct{}
+
+func (UnimplementedTraceSourceControlServer) Send(context.Context, *SendControlRequest) (*SendControlResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method Send not implemented")
+}
+func (UnimplementedTraceSourceControlServer) SetParameter(context.Context, *SetParameterRequest) (*SetParameterResponse, error) {
+	return nil, status.Errorf(codes.Unimplemented, "method SetParameter not implemented")
+}
+func (UnimplementedTraceSourceControlServer) mustEmbedUnimplementedTraceSourceControlServer() {}
+func (UnimplementedTraceSourceControlServer) testEmbeddedByValue()                            {}
+
+// UnsafeTraceSourceControlServer may be embedded to opt out of forward compatibility for this service.
+// Use of this interface is not recommended, as added methods to TraceSourceControlServer will
+// result in compilation errors.
+type UnsafeTraceSourceControlServer interface {
+	mustEmbedUnimplementedTraceSourceControlServer()
+}
+
+func RegisterTraceSourceControlServer(s grpc.ServiceRegistrar, srv TraceSourceControlServer) {
+	// If the following call pancis, it indicates UnimplementedTraceSourceControlServer was
+	// embedded by pointer and is nil.  This will cause panics if an
+	// unimplemented method is ever invoked, so we test this at initialization
+	// time to prevent it from happening at runtime later due to I/O.
+	if t, ok := srv.(interface{ testEmbeddedByValue() }); ok {
+		t.testEmbeddedByValue()
+	}
+	s.RegisterService(&TraceSourceControl_ServiceDesc, srv)
+}
+
+func _TraceSourceControl_Send_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(SendControlRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(TraceSourceControlServer).Send(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: TraceSourceControl_Send_FullMethodName,
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(TraceSourceControlServer).Send(ctx, req.(*SendControlRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+func _TraceSourceControl_SetParameter_Handler(srv interface{}, ctx context.Context, dec func(interface{}) error, interceptor grpc.UnaryServerInterceptor) (interface{}, error) {
+	in := new(SetParameterRequest)
+	if err := dec(in); err != nil {
+		return nil, err
+	}
+	if interceptor == nil {
+		return srv.(TraceSourceControlServer).SetParameter(ctx, in)
+	}
+	info := &grpc.UnaryServerInfo{
+		Server:     srv,
+		FullMethod: TraceSourceControl_SetParameter_FullMethodName,
+	}
+	handler := func(ctx context.Context, req interface{}) (interface{}, error) {
+		return srv.(TraceSourceControlServer).SetParameter(ctx, req.(*SetParameterRequest))
+	}
+	return interceptor(ctx, in, info, handler)
+}
+
+// TraceSourceControl_ServiceDesc is the grpc.ServiceDesc for TraceSourceControl service.
+// It's only intended for direct use with grpc.RegisterService,
+// and not to be introspected or modified (even as a copy)
+var TraceSourceControl_ServiceDesc = grpc.ServiceDesc{
+	ServiceName: "zeloscloud.trace.TraceSourceControl",
+	HandlerType: (*TraceSourceControlServer)(nil),
+	Methods: []grpc.MethodDesc{
+		{
+			MethodName: "Send",
+			Handler:    _TraceSourceControl_Send_Handler,
+		},
+		{
+			MethodName: "SetParameter",
+			Handler:    _TraceSourceControl_SetParameter_Handler,
+		},
+	},
+	Streams:  []grpc.StreamDesc{},
+	Metadata: "zeloscloud/trace/control.proto",
+}
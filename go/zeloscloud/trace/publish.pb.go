@@ -24,6 +24,8 @@ const (
 type PublishRequest struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
 	TraceMessages []*TraceMessage        `protobuf:"bytes,1,rep,name=trace_messages,json=traceMessages,proto3" json:"trace_messages,omitempty"`
+	// Sent in the first request of the stream
+	Handshake     *Handshake `protobuf:"bytes,2,opt,name=handshake,proto3" json:"handshake,omitempty"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
@@ -65,6 +67,13 @@ func (x *PublishRequest) GetTraceMessages() []*TraceMessage {
 	return nil
 }
 
+func (x *PublishRequest) GetHandshake() *Handshake {
+	if x != nil {
+		return x.Handshake
+	}
+	return nil
+}
+
 type PublishStatus struct {
 	state              protoimpl.MessageState `protogen:"open.v1"`
 	TotalMessages      uint64                 `protobuf:"varint,1,opt,name=total_messages,json=totalMessages,proto3" json:"total_messages,omitempty"`
@@ -134,8 +143,12 @@ func (x *PublishStatus) GetLastError() string {
 }
 
 type PublishResponse struct {
-	state         protoimpl.MessageState `protogen:"open.v1"`
-	Status        *PublishStatus         `protobuf:"bytes,1,opt,name=status,proto3" json:"status,omitempty"`
+	state  protoimpl.MessageState `protogen:"open.v1"`
+	Status *PublishStatus         `protobuf:"bytes,1,opt,name=status,proto3" json:"status,omitempty"`
+	// Sent in reply to the client's handshake
+	Handshake *Handshake `protobuf:"bytes,2,opt,name=handshake,proto3" json:"handshake,omitempty"`
+	// Sent to clients that agreed to CAPABILITY_CONTROL
+	Control       *TraceControl `protobuf:"bytes,3,opt,name=control,proto3" json:"control,omitempty"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
@@ -177,21 +190,38 @@ func (x *PublishResponse) GetStatus() *PublishStatus {
 	return nil
 }
 
+func (x *PublishResponse) GetHandshake() *Handshake {
+	if x != nil {
+		return x.Handshake
+	}
+	return nil
+}
+
+func (x *PublishResponse) GetControl() *TraceControl {
+	if x != nil {
+		return x.Control
+	}
+	return nil
+}
+
 var File_zeloscloud_trace_publish_proto protoreflect.FileDescriptor
 
 const file_zeloscloud_trace_publish_proto_rawDesc = "" +
 	"\n" +
-	"\x1ezeloscloud/trace/publish.proto\x12\x10zeloscloud.trace\x1a\x1czeloscloud/trace/trace.proto\"W\n" +
+	"\x1ezeloscloud/trace/publish.proto\x12\x10zeloscloud.trace\x1a\x1czeloscloud/trace/trace.proto\"\x92\x01\n" +
 	"\x0ePublishRequest\x12E\n" +
-	"\x0etrace_messages\x18\x01 \x03(\v2\x1e.zeloscloud.trace.TraceMessageR\rtraceMessages\"\xaf\x01\n" +
+	"\x0etrace_messages\x18\x01 \x03(\v2\x1e.zeloscloud.trace.TraceMessageR\rtraceMessages\x129\n" +
+	"\thandshake\x18\x02 \x01(\v2\x1b.zeloscloud.trace.HandshakeR\thandshake\"\xaf\x01\n" +
 	"\rPublishStatus\x12%\n" +
 	"\x0etotal_messages\x18\x01 \x01(\x04R\rtotalMessages\x12/\n" +
 	"\x13successful_messages\x18\x02 \x01(\x04R\x12successfulMessages\x12'\n" +
 	"\x0ffailed_messages\x18\x03 \x01(\x04R\x0efailedMessages\x12\x1d\n" +
 	"\n" +
-	"last_error\x18\x04 \x01(\tR\tlastError\"J\n" +
+	"last_error\x18\x04 \x01(\tR\tlastError\"\xbf\x01\n" +
 	"\x0fPublishResponse\x127\n" +
-	"\x06status\x18\x01 \x01(\v2\x1f.zeloscloud.trace.PublishStatusR\x06status2b\n" +
+	"\x06status\x18\x01 \x01(\v2\x1f.zeloscloud.trace.PublishStatusR\x06status\x129\n" +
+	"\thandshake\x18\x02 \x01(\v2\x1b.zeloscloud.trace.HandshakeR\thandshake\x128\n" +
+	"\acontrol\x18\x03 \x01(\v2\x1e.zeloscloud.trace.TraceControlR\acontrol2b\n" +
 	"\fTracePublish\x12R\n" +
 	"\aPublish\x12 .zeloscloud.trace.PublishRequest\x1a!.zeloscloud.trace.PublishResponse(\x010\x01b\x06proto3"
 
@@ -213,17 +243,22 @@ var file_zeloscloud_trace_publish_proto_goTypes = []any{
 	(*PublishStatus)(nil),   // 1: zeloscloud.trace.PublishStatus
 	(*PublishResponse)(nil), // 2: zeloscloud.trace.PublishResponse
 	(*TraceMessage)(nil),    // 3: zeloscloud.trace.TraceMessage
+	(*Handshake)(nil),       // 4: zeloscloud.trace.Handshake
+	(*TraceControl)(nil),    // 5: zeloscloud.trace.TraceControl
 }
 var file_zeloscloud_trace_publish_proto_depIdxs = []int32{
 	3, // 0: zeloscloud.trace.PublishRequest.trace_messages:type_name -> zeloscloud.trace.TraceMessage
-	1, // 1: zeloscloud.trace.PublishResponse.status:type_name -> zeloscloud.trace.PublishStatus
-	0, // 2: zeloscloud.trace.TracePublish.Publish:input_type -> zeloscloud.trace.PublishRequest
-	2, // 3: zeloscloud.trace.TracePublish.Publish:output_type -> zeloscloud.trace.PublishResponse
-	3, // [3:4] is the sub-list for method output_type
-	2, // [2:3] is the sub-list for method input_type
-	2, // [2:2] is the sub-list for extension type_name
-	2, // [2:2] is the sub-list for extension extendee
-	0, // [0:2] is the sub-list for field type_name
+	4, // 1: zeloscloud.trace.PublishRequest.handshake:type_name -> zeloscloud.trace.Handshake
+	1, // 2: zeloscloud.trace.PublishResponse.status:type_name -> zeloscloud.trace.PublishStatus
+	4, // 3: zeloscloud.trace.PublishResponse.handshake:type_name -> zeloscloud.trace.Handshake
+	5, // 4: zeloscloud.trace.PublishResponse.control:type_name -> zeloscloud.trace.TraceControl
+	0, // 5: zeloscloud.trace.TracePublish.Publish:input_type -> zeloscloud.trace.PublishRequest
+	2, // 6: zeloscloud.trace.TracePublish.Publish:output_type -> zeloscloud.trace.PublishResponse
+	6, // [6:7] is the sub-list for method output_type
+	5, // [5:6] is the sub-list for method input_type
+	5, // [5:5] is the sub-list for extension type_name
+	5, // [5:5] is the sub-list for extension extendee
+	0, // [0:5] is the sub-list for field type_name
 }
 
 func init() { file_zeloscloud_trace_publish_proto_init() }
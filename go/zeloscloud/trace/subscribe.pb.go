@@ -21,6 +21,55 @@ const (
 	_ = protoimpl.EnforceVersion(protoimpl.MaxVersion - 20)
 )
 
+type SubscribeCommandKind int32
+
+const (
+	SubscribeCommandKind_SUBSCRIBE_COMMAND_KIND_UNSPECIFIED SubscribeCommandKind = 0
+	SubscribeCommandKind_SUBSCRIBE_COMMAND_KIND_SUBSCRIBE   SubscribeCommandKind = 1
+	SubscribeCommandKind_SUBSCRIBE_COMMAND_KIND_UNSUBSCRIBE SubscribeCommandKind = 2
+)
+
+// Enum value maps for SubscribeCommandKind.
+var (
+	SubscribeCommandKind_name = map[int32]string{
+		0: "SUBSCRIBE_COMMAND_KIND_UNSPECIFIED",
+		1: "SUBSCRIBE_COMMAND_KIND_SUBSCRIBE",
+		2: "SUBSCRIBE_COMMAND_KIND_UNSUBSCRIBE",
+	}
+	SubscribeCommandKind_value = map[string]int32{
+		"SUBSCRIBE_COMMAND_KIND_UNSPECIFIED": 0,
+		"SUBSCRIBE_COMMAND_KIND_SUBSCRIBE":   1,
+		"SUBSCRIBE_COMMAND_KIND_UNSUBSCRIBE": 2,
+	}
+)
+
+func (x SubscribeCommandKind) Enum() *SubscribeCommandKind {
+	p := new(SubscribeCommandKind)
+	*p = x
+	return p
+}
+
+func (x SubscribeCommandKind) String() string {
+	return protoimpl.X.EnumStringOf(x.Descriptor(), protoreflect.EnumNumber(x))
+}
+
+func (SubscribeCommandKind) Descriptor() protoreflect.EnumDescriptor {
+	return file_zeloscloud_trace_subscribe_proto_enumTypes[0].Descriptor()
+}
+
+func (SubscribeCommandKind) Type() protoreflect.EnumType {
+	return &file_zeloscloud_trace_subscribe_proto_enumTypes[0]
+}
+
+func (x SubscribeCommandKind) Number() protoreflect.EnumNumber {
+	return protoreflect.EnumNumber(x)
+}
+
+// Deprecated: Use SubscribeCommandKind.Descriptor instead.
+func (SubscribeCommandKind) EnumDescriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_subscribe_proto_rawDescGZIP(), []int{0}
+}
+
 type SubscribeCommand struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
 	Filter        *string                `protobuf:"bytes,1,opt,name=filter,proto3,oneof" json:"filter,omitempty"`
@@ -123,6 +172,7 @@ type SubscribeRequest struct {
 	//
 	//	*SubscribeRequest_Subscribe
 	//	*SubscribeRequest_Unsubscribe
+	//	*SubscribeRequest_Handshake
 	Cmd           isSubscribeRequest_Cmd `protobuf_oneof:"cmd"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
@@ -183,6 +233,15 @@ func (x *SubscribeRequest) GetUnsubscribe() *UnsubscribeCommand {
 	return nil
 }
 
+func (x *SubscribeRequest) GetHandshake() *Handshake {
+	if x != nil {
+		if x, ok := x.Cmd.(*SubscribeRequest_Handshake); ok {
+			return x.Handshake
+		}
+	}
+	return nil
+}
+
 type isSubscribeRequest_Cmd interface {
 	isSubscribeRequest_Cmd()
 }
@@ -195,15 +254,139 @@ type SubscribeRequest_Unsubscribe struct {
 	Unsubscribe *UnsubscribeCommand `protobuf:"bytes,2,opt,name=unsubscribe,proto3,oneof"`
 }
 
+type SubscribeRequest_Handshake struct {
+	// Sent before any other command
+	Handshake *Handshake `protobuf:"bytes,3,opt,name=handshake,proto3,oneof"`
+}
+
 func (*SubscribeRequest_Subscribe) isSubscribeRequest_Cmd() {}
 
 func (*SubscribeRequest_Unsubscribe) isSubscribeRequest_Cmd() {}
 
+func (*SubscribeRequest_Handshake) isSubscribeRequest_Cmd() {}
+
+// Sent once a subscribe or unsubscribe command has been applied
+type SubscribeCommandAck struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Kind          SubscribeCommandKind   `protobuf:"varint,1,opt,name=kind,proto3,enum=zeloscloud.trace.SubscribeCommandKind" json:"kind,omitempty"`
+	Filter        *string                `protobuf:"bytes,2,opt,name=filter,proto3,oneof" json:"filter,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SubscribeCommandAck) Reset() {
+	*x = SubscribeCommandAck{}
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[3]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SubscribeCommandAck) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SubscribeCommandAck) ProtoMessage() {}
+
+func (x *SubscribeCommandAck) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[3]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SubscribeCommandAck.ProtoReflect.Descriptor instead.
+func (*SubscribeCommandAck) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_subscribe_proto_rawDescGZIP(), []int{3}
+}
+
+func (x *SubscribeCommandAck) GetKind() SubscribeCommandKind {
+	if x != nil {
+		return x.Kind
+	}
+	return SubscribeCommandKind_SUBSCRIBE_COMMAND_KIND_UNSPECIFIED
+}
+
+func (x *SubscribeCommandAck) GetFilter() string {
+	if x != nil && x.Filter != nil {
+		return *x.Filter
+	}
+	return ""
+}
+
+// Sent when a subscribe or unsubscribe command could not be applied
+type SubscribeCommandError struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Kind          SubscribeCommandKind   `protobuf:"varint,1,opt,name=kind,proto3,enum=zeloscloud.trace.SubscribeCommandKind" json:"kind,omitempty"`
+	Filter        *string                `protobuf:"bytes,2,opt,name=filter,proto3,oneof" json:"filter,omitempty"`
+	Message       string                 `protobuf:"bytes,3,opt,name=message,proto3" json:"message,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SubscribeCommandError) Reset() {
+	*x = SubscribeCommandError{}
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[4]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SubscribeCommandError) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SubscribeCommandError) ProtoMessage() {}
+
+func (x *SubscribeCommandError) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[4]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SubscribeCommandError.ProtoReflect.Descriptor instead.
+func (*SubscribeCommandError) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_subscribe_proto_rawDescGZIP(), []int{4}
+}
+
+func (x *SubscribeCommandError) GetKind() SubscribeCommandKind {
+	if x != nil {
+		return x.Kind
+	}
+	return SubscribeCommandKind_SUBSCRIBE_COMMAND_KIND_UNSPECIFIED
+}
+
+func (x *SubscribeCommandError) GetFilter() string {
+	if x != nil && x.Filter != nil {
+		return *x.Filter
+	}
+	return ""
+}
+
+func (x *SubscribeCommandError) GetMessage() string {
+	if x != nil {
+		return x.Message
+	}
+	return ""
+}
+
 type SubscribeResponse struct {
 	state protoimpl.MessageState `protogen:"open.v1"`
 	// Types that are valid to be assigned to Msg:
 	//
 	//	*SubscribeResponse_TraceMessageBatch
+	//	*SubscribeResponse_CommandAck
+	//	*SubscribeResponse_CommandError
+	//	*SubscribeResponse_Handshake
 	Msg           isSubscribeResponse_Msg `protobuf_oneof:"msg"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
@@ -211,7 +394,7 @@ type SubscribeResponse struct {
 
 func (x *SubscribeResponse) Reset() {
 	*x = SubscribeResponse{}
-	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[3]
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[5]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -223,7 +406,7 @@ func (x *SubscribeResponse) String() string {
 func (*SubscribeResponse) ProtoMessage() {}
 
 func (x *SubscribeResponse) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[3]
+	mi := &file_zeloscloud_trace_subscribe_proto_msgTypes[5]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -236,7 +419,7 @@ func (x *SubscribeResponse) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use SubscribeResponse.ProtoReflect.Descriptor instead.
 func (*SubscribeResponse) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_subscribe_proto_rawDescGZIP(), []int{3}
+	return file_zeloscloud_trace_subscribe_proto_rawDescGZIP(), []int{5}
 }
 
 func (x *SubscribeResponse) GetMsg() isSubscribeResponse_Msg {
@@ -255,6 +438,33 @@ func (x *SubscribeResponse) GetTraceMessageBatch() *TraceMessageBatch {
 	return nil
 }
 
+func (x *SubscribeResponse) GetCommandAck() *SubscribeCommandAck {
+	if x != nil {
+		if x, ok := x.Msg.(*SubscribeResponse_CommandAck); ok {
+			return x.CommandAck
+		}
+	}
+	return nil
+}
+
+func (x *SubscribeResponse) GetCommandError() *SubscribeCommandError {
+	if x != nil {
+		if x, ok := x.Msg.(*SubscribeResponse_CommandError); ok {
+			return x.CommandError
+		}
+	}
+	return nil
+}
+
+func (x *SubscribeResponse) GetHandshake() *Handshake {
+	if x != nil {
+		if x, ok := x.Msg.(*SubscribeResponse_Handshake); ok {
+			return x.Handshake
+		}
+	}
+	return nil
+}
+
 type isSubscribeResponse_Msg interface {
 	isSubscribeResponse_Msg()
 }
@@ -263,8 +473,27 @@ type SubscribeResponse_TraceMessageBatch struct {
 	TraceMessageBatch *TraceMessageBatch `protobuf:"bytes,1,opt,name=trace_message_batch,json=traceMessageBatch,proto3,oneof"`
 }
 
+type SubscribeResponse_CommandAck struct {
+	CommandAck *SubscribeCommandAck `protobuf:"bytes,2,opt,name=command_ack,json=commandAck,proto3,oneof"`
+}
+
+type SubscribeResponse_CommandError struct {
+	CommandError *SubscribeCommandError `protobuf:"bytes,3,opt,name=command_error,json=commandError,proto3,oneof"`
+}
+
+type SubscribeResponse_Handshake struct {
+	// Sent in reply to the client's handshake
+	Handshake *Handshake `protobuf:"bytes,4,opt,name=handshake,proto3,oneof"`
+}
+
 func (*SubscribeResponse_TraceMessageBatch) isSubscribeResponse_Msg() {}
 
+func (*SubscribeResponse_CommandAck) isSubscribeResponse_Msg() {}
+
+func (*SubscribeResponse_CommandError) isSubscribeResponse_Msg() {}
+
+func (*SubscribeResponse_Handshake) isSubscribeResponse_Msg() {}
+
 var File_zeloscloud_trace_subscribe_proto protoreflect.FileDescriptor
 
 const file_zeloscloud_trace_subscribe_proto_rawDesc = "" +
@@ -278,14 +507,32 @@ const file_zeloscloud_trace_subscribe_proto_rawDesc = "" +
 	"\v_start_time\"<\n" +
 	"\x12UnsubscribeCommand\x12\x1b\n" +
 	"\x06filter\x18\x01 \x01(\tH\x00R\x06filter\x88\x01\x01B\t\n" +
-	"\a_filter\"\xa7\x01\n" +
+	"\a_filter\"\xe4\x01\n" +
 	"\x10SubscribeRequest\x12B\n" +
 	"\tsubscribe\x18\x01 \x01(\v2\".zeloscloud.trace.SubscribeCommandH\x00R\tsubscribe\x12H\n" +
-	"\vunsubscribe\x18\x02 \x01(\v2$.zeloscloud.trace.UnsubscribeCommandH\x00R\vunsubscribeB\x05\n" +
-	"\x03cmd\"q\n" +
+	"\vunsubscribe\x18\x02 \x01(\v2$.zeloscloud.trace.UnsubscribeCommandH\x00R\vunsubscribe\x12;\n" +
+	"\thandshake\x18\x03 \x01(\v2\x1b.zeloscloud.trace.HandshakeH\x00R\thandshakeB\x05\n" +
+	"\x03cmd\"y\n" +
+	"\x13SubscribeCommandAck\x12:\n" +
+	"\x04kind\x18\x01 \x01(\x0e2&.zeloscloud.trace.SubscribeCommandKindR\x04kind\x12\x1b\n" +
+	"\x06filter\x18\x02 \x01(\tH\x00R\x06filter\x88\x01\x01B\t\n" +
+	"\a_filter\"\x95\x01\n" +
+	"\x15SubscribeCommandError\x12:\n" +
+	"\x04kind\x18\x01 \x01(\x0e2&.zeloscloud.trace.SubscribeCommandKindR\x04kind\x12\x1b\n" +
+	"\x06filter\x18\x02 \x01(\tH\x00R\x06filter\x88\x01\x01\x12\x18\n" +
+	"\amessage\x18\x03 \x01(\tR\amessageB\t\n" +
+	"\a_filter\"\xc8\x02\n" +
 	"\x11SubscribeResponse\x12U\n" +
-	"\x13trace_message_batch\x18\x01 \x01(\v2#.zeloscloud.trace.TraceMessageBatchH\x00R\x11traceMessageBatchB\x05\n" +
-	"\x03msg2j\n" +
+	"\x13trace_message_batch\x18\x01 \x01(\v2#.zeloscloud.trace.TraceMessageBatchH\x00R\x11traceMessageBatch\x12H\n" +
+	"\vcommand_ack\x18\x02 \x01(\v2%.zeloscloud.trace.SubscribeCommandAckH\x00R\n" +
+	"commandAck\x12N\n" +
+	"\rcommand_error\x18\x03 \x01(\v2'.zeloscloud.trace.SubscribeCommandErrorH\x00R\fcommandError\x12;\n" +
+	"\thandshake\x18\x04 \x01(\v2\x1b.zeloscloud.trace.HandshakeH\x00R\thandshakeB\x05\n" +
+	"\x03msg*\x8c\x01\n" +
+	"\x14SubscribeCommandKind\x12&\n" +
+	"\"SUBSCRIBE_COMMAND_KIND_UNSPECIFIED\x10\x00\x12$\n" +
+	" SUBSCRIBE_COMMAND_KIND_SUBSCRIBE\x10\x01\x12&\n" +
+	"\"SUBSCRIBE_COMMAND_KIND_UNSUBSCRIBE\x10\x022j\n" +
 	"\x0eTraceSubscribe\x12X\n" +
 	"\tSubscribe\x12\".zeloscloud.trace.SubscribeRequest\x1a#.zeloscloud.trace.SubscribeResponse(\x010\x01b\x06proto3"
 
@@ -301,25 +548,36 @@ func file_zeloscloud_trace_subscribe_proto_rawDescGZIP() []byte {
 	return file_zeloscloud_trace_subscribe_proto_rawDescData
 }
 
-var file_zeloscloud_trace_subscribe_proto_msgTypes = make([]protoimpl.MessageInfo, 4)
+var file_zeloscloud_trace_subscribe_proto_enumTypes = make([]protoimpl.EnumInfo, 1)
+var file_zeloscloud_trace_subscribe_proto_msgTypes = make([]protoimpl.MessageInfo, 6)
 var file_zeloscloud_trace_subscribe_proto_goTypes = []any{
-	(*SubscribeCommand)(nil),   // 0: zeloscloud.trace.SubscribeCommand
-	(*UnsubscribeCommand)(nil), // 1: zeloscloud.trace.UnsubscribeCommand
-	(*SubscribeRequest)(nil),   // 2: zeloscloud.trace.SubscribeRequest
-	(*SubscribeResponse)(nil),  // 3: zeloscloud.trace.SubscribeResponse
-	(*TraceMessageBatch)(nil),  // 4: zeloscloud.trace.TraceMessageBatch
+	(SubscribeCommandKind)(0),     // 0: zeloscloud.trace.SubscribeCommandKind
+	(*SubscribeCommand)(nil),      // 1: zeloscloud.trace.SubscribeCommand
+	(*UnsubscribeCommand)(nil),    // 2: zeloscloud.trace.UnsubscribeCommand
+	(*SubscribeRequest)(nil),      // 3: zeloscloud.trace.SubscribeRequest
+	(*SubscribeCommandAck)(nil),   // 4: zeloscloud.trace.SubscribeCommandAck
+	(*SubscribeCommandError)(nil), // 5: zeloscloud.trace.SubscribeCommandError
+	(*SubscribeResponse)(nil),     // 6: zeloscloud.trace.SubscribeResponse
+	(*Handshake)(nil),             // 7: zeloscloud.trace.Handshake
+	(*TraceMessageBatch)(nil),     // 8: zeloscloud.trace.TraceMessageBatch
 }
 var file_zeloscloud_trace_subscribe_proto_depIdxs = []int32{
-	0, // 0: zeloscloud.trace.SubscribeRequest.subscribe:type_name -> zeloscloud.trace.SubscribeCommand
-	1, // 1: zeloscloud.trace.SubscribeRequest.unsubscribe:type_name -> zeloscloud.trace.UnsubscribeCommand
-	4, // 2: zeloscloud.trace.SubscribeResponse.trace_message_batch:type_name -> zeloscloud.trace.TraceMessageBatch
-	2, // 3: zeloscloud.trace.TraceSubscribe.Subscribe:input_type -> zeloscloud.trace.SubscribeRequest
-	3, // 4: zeloscloud.trace.TraceSubscribe.Subscribe:output_type -> zeloscloud.trace.SubscribeResponse
-	4, // [4:5] is the sub-list for method output_type
-	3, // [3:4] is the sub-list for method input_type
-	3, // [3:3] is the sub-list for extension type_name
-	3, // [3:3] is the sub-list for extension extendee
-	0, // [0:3] is the sub-list for field type_name
+	1,  // 0: zeloscloud.trace.SubscribeRequest.subscribe:type_name -> zeloscloud.trace.SubscribeCommand
+	2,  // 1: zeloscloud.trace.SubscribeRequest.unsubscribe:type_name -> zeloscloud.trace.UnsubscribeCommand
+	7,  // 2: zeloscloud.trace.SubscribeRequest.handshake:type_name -> zeloscloud.trace.Handshake
+	0,  // 3: zeloscloud.trace.SubscribeCommandAck.kind:type_name -> zeloscloud.trace.SubscribeCommandKind
+	0,  // 4: zeloscloud.trace.SubscribeCommandError.kind:type_name -> zeloscloud.trace.SubscribeCommandKind
+	8,  // 5: zeloscloud.trace.SubscribeResponse.trace_message_batch:type_name -> zeloscloud.trace.TraceMessageBatch
+	4,  // 6: zeloscloud.trace.SubscribeResponse.command_ack:type_name -> zeloscloud.trace.SubscribeCommandAck
+	5,  // 7: zeloscloud.trace.SubscribeResponse.command_error:type_name -> zeloscloud.trace.SubscribeCommandError
+	7,  // 8: zeloscloud.trace.SubscribeResponse.handshake:type_name -> zeloscloud.trace.Handshake
+	3,  // 9: zeloscloud.trace.TraceSubscribe.Subscribe:input_type -> zeloscloud.trace.SubscribeRequest
+	6,  // 10: zeloscloud.trace.TraceSubscribe.Subscribe:output_type -> zeloscloud.trace.SubscribeResponse
+	10, // [10:11] is the sub-list for method output_type
+	9,  // [9:10] is the sub-list for method input_type
+	9,  // [9:9] is the sub-list for extension type_name
+	9,  // [9:9] is the sub-list for extension extendee
+	0,  // [0:9] is the sub-list for field type_name
 }
 
 func init() { file_zeloscloud_trace_subscribe_proto_init() }
@@ -333,22 +591,29 @@ func file_zeloscloud_trace_subscribe_proto_init() {
 	file_zeloscloud_trace_subscribe_proto_msgTypes[2].OneofWrappers = []any{
 		(*SubscribeRequest_Subscribe)(nil),
 		(*SubscribeRequest_Unsubscribe)(nil),
+		(*SubscribeRequest_Handshake)(nil),
 	}
-	file_zeloscloud_trace_subscribe_proto_msgTypes[3].OneofWrappers = []any{
+	file_zeloscloud_trace_subscribe_proto_msgTypes[3].OneofWrappers = []any{}
+	file_zeloscloud_trace_subscribe_proto_msgTypes[4].OneofWrappers = []any{}
+	file_zeloscloud_trace_subscribe_proto_msgTypes[5].OneofWrappers = []any{
 		(*SubscribeResponse_TraceMessageBatch)(nil),
+		(*SubscribeResponse_CommandAck)(nil),
+		(*SubscribeResponse_CommandError)(nil),
+		(*SubscribeResponse_Handshake)(nil),
 	}
 	type x struct{}
 	out := protoimpl.TypeBuilder{
 		File: protoimpl.DescBuilder{
 			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
 			RawDescriptor: unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_subscribe_proto_rawDesc), len(file_zeloscloud_trace_subscribe_proto_rawDesc)),
-			NumEnums:      0,
-			NumMessages:   4,
+			NumEnums:      1,
+			NumMessages:   6,
 			NumExtensions: 0,
 			NumServices:   1,
 		},
 		GoTypes:           file_zeloscloud_trace_subscribe_proto_goTypes,
 		DependencyIndexes: file_zeloscloud_trace_subscribe_proto_depIdxs,
+		EnumInfos:         file_zeloscloud_trace_subscribe_proto_enumTypes,
 		MessageInfos:      file_zeloscloud_trace_subscribe_proto_msgTypes,
 	}.Build()
 	File_zeloscloud_trace_subscribe_proto = out.File
@@ -106,6 +106,250 @@ func (DataType) EnumDescriptor() ([]byte, []int) {
 	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{0}
 }
 
+// How the values of a field relate to each other over time. Fields without a kind are gauges.
+type FieldKind int32
+
+const (
+	// Read as a gauge
+	FieldKind_FIELD_KIND_UNSPECIFIED FieldKind = 0
+	// A measurement at the time of the event, such as a temperature
+	FieldKind_FIELD_KIND_GAUGE FieldKind = 1
+	// A monotonically increasing count, such as packets received, which may wrap around or reset to zero
+	FieldKind_FIELD_KIND_COUNTER FieldKind = 2
+	// The change since the previous event, such as packets received since the last sample
+	FieldKind_FIELD_KIND_DELTA FieldKind = 3
+	// One of a set of states, usually named by a value table
+	FieldKind_FIELD_KIND_STATE FieldKind = 4
+)
+
+// Enum value maps for FieldKind.
+var (
+	FieldKind_name = map[int32]string{
+		0: "FIELD_KIND_UNSPECIFIED",
+		1: "FIELD_KIND_GAUGE",
+		2: "FIELD_KIND_COUNTER",
+		3: "FIELD_KIND_DELTA",
+		4: "FIELD_KIND_STATE",
+	}
+	FieldKind_value = map[string]int32{
+		"FIELD_KIND_UNSPECIFIED": 0,
+		"FIELD_KIND_GAUGE":       1,
+		"FIELD_KIND_COUNTER":     2,
+		"FIELD_KIND_DELTA":       3,
+		"FIELD_KIND_STATE":       4,
+	}
+)
+
+func (x FieldKind) Enum() *FieldKind {
+	p := new(FieldKind)
+	*p = x
+	return p
+}
+
+func (x FieldKind) String() string {
+	return protoimpl.X.EnumStringOf(x.Descriptor(), protoreflect.EnumNumber(x))
+}
+
+func (FieldKind) Descriptor() protoreflect.EnumDescriptor {
+	return file_zeloscloud_trace_trace_proto_enumTypes[1].Descriptor()
+}
+
+func (FieldKind) Type() protoreflect.EnumType {
+	return &file_zeloscloud_trace_trace_proto_enumTypes[1]
+}
+
+func (x FieldKind) Number() protoreflect.EnumNumber {
+	return protoreflect.EnumNumber(x)
+}
+
+// Deprecated: Use FieldKind.Descriptor instead.
+func (FieldKind) EnumDescriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{1}
+}
+
+// How a dashboard should plot a field by default
+type DisplayHint int32
+
+const (
+	DisplayHint_DISPLAY_HINT_UNSPECIFIED DisplayHint = 0
+	// A line between samples, for continuous measurements
+	DisplayHint_DISPLAY_HINT_LINE DisplayHint = 1
+	// A step held until the next sample, for states and setpoints
+	DisplayHint_DISPLAY_HINT_STEP DisplayHint = 2
+	// Unconnected points, for sparse or noisy samples
+	DisplayHint_DISPLAY_HINT_SCATTER DisplayHint = 3
+	// A bar per sample, for counts and deltas
+	DisplayHint_DISPLAY_HINT_BAR DisplayHint = 4
+	// The latest value as text
+	DisplayHint_DISPLAY_HINT_TEXT DisplayHint = 5
+)
+
+// Enum value maps for DisplayHint.
+var (
+	DisplayHint_name = map[int32]string{
+		0: "DISPLAY_HINT_UNSPECIFIED",
+		1: "DISPLAY_HINT_LINE",
+		2: "DISPLAY_HINT_STEP",
+		3: "DISPLAY_HINT_SCATTER",
+		4: "DISPLAY_HINT_BAR",
+		5: "DISPLAY_HINT_TEXT",
+	}
+	DisplayHint_value = map[string]int32{
+		"DISPLAY_HINT_UNSPECIFIED": 0,
+		"DISPLAY_HINT_LINE":        1,
+		"DISPLAY_HINT_STEP":        2,
+		"DISPLAY_HINT_SCATTER":     3,
+		"DISPLAY_HINT_BAR":         4,
+		"DISPLAY_HINT_TEXT":        5,
+	}
+)
+
+func (x DisplayHint) Enum() *DisplayHint {
+	p := new(DisplayHint)
+	*p = x
+	return p
+}
+
+func (x DisplayHint) String() string {
+	return protoimpl.X.EnumStringOf(x.Descriptor(), protoreflect.EnumNumber(x))
+}
+
+func (DisplayHint) Descriptor() protoreflect.EnumDescriptor {
+	return file_zeloscloud_trace_trace_proto_enumTypes[2].Descriptor()
+}
+
+func (DisplayHint) Type() protoreflect.EnumType {
+	return &file_zeloscloud_trace_trace_proto_enumTypes[2]
+}
+
+func (x DisplayHint) Number() protoreflect.EnumNumber {
+	return protoreflect.EnumNumber(x)
+}
+
+// Deprecated: Use DisplayHint.Descriptor instead.
+func (DisplayHint) EnumDescriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{2}
+}
+
+// Severity of a TraceLog, ordered from least to most severe
+type LogLevel int32
+
+const (
+	LogLevel_LOG_LEVEL_UNSPECIFIED LogLevel = 0
+	LogLevel_LOG_LEVEL_TRACE       LogLevel = 1
+	LogLevel_LOG_LEVEL_DEBUG       LogLevel = 2
+	LogLevel_LOG_LEVEL_INFO        LogLevel = 3
+	LogLevel_LOG_LEVEL_WARN        LogLevel = 4
+	LogLevel_LOG_LEVEL_ERROR       LogLevel = 5
+)
+
+// Enum value maps for LogLevel.
+var (
+	LogLevel_name = map[int32]string{
+		0: "LOG_LEVEL_UNSPECIFIED",
+		1: "LOG_LEVEL_TRACE",
+		2: "LOG_LEVEL_DEBUG",
+		3: "LOG_LEVEL_INFO",
+		4: "LOG_LEVEL_WARN",
+		5: "LOG_LEVEL_ERROR",
+	}
+	LogLevel_value = map[string]int32{
+		"LOG_LEVEL_UNSPECIFIED": 0,
+		"LOG_LEVEL_TRACE":       1,
+		"LOG_LEVEL_DEBUG":       2,
+		"LOG_LEVEL_INFO":        3,
+		"LOG_LEVEL_WARN":        4,
+		"LOG_LEVEL_ERROR":       5,
+	}
+)
+
+func (x LogLevel) Enum() *LogLevel {
+	p := new(LogLevel)
+	*p = x
+	return p
+}
+
+func (x LogLevel) String() string {
+	return protoimpl.X.EnumStringOf(x.Descriptor(), protoreflect.EnumNumber(x))
+}
+
+func (LogLevel) Descriptor() protoreflect.EnumDescriptor {
+	return file_zeloscloud_trace_trace_proto_enumTypes[3].Descriptor()
+}
+
+func (LogLevel) Type() protoreflect.EnumType {
+	return &file_zeloscloud_trace_trace_proto_enumTypes[3]
+}
+
+func (x LogLevel) Number() protoreflect.EnumNumber {
+	return protoreflect.EnumNumber(x)
+}
+
+// Deprecated: Use LogLevel.Descriptor instead.
+func (LogLevel) EnumDescriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{3}
+}
+
+// Optional protocol features, advertised in a Handshake
+type Capability int32
+
+const (
+	Capability_CAPABILITY_UNSPECIFIED Capability = 0
+	// gzip compressed gRPC messages
+	Capability_CAPABILITY_GZIP Capability = 1
+	// TraceEventBatch messages
+	Capability_CAPABILITY_EVENT_BATCH Capability = 2
+	// PublishStatus heartbeats, and acks and errors for subscribe commands
+	Capability_CAPABILITY_ACKS Capability = 3
+	// TraceControl commands sent to publishers
+	Capability_CAPABILITY_CONTROL Capability = 4
+)
+
+// Enum value maps for Capability.
+var (
+	Capability_name = map[int32]string{
+		0: "CAPABILITY_UNSPECIFIED",
+		1: "CAPABILITY_GZIP",
+		2: "CAPABILITY_EVENT_BATCH",
+		3: "CAPABILITY_ACKS",
+		4: "CAPABILITY_CONTROL",
+	}
+	Capability_value = map[string]int32{
+		"CAPABILITY_UNSPECIFIED": 0,
+		"CAPABILITY_GZIP":        1,
+		"CAPABILITY_EVENT_BATCH": 2,
+		"CAPABILITY_ACKS":        3,
+		"CAPABILITY_CONTROL":     4,
+	}
+)
+
+func (x Capability) Enum() *Capability {
+	p := new(Capability)
+	*p = x
+	return p
+}
+
+func (x Capability) String() string {
+	return protoimpl.X.EnumStringOf(x.Descriptor(), protoreflect.EnumNumber(x))
+}
+
+func (Capability) Descriptor() protoreflect.EnumDescriptor {
+	return file_zeloscloud_trace_trace_proto_enumTypes[4].Descriptor()
+}
+
+func (Capability) Type() protoreflect.EnumType {
+	return &file_zeloscloud_trace_trace_proto_enumTypes[4]
+}
+
+func (x Capability) Number() protoreflect.EnumNumber {
+	return protoreflect.EnumNumber(x)
+}
+
+// Deprecated: Use Capability.Descriptor instead.
+func (Capability) EnumDescriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{4}
+}
+
 type Value struct {
 	state protoimpl.MessageState `protogen:"open.v1"`
 	// Types that are valid to be assigned to Value:
@@ -384,18 +628,99 @@ func (*Value_String_) isValue_Value() {}
 
 func (*Value_Bool) isValue_Value() {}
 
+// Describes a field to the people looking at it. Every part is optional.
+type FieldDisplay struct {
+	state       protoimpl.MessageState `protogen:"open.v1"`
+	Description *string                `protobuf:"bytes,1,opt,name=description,proto3,oneof" json:"description,omitempty"`
+	// The range of values expected, e.g. a sensor's range
+	Min *float64 `protobuf:"fixed64,2,opt,name=min,proto3,oneof" json:"min,omitempty"`
+	Max *float64 `protobuf:"fixed64,3,opt,name=max,proto3,oneof" json:"max,omitempty"`
+	// Number of decimal places to show
+	Precision     *uint32     `protobuf:"varint,4,opt,name=precision,proto3,oneof" json:"precision,omitempty"`
+	Hint          DisplayHint `protobuf:"varint,5,opt,name=hint,proto3,enum=zeloscloud.trace.DisplayHint" json:"hint,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *FieldDisplay) Reset() {
+	*x = FieldDisplay{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[1]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *FieldDisplay) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*FieldDisplay) ProtoMessage() {}
+
+func (x *FieldDisplay) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[1]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use FieldDisplay.ProtoReflect.Descriptor instead.
+func (*FieldDisplay) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{1}
+}
+
+func (x *FieldDisplay) GetDescription() string {
+	if x != nil && x.Description != nil {
+		return *x.Description
+	}
+	return ""
+}
+
+func (x *FieldDisplay) GetMin() float64 {
+	if x != nil && x.Min != nil {
+		return *x.Min
+	}
+	return 0
+}
+
+func (x *FieldDisplay) GetMax() float64 {
+	if x != nil && x.Max != nil {
+		return *x.Max
+	}
+	return 0
+}
+
+func (x *FieldDisplay) GetPrecision() uint32 {
+	if x != nil && x.Precision != nil {
+		return *x.Precision
+	}
+	return 0
+}
+
+func (x *FieldDisplay) GetHint() DisplayHint {
+	if x != nil {
+		return x.Hint
+	}
+	return DisplayHint_DISPLAY_HINT_UNSPECIFIED
+}
+
 type TraceEventFieldMetadata struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
 	Name          string                 `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
 	DataType      DataType               `protobuf:"varint,2,opt,name=data_type,json=dataType,proto3,enum=zeloscloud.trace.DataType" json:"data_type,omitempty"`
 	Unit          *string                `protobuf:"bytes,3,opt,name=unit,proto3,oneof" json:"unit,omitempty"`
+	Kind          FieldKind              `protobuf:"varint,4,opt,name=kind,proto3,enum=zeloscloud.trace.FieldKind" json:"kind,omitempty"`
+	Display       *FieldDisplay          `protobuf:"bytes,5,opt,name=display,proto3" json:"display,omitempty"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
 
 func (x *TraceEventFieldMetadata) Reset() {
 	*x = TraceEventFieldMetadata{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[1]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[2]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -407,7 +732,7 @@ func (x *TraceEventFieldMetadata) String() string {
 func (*TraceEventFieldMetadata) ProtoMessage() {}
 
 func (x *TraceEventFieldMetadata) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[1]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[2]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -420,7 +745,7 @@ func (x *TraceEventFieldMetadata) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEventFieldMetadata.ProtoReflect.Descriptor instead.
 func (*TraceEventFieldMetadata) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{1}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{2}
 }
 
 func (x *TraceEventFieldMetadata) GetName() string {
@@ -444,17 +769,33 @@ func (x *TraceEventFieldMetadata) GetUnit() string {
 	return ""
 }
 
+func (x *TraceEventFieldMetadata) GetKind() FieldKind {
+	if x != nil {
+		return x.Kind
+	}
+	return FieldKind_FIELD_KIND_UNSPECIFIED
+}
+
+func (x *TraceEventFieldMetadata) GetDisplay() *FieldDisplay {
+	if x != nil {
+		return x.Display
+	}
+	return nil
+}
+
 type TraceSegmentStart struct {
-	state         protoimpl.MessageState `protogen:"open.v1"`
-	TimeNs        int64                  `protobuf:"fixed64,1,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
-	SourceName    string                 `protobuf:"bytes,2,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	state      protoimpl.MessageState `protogen:"open.v1"`
+	TimeNs     int64                  `protobuf:"fixed64,1,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	SourceName string                 `protobuf:"bytes,2,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	// Where the segment was produced, e.g. host, pid and version, and any tags added by the application
+	Attributes    map[string]string `protobuf:"bytes,3,rep,name=attributes,proto3" json:"attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
 
 func (x *TraceSegmentStart) Reset() {
 	*x = TraceSegmentStart{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[2]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[3]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -466,7 +807,7 @@ func (x *TraceSegmentStart) String() string {
 func (*TraceSegmentStart) ProtoMessage() {}
 
 func (x *TraceSegmentStart) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[2]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[3]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -479,7 +820,7 @@ func (x *TraceSegmentStart) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceSegmentStart.ProtoReflect.Descriptor instead.
 func (*TraceSegmentStart) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{2}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{3}
 }
 
 func (x *TraceSegmentStart) GetTimeNs() int64 {
@@ -496,6 +837,13 @@ func (x *TraceSegmentStart) GetSourceName() string {
 	return ""
 }
 
+func (x *TraceSegmentStart) GetAttributes() map[string]string {
+	if x != nil {
+		return x.Attributes
+	}
+	return nil
+}
+
 type TraceSegmentEnd struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
 	TimeNs        int64                  `protobuf:"fixed64,1,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
@@ -505,7 +853,7 @@ type TraceSegmentEnd struct {
 
 func (x *TraceSegmentEnd) Reset() {
 	*x = TraceSegmentEnd{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[3]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[4]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -517,7 +865,7 @@ func (x *TraceSegmentEnd) String() string {
 func (*TraceSegmentEnd) ProtoMessage() {}
 
 func (x *TraceSegmentEnd) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[3]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[4]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -530,7 +878,7 @@ func (x *TraceSegmentEnd) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceSegmentEnd.ProtoReflect.Descriptor instead.
 func (*TraceSegmentEnd) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{3}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{4}
 }
 
 func (x *TraceSegmentEnd) GetTimeNs() int64 {
@@ -541,16 +889,19 @@ func (x *TraceSegmentEnd) GetTimeNs() int64 {
 }
 
 type TraceEventSchema struct {
-	state         protoimpl.MessageState     `protogen:"open.v1"`
-	Name          string                     `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
-	Fields        []*TraceEventFieldMetadata `protobuf:"bytes,2,rep,name=fields,proto3" json:"fields,omitempty"`
+	state  protoimpl.MessageState     `protogen:"open.v1"`
+	Name   string                     `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
+	Fields []*TraceEventFieldMetadata `protobuf:"bytes,2,rep,name=fields,proto3" json:"fields,omitempty"`
+	// Starts at 0 and increases each time the event's fields change within a segment. Events are read with the latest
+	// version sent before them.
+	Version       uint32 `protobuf:"varint,3,opt,name=version,proto3" json:"version,omitempty"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
 
 func (x *TraceEventSchema) Reset() {
 	*x = TraceEventSchema{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[4]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[5]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -562,7 +913,7 @@ func (x *TraceEventSchema) String() string {
 func (*TraceEventSchema) ProtoMessage() {}
 
 func (x *TraceEventSchema) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[4]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[5]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -575,7 +926,7 @@ func (x *TraceEventSchema) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEventSchema.ProtoReflect.Descriptor instead.
 func (*TraceEventSchema) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{4}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{5}
 }
 
 func (x *TraceEventSchema) GetName() string {
@@ -592,6 +943,13 @@ func (x *TraceEventSchema) GetFields() []*TraceEventFieldMetadata {
 	return nil
 }
 
+func (x *TraceEventSchema) GetVersion() uint32 {
+	if x != nil {
+		return x.Version
+	}
+	return 0
+}
+
 type TraceEventFieldNamedValuesEntry struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
 	Name          string                 `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
@@ -602,7 +960,7 @@ type TraceEventFieldNamedValuesEntry struct {
 
 func (x *TraceEventFieldNamedValuesEntry) Reset() {
 	*x = TraceEventFieldNamedValuesEntry{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[5]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[6]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -614,7 +972,7 @@ func (x *TraceEventFieldNamedValuesEntry) String() string {
 func (*TraceEventFieldNamedValuesEntry) ProtoMessage() {}
 
 func (x *TraceEventFieldNamedValuesEntry) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[5]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[6]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -627,7 +985,7 @@ func (x *TraceEventFieldNamedValuesEntry) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEventFieldNamedValuesEntry.ProtoReflect.Descriptor instead.
 func (*TraceEventFieldNamedValuesEntry) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{5}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{6}
 }
 
 func (x *TraceEventFieldNamedValuesEntry) GetName() string {
@@ -655,7 +1013,7 @@ type TraceEventFieldNamedValues struct {
 
 func (x *TraceEventFieldNamedValues) Reset() {
 	*x = TraceEventFieldNamedValues{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[6]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[7]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -667,7 +1025,7 @@ func (x *TraceEventFieldNamedValues) String() string {
 func (*TraceEventFieldNamedValues) ProtoMessage() {}
 
 func (x *TraceEventFieldNamedValues) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[6]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[7]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -680,7 +1038,7 @@ func (x *TraceEventFieldNamedValues) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEventFieldNamedValues.ProtoReflect.Descriptor instead.
 func (*TraceEventFieldNamedValues) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{6}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{7}
 }
 
 func (x *TraceEventFieldNamedValues) GetEventName() string {
@@ -714,7 +1072,7 @@ type TraceEventFieldEntry struct {
 
 func (x *TraceEventFieldEntry) Reset() {
 	*x = TraceEventFieldEntry{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[7]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[8]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -726,7 +1084,7 @@ func (x *TraceEventFieldEntry) String() string {
 func (*TraceEventFieldEntry) ProtoMessage() {}
 
 func (x *TraceEventFieldEntry) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[7]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[8]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -739,7 +1097,7 @@ func (x *TraceEventFieldEntry) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEventFieldEntry.ProtoReflect.Descriptor instead.
 func (*TraceEventFieldEntry) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{7}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{8}
 }
 
 func (x *TraceEventFieldEntry) GetName() string {
@@ -767,7 +1125,7 @@ type TraceEvent struct {
 
 func (x *TraceEvent) Reset() {
 	*x = TraceEvent{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[8]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[9]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -779,7 +1137,7 @@ func (x *TraceEvent) String() string {
 func (*TraceEvent) ProtoMessage() {}
 
 func (x *TraceEvent) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[8]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[9]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -792,7 +1150,7 @@ func (x *TraceEvent) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceEvent.ProtoReflect.Descriptor instead.
 func (*TraceEvent) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{8}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{9}
 }
 
 func (x *TraceEvent) GetTimeNs() int64 {
@@ -816,39 +1174,31 @@ func (x *TraceEvent) GetFields() []*TraceEventFieldEntry {
 	return nil
 }
 
-type TraceMessage struct {
-	state protoimpl.MessageState `protogen:"open.v1"`
-	// The UUIDv7 of the segment
-	SegmentId []byte `protobuf:"bytes,1,opt,name=segment_id,json=segmentId,proto3" json:"segment_id,omitempty"`
-	// The name of the source that generated the segment
-	SourceName string `protobuf:"bytes,7,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
-	// Types that are valid to be assigned to Msg:
-	//
-	//	*TraceMessage_SegmentStart
-	//	*TraceMessage_SegmentEnd
-	//	*TraceMessage_EventSchema
-	//	*TraceMessage_EventFieldNamedValues
-	//	*TraceMessage_Event
-	Msg           isTraceMessage_Msg `protobuf_oneof:"msg"`
+// An action a source can be asked to run
+type TraceActionSchema struct {
+	state         protoimpl.MessageState     `protogen:"open.v1"`
+	Name          string                     `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
+	Parameters    []*TraceEventFieldMetadata `protobuf:"bytes,2,rep,name=parameters,proto3" json:"parameters,omitempty"`
+	Returns       []*TraceEventFieldMetadata `protobuf:"bytes,3,rep,name=returns,proto3" json:"returns,omitempty"`
 	unknownFields protoimpl.UnknownFields
 	sizeCache     protoimpl.SizeCache
 }
 
-func (x *TraceMessage) Reset() {
-	*x = TraceMessage{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[9]
+func (x *TraceActionSchema) Reset() {
+	*x = TraceActionSchema{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[10]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
 
-func (x *TraceMessage) String() string {
+func (x *TraceActionSchema) String() string {
 	return protoimpl.X.MessageStringOf(x)
 }
 
-func (*TraceMessage) ProtoMessage() {}
+func (*TraceActionSchema) ProtoMessage() {}
 
-func (x *TraceMessage) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[9]
+func (x *TraceActionSchema) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[10]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -859,110 +1209,1580 @@ func (x *TraceMessage) ProtoReflect() protoreflect.Message {
 	return mi.MessageOf(x)
 }
 
-// Deprecated: Use TraceMessage.ProtoReflect.Descriptor instead.
-func (*TraceMessage) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{9}
+// Deprecated: Use TraceActionSchema.ProtoReflect.Descriptor instead.
+func (*TraceActionSchema) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{10}
 }
 
-func (x *TraceMessage) GetSegmentId() []byte {
+func (x *TraceActionSchema) GetName() string {
 	if x != nil {
-		return x.SegmentId
+		return x.Name
 	}
-	return nil
+	return ""
+}
+
+func (x *TraceActionSchema) GetParameters() []*TraceEventFieldMetadata {
+	if x != nil {
+		return x.Parameters
+	}
+	return nil
+}
+
+func (x *TraceActionSchema) GetReturns() []*TraceEventFieldMetadata {
+	if x != nil {
+		return x.Returns
+	}
+	return nil
+}
+
+// The outcome of a TraceActionCall, sent by the source that ran it
+type TraceActionResult struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the call
+	CallId     []byte                  `protobuf:"bytes,1,opt,name=call_id,json=callId,proto3" json:"call_id,omitempty"`
+	ActionName string                  `protobuf:"bytes,2,opt,name=action_name,json=actionName,proto3" json:"action_name,omitempty"`
+	Values     []*TraceEventFieldEntry `protobuf:"bytes,3,rep,name=values,proto3" json:"values,omitempty"`
+	// Set if the call failed, in which case there are no values
+	Error         *string `protobuf:"bytes,4,opt,name=error,proto3,oneof" json:"error,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceActionResult) Reset() {
+	*x = TraceActionResult{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[11]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceActionResult) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceActionResult) ProtoMessage() {}
+
+func (x *TraceActionResult) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[11]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceActionResult.ProtoReflect.Descriptor instead.
+func (*TraceActionResult) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{11}
+}
+
+func (x *TraceActionResult) GetCallId() []byte {
+	if x != nil {
+		return x.CallId
+	}
+	return nil
+}
+
+func (x *TraceActionResult) GetActionName() string {
+	if x != nil {
+		return x.ActionName
+	}
+	return ""
+}
+
+func (x *TraceActionResult) GetValues() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Values
+	}
+	return nil
+}
+
+func (x *TraceActionResult) GetError() string {
+	if x != nil && x.Error != nil {
+		return *x.Error
+	}
+	return ""
+}
+
+// The values of one field across the samples of a TraceEventBatch. Only the list matching the field's data type is
+// set, using the same encodings as Value.
+type TraceEventColumn struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// Index of the field in the event's schema
+	FieldIndex uint32 `protobuf:"varint,1,opt,name=field_index,json=fieldIndex,proto3" json:"field_index,omitempty"`
+	// Indices of the samples that have a value for this field, in ascending order. Empty when every sample has one.
+	Present       []uint32  `protobuf:"varint,2,rep,packed,name=present,proto3" json:"present,omitempty"`
+	Ints          []int64   `protobuf:"zigzag64,3,rep,packed,name=ints,proto3" json:"ints,omitempty"`
+	Uints         []uint64  `protobuf:"varint,4,rep,packed,name=uints,proto3" json:"uints,omitempty"`
+	Float32S      []float32 `protobuf:"fixed32,5,rep,packed,name=float32s,proto3" json:"float32s,omitempty"`
+	Float64S      []float64 `protobuf:"fixed64,6,rep,packed,name=float64s,proto3" json:"float64s,omitempty"`
+	TimestampsNs  []int64   `protobuf:"fixed64,7,rep,packed,name=timestamps_ns,json=timestampsNs,proto3" json:"timestamps_ns,omitempty"`
+	Binaries      [][]byte  `protobuf:"bytes,8,rep,name=binaries,proto3" json:"binaries,omitempty"`
+	Strings       []string  `protobuf:"bytes,9,rep,name=strings,proto3" json:"strings,omitempty"`
+	Bools         []bool    `protobuf:"varint,10,rep,packed,name=bools,proto3" json:"bools,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceEventColumn) Reset() {
+	*x = TraceEventColumn{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[12]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceEventColumn) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceEventColumn) ProtoMessage() {}
+
+func (x *TraceEventColumn) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[12]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceEventColumn.ProtoReflect.Descriptor instead.
+func (*TraceEventColumn) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{12}
+}
+
+func (x *TraceEventColumn) GetFieldIndex() uint32 {
+	if x != nil {
+		return x.FieldIndex
+	}
+	return 0
+}
+
+func (x *TraceEventColumn) GetPresent() []uint32 {
+	if x != nil {
+		return x.Present
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetInts() []int64 {
+	if x != nil {
+		return x.Ints
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetUints() []uint64 {
+	if x != nil {
+		return x.Uints
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetFloat32S() []float32 {
+	if x != nil {
+		return x.Float32S
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetFloat64S() []float64 {
+	if x != nil {
+		return x.Float64S
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetTimestampsNs() []int64 {
+	if x != nil {
+		return x.TimestampsNs
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetBinaries() [][]byte {
+	if x != nil {
+		return x.Binaries
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetStrings() []string {
+	if x != nil {
+		return x.Strings
+	}
+	return nil
+}
+
+func (x *TraceEventColumn) GetBools() []bool {
+	if x != nil {
+		return x.Bools
+	}
+	return nil
+}
+
+// N samples of one event, packed as a timestamp column plus a value column per field. Field names and data types come
+// from the event's schema, which must be sent before the batch.
+type TraceEventBatch struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	Name          string                 `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
+	TimeNs        []int64                `protobuf:"fixed64,2,rep,packed,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	Columns       []*TraceEventColumn    `protobuf:"bytes,3,rep,name=columns,proto3" json:"columns,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceEventBatch) Reset() {
+	*x = TraceEventBatch{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[13]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceEventBatch) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceEventBatch) ProtoMessage() {}
+
+func (x *TraceEventBatch) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[13]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceEventBatch.ProtoReflect.Descriptor instead.
+func (*TraceEventBatch) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{13}
+}
+
+func (x *TraceEventBatch) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *TraceEventBatch) GetTimeNs() []int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return nil
+}
+
+func (x *TraceEventBatch) GetColumns() []*TraceEventColumn {
+	if x != nil {
+		return x.Columns
+	}
+	return nil
+}
+
+// Start of a session grouping the segments of e.g. a test run. Sent with the session id as the segment id and the
+// session name as the source name.
+type TraceSessionStart struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the session
+	SessionId  []byte            `protobuf:"bytes,1,opt,name=session_id,json=sessionId,proto3" json:"session_id,omitempty"`
+	TimeNs     int64             `protobuf:"fixed64,2,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	Name       string            `protobuf:"bytes,3,opt,name=name,proto3" json:"name,omitempty"`
+	Attributes map[string]string `protobuf:"bytes,4,rep,name=attributes,proto3" json:"attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
+	// Segments that start while the session is open with attributes including all of these join it, none do if it is
+	// empty
+	JoinAttributes map[string]string `protobuf:"bytes,5,rep,name=join_attributes,json=joinAttributes,proto3" json:"join_attributes,omitempty" protobuf_key:"bytes,1,opt,name=key,proto3" protobuf_val:"bytes,2,opt,name=value,proto3"`
+	unknownFields  protoimpl.UnknownFields
+	sizeCache      protoimpl.SizeCache
+}
+
+func (x *TraceSessionStart) Reset() {
+	*x = TraceSessionStart{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[14]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceSessionStart) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceSessionStart) ProtoMessage() {}
+
+func (x *TraceSessionStart) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[14]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceSessionStart.ProtoReflect.Descriptor instead.
+func (*TraceSessionStart) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{14}
+}
+
+func (x *TraceSessionStart) GetSessionId() []byte {
+	if x != nil {
+		return x.SessionId
+	}
+	return nil
+}
+
+func (x *TraceSessionStart) GetTimeNs() int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return 0
+}
+
+func (x *TraceSessionStart) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *TraceSessionStart) GetAttributes() map[string]string {
+	if x != nil {
+		return x.Attributes
+	}
+	return nil
+}
+
+func (x *TraceSessionStart) GetJoinAttributes() map[string]string {
+	if x != nil {
+		return x.JoinAttributes
+	}
+	return nil
+}
+
+// End of a session, sent like its TraceSessionStart
+type TraceSessionEnd struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	SessionId     []byte                 `protobuf:"bytes,1,opt,name=session_id,json=sessionId,proto3" json:"session_id,omitempty"`
+	TimeNs        int64                  `protobuf:"fixed64,2,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceSessionEnd) Reset() {
+	*x = TraceSessionEnd{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[15]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceSessionEnd) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceSessionEnd) ProtoMessage() {}
+
+func (x *TraceSessionEnd) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[15]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceSessionEnd.ProtoReflect.Descriptor instead.
+func (*TraceSessionEnd) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{15}
+}
+
+func (x *TraceSessionEnd) GetSessionId() []byte {
+	if x != nil {
+		return x.SessionId
+	}
+	return nil
+}
+
+func (x *TraceSessionEnd) GetTimeNs() int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return 0
+}
+
+// Adds the segment this message is sent with to a session
+type TraceSessionJoin struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	SessionId     []byte                 `protobuf:"bytes,1,opt,name=session_id,json=sessionId,proto3" json:"session_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceSessionJoin) Reset() {
+	*x = TraceSessionJoin{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[16]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceSessionJoin) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceSessionJoin) ProtoMessage() {}
+
+func (x *TraceSessionJoin) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[16]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceSessionJoin.ProtoReflect.Descriptor instead.
+func (*TraceSessionJoin) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{16}
+}
+
+func (x *TraceSessionJoin) GetSessionId() []byte {
+	if x != nil {
+		return x.SessionId
+	}
+	return nil
+}
+
+// A bookmark on the timeline such as "brake test started", covering start_time_ns..=end_time_ns, which are equal for a
+// single point in time
+type TraceAnnotation struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the annotation
+	AnnotationId  []byte   `protobuf:"bytes,1,opt,name=annotation_id,json=annotationId,proto3" json:"annotation_id,omitempty"`
+	StartTimeNs   int64    `protobuf:"fixed64,2,opt,name=start_time_ns,json=startTimeNs,proto3" json:"start_time_ns,omitempty"`
+	EndTimeNs     int64    `protobuf:"fixed64,3,opt,name=end_time_ns,json=endTimeNs,proto3" json:"end_time_ns,omitempty"`
+	Text          string   `protobuf:"bytes,4,opt,name=text,proto3" json:"text,omitempty"`
+	Tags          []string `protobuf:"bytes,5,rep,name=tags,proto3" json:"tags,omitempty"`
+	Author        *string  `protobuf:"bytes,6,opt,name=author,proto3,oneof" json:"author,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceAnnotation) Reset() {
+	*x = TraceAnnotation{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[17]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceAnnotation) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceAnnotation) ProtoMessage() {}
+
+func (x *TraceAnnotation) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[17]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceAnnotation.ProtoReflect.Descriptor instead.
+func (*TraceAnnotation) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{17}
+}
+
+func (x *TraceAnnotation) GetAnnotationId() []byte {
+	if x != nil {
+		return x.AnnotationId
+	}
+	return nil
+}
+
+func (x *TraceAnnotation) GetStartTimeNs() int64 {
+	if x != nil {
+		return x.StartTimeNs
+	}
+	return 0
+}
+
+func (x *TraceAnnotation) GetEndTimeNs() int64 {
+	if x != nil {
+		return x.EndTimeNs
+	}
+	return 0
+}
+
+func (x *TraceAnnotation) GetText() string {
+	if x != nil {
+		return x.Text
+	}
+	return ""
+}
+
+func (x *TraceAnnotation) GetTags() []string {
+	if x != nil {
+		return x.Tags
+	}
+	return nil
+}
+
+func (x *TraceAnnotation) GetAuthor() string {
+	if x != nil && x.Author != nil {
+		return *x.Author
+	}
+	return ""
+}
+
+// A structured log line of a source, routed and filtered like its events
+type TraceLog struct {
+	state  protoimpl.MessageState `protogen:"open.v1"`
+	TimeNs int64                  `protobuf:"fixed64,1,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	Level  LogLevel               `protobuf:"varint,2,opt,name=level,proto3,enum=zeloscloud.trace.LogLevel" json:"level,omitempty"`
+	// The module or component that logged, e.g. motor::can
+	Target        string                  `protobuf:"bytes,3,opt,name=target,proto3" json:"target,omitempty"`
+	Message       string                  `protobuf:"bytes,4,opt,name=message,proto3" json:"message,omitempty"`
+	Fields        []*TraceEventFieldEntry `protobuf:"bytes,5,rep,name=fields,proto3" json:"fields,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceLog) Reset() {
+	*x = TraceLog{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[18]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceLog) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceLog) ProtoMessage() {}
+
+func (x *TraceLog) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[18]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceLog.ProtoReflect.Descriptor instead.
+func (*TraceLog) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{18}
+}
+
+func (x *TraceLog) GetTimeNs() int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return 0
+}
+
+func (x *TraceLog) GetLevel() LogLevel {
+	if x != nil {
+		return x.Level
+	}
+	return LogLevel_LOG_LEVEL_UNSPECIFIED
+}
+
+func (x *TraceLog) GetTarget() string {
+	if x != nil {
+		return x.Target
+	}
+	return ""
+}
+
+func (x *TraceLog) GetMessage() string {
+	if x != nil {
+		return x.Message
+	}
+	return ""
+}
+
+func (x *TraceLog) GetFields() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Fields
+	}
+	return nil
+}
+
+// Start of a named interval of a source, such as a state of a state machine, which lasts until the TraceIntervalEnd with
+// the same interval_id
+type TraceIntervalBegin struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the interval
+	IntervalId []byte `protobuf:"bytes,1,opt,name=interval_id,json=intervalId,proto3" json:"interval_id,omitempty"`
+	// The interval this one is nested in, if any
+	ParentId      []byte                  `protobuf:"bytes,2,opt,name=parent_id,json=parentId,proto3,oneof" json:"parent_id,omitempty"`
+	TimeNs        int64                   `protobuf:"fixed64,3,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	Name          string                  `protobuf:"bytes,4,opt,name=name,proto3" json:"name,omitempty"`
+	Fields        []*TraceEventFieldEntry `protobuf:"bytes,5,rep,name=fields,proto3" json:"fields,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceIntervalBegin) Reset() {
+	*x = TraceIntervalBegin{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[19]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceIntervalBegin) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceIntervalBegin) ProtoMessage() {}
+
+func (x *TraceIntervalBegin) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[19]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceIntervalBegin.ProtoReflect.Descriptor instead.
+func (*TraceIntervalBegin) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{19}
+}
+
+func (x *TraceIntervalBegin) GetIntervalId() []byte {
+	if x != nil {
+		return x.IntervalId
+	}
+	return nil
+}
+
+func (x *TraceIntervalBegin) GetParentId() []byte {
+	if x != nil {
+		return x.ParentId
+	}
+	return nil
+}
+
+func (x *TraceIntervalBegin) GetTimeNs() int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return 0
+}
+
+func (x *TraceIntervalBegin) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *TraceIntervalBegin) GetFields() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Fields
+	}
+	return nil
+}
+
+// End of an interval, with the name of its TraceIntervalBegin so it can be filtered the same way
+type TraceIntervalEnd struct {
+	state         protoimpl.MessageState  `protogen:"open.v1"`
+	IntervalId    []byte                  `protobuf:"bytes,1,opt,name=interval_id,json=intervalId,proto3" json:"interval_id,omitempty"`
+	TimeNs        int64                   `protobuf:"fixed64,2,opt,name=time_ns,json=timeNs,proto3" json:"time_ns,omitempty"`
+	Name          string                  `protobuf:"bytes,3,opt,name=name,proto3" json:"name,omitempty"`
+	Fields        []*TraceEventFieldEntry `protobuf:"bytes,4,rep,name=fields,proto3" json:"fields,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceIntervalEnd) Reset() {
+	*x = TraceIntervalEnd{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[20]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceIntervalEnd) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceIntervalEnd) ProtoMessage() {}
+
+func (x *TraceIntervalEnd) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[20]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceIntervalEnd.ProtoReflect.Descriptor instead.
+func (*TraceIntervalEnd) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{20}
+}
+
+func (x *TraceIntervalEnd) GetIntervalId() []byte {
+	if x != nil {
+		return x.IntervalId
+	}
+	return nil
+}
+
+func (x *TraceIntervalEnd) GetTimeNs() int64 {
+	if x != nil {
+		return x.TimeNs
+	}
+	return 0
+}
+
+func (x *TraceIntervalEnd) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *TraceIntervalEnd) GetFields() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Fields
+	}
+	return nil
+}
+
+type TraceMessage struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the segment
+	SegmentId []byte `protobuf:"bytes,1,opt,name=segment_id,json=segmentId,proto3" json:"segment_id,omitempty"`
+	// The name of the source that generated the segment
+	SourceName string `protobuf:"bytes,7,opt,name=source_name,json=sourceName,proto3" json:"source_name,omitempty"`
+	// Types that are valid to be assigned to Msg:
+	//
+	//	*TraceMessage_SegmentStart
+	//	*TraceMessage_SegmentEnd
+	//	*TraceMessage_EventSchema
+	//	*TraceMessage_EventFieldNamedValues
+	//	*TraceMessage_Event
+	//	*TraceMessage_EventBatch
+	//	*TraceMessage_ActionSchema
+	//	*TraceMessage_ActionResult
+	//	*TraceMessage_SessionStart
+	//	*TraceMessage_SessionEnd
+	//	*TraceMessage_SessionJoin
+	//	*TraceMessage_Annotation
+	//	*TraceMessage_Log
+	//	*TraceMessage_IntervalBegin
+	//	*TraceMessage_IntervalEnd
+	Msg           isTraceMessage_Msg `protobuf_oneof:"msg"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceMessage) Reset() {
+	*x = TraceMessage{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[21]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceMessage) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceMessage) ProtoMessage() {}
+
+func (x *TraceMessage) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[21]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceMessage.ProtoReflect.Descriptor instead.
+func (*TraceMessage) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{21}
+}
+
+func (x *TraceMessage) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
 }
 
 func (x *TraceMessage) GetSourceName() string {
 	if x != nil {
-		return x.SourceName
+		return x.SourceName
+	}
+	return ""
+}
+
+func (x *TraceMessage) GetMsg() isTraceMessage_Msg {
+	if x != nil {
+		return x.Msg
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetSegmentStart() *TraceSegmentStart {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_SegmentStart); ok {
+			return x.SegmentStart
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetSegmentEnd() *TraceSegmentEnd {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_SegmentEnd); ok {
+			return x.SegmentEnd
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetEventSchema() *TraceEventSchema {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_EventSchema); ok {
+			return x.EventSchema
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetEventFieldNamedValues() *TraceEventFieldNamedValues {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_EventFieldNamedValues); ok {
+			return x.EventFieldNamedValues
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetEvent() *TraceEvent {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_Event); ok {
+			return x.Event
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetEventBatch() *TraceEventBatch {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_EventBatch); ok {
+			return x.EventBatch
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetActionSchema() *TraceActionSchema {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_ActionSchema); ok {
+			return x.ActionSchema
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetActionResult() *TraceActionResult {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_ActionResult); ok {
+			return x.ActionResult
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetSessionStart() *TraceSessionStart {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_SessionStart); ok {
+			return x.SessionStart
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetSessionEnd() *TraceSessionEnd {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_SessionEnd); ok {
+			return x.SessionEnd
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetSessionJoin() *TraceSessionJoin {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_SessionJoin); ok {
+			return x.SessionJoin
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetAnnotation() *TraceAnnotation {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_Annotation); ok {
+			return x.Annotation
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetLog() *TraceLog {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_Log); ok {
+			return x.Log
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetIntervalBegin() *TraceIntervalBegin {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_IntervalBegin); ok {
+			return x.IntervalBegin
+		}
+	}
+	return nil
+}
+
+func (x *TraceMessage) GetIntervalEnd() *TraceIntervalEnd {
+	if x != nil {
+		if x, ok := x.Msg.(*TraceMessage_IntervalEnd); ok {
+			return x.IntervalEnd
+		}
+	}
+	return nil
+}
+
+type isTraceMessage_Msg interface {
+	isTraceMessage_Msg()
+}
+
+type TraceMessage_SegmentStart struct {
+	SegmentStart *TraceSegmentStart `protobuf:"bytes,2,opt,name=segment_start,json=segmentStart,proto3,oneof"`
+}
+
+type TraceMessage_SegmentEnd struct {
+	SegmentEnd *TraceSegmentEnd `protobuf:"bytes,3,opt,name=segment_end,json=segmentEnd,proto3,oneof"`
+}
+
+type TraceMessage_EventSchema struct {
+	EventSchema *TraceEventSchema `protobuf:"bytes,4,opt,name=event_schema,json=eventSchema,proto3,oneof"`
+}
+
+type TraceMessage_EventFieldNamedValues struct {
+	EventFieldNamedValues *TraceEventFieldNamedValues `protobuf:"bytes,5,opt,name=event_field_named_values,json=eventFieldNamedValues,proto3,oneof"`
+}
+
+type TraceMessage_Event struct {
+	Event *TraceEvent `protobuf:"bytes,6,opt,name=event,proto3,oneof"`
+}
+
+type TraceMessage_EventBatch struct {
+	EventBatch *TraceEventBatch `protobuf:"bytes,8,opt,name=event_batch,json=eventBatch,proto3,oneof"`
+}
+
+type TraceMessage_ActionSchema struct {
+	ActionSchema *TraceActionSchema `protobuf:"bytes,9,opt,name=action_schema,json=actionSchema,proto3,oneof"`
+}
+
+type TraceMessage_ActionResult struct {
+	ActionResult *TraceActionResult `protobuf:"bytes,10,opt,name=action_result,json=actionResult,proto3,oneof"`
+}
+
+type TraceMessage_SessionStart struct {
+	SessionStart *TraceSessionStart `protobuf:"bytes,11,opt,name=session_start,json=sessionStart,proto3,oneof"`
+}
+
+type TraceMessage_SessionEnd struct {
+	SessionEnd *TraceSessionEnd `protobuf:"bytes,12,opt,name=session_end,json=sessionEnd,proto3,oneof"`
+}
+
+type TraceMessage_SessionJoin struct {
+	SessionJoin *TraceSessionJoin `protobuf:"bytes,13,opt,name=session_join,json=sessionJoin,proto3,oneof"`
+}
+
+type TraceMessage_Annotation struct {
+	Annotation *TraceAnnotation `protobuf:"bytes,14,opt,name=annotation,proto3,oneof"`
+}
+
+type TraceMessage_Log struct {
+	Log *TraceLog `protobuf:"bytes,15,opt,name=log,proto3,oneof"`
+}
+
+type TraceMessage_IntervalBegin struct {
+	IntervalBegin *TraceIntervalBegin `protobuf:"bytes,16,opt,name=interval_begin,json=intervalBegin,proto3,oneof"`
+}
+
+type TraceMessage_IntervalEnd struct {
+	IntervalEnd *TraceIntervalEnd `protobuf:"bytes,17,opt,name=interval_end,json=intervalEnd,proto3,oneof"`
+}
+
+func (*TraceMessage_SegmentStart) isTraceMessage_Msg() {}
+
+func (*TraceMessage_SegmentEnd) isTraceMessage_Msg() {}
+
+func (*TraceMessage_EventSchema) isTraceMessage_Msg() {}
+
+func (*TraceMessage_EventFieldNamedValues) isTraceMessage_Msg() {}
+
+func (*TraceMessage_Event) isTraceMessage_Msg() {}
+
+func (*TraceMessage_EventBatch) isTraceMessage_Msg() {}
+
+func (*TraceMessage_ActionSchema) isTraceMessage_Msg() {}
+
+func (*TraceMessage_ActionResult) isTraceMessage_Msg() {}
+
+func (*TraceMessage_SessionStart) isTraceMessage_Msg() {}
+
+func (*TraceMessage_SessionEnd) isTraceMessage_Msg() {}
+
+func (*TraceMessage_SessionJoin) isTraceMessage_Msg() {}
+
+func (*TraceMessage_Annotation) isTraceMessage_Msg() {}
+
+func (*TraceMessage_Log) isTraceMessage_Msg() {}
+
+func (*TraceMessage_IntervalBegin) isTraceMessage_Msg() {}
+
+func (*TraceMessage_IntervalEnd) isTraceMessage_Msg() {}
+
+// The first message on a Publish or Subscribe stream. The client sends the versions and capabilities it supports, and
+// the server answers with the version and capabilities both sides will use, or fails the stream with
+// FAILED_PRECONDITION and the metadata "zelos-handshake: rejected" if their versions do not overlap. Peers that predate
+// the handshake never send one and are treated as protocol version 1 with acks.
+type Handshake struct {
+	state           protoimpl.MessageState `protogen:"open.v1"`
+	ProtocolVersion uint32                 `protobuf:"varint,1,opt,name=protocol_version,json=protocolVersion,proto3" json:"protocol_version,omitempty"`
+	// Oldest protocol version the sender still supports
+	MinProtocolVersion uint32       `protobuf:"varint,2,opt,name=min_protocol_version,json=minProtocolVersion,proto3" json:"min_protocol_version,omitempty"`
+	Capabilities       []Capability `protobuf:"varint,3,rep,packed,name=capabilities,proto3,enum=zeloscloud.trace.Capability" json:"capabilities,omitempty"`
+	unknownFields      protoimpl.UnknownFields
+	sizeCache          protoimpl.SizeCache
+}
+
+func (x *Handshake) Reset() {
+	*x = Handshake{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[22]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *Handshake) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*Handshake) ProtoMessage() {}
+
+func (x *Handshake) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[22]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use Handshake.ProtoReflect.Descriptor instead.
+func (*Handshake) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{22}
+}
+
+func (x *Handshake) GetProtocolVersion() uint32 {
+	if x != nil {
+		return x.ProtocolVersion
+	}
+	return 0
+}
+
+func (x *Handshake) GetMinProtocolVersion() uint32 {
+	if x != nil {
+		return x.MinProtocolVersion
+	}
+	return 0
+}
+
+func (x *Handshake) GetCapabilities() []Capability {
+	if x != nil {
+		return x.Capabilities
+	}
+	return nil
+}
+
+// Enable or disable emitting an event
+type SetEventEnabled struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// All events of the source except its parameters' events if unset
+	EventName     *string `protobuf:"bytes,1,opt,name=event_name,json=eventName,proto3,oneof" json:"event_name,omitempty"`
+	Enabled       bool    `protobuf:"varint,2,opt,name=enabled,proto3" json:"enabled,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SetEventEnabled) Reset() {
+	*x = SetEventEnabled{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[23]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SetEventEnabled) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SetEventEnabled) ProtoMessage() {}
+
+func (x *SetEventEnabled) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[23]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SetEventEnabled.ProtoReflect.Descriptor instead.
+func (*SetEventEnabled) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{23}
+}
+
+func (x *SetEventEnabled) GetEventName() string {
+	if x != nil && x.EventName != nil {
+		return *x.EventName
 	}
 	return ""
 }
 
-func (x *TraceMessage) GetMsg() isTraceMessage_Msg {
+func (x *SetEventEnabled) GetEnabled() bool {
 	if x != nil {
-		return x.Msg
+		return x.Enabled
+	}
+	return false
+}
+
+// Limit how often an event is emitted
+type SetSampleRate struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// All events of the source except its parameters' events if unset
+	EventName *string `protobuf:"bytes,1,opt,name=event_name,json=eventName,proto3,oneof" json:"event_name,omitempty"`
+	// Maximum events per second, unset removes the limit
+	MaxRateHz     *float64 `protobuf:"fixed64,2,opt,name=max_rate_hz,json=maxRateHz,proto3,oneof" json:"max_rate_hz,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SetSampleRate) Reset() {
+	*x = SetSampleRate{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[24]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SetSampleRate) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SetSampleRate) ProtoMessage() {}
+
+func (x *SetSampleRate) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[24]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SetSampleRate.ProtoReflect.Descriptor instead.
+func (*SetSampleRate) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{24}
+}
+
+func (x *SetSampleRate) GetEventName() string {
+	if x != nil && x.EventName != nil {
+		return *x.EventName
+	}
+	return ""
+}
+
+func (x *SetSampleRate) GetMaxRateHz() float64 {
+	if x != nil && x.MaxRateHz != nil {
+		return *x.MaxRateHz
+	}
+	return 0
+}
+
+// Emit the source's event schemas, value tables and action schemas again
+type ResendMetadata struct {
+	state         protoimpl.MessageState `protogen:"open.v1"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *ResendMetadata) Reset() {
+	*x = ResendMetadata{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[25]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *ResendMetadata) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*ResendMetadata) ProtoMessage() {}
+
+func (x *ResendMetadata) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[25]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use ResendMetadata.ProtoReflect.Descriptor instead.
+func (*ResendMetadata) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{25}
+}
+
+// Run one of the source's actions, which answers with a TraceActionResult
+type TraceActionCall struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// The UUIDv7 of the call
+	CallId     []byte                  `protobuf:"bytes,1,opt,name=call_id,json=callId,proto3" json:"call_id,omitempty"`
+	ActionName string                  `protobuf:"bytes,2,opt,name=action_name,json=actionName,proto3" json:"action_name,omitempty"`
+	Arguments  []*TraceEventFieldEntry `protobuf:"bytes,3,rep,name=arguments,proto3" json:"arguments,omitempty"`
+	// The segment of the source instance that runs the call, every instance with the source name if unset
+	SegmentId     []byte `protobuf:"bytes,4,opt,name=segment_id,json=segmentId,proto3,oneof" json:"segment_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceActionCall) Reset() {
+	*x = TraceActionCall{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[26]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceActionCall) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceActionCall) ProtoMessage() {}
+
+func (x *TraceActionCall) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[26]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceActionCall.ProtoReflect.Descriptor instead.
+func (*TraceActionCall) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{26}
+}
+
+func (x *TraceActionCall) GetCallId() []byte {
+	if x != nil {
+		return x.CallId
 	}
 	return nil
 }
 
-func (x *TraceMessage) GetSegmentStart() *TraceSegmentStart {
+func (x *TraceActionCall) GetActionName() string {
 	if x != nil {
-		if x, ok := x.Msg.(*TraceMessage_SegmentStart); ok {
-			return x.SegmentStart
+		return x.ActionName
+	}
+	return ""
+}
+
+func (x *TraceActionCall) GetArguments() []*TraceEventFieldEntry {
+	if x != nil {
+		return x.Arguments
+	}
+	return nil
+}
+
+func (x *TraceActionCall) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+// Set one of the source's parameters. Values with the wrong type or out of range are rejected and leave the parameter
+// unchanged.
+type SetParameter struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	Name  string                 `protobuf:"bytes,1,opt,name=name,proto3" json:"name,omitempty"`
+	Value *Value                 `protobuf:"bytes,2,opt,name=value,proto3" json:"value,omitempty"`
+	// The UUIDv7 of the request, answered by a TraceActionResult with the accepted value or why it was rejected. No
+	// answer is sent if unset.
+	CallId []byte `protobuf:"bytes,3,opt,name=call_id,json=callId,proto3,oneof" json:"call_id,omitempty"`
+	// The segment of the source instance to set the parameter of, every instance with the source name if unset
+	SegmentId     []byte `protobuf:"bytes,4,opt,name=segment_id,json=segmentId,proto3,oneof" json:"segment_id,omitempty"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *SetParameter) Reset() {
+	*x = SetParameter{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[27]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *SetParameter) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*SetParameter) ProtoMessage() {}
+
+func (x *SetParameter) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[27]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
 		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use SetParameter.ProtoReflect.Descriptor instead.
+func (*SetParameter) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{27}
+}
+
+func (x *SetParameter) GetName() string {
+	if x != nil {
+		return x.Name
+	}
+	return ""
+}
+
+func (x *SetParameter) GetValue() *Value {
+	if x != nil {
+		return x.Value
 	}
 	return nil
 }
 
-func (x *TraceMessage) GetSegmentEnd() *TraceSegmentEnd {
+func (x *SetParameter) GetCallId() []byte {
 	if x != nil {
-		if x, ok := x.Msg.(*TraceMessage_SegmentEnd); ok {
-			return x.SegmentEnd
+		return x.CallId
+	}
+	return nil
+}
+
+func (x *SetParameter) GetSegmentId() []byte {
+	if x != nil {
+		return x.SegmentId
+	}
+	return nil
+}
+
+// A command sent from a trace server back to publishers
+type TraceControl struct {
+	state protoimpl.MessageState `protogen:"open.v1"`
+	// All sources if unset
+	SourceName *string `protobuf:"bytes,1,opt,name=source_name,json=sourceName,proto3,oneof" json:"source_name,omitempty"`
+	// Types that are valid to be assigned to Cmd:
+	//
+	//	*TraceControl_SetEventEnabled
+	//	*TraceControl_SetSampleRate
+	//	*TraceControl_ResendMetadata
+	//	*TraceControl_CallAction
+	//	*TraceControl_SetParameter
+	Cmd           isTraceControl_Cmd `protobuf_oneof:"cmd"`
+	unknownFields protoimpl.UnknownFields
+	sizeCache     protoimpl.SizeCache
+}
+
+func (x *TraceControl) Reset() {
+	*x = TraceControl{}
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[28]
+	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+	ms.StoreMessageInfo(mi)
+}
+
+func (x *TraceControl) String() string {
+	return protoimpl.X.MessageStringOf(x)
+}
+
+func (*TraceControl) ProtoMessage() {}
+
+func (x *TraceControl) ProtoReflect() protoreflect.Message {
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[28]
+	if x != nil {
+		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
+		if ms.LoadMessageInfo() == nil {
+			ms.StoreMessageInfo(mi)
+		}
+		return ms
+	}
+	return mi.MessageOf(x)
+}
+
+// Deprecated: Use TraceControl.ProtoReflect.Descriptor instead.
+func (*TraceControl) Descriptor() ([]byte, []int) {
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{28}
+}
+
+func (x *TraceControl) GetSourceName() string {
+	if x != nil && x.SourceName != nil {
+		return *x.SourceName
+	}
+	return ""
+}
+
+func (x *TraceControl) GetCmd() isTraceControl_Cmd {
+	if x != nil {
+		return x.Cmd
+	}
+	return nil
+}
+
+func (x *TraceControl) GetSetEventEnabled() *SetEventEnabled {
+	if x != nil {
+		if x, ok := x.Cmd.(*TraceControl_SetEventEnabled); ok {
+			return x.SetEventEnabled
 		}
 	}
 	return nil
 }
 
-func (x *TraceMessage) GetEventSchema() *TraceEventSchema {
+func (x *TraceControl) GetSetSampleRate() *SetSampleRate {
 	if x != nil {
-		if x, ok := x.Msg.(*TraceMessage_EventSchema); ok {
-			return x.EventSchema
+		if x, ok := x.Cmd.(*TraceControl_SetSampleRate); ok {
+			return x.SetSampleRate
 		}
 	}
 	return nil
 }
 
-func (x *TraceMessage) GetEventFieldNamedValues() *TraceEventFieldNamedValues {
+func (x *TraceControl) GetResendMetadata() *ResendMetadata {
 	if x != nil {
-		if x, ok := x.Msg.(*TraceMessage_EventFieldNamedValues); ok {
-			return x.EventFieldNamedValues
+		if x, ok := x.Cmd.(*TraceControl_ResendMetadata); ok {
+			return x.ResendMetadata
 		}
 	}
 	return nil
 }
 
-func (x *TraceMessage) GetEvent() *TraceEvent {
+func (x *TraceControl) GetCallAction() *TraceActionCall {
 	if x != nil {
-		if x, ok := x.Msg.(*TraceMessage_Event); ok {
-			return x.Event
+		if x, ok := x.Cmd.(*TraceControl_CallAction); ok {
+			return x.CallAction
 		}
 	}
 	return nil
 }
 
-type isTraceMessage_Msg interface {
-	isTraceMessage_Msg()
+func (x *TraceControl) GetSetParameter() *SetParameter {
+	if x != nil {
+		if x, ok := x.Cmd.(*TraceControl_SetParameter); ok {
+			return x.SetParameter
+		}
+	}
+	return nil
 }
 
-type TraceMessage_SegmentStart struct {
-	SegmentStart *TraceSegmentStart `protobuf:"bytes,2,opt,name=segment_start,json=segmentStart,proto3,oneof"`
+type isTraceControl_Cmd interface {
+	isTraceControl_Cmd()
 }
 
-type TraceMessage_SegmentEnd struct {
-	SegmentEnd *TraceSegmentEnd `protobuf:"bytes,3,opt,name=segment_end,json=segmentEnd,proto3,oneof"`
+type TraceControl_SetEventEnabled struct {
+	SetEventEnabled *SetEventEnabled `protobuf:"bytes,2,opt,name=set_event_enabled,json=setEventEnabled,proto3,oneof"`
 }
 
-type TraceMessage_EventSchema struct {
-	EventSchema *TraceEventSchema `protobuf:"bytes,4,opt,name=event_schema,json=eventSchema,proto3,oneof"`
+type TraceControl_SetSampleRate struct {
+	SetSampleRate *SetSampleRate `protobuf:"bytes,3,opt,name=set_sample_rate,json=setSampleRate,proto3,oneof"`
 }
 
-type TraceMessage_EventFieldNamedValues struct {
-	EventFieldNamedValues *TraceEventFieldNamedValues `protobuf:"bytes,5,opt,name=event_field_named_values,json=eventFieldNamedValues,proto3,oneof"`
+type TraceControl_ResendMetadata struct {
+	ResendMetadata *ResendMetadata `protobuf:"bytes,4,opt,name=resend_metadata,json=resendMetadata,proto3,oneof"`
 }
 
-type TraceMessage_Event struct {
-	Event *TraceEvent `protobuf:"bytes,6,opt,name=event,proto3,oneof"`
+type TraceControl_CallAction struct {
+	CallAction *TraceActionCall `protobuf:"bytes,5,opt,name=call_action,json=callAction,proto3,oneof"`
 }
 
-func (*TraceMessage_SegmentStart) isTraceMessage_Msg() {}
+type TraceControl_SetParameter struct {
+	SetParameter *SetParameter `protobuf:"bytes,6,opt,name=set_parameter,json=setParameter,proto3,oneof"`
+}
 
-func (*TraceMessage_SegmentEnd) isTraceMessage_Msg() {}
+func (*TraceControl_SetEventEnabled) isTraceControl_Cmd() {}
 
-func (*TraceMessage_EventSchema) isTraceMessage_Msg() {}
+func (*TraceControl_SetSampleRate) isTraceControl_Cmd() {}
 
-func (*TraceMessage_EventFieldNamedValues) isTraceMessage_Msg() {}
+func (*TraceControl_ResendMetadata) isTraceControl_Cmd() {}
 
-func (*TraceMessage_Event) isTraceMessage_Msg() {}
+func (*TraceControl_CallAction) isTraceControl_Cmd() {}
+
+func (*TraceControl_SetParameter) isTraceControl_Cmd() {}
 
 type TraceMessageBatch struct {
 	state         protoimpl.MessageState `protogen:"open.v1"`
@@ -973,7 +2793,7 @@ type TraceMessageBatch struct {
 
 func (x *TraceMessageBatch) Reset() {
 	*x = TraceMessageBatch{}
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[10]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[29]
 	ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 	ms.StoreMessageInfo(mi)
 }
@@ -985,7 +2805,7 @@ func (x *TraceMessageBatch) String() string {
 func (*TraceMessageBatch) ProtoMessage() {}
 
 func (x *TraceMessageBatch) ProtoReflect() protoreflect.Message {
-	mi := &file_zeloscloud_trace_trace_proto_msgTypes[10]
+	mi := &file_zeloscloud_trace_trace_proto_msgTypes[29]
 	if x != nil {
 		ms := protoimpl.X.MessageStateOf(protoimpl.Pointer(x))
 		if ms.LoadMessageInfo() == nil {
@@ -998,7 +2818,7 @@ func (x *TraceMessageBatch) ProtoReflect() protoreflect.Message {
 
 // Deprecated: Use TraceMessageBatch.ProtoReflect.Descriptor instead.
 func (*TraceMessageBatch) Descriptor() ([]byte, []int) {
-	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{10}
+	return file_zeloscloud_trace_trace_proto_rawDescGZIP(), []int{29}
 }
 
 func (x *TraceMessageBatch) GetMessages() []*TraceMessage {
@@ -1029,21 +2849,41 @@ const file_zeloscloud_trace_trace_proto_rawDesc = "" +
 	"\x06binary\x18\f \x01(\fH\x00R\x06binary\x12\x18\n" +
 	"\x06string\x18\r \x01(\tH\x00R\x06string\x12\x14\n" +
 	"\x04bool\x18\x0e \x01(\bH\x00R\x04boolB\a\n" +
-	"\x05value\"\x88\x01\n" +
+	"\x05value\"\xe7\x01\n" +
+	"\fFieldDisplay\x12%\n" +
+	"\vdescription\x18\x01 \x01(\tH\x00R\vdescription\x88\x01\x01\x12\x15\n" +
+	"\x03min\x18\x02 \x01(\x01H\x01R\x03min\x88\x01\x01\x12\x15\n" +
+	"\x03max\x18\x03 \x01(\x01H\x02R\x03max\x88\x01\x01\x12!\n" +
+	"\tprecision\x18\x04 \x01(\rH\x03R\tprecision\x88\x01\x01\x121\n" +
+	"\x04hint\x18\x05 \x01(\x0e2\x1d.zeloscloud.trace.DisplayHintR\x04hintB\x0e\n" +
+	"\f_descriptionB\x06\n" +
+	"\x04_minB\x06\n" +
+	"\x04_maxB\f\n" +
+	"\n" +
+	"_precision\"\xf3\x01\n" +
 	"\x17TraceEventFieldMetadata\x12\x12\n" +
 	"\x04name\x18\x01 \x01(\tR\x04name\x127\n" +
 	"\tdata_type\x18\x02 \x01(\x0e2\x1a.zeloscloud.trace.DataTypeR\bdataType\x12\x17\n" +
-	"\x04unit\x18\x03 \x01(\tH\x00R\x04unit\x88\x01\x01B\a\n" +
-	"\x05_unit\"M\n" +
+	"\x04unit\x18\x03 \x01(\tH\x00R\x04unit\x88\x01\x01\x12/\n" +
+	"\x04kind\x18\x04 \x01(\x0e2\x1b.zeloscloud.trace.FieldKindR\x04kind\x128\n" +
+	"\adisplay\x18\x05 \x01(\v2\x1e.zeloscloud.trace.FieldDisplayR\adisplayB\a\n" +
+	"\x05_unit\"\xe1\x01\n" +
 	"\x11TraceSegmentStart\x12\x17\n" +
 	"\atime_ns\x18\x01 \x01(\x10R\x06timeNs\x12\x1f\n" +
 	"\vsource_name\x18\x02 \x01(\tR\n" +
-	"sourceName\"*\n" +
+	"sourceName\x12S\n" +
+	"\n" +
+	"attributes\x18\x03 \x03(\v23.zeloscloud.trace.TraceSegmentStart.AttributesEntryR\n" +
+	"attributes\x1a=\n" +
+	"\x0fAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\"*\n" +
 	"\x0fTraceSegmentEnd\x12\x17\n" +
-	"\atime_ns\x18\x01 \x01(\x10R\x06timeNs\"i\n" +
+	"\atime_ns\x18\x01 \x01(\x10R\x06timeNs\"\x83\x01\n" +
 	"\x10TraceEventSchema\x12\x12\n" +
 	"\x04name\x18\x01 \x01(\tR\x04name\x12A\n" +
-	"\x06fields\x18\x02 \x03(\v2).zeloscloud.trace.TraceEventFieldMetadataR\x06fields\"d\n" +
+	"\x06fields\x18\x02 \x03(\v2).zeloscloud.trace.TraceEventFieldMetadataR\x06fields\x12\x18\n" +
+	"\aversion\x18\x03 \x01(\rR\aversion\"d\n" +
 	"\x1fTraceEventFieldNamedValuesEntry\x12\x12\n" +
 	"\x04name\x18\x01 \x01(\tR\x04name\x12-\n" +
 	"\x05value\x18\x02 \x01(\v2\x17.zeloscloud.trace.ValueR\x05value\"\xa5\x01\n" +
@@ -1060,7 +2900,88 @@ const file_zeloscloud_trace_trace_proto_rawDesc = "" +
 	"TraceEvent\x12\x17\n" +
 	"\atime_ns\x18\x01 \x01(\x10R\x06timeNs\x12\x12\n" +
 	"\x04name\x18\x02 \x01(\tR\x04name\x12>\n" +
-	"\x06fields\x18\x03 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06fields\"\xcf\x03\n" +
+	"\x06fields\x18\x03 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06fields\"\xb7\x01\n" +
+	"\x11TraceActionSchema\x12\x12\n" +
+	"\x04name\x18\x01 \x01(\tR\x04name\x12I\n" +
+	"\n" +
+	"parameters\x18\x02 \x03(\v2).zeloscloud.trace.TraceEventFieldMetadataR\n" +
+	"parameters\x12C\n" +
+	"\areturns\x18\x03 \x03(\v2).zeloscloud.trace.TraceEventFieldMetadataR\areturns\"\xb2\x01\n" +
+	"\x11TraceActionResult\x12\x17\n" +
+	"\acall_id\x18\x01 \x01(\fR\x06callId\x12\x1f\n" +
+	"\vaction_name\x18\x02 \x01(\tR\n" +
+	"actionName\x12>\n" +
+	"\x06values\x18\x03 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06values\x12\x19\n" +
+	"\x05error\x18\x04 \x01(\tH\x00R\x05error\x88\x01\x01B\b\n" +
+	"\x06_error\"\xa0\x02\n" +
+	"\x10TraceEventColumn\x12\x1f\n" +
+	"\vfield_index\x18\x01 \x01(\rR\n" +
+	"fieldIndex\x12\x18\n" +
+	"\apresent\x18\x02 \x03(\rR\apresent\x12\x12\n" +
+	"\x04ints\x18\x03 \x03(\x12R\x04ints\x12\x14\n" +
+	"\x05uints\x18\x04 \x03(\x04R\x05uints\x12\x1a\n" +
+	"\bfloat32s\x18\x05 \x03(\x02R\bfloat32s\x12\x1a\n" +
+	"\bfloat64s\x18\x06 \x03(\x01R\bfloat64s\x12#\n" +
+	"\rtimestamps_ns\x18\a \x03(\x10R\ftimestampsNs\x12\x1a\n" +
+	"\bbinaries\x18\b \x03(\fR\bbinaries\x12\x18\n" +
+	"\astrings\x18\t \x03(\tR\astrings\x12\x14\n" +
+	"\x05bools\x18\n" +
+	" \x03(\bR\x05bools\"|\n" +
+	"\x0fTraceEventBatch\x12\x12\n" +
+	"\x04name\x18\x01 \x01(\tR\x04name\x12\x17\n" +
+	"\atime_ns\x18\x02 \x03(\x10R\x06timeNs\x12<\n" +
+	"\acolumns\x18\x03 \x03(\v2\".zeloscloud.trace.TraceEventColumnR\acolumns\"\x98\x03\n" +
+	"\x11TraceSessionStart\x12\x1d\n" +
+	"\n" +
+	"session_id\x18\x01 \x01(\fR\tsessionId\x12\x17\n" +
+	"\atime_ns\x18\x02 \x01(\x10R\x06timeNs\x12\x12\n" +
+	"\x04name\x18\x03 \x01(\tR\x04name\x12S\n" +
+	"\n" +
+	"attributes\x18\x04 \x03(\v23.zeloscloud.trace.TraceSessionStart.AttributesEntryR\n" +
+	"attributes\x12`\n" +
+	"\x0fjoin_attributes\x18\x05 \x03(\v27.zeloscloud.trace.TraceSessionStart.JoinAttributesEntryR\x0ejoinAttributes\x1a=\n" +
+	"\x0fAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\x1aA\n" +
+	"\x13JoinAttributesEntry\x12\x10\n" +
+	"\x03key\x18\x01 \x01(\tR\x03key\x12\x14\n" +
+	"\x05value\x18\x02 \x01(\tR\x05value:\x028\x01\"I\n" +
+	"\x0fTraceSessionEnd\x12\x1d\n" +
+	"\n" +
+	"session_id\x18\x01 \x01(\fR\tsessionId\x12\x17\n" +
+	"\atime_ns\x18\x02 \x01(\x10R\x06timeNs\"1\n" +
+	"\x10TraceSessionJoin\x12\x1d\n" +
+	"\n" +
+	"session_id\x18\x01 \x01(\fR\tsessionId\"\xca\x01\n" +
+	"\x0fTraceAnnotation\x12#\n" +
+	"\rannotation_id\x18\x01 \x01(\fR\fannotationId\x12\"\n" +
+	"\rstart_time_ns\x18\x02 \x01(\x10R\vstartTimeNs\x12\x1e\n" +
+	"\vend_time_ns\x18\x03 \x01(\x10R\tendTimeNs\x12\x12\n" +
+	"\x04text\x18\x04 \x01(\tR\x04text\x12\x12\n" +
+	"\x04tags\x18\x05 \x03(\tR\x04tags\x12\x1b\n" +
+	"\x06author\x18\x06 \x01(\tH\x00R\x06author\x88\x01\x01B\t\n" +
+	"\a_author\"\xc7\x01\n" +
+	"\bTraceLog\x12\x17\n" +
+	"\atime_ns\x18\x01 \x01(\x10R\x06timeNs\x120\n" +
+	"\x05level\x18\x02 \x01(\x0e2\x1a.zeloscloud.trace.LogLevelR\x05level\x12\x16\n" +
+	"\x06target\x18\x03 \x01(\tR\x06target\x12\x18\n" +
+	"\amessage\x18\x04 \x01(\tR\amessage\x12>\n" +
+	"\x06fields\x18\x05 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06fields\"\xd2\x01\n" +
+	"\x12TraceIntervalBegin\x12\x1f\n" +
+	"\vinterval_id\x18\x01 \x01(\fR\n" +
+	"intervalId\x12 \n" +
+	"\tparent_id\x18\x02 \x01(\fH\x00R\bparentId\x88\x01\x01\x12\x17\n" +
+	"\atime_ns\x18\x03 \x01(\x10R\x06timeNs\x12\x12\n" +
+	"\x04name\x18\x04 \x01(\tR\x04name\x12>\n" +
+	"\x06fields\x18\x05 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06fieldsB\f\n" +
+	"\n" +
+	"_parent_id\"\xa0\x01\n" +
+	"\x10TraceIntervalEnd\x12\x1f\n" +
+	"\vinterval_id\x18\x01 \x01(\fR\n" +
+	"intervalId\x12\x17\n" +
+	"\atime_ns\x18\x02 \x01(\x10R\x06timeNs\x12\x12\n" +
+	"\x04name\x18\x03 \x01(\tR\x04name\x12>\n" +
+	"\x06fields\x18\x04 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\x06fields\"\x95\t\n" +
 	"\fTraceMessage\x12\x1d\n" +
 	"\n" +
 	"segment_id\x18\x01 \x01(\fR\tsegmentId\x12\x1f\n" +
@@ -1071,8 +2992,67 @@ const file_zeloscloud_trace_trace_proto_rawDesc = "" +
 	"segmentEnd\x12G\n" +
 	"\fevent_schema\x18\x04 \x01(\v2\".zeloscloud.trace.TraceEventSchemaH\x00R\veventSchema\x12g\n" +
 	"\x18event_field_named_values\x18\x05 \x01(\v2,.zeloscloud.trace.TraceEventFieldNamedValuesH\x00R\x15eventFieldNamedValues\x124\n" +
-	"\x05event\x18\x06 \x01(\v2\x1c.zeloscloud.trace.TraceEventH\x00R\x05eventB\x05\n" +
-	"\x03msg\"O\n" +
+	"\x05event\x18\x06 \x01(\v2\x1c.zeloscloud.trace.TraceEventH\x00R\x05event\x12D\n" +
+	"\vevent_batch\x18\b \x01(\v2!.zeloscloud.trace.TraceEventBatchH\x00R\n" +
+	"eventBatch\x12J\n" +
+	"\raction_schema\x18\t \x01(\v2#.zeloscloud.trace.TraceActionSchemaH\x00R\factionSchema\x12J\n" +
+	"\raction_result\x18\n" +
+	" \x01(\v2#.zeloscloud.trace.TraceActionResultH\x00R\factionResult\x12J\n" +
+	"\rsession_start\x18\v \x01(\v2#.zeloscloud.trace.TraceSessionStartH\x00R\fsessionStart\x12D\n" +
+	"\vsession_end\x18\f \x01(\v2!.zeloscloud.trace.TraceSessionEndH\x00R\n" +
+	"sessionEnd\x12G\n" +
+	"\fsession_join\x18\r \x01(\v2\".zeloscloud.trace.TraceSessionJoinH\x00R\vsessionJoin\x12C\n" +
+	"\n" +
+	"annotation\x18\x0e \x01(\v2!.zeloscloud.trace.TraceAnnotationH\x00R\n" +
+	"annotation\x12.\n" +
+	"\x03log\x18\x0f \x01(\v2\x1a.zeloscloud.trace.TraceLogH\x00R\x03log\x12M\n" +
+	"\x0einterval_begin\x18\x10 \x01(\v2$.zeloscloud.trace.TraceIntervalBeginH\x00R\rintervalBegin\x12G\n" +
+	"\finterval_end\x18\x11 \x01(\v2\".zeloscloud.trace.TraceIntervalEndH\x00R\vintervalEndB\x05\n" +
+	"\x03msg\"\xaa\x01\n" +
+	"\tHandshake\x12)\n" +
+	"\x10protocol_version\x18\x01 \x01(\rR\x0fprotocolVersion\x120\n" +
+	"\x14min_protocol_version\x18\x02 \x01(\rR\x12minProtocolVersion\x12@\n" +
+	"\fcapabilities\x18\x03 \x03(\x0e2\x1c.zeloscloud.trace.CapabilityR\fcapabilities\"^\n" +
+	"\x0fSetEventEnabled\x12\"\n" +
+	"\n" +
+	"event_name\x18\x01 \x01(\tH\x00R\teventName\x88\x01\x01\x12\x18\n" +
+	"\aenabled\x18\x02 \x01(\bR\aenabledB\r\n" +
+	"\v_event_name\"w\n" +
+	"\rSetSampleRate\x12\"\n" +
+	"\n" +
+	"event_name\x18\x01 \x01(\tH\x00R\teventName\x88\x01\x01\x12#\n" +
+	"\vmax_rate_hz\x18\x02 \x01(\x01H\x01R\tmaxRateHz\x88\x01\x01B\r\n" +
+	"\v_event_nameB\x0e\n" +
+	"\f_max_rate_hz\"\x10\n" +
+	"\x0eResendMetadata\"\xc4\x01\n" +
+	"\x0fTraceActionCall\x12\x17\n" +
+	"\acall_id\x18\x01 \x01(\fR\x06callId\x12\x1f\n" +
+	"\vaction_name\x18\x02 \x01(\tR\n" +
+	"actionName\x12D\n" +
+	"\targuments\x18\x03 \x03(\v2&.zeloscloud.trace.TraceEventFieldEntryR\targuments\x12\"\n" +
+	"\n" +
+	"segment_id\x18\x04 \x01(\fH\x00R\tsegmentId\x88\x01\x01B\r\n" +
+	"\v_segment_id\"\xae\x01\n" +
+	"\fSetParameter\x12\x12\n" +
+	"\x04name\x18\x01 \x01(\tR\x04name\x12-\n" +
+	"\x05value\x18\x02 \x01(\v2\x17.zeloscloud.trace.ValueR\x05value\x12\x1c\n" +
+	"\acall_id\x18\x03 \x01(\fH\x00R\x06callId\x88\x01\x01\x12\"\n" +
+	"\n" +
+	"segment_id\x18\x04 \x01(\fH\x01R\tsegmentId\x88\x01\x01B\n" +
+	"\n" +
+	"\b_call_idB\r\n" +
+	"\v_segment_id\"\xc1\x03\n" +
+	"\fTraceControl\x12$\n" +
+	"\vsource_name\x18\x01 \x01(\tH\x01R\n" +
+	"sourceName\x88\x01\x01\x12O\n" +
+	"\x11set_event_enabled\x18\x02 \x01(\v2!.zeloscloud.trace.SetEventEnabledH\x00R\x0fsetEventEnabled\x12I\n" +
+	"\x0fset_sample_rate\x18\x03 \x01(\v2\x1f.zeloscloud.trace.SetSampleRateH\x00R\rsetSampleRate\x12K\n" +
+	"\x0fresend_metadata\x18\x04 \x01(\v2 .zeloscloud.trace.ResendMetadataH\x00R\x0eresendMetadata\x12D\n" +
+	"\vcall_action\x18\x05 \x01(\v2!.zeloscloud.trace.TraceActionCallH\x00R\n" +
+	"callAction\x12E\n" +
+	"\rset_parameter\x18\x06 \x01(\v2\x1e.zeloscloud.trace.SetParameterH\x00R\fsetParameterB\x05\n" +
+	"\x03cmdB\x0e\n" +
+	"\f_source_name\"O\n" +
 	"\x11TraceMessageBatch\x12:\n" +
 	"\bmessages\x18\x01 \x03(\v2\x1e.zeloscloud.trace.TraceMessageR\bmessages*\xd9\x02\n" +
 	"\bDataType\x12\x19\n" +
@@ -1091,7 +3071,34 @@ const file_zeloscloud_trace_trace_proto_rawDesc = "" +
 	"\x16DATA_TYPE_TIMESTAMP_NS\x10\v\x12\x14\n" +
 	"\x10DATA_TYPE_BINARY\x10\f\x12\x14\n" +
 	"\x10DATA_TYPE_STRING\x10\r\x12\x12\n" +
-	"\x0eDATA_TYPE_BOOL\x10\x0eb\x06proto3"
+	"\x0eDATA_TYPE_BOOL\x10\x0e*\x81\x01\n" +
+	"\tFieldKind\x12\x1a\n" +
+	"\x16FIELD_KIND_UNSPECIFIED\x10\x00\x12\x14\n" +
+	"\x10FIELD_KIND_GAUGE\x10\x01\x12\x16\n" +
+	"\x12FIELD_KIND_COUNTER\x10\x02\x12\x14\n" +
+	"\x10FIELD_KIND_DELTA\x10\x03\x12\x14\n" +
+	"\x10FIELD_KIND_STATE\x10\x04*\xa0\x01\n" +
+	"\vDisplayHint\x12\x1c\n" +
+	"\x18DISPLAY_HINT_UNSPECIFIED\x10\x00\x12\x15\n" +
+	"\x11DISPLAY_HINT_LINE\x10\x01\x12\x15\n" +
+	"\x11DISPLAY_HINT_STEP\x10\x02\x12\x18\n" +
+	"\x14DISPLAY_HINT_SCATTER\x10\x03\x12\x14\n" +
+	"\x10DISPLAY_HINT_BAR\x10\x04\x12\x15\n" +
+	"\x11DISPLAY_HINT_TEXT\x10\x05*\x8c\x01\n" +
+	"\bLogLevel\x12\x19\n" +
+	"\x15LOG_LEVEL_UNSPECIFIED\x10\x00\x12\x13\n" +
+	"\x0fLOG_LEVEL_TRACE\x10\x01\x12\x13\n" +
+	"\x0fLOG_LEVEL_DEBUG\x10\x02\x12\x12\n" +
+	"\x0eLOG_LEVEL_INFO\x10\x03\x12\x12\n" +
+	"\x0eLOG_LEVEL_WARN\x10\x04\x12\x13\n" +
+	"\x0fLOG_LEVEL_ERROR\x10\x05*\x86\x01\n" +
+	"\n" +
+	"Capability\x12\x1a\n" +
+	"\x16CAPABILITY_UNSPECIFIED\x10\x00\x12\x13\n" +
+	"\x0fCAPABILITY_GZIP\x10\x01\x12\x1a\n" +
+	"\x16CAPABILITY_EVENT_BATCH\x10\x02\x12\x13\n" +
+	"\x0fCAPABILITY_ACKS\x10\x03\x12\x16\n" +
+	"\x12CAPABILITY_CONTROL\x10\x04b\x06proto3"
 
 var (
 	file_zeloscloud_trace_trace_proto_rawDescOnce sync.Once
@@ -1105,40 +3112,98 @@ func file_zeloscloud_trace_trace_proto_rawDescGZIP() []byte {
 	return file_zeloscloud_trace_trace_proto_rawDescData
 }
 
-var file_zeloscloud_trace_trace_proto_enumTypes = make([]protoimpl.EnumInfo, 1)
-var file_zeloscloud_trace_trace_proto_msgTypes = make([]protoimpl.MessageInfo, 11)
+var file_zeloscloud_trace_trace_proto_enumTypes = make([]protoimpl.EnumInfo, 5)
+var file_zeloscloud_trace_trace_proto_msgTypes = make([]protoimpl.MessageInfo, 33)
 var file_zeloscloud_trace_trace_proto_goTypes = []any{
 	(DataType)(0),                           // 0: zeloscloud.trace.DataType
-	(*Value)(nil),                           // 1: zeloscloud.trace.Value
-	(*TraceEventFieldMetadata)(nil),         // 2: zeloscloud.trace.TraceEventFieldMetadata
-	(*TraceSegmentStart)(nil),               // 3: zeloscloud.trace.TraceSegmentStart
-	(*TraceSegmentEnd)(nil),                 // 4: zeloscloud.trace.TraceSegmentEnd
-	(*TraceEventSchema)(nil),                // 5: zeloscloud.trace.TraceEventSchema
-	(*TraceEventFieldNamedValuesEntry)(nil), // 6: zeloscloud.trace.TraceEventFieldNamedValuesEntry
-	(*TraceEventFieldNamedValues)(nil),      // 7: zeloscloud.trace.TraceEventFieldNamedValues
-	(*TraceEventFieldEntry)(nil),            // 8: zeloscloud.trace.TraceEventFieldEntry
-	(*TraceEvent)(nil),                      // 9: zeloscloud.trace.TraceEvent
-	(*TraceMessage)(nil),                    // 10: zeloscloud.trace.TraceMessage
-	(*TraceMessageBatch)(nil),               // 11: zeloscloud.trace.TraceMessageBatch
+	(FieldKind)(0),                          // 1: zeloscloud.trace.FieldKind
+	(DisplayHint)(0),                        // 2: zeloscloud.trace.DisplayHint
+	(LogLevel)(0),                           // 3: zeloscloud.trace.LogLevel
+	(Capability)(0),                         // 4: zeloscloud.trace.Capability
+	(*Value)(nil),                           // 5: zeloscloud.trace.Value
+	(*FieldDisplay)(nil),                    // 6: zeloscloud.trace.FieldDisplay
+	(*TraceEventFieldMetadata)(nil),         // 7: zeloscloud.trace.TraceEventFieldMetadata
+	(*TraceSegmentStart)(nil),               // 8: zeloscloud.trace.TraceSegmentStart
+	(*TraceSegmentEnd)(nil),                 // 9: zeloscloud.trace.TraceSegmentEnd
+	(*TraceEventSchema)(nil),                // 10: zeloscloud.trace.TraceEventSchema
+	(*TraceEventFieldNamedValuesEntry)(nil), // 11: zeloscloud.trace.TraceEventFieldNamedValuesEntry
+	(*TraceEventFieldNamedValues)(nil),      // 12: zeloscloud.trace.TraceEventFieldNamedValues
+	(*TraceEventFieldEntry)(nil),            // 13: zeloscloud.trace.TraceEventFieldEntry
+	(*TraceEvent)(nil),                      // 14: zeloscloud.trace.TraceEvent
+	(*TraceActionSchema)(nil),               // 15: zeloscloud.trace.TraceActionSchema
+	(*TraceActionResult)(nil),               // 16: zeloscloud.trace.TraceActionResult
+	(*TraceEventColumn)(nil),                // 17: zeloscloud.trace.TraceEventColumn
+	(*TraceEventBatch)(nil),                 // 18: zeloscloud.trace.TraceEventBatch
+	(*TraceSessionStart)(nil),               // 19: zeloscloud.trace.TraceSessionStart
+	(*TraceSessionEnd)(nil),                 // 20: zeloscloud.trace.TraceSessionEnd
+	(*TraceSessionJoin)(nil),                // 21: zeloscloud.trace.TraceSessionJoin
+	(*TraceAnnotation)(nil),                 // 22: zeloscloud.trace.TraceAnnotation
+	(*TraceLog)(nil),                        // 23: zeloscloud.trace.TraceLog
+	(*TraceIntervalBegin)(nil),              // 24: zeloscloud.trace.TraceIntervalBegin
+	(*TraceIntervalEnd)(nil),                // 25: zeloscloud.trace.TraceIntervalEnd
+	(*TraceMessage)(nil),                    // 26: zeloscloud.trace.TraceMessage
+	(*Handshake)(nil),                       // 27: zeloscloud.trace.Handshake
+	(*SetEventEnabled)(nil),                 // 28: zeloscloud.trace.SetEventEnabled
+	(*SetSampleRate)(nil),                   // 29: zeloscloud.trace.SetSampleRate
+	(*ResendMetadata)(nil),                  // 30: zeloscloud.trace.ResendMetadata
+	(*TraceActionCall)(nil),                 // 31: zeloscloud.trace.TraceActionCall
+	(*SetParameter)(nil),                    // 32: zeloscloud.trace.SetParameter
+	(*TraceControl)(nil),                    // 33: zeloscloud.trace.TraceControl
+	(*TraceMessageBatch)(nil),               // 34: zeloscloud.trace.TraceMessageBatch
+	nil,                                     // 35: zeloscloud.trace.TraceSegmentStart.AttributesEntry
+	nil,                                     // 36: zeloscloud.trace.TraceSessionStart.AttributesEntry
+	nil,                                     // 37: zeloscloud.trace.TraceSessionStart.JoinAttributesEntry
 }
 var file_zeloscloud_trace_trace_proto_depIdxs = []int32{
-	0,  // 0: zeloscloud.trace.TraceEventFieldMetadata.data_type:type_name -> zeloscloud.trace.DataType
-	2,  // 1: zeloscloud.trace.TraceEventSchema.fields:type_name -> zeloscloud.trace.TraceEventFieldMetadata
-	1,  // 2: zeloscloud.trace.TraceEventFieldNamedValuesEntry.value:type_name -> zeloscloud.trace.Value
-	6,  // 3: zeloscloud.trace.TraceEventFieldNamedValues.values:type_name -> zeloscloud.trace.TraceEventFieldNamedValuesEntry
-	1,  // 4: zeloscloud.trace.TraceEventFieldEntry.value:type_name -> zeloscloud.trace.Value
-	8,  // 5: zeloscloud.trace.TraceEvent.fields:type_name -> zeloscloud.trace.TraceEventFieldEntry
-	3,  // 6: zeloscloud.trace.TraceMessage.segment_start:type_name -> zeloscloud.trace.TraceSegmentStart
-	4,  // 7: zeloscloud.trace.TraceMessage.segment_end:type_name -> zeloscloud.trace.TraceSegmentEnd
-	5,  // 8: zeloscloud.trace.TraceMessage.event_schema:type_name -> zeloscloud.trace.TraceEventSchema
-	7,  // 9: zeloscloud.trace.TraceMessage.event_field_named_values:type_name -> zeloscloud.trace.TraceEventFieldNamedValues
-	9,  // 10: zeloscloud.trace.TraceMessage.event:type_name -> zeloscloud.trace.TraceEvent
-	10, // 11: zeloscloud.trace.TraceMessageBatch.messages:type_name -> zeloscloud.trace.TraceMessage
-	12, // [12:12] is the sub-list for method output_type
-	12, // [12:12] is the sub-list for method input_type
-	12, // [12:12] is the sub-list for extension type_name
-	12, // [12:12] is the sub-list for extension extendee
-	0,  // [0:12] is the sub-list for field type_name
+	2,  // 0: zeloscloud.trace.FieldDisplay.hint:type_name -> zeloscloud.trace.DisplayHint
+	0,  // 1: zeloscloud.trace.TraceEventFieldMetadata.data_type:type_name -> zeloscloud.trace.DataType
+	1,  // 2: zeloscloud.trace.TraceEventFieldMetadata.kind:type_name -> zeloscloud.trace.FieldKind
+	6,  // 3: zeloscloud.trace.TraceEventFieldMetadata.display:type_name -> zeloscloud.trace.FieldDisplay
+	35, // 4: zeloscloud.trace.TraceSegmentStart.attributes:type_name -> zeloscloud.trace.TraceSegmentStart.AttributesEntry
+	7,  // 5: zeloscloud.trace.TraceEventSchema.fields:type_name -> zeloscloud.trace.TraceEventFieldMetadata
+	5,  // 6: zeloscloud.trace.TraceEventFieldNamedValuesEntry.value:type_name -> zeloscloud.trace.Value
+	11, // 7: zeloscloud.trace.TraceEventFieldNamedValues.values:type_name -> zeloscloud.trace.TraceEventFieldNamedValuesEntry
+	5,  // 8: zeloscloud.trace.TraceEventFieldEntry.value:type_name -> zeloscloud.trace.Value
+	13, // 9: zeloscloud.trace.TraceEvent.fields:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	7,  // 10: zeloscloud.trace.TraceActionSchema.parameters:type_name -> zeloscloud.trace.TraceEventFieldMetadata
+	7,  // 11: zeloscloud.trace.TraceActionSchema.returns:type_name -> zeloscloud.trace.TraceEventFieldMetadata
+	13, // 12: zeloscloud.trace.TraceActionResult.values:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	17, // 13: zeloscloud.trace.TraceEventBatch.columns:type_name -> zeloscloud.trace.TraceEventColumn
+	36, // 14: zeloscloud.trace.TraceSessionStart.attributes:type_name -> zeloscloud.trace.TraceSessionStart.AttributesEntry
+	37, // 15: zeloscloud.trace.TraceSessionStart.join_attributes:type_name -> zeloscloud.trace.TraceSessionStart.JoinAttributesEntry
+	3,  // 16: zeloscloud.trace.TraceLog.level:type_name -> zeloscloud.trace.LogLevel
+	13, // 17: zeloscloud.trace.TraceLog.fields:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	13, // 18: zeloscloud.trace.TraceIntervalBegin.fields:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	13, // 19: zeloscloud.trace.TraceIntervalEnd.fields:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	8,  // 20: zeloscloud.trace.TraceMessage.segment_start:type_name -> zeloscloud.trace.TraceSegmentStart
+	9,  // 21: zeloscloud.trace.TraceMessage.segment_end:type_name -> zeloscloud.trace.TraceSegmentEnd
+	10, // 22: zeloscloud.trace.TraceMessage.event_schema:type_name -> zeloscloud.trace.TraceEventSchema
+	12, // 23: zeloscloud.trace.TraceMessage.event_field_named_values:type_name -> zeloscloud.trace.TraceEventFieldNamedValues
+	14, // 24: zeloscloud.trace.TraceMessage.event:type_name -> zeloscloud.trace.TraceEvent
+	18, // 25: zeloscloud.trace.TraceMessage.event_batch:type_name -> zeloscloud.trace.TraceEventBatch
+	15, // 26: zeloscloud.trace.TraceMessage.action_schema:type_name -> zeloscloud.trace.TraceActionSchema
+	16, // 27: zeloscloud.trace.TraceMessage.action_result:type_name -> zeloscloud.trace.TraceActionResult
+	19, // 28: zeloscloud.trace.TraceMessage.session_start:type_name -> zeloscloud.trace.TraceSessionStart
+	20, // 29: zeloscloud.trace.TraceMessage.session_end:type_name -> zeloscloud.trace.TraceSessionEnd
+	21, // 30: zeloscloud.trace.TraceMessage.session_join:type_name -> zeloscloud.trace.TraceSessionJoin
+	22, // 31: zeloscloud.trace.TraceMessage.annotation:type_name -> zeloscloud.trace.TraceAnnotation
+	23, // 32: zeloscloud.trace.TraceMessage.log:type_name -> zeloscloud.trace.TraceLog
+	24, // 33: zeloscloud.trace.TraceMessage.interval_begin:type_name -> zeloscloud.trace.TraceIntervalBegin
+	25, // 34: zeloscloud.trace.TraceMessage.interval_end:type_name -> zeloscloud.trace.TraceIntervalEnd
+	4,  // 35: zeloscloud.trace.Handshake.capabilities:type_name -> zeloscloud.trace.Capability
+	13, // 36: zeloscloud.trace.TraceActionCall.arguments:type_name -> zeloscloud.trace.TraceEventFieldEntry
+	5,  // 37: zeloscloud.trace.SetParameter.value:type_name -> zeloscloud.trace.Value
+	28, // 38: zeloscloud.trace.TraceControl.set_event_enabled:type_name -> zeloscloud.trace.SetEventEnabled
+	29, // 39: zeloscloud.trace.TraceControl.set_sample_rate:type_name -> zeloscloud.trace.SetSampleRate
+	30, // 40: zeloscloud.trace.TraceControl.resend_metadata:type_name -> zeloscloud.trace.ResendMetadata
+	31, // 41: zeloscloud.trace.TraceControl.call_action:type_name -> zeloscloud.trace.TraceActionCall
+	32, // 42: zeloscloud.trace.TraceControl.set_parameter:type_name -> zeloscloud.trace.SetParameter
+	26, // 43: zeloscloud.trace.TraceMessageBatch.messages:type_name -> zeloscloud.trace.TraceMessage
+	44, // [44:44] is the sub-list for method output_type
+	44, // [44:44] is the sub-list for method input_type
+	44, // [44:44] is the sub-list for extension type_name
+	44, // [44:44] is the sub-list for extension extendee
+	0,  // [0:44] is the sub-list for field type_name
 }
 
 func init() { file_zeloscloud_trace_trace_proto_init() }
@@ -1163,20 +3228,45 @@ func file_zeloscloud_trace_trace_proto_init() {
 		(*Value_Bool)(nil),
 	}
 	file_zeloscloud_trace_trace_proto_msgTypes[1].OneofWrappers = []any{}
-	file_zeloscloud_trace_trace_proto_msgTypes[9].OneofWrappers = []any{
+	file_zeloscloud_trace_trace_proto_msgTypes[2].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[11].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[17].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[19].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[21].OneofWrappers = []any{
 		(*TraceMessage_SegmentStart)(nil),
 		(*TraceMessage_SegmentEnd)(nil),
 		(*TraceMessage_EventSchema)(nil),
 		(*TraceMessage_EventFieldNamedValues)(nil),
 		(*TraceMessage_Event)(nil),
+		(*TraceMessage_EventBatch)(nil),
+		(*TraceMessage_ActionSchema)(nil),
+		(*TraceMessage_ActionResult)(nil),
+		(*TraceMessage_SessionStart)(nil),
+		(*TraceMessage_SessionEnd)(nil),
+		(*TraceMessage_SessionJoin)(nil),
+		(*TraceMessage_Annotation)(nil),
+		(*TraceMessage_Log)(nil),
+		(*TraceMessage_IntervalBegin)(nil),
+		(*TraceMessage_IntervalEnd)(nil),
+	}
+	file_zeloscloud_trace_trace_proto_msgTypes[23].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[24].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[26].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[27].OneofWrappers = []any{}
+	file_zeloscloud_trace_trace_proto_msgTypes[28].OneofWrappers = []any{
+		(*TraceControl_SetEventEnabled)(nil),
+		(*TraceControl_SetSampleRate)(nil),
+		(*TraceControl_ResendMetadata)(nil),
+		(*TraceControl_CallAction)(nil),
+		(*TraceControl_SetParameter)(nil),
 	}
 	type x struct{}
 	out := protoimpl.TypeBuilder{
 		File: protoimpl.DescBuilder{
 			GoPackagePath: reflect.TypeOf(x{}).PkgPath(),
 			RawDescriptor: unsafe.Slice(unsafe.StringData(file_zeloscloud_trace_trace_proto_rawDesc), len(file_zeloscloud_trace_trace_proto_rawDesc)),
-			NumEnums:      1,
-			NumMessages:   11,
+			NumEnums:      5,
+			NumMessages:   33,
 			NumExtensions: 0,
 			NumServices:   0,
 		},